@@ -0,0 +1,37 @@
+use clap::Parser;
+
+#[derive(clap::ValueEnum, Copy, Clone, Debug)]
+pub enum RunModel {
+    Serial,
+    Parallel,
+}
+
+/// Evolving even-parity circuits with Cartesian GP in Rust
+#[derive(Parser, Debug, Copy, Clone)]
+#[clap(author, version, about, long_about = None)]
+pub struct Args {
+    /// Should we use parallelism when doing the run?
+    #[clap(short, long, value_enum, default_value_t = RunModel::Serial)]
+    pub run_model: RunModel,
+
+    /// The number of children made each generation, i.e., the λ in the
+    /// (1+λ) evolution strategy
+    #[clap(short, long, value_parser, default_value_t = 4)]
+    pub population_size: usize,
+
+    /// Number of generations to run
+    #[clap(short, long, value_parser, default_value_t = 20_000)]
+    pub num_generations: usize,
+
+    /// Number of input bits
+    #[clap(short = 'b', long, value_parser, default_value_t = 3)]
+    pub num_bits: usize,
+
+    /// Number of columns in the (single row) grid of nodes
+    #[clap(short, long, value_parser, default_value_t = 100)]
+    pub columns: usize,
+
+    /// The probability of mutating each gene
+    #[clap(short, long, value_parser, default_value_t = 0.03)]
+    pub mutation_rate: f64,
+}
@@ -0,0 +1,74 @@
+pub mod args;
+
+use anyhow::Result;
+use clap::Parser;
+use ec_cgp::{
+    es::OnePlusLambda,
+    function::{BooleanFunction, FunctionSet},
+    generator::CgpGenerator,
+    layout::Layout,
+    mutator::PointMutation,
+    scorer::{hamming_distance, CgpScorer},
+};
+use ec_core::{evaluation::cases::Cases, generator::Generator, test_results};
+use rand::thread_rng;
+
+use crate::args::{Args, RunModel};
+
+/*
+ * The even-n-parity problem: output `true` if an even number of the `n`
+ * input bits are `true`. Without `Xor` in the function set this needs a
+ * surprisingly large circuit, which makes it a classic CGP benchmark.
+ */
+
+fn main() -> Result<()> {
+    let args = Args::parse();
+
+    let training_cases = (0..1_u32 << args.num_bits)
+        .map(|n| {
+            let bits = (0..args.num_bits)
+                .map(|bit| n & (1 << bit) != 0)
+                .collect::<Vec<_>>();
+            let even = n.count_ones() % 2 == 0;
+            (bits, vec![even])
+        })
+        .collect::<Cases<Vec<bool>, Vec<bool>>>();
+
+    let scorer: CgpScorer<_, _, _, test_results::Error<i64>> =
+        CgpScorer::new(training_cases, hamming_distance, 1);
+
+    let layout = Layout::new(args.num_bits, 1, 1, args.columns)?;
+    let function_set = FunctionSet::new(BooleanFunction::AND_OR_NAND_NOR)?;
+
+    let mut rng = thread_rng();
+    let parent = CgpGenerator::new(layout, function_set.clone()).generate(&mut rng)?;
+    let mutator = PointMutation::new(function_set, args.mutation_rate)?;
+
+    let mut es = OnePlusLambda::new(parent, mutator, &scorer).with_lambda(args.population_size)?;
+
+    let mut best_error = es.parent().test_results.total_result.error;
+    println!("Initial error is {best_error}");
+
+    for generation_number in 0..args.num_generations {
+        match args.run_model {
+            RunModel::Serial => es.serial_next()?,
+            RunModel::Parallel => es.par_next()?,
+        }
+
+        let error = es.parent().test_results.total_result.error;
+        if error < best_error {
+            best_error = error;
+            println!(
+                "Generation {generation_number} error is {error} with {} active nodes",
+                es.parent().genome.num_active_nodes()
+            );
+        }
+
+        if error == 0 {
+            println!("SUCCESS\n{}", es.parent().genome);
+            break;
+        }
+    }
+
+    Ok(())
+}
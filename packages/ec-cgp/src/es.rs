@@ -0,0 +1,173 @@
+use anyhow::{ensure, Result};
+use ec_core::{
+    individual::{ec::EcIndividual, scorer::Scorer},
+    operator::mutator::Mutator,
+};
+use rand::rngs::ThreadRng;
+use rayon::prelude::{IntoParallelIterator, ParallelIterator};
+
+/// The (1+λ) evolution strategy traditionally used with CGP.
+///
+/// Each generation, the parent is mutated `lambda` times and the best child
+/// replaces the parent if it's _at least as good_ as the parent. Accepting
+/// equally good children lets the search drift across neutral changes
+/// (e.g., to inactive nodes), which is important to how CGP works. The usual
+/// choice of `lambda` is 4, which is the default.
+pub struct OnePlusLambda<G, R, M, S> {
+    parent: EcIndividual<G, R>,
+    mutator: M,
+    scorer: S,
+    lambda: usize,
+}
+
+impl<G, R, M, S> OnePlusLambda<G, R, M, S> {
+    pub const DEFAULT_LAMBDA: usize = 4;
+
+    /// Start the strategy from the given parent genome, which is scored with
+    /// `scorer`.
+    pub fn new(parent: G, mutator: M, scorer: S) -> Self
+    where
+        S: Scorer<G, Score = R>,
+    {
+        let test_results = scorer.score(&parent);
+        Self {
+            parent: EcIndividual::new(parent, test_results),
+            mutator,
+            scorer,
+            lambda: Self::DEFAULT_LAMBDA,
+        }
+    }
+
+    /// # Errors
+    ///
+    /// This fails if `lambda` is zero.
+    pub fn with_lambda(mut self, lambda: usize) -> Result<Self> {
+        ensure!(lambda > 0, "A (1+λ) strategy needs at least one child");
+        self.lambda = lambda;
+        Ok(self)
+    }
+
+    /// The current parent, which is always the best individual found so far.
+    pub const fn parent(&self) -> &EcIndividual<G, R> {
+        &self.parent
+    }
+
+    pub const fn lambda(&self) -> usize {
+        self.lambda
+    }
+}
+
+impl<G, R, M, S> OnePlusLambda<G, R, M, S>
+where
+    G: Clone,
+    R: Ord,
+    M: Mutator<G>,
+    S: Scorer<G, Score = R>,
+{
+    fn make_child(&self, rng: &mut ThreadRng) -> Result<EcIndividual<G, R>> {
+        let genome = self.mutator.mutate(self.parent.genome.clone(), rng)?;
+        let test_results = self.scorer.score(&genome);
+        Ok(EcIndividual::new(genome, test_results))
+    }
+
+    fn replace_parent(&mut self, children: Vec<EcIndividual<G, R>>) {
+        // `max_by` returns the last of several equally good children, which
+        // is as good a choice as any.
+        if let Some(best_child) = children
+            .into_iter()
+            .max_by(|x, y| x.test_results.cmp(&y.test_results))
+        {
+            if best_child.test_results >= self.parent.test_results {
+                self.parent = best_child;
+            }
+        }
+    }
+
+    /// Make and score the next `lambda` children serially.
+    ///
+    /// # Errors
+    ///
+    /// This can return errors if mutating any of the children fails.
+    pub fn serial_next(&mut self) -> Result<()> {
+        let mut rng = rand::thread_rng();
+        let children = (0..self.lambda)
+            .map(|_| self.make_child(&mut rng))
+            .collect::<Result<_>>()?;
+        self.replace_parent(children);
+        Ok(())
+    }
+
+    /// Make and score the next `lambda` children using a Rayon parallel
+    /// iterator, which is worthwhile when scoring is expensive.
+    ///
+    /// # Errors
+    ///
+    /// This can return errors if mutating any of the children fails.
+    pub fn par_next(&mut self) -> Result<()>
+    where
+        G: Send + Sync,
+        R: Send + Sync,
+        M: Sync,
+        S: Sync,
+    {
+        let children = (0..self.lambda)
+            .into_par_iter()
+            .map_init(rand::thread_rng, |rng, _| self.make_child(rng))
+            .collect::<Result<_>>()?;
+        self.replace_parent(children);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ec_core::{individual::scorer::FnScorer, test_results::Error};
+
+    use super::*;
+
+    struct Decrement;
+
+    impl Mutator<i64> for Decrement {
+        fn mutate(&self, genome: i64, _: &mut ThreadRng) -> Result<i64> {
+            Ok(genome - 1)
+        }
+    }
+
+    struct Identity;
+
+    impl Mutator<i64> for Identity {
+        fn mutate(&self, genome: i64, _: &mut ThreadRng) -> Result<i64> {
+            Ok(genome)
+        }
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn better_children_replace_the_parent() {
+        let scorer = FnScorer(|genome: &i64| Error::from(genome.abs()));
+        let mut es = OnePlusLambda::new(3, Decrement, scorer);
+        for _ in 0..3 {
+            es.serial_next().unwrap();
+        }
+        assert_eq!(es.parent().genome, 0);
+        // Children are now worse, so the parent stays.
+        es.par_next().unwrap();
+        assert_eq!(es.parent().genome, 0);
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn equally_good_children_replace_the_parent() {
+        // The scorer gives every genome the same score, so the child always
+        // replaces the parent.
+        let scorer = FnScorer(|_: &i64| Error::from(0));
+        let mut es = OnePlusLambda::new(3, Decrement, scorer)
+            .with_lambda(1)
+            .unwrap();
+        es.serial_next().unwrap();
+        assert_eq!(es.parent().genome, 2);
+        assert!(OnePlusLambda::new(3, Identity, scorer)
+            .with_lambda(0)
+            .is_err());
+    }
+}
@@ -0,0 +1,126 @@
+use anyhow::{ensure, Context, Result};
+use rand::{rngs::ThreadRng, seq::SliceRandom};
+
+/// A function that can be used as the function gene of a CGP node.
+pub trait CgpFunction: Clone {
+    /// The type of the values computed by the program.
+    type Value;
+
+    /// The number of arguments this function takes.
+    fn arity(&self) -> usize;
+
+    /// Compute the value of this function. `arguments` always has exactly
+    /// `arity()` values.
+    fn apply(&self, arguments: &[Self::Value]) -> Self::Value;
+}
+
+/// The set of functions that nodes can use.
+#[derive(Debug, Clone)]
+pub struct FunctionSet<F> {
+    functions: Vec<F>,
+    max_arity: usize,
+}
+
+impl<F: CgpFunction> FunctionSet<F> {
+    /// # Errors
+    ///
+    /// This fails if there are no functions.
+    pub fn new(functions: impl IntoIterator<Item = F>) -> Result<Self> {
+        let functions = functions.into_iter().collect::<Vec<_>>();
+        ensure!(!functions.is_empty(), "The function set can't be empty");
+        let max_arity = functions
+            .iter()
+            .map(CgpFunction::arity)
+            .max()
+            .unwrap_or_default();
+        Ok(Self {
+            functions,
+            max_arity,
+        })
+    }
+
+    #[must_use]
+    pub fn functions(&self) -> &[F] {
+        &self.functions
+    }
+
+    /// The largest arity of any function in the set, which is the number of
+    /// connection genes each node needs.
+    #[must_use]
+    pub const fn max_arity(&self) -> usize {
+        self.max_arity
+    }
+
+    /// # Errors
+    ///
+    /// This can't actually fail since the constructor ensures the set isn't
+    /// empty, but it's an error rather than a panic if it somehow does.
+    pub fn random_function(&self, rng: &mut ThreadRng) -> Result<F> {
+        self.functions
+            .choose(rng)
+            .cloned()
+            .context("The function set was unexpectedly empty")
+    }
+}
+
+/// The usual gates for evolving Boolean circuits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BooleanFunction {
+    And,
+    Or,
+    Nand,
+    Nor,
+    Xor,
+    Not,
+}
+
+impl BooleanFunction {
+    /// The two-argument gates traditionally used for the even-parity
+    /// problems, which can't express parity directly.
+    pub const AND_OR_NAND_NOR: [Self; 4] = [Self::And, Self::Or, Self::Nand, Self::Nor];
+}
+
+impl CgpFunction for BooleanFunction {
+    type Value = bool;
+
+    fn arity(&self) -> usize {
+        match self {
+            Self::Not => 1,
+            _ => 2,
+        }
+    }
+
+    fn apply(&self, arguments: &[bool]) -> bool {
+        match self {
+            Self::And => arguments[0] && arguments[1],
+            Self::Or => arguments[0] || arguments[1],
+            Self::Nand => !(arguments[0] && arguments[1]),
+            Self::Nor => !(arguments[0] || arguments[1]),
+            Self::Xor => arguments[0] != arguments[1],
+            Self::Not => !arguments[0],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn max_arity() {
+        let functions = FunctionSet::new([BooleanFunction::Not]).unwrap();
+        assert_eq!(functions.max_arity(), 1);
+        let functions = FunctionSet::new([BooleanFunction::Not, BooleanFunction::Xor]).unwrap();
+        assert_eq!(functions.max_arity(), 2);
+        assert!(FunctionSet::<BooleanFunction>::new([]).is_err());
+    }
+
+    #[test]
+    fn gates() {
+        assert!(BooleanFunction::Nand.apply(&[true, false]));
+        assert!(!BooleanFunction::Nor.apply(&[true, false]));
+        assert!(BooleanFunction::Xor.apply(&[true, false]));
+        assert!(!BooleanFunction::Not.apply(&[true]));
+    }
+}
@@ -0,0 +1,74 @@
+use anyhow::Result;
+use ec_core::generator::Generator;
+use rand::rngs::ThreadRng;
+
+use crate::{
+    function::{CgpFunction, FunctionSet},
+    genome::{Cgp, Node},
+    layout::Layout,
+};
+
+/// Generate random CGP genomes with the given layout, where every gene is
+/// chosen uniformly from the values that are valid in its position.
+pub struct CgpGenerator<F> {
+    layout: Layout,
+    function_set: FunctionSet<F>,
+}
+
+impl<F> CgpGenerator<F> {
+    #[must_use]
+    pub const fn new(layout: Layout, function_set: FunctionSet<F>) -> Self {
+        Self {
+            layout,
+            function_set,
+        }
+    }
+}
+
+impl<F: CgpFunction> Generator<Cgp<F>> for CgpGenerator<F> {
+    fn generate(&self, rng: &mut ThreadRng) -> Result<Cgp<F>> {
+        let nodes = (0..self.layout.num_nodes())
+            .map(|index| {
+                let column = self.layout.column(index);
+                Ok(Node {
+                    function: self.function_set.random_function(rng)?,
+                    connections: (0..self.function_set.max_arity())
+                        .map(|_| self.layout.random_connection(column, rng))
+                        .collect(),
+                })
+            })
+            .collect::<Result<_>>()?;
+        let outputs = (0..self.layout.num_outputs())
+            .map(|_| self.layout.random_output(rng))
+            .collect();
+        Cgp::new(self.layout, nodes, outputs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::function::BooleanFunction;
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn generated_genomes_are_valid() {
+        let mut rng = rand::thread_rng();
+        let layout = Layout::new(3, 2, 2, 10)
+            .unwrap()
+            .with_levels_back(3)
+            .unwrap();
+        let function_set = FunctionSet::new([BooleanFunction::Not, BooleanFunction::Xor]).unwrap();
+        let generator = CgpGenerator::new(layout, function_set);
+        for _ in 0..20 {
+            // `Cgp::new` checks all the genes, so just generating without
+            // an error is the real test.
+            let genome = generator.generate(&mut rng).unwrap();
+            assert!(genome
+                .nodes()
+                .iter()
+                .all(|node| node.connections.len() == 2));
+            assert_eq!(genome.evaluate(&[true, false, true]).unwrap().len(), 2);
+        }
+    }
+}
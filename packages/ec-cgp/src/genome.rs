@@ -0,0 +1,310 @@
+use std::fmt::{Debug, Display};
+
+use anyhow::{ensure, Context, Result};
+use ec_core::genome::Genome;
+
+use crate::{function::CgpFunction, layout::Layout};
+
+/// A single node in the CGP grid: a function gene, and connection genes
+/// holding the addresses of the node's arguments.
+///
+/// Every node has as many connection genes as the largest arity in the
+/// function set, and a node whose function has a smaller arity just ignores
+/// the extra connections. Keeping them means that a later mutation to a
+/// function with more arguments has something to work with.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Node<F> {
+    pub function: F,
+    pub connections: Vec<usize>,
+}
+
+/// A CGP genome: a grid of nodes (stored column by column) and the output
+/// genes that say where each of the program's outputs comes from.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Cgp<F> {
+    layout: Layout,
+    nodes: Vec<Node<F>>,
+    outputs: Vec<usize>,
+}
+
+impl<F> Genome for Cgp<F> {
+    type Gene = Node<F>;
+}
+
+impl<F: CgpFunction> Cgp<F> {
+    /// # Errors
+    ///
+    /// This fails if the number of nodes or outputs doesn't match the layout,
+    /// if a node doesn't have enough connections for its function, or if any
+    /// connection or output gene isn't valid for its position in the grid.
+    pub fn new(layout: Layout, nodes: Vec<Node<F>>, outputs: Vec<usize>) -> Result<Self> {
+        ensure!(
+            nodes.len() == layout.num_nodes(),
+            "The layout has {} nodes, but {} were given",
+            layout.num_nodes(),
+            nodes.len()
+        );
+        ensure!(
+            outputs.len() == layout.num_outputs(),
+            "The layout has {} outputs, but {} were given",
+            layout.num_outputs(),
+            outputs.len()
+        );
+        for (index, node) in nodes.iter().enumerate() {
+            ensure!(
+                node.connections.len() >= node.function.arity(),
+                "Node {index} has {} connections but its function needs {}",
+                node.connections.len(),
+                node.function.arity()
+            );
+            let column = layout.column(index);
+            for &address in &node.connections {
+                ensure!(
+                    layout.is_valid_connection(column, address),
+                    "Node {index} (in column {column}) can't be connected to address {address}"
+                );
+            }
+        }
+        for &address in &outputs {
+            ensure!(
+                layout.is_valid_output(address),
+                "An output can't be connected to address {address}"
+            );
+        }
+        Ok(Self {
+            layout,
+            nodes,
+            outputs,
+        })
+    }
+
+    /// Which nodes are active, i.e., are used (directly or indirectly) to
+    /// compute the outputs. The result has one entry for each node.
+    #[must_use]
+    pub fn active_nodes(&self) -> Vec<bool> {
+        let num_inputs = self.layout.num_inputs();
+        let mut active = vec![false; self.nodes.len()];
+        let mut to_visit = self.outputs.clone();
+        while let Some(address) = to_visit.pop() {
+            let Some(index) = address.checked_sub(num_inputs) else {
+                continue;
+            };
+            if !active[index] {
+                active[index] = true;
+                let node = &self.nodes[index];
+                to_visit.extend(&node.connections[..node.function.arity()]);
+            }
+        }
+        active
+    }
+
+    /// The number of active nodes.
+    #[must_use]
+    pub fn num_active_nodes(&self) -> usize {
+        self.active_nodes()
+            .into_iter()
+            .filter(|&is_active| is_active)
+            .count()
+    }
+
+    /// Run the program on the given inputs, returning one value for each
+    /// output. Only the active nodes are evaluated.
+    ///
+    /// # Errors
+    ///
+    /// This fails if the number of inputs doesn't match the layout.
+    pub fn evaluate(&self, inputs: &[F::Value]) -> Result<Vec<F::Value>>
+    where
+        F::Value: Clone,
+    {
+        let num_inputs = self.layout.num_inputs();
+        ensure!(
+            inputs.len() == num_inputs,
+            "The program takes {num_inputs} inputs, but was given {}",
+            inputs.len()
+        );
+        let mut values: Vec<Option<F::Value>> = inputs.iter().cloned().map(Some).collect();
+        values.resize(num_inputs + self.nodes.len(), None);
+        let mut arguments = Vec::new();
+        for (index, (node, is_active)) in self.nodes.iter().zip(self.active_nodes()).enumerate() {
+            if !is_active {
+                continue;
+            }
+            arguments.clear();
+            for &address in &node.connections[..node.function.arity()] {
+                // Connections always point to inputs or to earlier (active)
+                // nodes, so their values are already available.
+                arguments.push(
+                    values[address].clone().with_context(|| {
+                        format!("Node {index} read the unset address {address}")
+                    })?,
+                );
+            }
+            values[num_inputs + index] = Some(node.function.apply(&arguments));
+        }
+        self.outputs
+            .iter()
+            .map(|&address| {
+                values[address]
+                    .clone()
+                    .with_context(|| format!("An output read the unset address {address}"))
+            })
+            .collect()
+    }
+}
+
+impl<F> Cgp<F> {
+    #[must_use]
+    pub const fn layout(&self) -> &Layout {
+        &self.layout
+    }
+
+    #[must_use]
+    pub fn nodes(&self) -> &[Node<F>] {
+        &self.nodes
+    }
+
+    /// The output genes, i.e., the address that each output is read from.
+    #[must_use]
+    pub fn outputs(&self) -> &[usize] {
+        &self.outputs
+    }
+
+    // Mutation has to maintain the genome's invariants, so this is only
+    // available inside the crate.
+    pub(crate) fn genes_mut(&mut self) -> (&mut [Node<F>], &mut [usize]) {
+        (&mut self.nodes, &mut self.outputs)
+    }
+}
+
+/// Display the active nodes, one per line, followed by the outputs, e.g.,
+///
+/// ```text
+/// n3 = And(i0, i1)
+/// n5 = Not(n3)
+/// outputs: n5, i2
+/// ```
+impl<F> Display for Cgp<F>
+where
+    F: CgpFunction + Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let num_inputs = self.layout.num_inputs();
+        let name = |address: usize| {
+            if address < num_inputs {
+                format!("i{address}")
+            } else {
+                format!("n{address}")
+            }
+        };
+        for (index, (node, is_active)) in self.nodes.iter().zip(self.active_nodes()).enumerate() {
+            if is_active {
+                let arguments = node.connections[..node.function.arity()]
+                    .iter()
+                    .map(|&address| name(address))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                writeln!(
+                    f,
+                    "{} = {:?}({arguments})",
+                    name(num_inputs + index),
+                    node.function
+                )?;
+            }
+        }
+        let outputs = self
+            .outputs
+            .iter()
+            .map(|&address| name(address))
+            .collect::<Vec<_>>()
+            .join(", ");
+        write!(f, "outputs: {outputs}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::function::BooleanFunction::{self, And, Not, Or, Xor};
+
+    fn node(function: BooleanFunction, connections: [usize; 2]) -> Node<BooleanFunction> {
+        Node {
+            function,
+            connections: connections.to_vec(),
+        }
+    }
+
+    // Two inputs (0 and 1), one row of four nodes (2..6), one output.
+    #[allow(clippy::unwrap_used)]
+    fn example() -> Cgp<BooleanFunction> {
+        let layout = Layout::new(2, 1, 1, 4).unwrap();
+        Cgp::new(
+            layout,
+            vec![
+                // n2 = i0 xor i1
+                node(Xor, [0, 1]),
+                // n3 is never used.
+                node(And, [0, 2]),
+                // n4 = not n2, which ignores its second connection.
+                node(Not, [2, 3]),
+                // n5 = n4 or i0
+                node(Or, [4, 0]),
+            ],
+            vec![5],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn active_nodes() {
+        assert_eq!(example().active_nodes(), vec![true, false, true, true]);
+        assert_eq!(example().num_active_nodes(), 3);
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn evaluate() {
+        let program = example();
+        // (not (i0 xor i1)) or i0
+        assert_eq!(program.evaluate(&[false, false]).unwrap(), vec![true]);
+        assert_eq!(program.evaluate(&[false, true]).unwrap(), vec![false]);
+        assert_eq!(program.evaluate(&[true, false]).unwrap(), vec![true]);
+        assert_eq!(program.evaluate(&[true, true]).unwrap(), vec![true]);
+        assert!(program.evaluate(&[true]).is_err());
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn outputs_can_read_inputs() {
+        let layout = Layout::new(2, 2, 1, 1).unwrap();
+        let program = Cgp::new(layout, vec![node(And, [0, 1])], vec![1, 2]).unwrap();
+        assert_eq!(program.active_nodes(), vec![true]);
+        assert_eq!(
+            program.evaluate(&[true, false]).unwrap(),
+            vec![false, false]
+        );
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn invalid_genomes_are_rejected() {
+        let layout = Layout::new(2, 1, 1, 2)
+            .unwrap()
+            .with_levels_back(1)
+            .unwrap();
+        // The node in column 1 can't connect to itself.
+        assert!(Cgp::new(layout, vec![node(And, [0, 1]), node(And, [3, 0])], vec![3]).is_err());
+        // The output address is out of range.
+        assert!(Cgp::new(layout, vec![node(And, [0, 1]), node(And, [2, 0])], vec![4]).is_err());
+        // Too few nodes.
+        assert!(Cgp::new(layout, vec![node(And, [0, 1])], vec![2]).is_err());
+    }
+
+    #[test]
+    fn display_shows_active_nodes() {
+        assert_eq!(
+            example().to_string(),
+            "n2 = Xor(i0, i1)\nn4 = Not(n2)\nn5 = Or(n4, i0)\noutputs: n5"
+        );
+    }
+}
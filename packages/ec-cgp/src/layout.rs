@@ -0,0 +1,186 @@
+use std::ops::Range;
+
+use anyhow::{ensure, Result};
+use rand::{rngs::ThreadRng, Rng};
+
+/// The shape of a CGP genome: how many inputs and outputs it has, and the
+/// size of its grid of nodes.
+///
+/// Genes refer to inputs and nodes by _address_: addresses
+/// `0..num_inputs` are the program inputs, and the node in row `r` of
+/// column `c` has address `num_inputs + c * rows + r`. A node in column `c`
+/// can be connected to any input, and to any node in the `levels_back`
+/// columns before it. Outputs can be connected to any input or node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Layout {
+    num_inputs: usize,
+    num_outputs: usize,
+    rows: usize,
+    columns: usize,
+    levels_back: usize,
+}
+
+impl Layout {
+    /// Create a layout with the given number of inputs, outputs, rows, and
+    /// columns. `levels_back` starts out as the number of columns, so a node
+    /// can be connected to any node in an earlier column.
+    ///
+    /// # Errors
+    ///
+    /// This fails if any of the arguments is zero.
+    pub fn new(num_inputs: usize, num_outputs: usize, rows: usize, columns: usize) -> Result<Self> {
+        ensure!(num_inputs > 0, "A CGP program needs at least one input");
+        ensure!(num_outputs > 0, "A CGP program needs at least one output");
+        ensure!(
+            rows > 0 && columns > 0,
+            "The grid of nodes must have at least one row and one column, not {rows}x{columns}"
+        );
+        Ok(Self {
+            num_inputs,
+            num_outputs,
+            rows,
+            columns,
+            levels_back: columns,
+        })
+    }
+
+    /// # Errors
+    ///
+    /// This fails if `levels_back` isn't between 1 and the number of columns.
+    pub fn with_levels_back(mut self, levels_back: usize) -> Result<Self> {
+        ensure!(
+            (1..=self.columns).contains(&levels_back),
+            "Levels back must be between 1 and the number of columns ({}), not {levels_back}",
+            self.columns
+        );
+        self.levels_back = levels_back;
+        Ok(self)
+    }
+
+    #[must_use]
+    pub const fn num_inputs(&self) -> usize {
+        self.num_inputs
+    }
+
+    #[must_use]
+    pub const fn num_outputs(&self) -> usize {
+        self.num_outputs
+    }
+
+    #[must_use]
+    pub const fn rows(&self) -> usize {
+        self.rows
+    }
+
+    #[must_use]
+    pub const fn columns(&self) -> usize {
+        self.columns
+    }
+
+    #[must_use]
+    pub const fn levels_back(&self) -> usize {
+        self.levels_back
+    }
+
+    /// The total number of nodes in the grid.
+    #[must_use]
+    pub const fn num_nodes(&self) -> usize {
+        self.rows * self.columns
+    }
+
+    /// The column that the node with the given index (not address) is in.
+    #[must_use]
+    pub const fn column(&self, node_index: usize) -> usize {
+        node_index / self.rows
+    }
+
+    /// The addresses of the nodes that a node in `column` can be connected
+    /// to. (It can also be connected to any of the inputs.)
+    #[must_use]
+    pub const fn node_sources(&self, column: usize) -> Range<usize> {
+        let first_column = column.saturating_sub(self.levels_back);
+        self.num_inputs + first_column * self.rows..self.num_inputs + column * self.rows
+    }
+
+    /// Can a node in `column` be connected to `address`?
+    #[must_use]
+    pub const fn is_valid_connection(&self, column: usize, address: usize) -> bool {
+        let sources = self.node_sources(column);
+        address < self.num_inputs || (sources.start <= address && address < sources.end)
+    }
+
+    /// Can an output be connected to `address`?
+    #[must_use]
+    pub const fn is_valid_output(&self, address: usize) -> bool {
+        address < self.num_inputs + self.num_nodes()
+    }
+
+    /// A random address that a node in `column` can be connected to.
+    pub fn random_connection(&self, column: usize, rng: &mut ThreadRng) -> usize {
+        let sources = self.node_sources(column);
+        let choice = rng.gen_range(0..self.num_inputs + sources.len());
+        if choice < self.num_inputs {
+            choice
+        } else {
+            sources.start + choice - self.num_inputs
+        }
+    }
+
+    /// A random address that an output can be connected to.
+    pub fn random_output(&self, rng: &mut ThreadRng) -> usize {
+        rng.gen_range(0..self.num_inputs + self.num_nodes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn node_sources_respect_levels_back() {
+        // Inputs are 0 and 1, column 0 is 2..5, column 1 is 5..8, etc.
+        let layout = Layout::new(2, 1, 3, 4)
+            .unwrap()
+            .with_levels_back(2)
+            .unwrap();
+        assert_eq!(layout.node_sources(0), 2..2);
+        assert_eq!(layout.node_sources(1), 2..5);
+        assert_eq!(layout.node_sources(3), 5..11);
+        assert!(layout.is_valid_connection(3, 0));
+        assert!(!layout.is_valid_connection(3, 4));
+        assert!(!layout.is_valid_connection(3, 11));
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn random_connections_are_valid() {
+        let mut rng = rand::thread_rng();
+        let layout = Layout::new(2, 1, 3, 4)
+            .unwrap()
+            .with_levels_back(1)
+            .unwrap();
+        for column in 0..layout.columns() {
+            for _ in 0..100 {
+                let address = layout.random_connection(column, &mut rng);
+                assert!(layout.is_valid_connection(column, address));
+            }
+        }
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn bad_layouts_are_rejected() {
+        assert!(Layout::new(0, 1, 1, 1).is_err());
+        assert!(Layout::new(1, 0, 1, 1).is_err());
+        assert!(Layout::new(1, 1, 0, 1).is_err());
+        assert!(Layout::new(1, 1, 1, 3)
+            .unwrap()
+            .with_levels_back(0)
+            .is_err());
+        assert!(Layout::new(1, 1, 1, 3)
+            .unwrap()
+            .with_levels_back(4)
+            .is_err());
+    }
+}
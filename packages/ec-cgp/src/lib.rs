@@ -0,0 +1,16 @@
+//! Cartesian genetic programming (CGP).
+//!
+//! A CGP genome is a grid of nodes, each of which has a function gene and
+//! connection genes saying where that function's arguments come from (either
+//! a program input or the output of a node in an earlier column), along with
+//! output genes saying which inputs or nodes provide the program's outputs.
+//! Only the nodes that the outputs (indirectly) depend on are active; the
+//! rest are neutral material that can become active through mutation.
+
+pub mod es;
+pub mod function;
+pub mod generator;
+pub mod genome;
+pub mod layout;
+pub mod mutator;
+pub mod scorer;
@@ -0,0 +1,111 @@
+use anyhow::{ensure, Result};
+use ec_core::operator::mutator::Mutator;
+use rand::{rngs::ThreadRng, Rng};
+
+use crate::{
+    function::{CgpFunction, FunctionSet},
+    genome::Cgp,
+};
+
+/// The standard CGP point mutation: each gene (function, connection, or
+/// output) is replaced with a random valid value with probability
+/// `mutation_rate`.
+///
+/// New connection genes always respect the layout's levels-back limit. The
+/// new value may happen to be the same as the old one.
+pub struct PointMutation<F> {
+    function_set: FunctionSet<F>,
+    mutation_rate: f64,
+}
+
+impl<F> PointMutation<F> {
+    /// # Errors
+    ///
+    /// This fails if `mutation_rate` isn't a probability.
+    pub fn new(function_set: FunctionSet<F>, mutation_rate: f64) -> Result<Self> {
+        ensure!(
+            (0.0..=1.0).contains(&mutation_rate),
+            "The mutation rate {mutation_rate} must be between 0 and 1"
+        );
+        Ok(Self {
+            function_set,
+            mutation_rate,
+        })
+    }
+}
+
+impl<F: CgpFunction> Mutator<Cgp<F>> for PointMutation<F> {
+    fn mutate(&self, mut genome: Cgp<F>, rng: &mut ThreadRng) -> Result<Cgp<F>> {
+        let layout = *genome.layout();
+        let (nodes, outputs) = genome.genes_mut();
+        for (index, node) in nodes.iter_mut().enumerate() {
+            if rng.gen_bool(self.mutation_rate) {
+                let function = self.function_set.random_function(rng)?;
+                ensure!(
+                    function.arity() <= node.connections.len(),
+                    "The function set has a function with arity {} but the genome's nodes only \
+                     have {} connections",
+                    function.arity(),
+                    node.connections.len()
+                );
+                node.function = function;
+            }
+            let column = layout.column(index);
+            for connection in &mut node.connections {
+                if rng.gen_bool(self.mutation_rate) {
+                    *connection = layout.random_connection(column, rng);
+                }
+            }
+        }
+        for output in outputs {
+            if rng.gen_bool(self.mutation_rate) {
+                *output = layout.random_output(rng);
+            }
+        }
+        Ok(genome)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ec_core::generator::Generator;
+
+    use super::*;
+    use crate::{function::BooleanFunction, generator::CgpGenerator, genome::Cgp, layout::Layout};
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn mutants_are_valid() {
+        let mut rng = rand::thread_rng();
+        let layout = Layout::new(2, 1, 1, 20)
+            .unwrap()
+            .with_levels_back(2)
+            .unwrap();
+        let function_set = FunctionSet::new(BooleanFunction::AND_OR_NAND_NOR).unwrap();
+        let genome = CgpGenerator::new(layout, function_set.clone())
+            .generate(&mut rng)
+            .unwrap();
+        let mutator = PointMutation::new(function_set, 0.5).unwrap();
+        let mutant = mutator.mutate(genome.clone(), &mut rng).unwrap();
+        assert_ne!(mutant, genome);
+        // Rebuilding the mutant checks that all its genes are still valid.
+        let (nodes, outputs) = (mutant.nodes().to_vec(), mutant.outputs().to_vec());
+        assert!(Cgp::new(layout, nodes, outputs).is_ok());
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn zero_rate_changes_nothing() {
+        let mut rng = rand::thread_rng();
+        let layout = Layout::new(2, 1, 1, 5).unwrap();
+        let function_set = FunctionSet::new(BooleanFunction::AND_OR_NAND_NOR).unwrap();
+        let genome = CgpGenerator::new(layout, function_set.clone())
+            .generate(&mut rng)
+            .unwrap();
+        let mutator = PointMutation::new(function_set, 0.0).unwrap();
+        assert_eq!(mutator.mutate(genome.clone(), &mut rng).unwrap(), genome);
+        assert!(
+            PointMutation::new(FunctionSet::new([BooleanFunction::Not]).unwrap(), 2.0).is_err()
+        );
+    }
+}
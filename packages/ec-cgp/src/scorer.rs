@@ -0,0 +1,102 @@
+use std::{iter::Sum, marker::PhantomData};
+
+use ec_core::{evaluation::cases::Cases, individual::scorer::Scorer, test_results::TestResults};
+
+use crate::{function::CgpFunction, genome::Cgp};
+
+/// Score CGP genomes by running them on each of a set of `Cases` and
+/// comparing the outputs to the expected outputs with `error_fn`.
+///
+/// If a genome can't be run on a case (because the case has the wrong
+/// number of inputs), that case gets the `penalty` value.
+pub struct CgpScorer<T, F, V, R> {
+    cases: Cases<Vec<T>, Vec<T>>,
+    error_fn: F,
+    penalty: V,
+    _result: PhantomData<fn() -> R>,
+}
+
+impl<T, F, V, R> CgpScorer<T, F, V, R> {
+    pub const fn new(cases: Cases<Vec<T>, Vec<T>>, error_fn: F, penalty: V) -> Self {
+        Self {
+            cases,
+            error_fn,
+            penalty,
+            _result: PhantomData,
+        }
+    }
+
+    #[must_use]
+    pub const fn cases(&self) -> &Cases<Vec<T>, Vec<T>> {
+        &self.cases
+    }
+}
+
+impl<G, T, F, V, R> Scorer<Cgp<G>> for CgpScorer<T, F, V, R>
+where
+    G: CgpFunction<Value = T>,
+    T: Clone,
+    F: Fn(&[T], &[T]) -> V,
+    V: Clone,
+    for<'a> R: From<V> + Sum<&'a R> + 'a,
+{
+    type Score = TestResults<R>;
+
+    fn score(&self, genome: &Cgp<G>) -> Self::Score {
+        self.cases
+            .iter()
+            .map(|case| {
+                genome.evaluate(&case.input).map_or_else(
+                    |_| self.penalty.clone(),
+                    |actual| (self.error_fn)(&actual, &case.output),
+                )
+            })
+            .collect()
+    }
+}
+
+/// An error function for Boolean circuits: the number of output bits that
+/// differ from the expected output.
+#[must_use]
+pub fn hamming_distance(actual: &[bool], expected: &[bool]) -> i64 {
+    let differences = actual
+        .iter()
+        .zip(expected)
+        .filter(|(actual, expected)| actual != expected)
+        .count();
+    i64::try_from(differences).unwrap_or(i64::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use ec_core::test_results::Error;
+
+    use super::*;
+    use crate::{
+        function::BooleanFunction::{self, Xor},
+        genome::Node,
+        layout::Layout,
+    };
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn score_xor() {
+        let cases = [[false, false], [false, true], [true, false], [true, true]]
+            .into_iter()
+            .map(|input| (input.to_vec(), vec![input[0] && input[1]]))
+            .collect::<Cases<Vec<bool>, Vec<bool>>>();
+        let scorer: CgpScorer<_, _, _, Error<i64>> = CgpScorer::new(cases, hamming_distance, 10);
+        let layout = Layout::new(2, 1, 1, 1).unwrap();
+        let xor: Cgp<BooleanFunction> = Cgp::new(
+            layout,
+            vec![Node {
+                function: Xor,
+                connections: vec![0, 1],
+            }],
+            vec![2],
+        )
+        .unwrap();
+        // Xor gets three of the four And cases wrong.
+        assert_eq!(scorer.score(&xor).total_result.error, 3);
+    }
+}
@@ -0,0 +1,417 @@
+//! Reading [`Cases`] from, and writing them to, CSV and JSON Lines files.
+//!
+//! In both formats each case is a row whose columns are either named
+//! `input` and `output`, or numbered `input1`, `input2`, ... and `output1`,
+//! `output2`, ... for problems with several inputs or outputs. A single
+//! input (or output) column is read as the whole input, while several
+//! numbered columns are read, in order, into a tuple or vector.
+//!
+//! In a JSON Lines file each row is a JSON object with a field for each
+//! column, e.g.,
+//!
+//! ```text
+//! {"input1": 3, "input2": [1, 2], "output1": "three"}
+//! ```
+//!
+//! In a CSV file the first row names the columns, and each cell is parsed
+//! as JSON if it can be, and as a string otherwise. That means that `3` is
+//! a number, `[1, 2]` is a vector, and `three` is a string, while a string
+//! that would otherwise be parsed as JSON has to be quoted, e.g., `"""3"""`.
+//!
+//! The writers always use the `input` and `output` columns, with tuples
+//! written as JSON arrays, so anything they write can be read back in.
+
+use std::{
+    fs::File,
+    io::{BufRead, BufReader, BufWriter, Read, Write},
+    path::Path,
+};
+
+use anyhow::{bail, ensure, Context, Result};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+
+use super::{Case, Cases};
+
+impl<Input, Output> Cases<Input, Output>
+where
+    Input: DeserializeOwned,
+    Output: DeserializeOwned,
+{
+    /// Read cases from CSV data, where the first row names the columns.
+    ///
+    /// # Errors
+    ///
+    /// This fails if the data isn't valid CSV, if the columns aren't named
+    /// as described in the [module documentation](self), or if any row
+    /// can't be converted to the input and output types.
+    pub fn from_csv(reader: impl Read) -> Result<Self> {
+        let mut reader = csv::Reader::from_reader(reader);
+        let layout = Layout::new(
+            reader
+                .headers()
+                .context("Failed to read the CSV header")?
+                .iter(),
+        )?;
+        reader
+            .records()
+            .enumerate()
+            .map(|(index, record)| {
+                let row_number = index + 1;
+                let record =
+                    record.with_context(|| format!("Failed to read CSV row {row_number}"))?;
+                let mut row = record.iter().map(parse_cell).collect::<Vec<_>>();
+                layout
+                    .case(&mut row)
+                    .with_context(|| format!("Invalid case in CSV row {row_number}"))
+            })
+            .collect()
+    }
+
+    /// Read cases from the CSV file at `path`; see [`Cases::from_csv`].
+    ///
+    /// # Errors
+    ///
+    /// This fails if the file can't be opened or if
+    /// [`from_csv`](Cases::from_csv) fails.
+    pub fn from_csv_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let file =
+            File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+        Self::from_csv(file)
+            .with_context(|| format!("Failed to read cases from {}", path.display()))
+    }
+
+    /// Read cases from JSON Lines data, where each non-blank line is a JSON
+    /// object with a field for each column.
+    ///
+    /// # Errors
+    ///
+    /// This fails if any line can't be read, isn't a JSON object, has
+    /// fields that aren't named as described in the
+    /// [module documentation](self), or can't be converted to the input and
+    /// output types.
+    pub fn from_json_lines(reader: impl BufRead) -> Result<Self> {
+        let mut cases = Self::new();
+        for (index, line) in reader.lines().enumerate() {
+            let line_number = index + 1;
+            let line = line.with_context(|| format!("Failed to read line {line_number}"))?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let case = parse_json_line(&line)
+                .with_context(|| format!("Invalid case on line {line_number}"))?;
+            cases.add_case(case);
+        }
+        Ok(cases)
+    }
+
+    /// Read cases from the JSON Lines file at `path`; see
+    /// [`Cases::from_json_lines`].
+    ///
+    /// # Errors
+    ///
+    /// This fails if the file can't be opened or if
+    /// [`from_json_lines`](Cases::from_json_lines) fails.
+    pub fn from_json_lines_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let file =
+            File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+        Self::from_json_lines(BufReader::new(file))
+            .with_context(|| format!("Failed to read cases from {}", path.display()))
+    }
+}
+
+impl<Input, Output> Cases<Input, Output>
+where
+    Input: Serialize,
+    Output: Serialize,
+{
+    /// Write the cases as CSV with `input` and `output` columns.
+    ///
+    /// # Errors
+    ///
+    /// This fails if a case can't be serialized or the data can't be
+    /// written.
+    pub fn write_csv(&self, writer: impl Write) -> Result<()> {
+        let mut writer = csv::Writer::from_writer(writer);
+        writer.write_record(["input", "output"])?;
+        for case in self {
+            let input = serde_json::to_value(&case.input)?;
+            let output = serde_json::to_value(&case.output)?;
+            writer.write_record([format_cell(&input), format_cell(&output)])?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Write the cases to a CSV file at `path`; see [`Cases::write_csv`].
+    ///
+    /// # Errors
+    ///
+    /// This fails if the file can't be created or if
+    /// [`write_csv`](Cases::write_csv) fails.
+    pub fn write_csv_file(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let file =
+            File::create(path).with_context(|| format!("Failed to create {}", path.display()))?;
+        self.write_csv(BufWriter::new(file))
+            .with_context(|| format!("Failed to write cases to {}", path.display()))
+    }
+
+    /// Write the cases as JSON Lines, with one
+    /// `{"input": ..., "output": ...}` object per line.
+    ///
+    /// # Errors
+    ///
+    /// This fails if a case can't be serialized or the data can't be
+    /// written.
+    pub fn write_json_lines(&self, mut writer: impl Write) -> Result<()> {
+        for case in self {
+            serde_json::to_writer(&mut writer, case)?;
+            writeln!(writer)?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Write the cases to a JSON Lines file at `path`; see
+    /// [`Cases::write_json_lines`].
+    ///
+    /// # Errors
+    ///
+    /// This fails if the file can't be created or if
+    /// [`write_json_lines`](Cases::write_json_lines) fails.
+    pub fn write_json_lines_file(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let file =
+            File::create(path).with_context(|| format!("Failed to create {}", path.display()))?;
+        self.write_json_lines(BufWriter::new(file))
+            .with_context(|| format!("Failed to write cases to {}", path.display()))
+    }
+}
+
+fn parse_json_line<Input, Output>(line: &str) -> Result<Case<Input, Output>>
+where
+    Input: DeserializeOwned,
+    Output: DeserializeOwned,
+{
+    let Value::Object(row) = serde_json::from_str(line)? else {
+        bail!("Expected a JSON object");
+    };
+    let layout = Layout::new(row.keys().map(String::as_str))?;
+    let mut row = row.into_iter().map(|(_, value)| value).collect::<Vec<_>>();
+    layout.case(&mut row)
+}
+
+/// A CSV cell is JSON if it can be parsed as JSON, and a string otherwise.
+fn parse_cell(cell: &str) -> Value {
+    serde_json::from_str(cell).unwrap_or_else(|_| Value::String(cell.to_string()))
+}
+
+/// The inverse of [`parse_cell`], which only quotes strings that would
+/// otherwise be parsed as something else.
+fn format_cell(value: &Value) -> String {
+    match value {
+        Value::String(string) if serde_json::from_str::<Value>(string).is_err() => string.clone(),
+        _ => value.to_string(),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Side {
+    Input,
+    Output,
+}
+
+impl Side {
+    const fn prefix(self) -> &'static str {
+        match self {
+            Self::Input => "input",
+            Self::Output => "output",
+        }
+    }
+}
+
+/// Which side of a case a column is on, and its number (if it has one).
+fn parse_column_name(name: &str) -> Result<(Side, Option<usize>)> {
+    let (side, suffix) = if let Some(suffix) = name.strip_prefix(Side::Input.prefix()) {
+        (Side::Input, suffix)
+    } else if let Some(suffix) = name.strip_prefix(Side::Output.prefix()) {
+        (Side::Output, suffix)
+    } else {
+        bail!("Unexpected column {name:?}, which should be `input<n>` or `output<n>`");
+    };
+    if suffix.is_empty() {
+        return Ok((side, None));
+    }
+    let number = suffix
+        .parse::<usize>()
+        .ok()
+        .filter(|&number| number > 0)
+        .with_context(|| {
+            format!("Unexpected column {name:?}, which should be `input<n>` or `output<n>`")
+        })?;
+    Ok((side, Some(number)))
+}
+
+/// The positions in a row of the input and output columns, in order.
+struct Layout {
+    inputs: Vec<usize>,
+    outputs: Vec<usize>,
+}
+
+impl Layout {
+    fn new<'a>(names: impl IntoIterator<Item = &'a str>) -> Result<Self> {
+        let mut inputs = Vec::new();
+        let mut outputs = Vec::new();
+        for (position, name) in names.into_iter().enumerate() {
+            match parse_column_name(name)? {
+                (Side::Input, number) => inputs.push((number, position)),
+                (Side::Output, number) => outputs.push((number, position)),
+            }
+        }
+        Ok(Self {
+            inputs: Self::positions(Side::Input, inputs)?,
+            outputs: Self::positions(Side::Output, outputs)?,
+        })
+    }
+
+    fn positions(side: Side, mut columns: Vec<(Option<usize>, usize)>) -> Result<Vec<usize>> {
+        let prefix = side.prefix();
+        columns.sort_unstable();
+        if let [(None, position)] = columns[..] {
+            return Ok(vec![position]);
+        }
+        ensure!(!columns.is_empty(), "There's no {prefix} column");
+        ensure!(
+            columns
+                .iter()
+                .zip(1..)
+                .all(|(&(number, _), expected)| number == Some(expected)),
+            "The {prefix} columns should be a single `{prefix}` column or `{prefix}1` to \
+             `{prefix}{}` with no gaps or repeats",
+            columns.len()
+        );
+        Ok(columns.into_iter().map(|(_, position)| position).collect())
+    }
+
+    fn case<Input, Output>(&self, row: &mut [Value]) -> Result<Case<Input, Output>>
+    where
+        Input: DeserializeOwned,
+        Output: DeserializeOwned,
+    {
+        let input = Self::deserialize(&self.inputs, row).context("Invalid input")?;
+        let output = Self::deserialize(&self.outputs, row).context("Invalid output")?;
+        Ok(Case::new(input, output))
+    }
+
+    /// Deserialize a single column as itself, and several columns as an
+    /// array, which is how `serde_json` represents tuples and vectors.
+    fn deserialize<T: DeserializeOwned>(positions: &[usize], row: &mut [Value]) -> Result<T> {
+        let value = match positions {
+            &[position] => row[position].take(),
+            _ => Value::Array(
+                positions
+                    .iter()
+                    .map(|&position| row[position].take())
+                    .collect(),
+            ),
+        };
+        Ok(serde_json::from_value(value)?)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_numbered_csv_columns() {
+        let csv = "input1,input2,output1\n3,\"[1, 2]\",three\n4,[],\"\"\"4\"\"\"\n";
+        let cases = Cases::<(i64, Vec<i64>), String>::from_csv(csv.as_bytes()).unwrap();
+        assert_eq!(
+            cases.into_iter().collect::<Vec<_>>(),
+            [
+                Case::new((3, vec![1, 2]), String::from("three")),
+                Case::new((4, vec![]), String::from("4")),
+            ]
+        );
+    }
+
+    #[test]
+    fn columns_can_be_in_any_order() {
+        let csv = "output1,input2,input1\ntrue,2,1\n";
+        let cases = Cases::<(i64, i64), bool>::from_csv(csv.as_bytes()).unwrap();
+        assert_eq!(cases.iter().next(), Some(&Case::new((1, 2), true)));
+    }
+
+    #[test]
+    fn read_json_lines() {
+        let json = "{\"input1\": 1.5, \"input2\": \"x\", \"output1\": true}\n\n\
+                    {\"output\": false, \"input\": [2.5, \"y\"]}\n";
+        let cases = Cases::<(f64, char), bool>::from_json_lines(json.as_bytes()).unwrap();
+        assert_eq!(
+            cases.into_iter().collect::<Vec<_>>(),
+            [Case::new((1.5, 'x'), true), Case::new((2.5, 'y'), false)]
+        );
+    }
+
+    #[test]
+    fn round_trip() {
+        let cases = [
+            ((1, String::from("one")), vec![1.0]),
+            ((2, String::from("2")), vec![]),
+            ((3, String::new()), vec![0.5, -3.0]),
+        ]
+        .into_iter()
+        .collect::<Cases<(i64, String), Vec<f64>>>();
+        let expected = cases.iter().cloned().collect::<Vec<_>>();
+
+        let mut csv = Vec::new();
+        cases.write_csv(&mut csv).unwrap();
+        let from_csv = Cases::<(i64, String), Vec<f64>>::from_csv(csv.as_slice()).unwrap();
+        assert_eq!(from_csv.into_iter().collect::<Vec<_>>(), expected);
+
+        let mut json = Vec::new();
+        cases.write_json_lines(&mut json).unwrap();
+        let from_json = Cases::<(i64, String), Vec<f64>>::from_json_lines(json.as_slice()).unwrap();
+        assert_eq!(from_json.into_iter().collect::<Vec<_>>(), expected);
+    }
+
+    fn csv_error(csv: &str) -> String {
+        format!(
+            "{:#}",
+            Cases::<(i64, i64), i64>::from_csv(csv.as_bytes()).unwrap_err()
+        )
+    }
+
+    #[test]
+    fn malformed_csv() {
+        assert!(
+            csv_error("input1,input2,output1\n1,2,3\n1,x,3\n")
+                .starts_with("Invalid case in CSV row 2: Invalid input")
+        );
+        assert!(csv_error("input1,input2,output1\n1,2\n").starts_with("Failed to read CSV row 1"));
+        assert!(csv_error("input1,input3,output1\n").contains("`input1` to `input2`"));
+        assert!(csv_error("input1,input2\n").contains("There's no output column"));
+        assert!(csv_error("input1,input2,expected\n").contains("Unexpected column \"expected\""));
+    }
+
+    #[test]
+    fn malformed_json_lines() {
+        let error = |json: &str| {
+            format!(
+                "{:#}",
+                Cases::<i64, i64>::from_json_lines(json.as_bytes()).unwrap_err()
+            )
+        };
+        assert!(
+            error("{\"input\": 1, \"output\": 2}\n[1, 2]").starts_with("Invalid case on line 2")
+        );
+        assert!(error("{\"input\": 1}").contains("There's no output column"));
+        assert!(error("{\"input\": 1, \"output\": 2.5}").contains("Invalid output"));
+        assert!(error("{\"input\": 1, \"output\": ").starts_with("Invalid case on line 1"));
+    }
+}
@@ -1,4 +1,9 @@
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+use serde::{Deserialize, Serialize};
+
+pub mod io;
+pub mod split;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Case<Input, Output = Input> {
     pub input: Input,
     pub output: Output,
@@ -45,10 +50,6 @@ impl<Input, Output> Cases<Input, Output> {
             .collect()
     }
 
-    // TODO: Add `from` that selects randomly from some cases
-    // TODO: Add `from` that gets cases from an external source
-    //    Maybe outside of this type?
-
     pub fn add_case(&mut self, case: impl Into<Case<Input, Output>>) {
         self.cases.push(case.into());
     }
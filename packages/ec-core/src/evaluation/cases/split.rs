@@ -0,0 +1,274 @@
+//! Splitting [`Cases`] into training and testing (or validation) cases, and
+//! sampling from them.
+//!
+//! All of these take the random number generator as an argument, so a
+//! seeded generator (e.g., `StdRng::seed_from_u64`) gives the same split on
+//! every run. A three-way train/validation/test split is two splits, e.g.,
+//! splitting off the testing cases and then splitting the rest into
+//! training and validation cases.
+
+use std::{collections::HashMap, hash::Hash};
+
+use anyhow::{ensure, Result};
+use num_traits::ToPrimitive;
+use rand::{seq::SliceRandom, Rng};
+
+use super::{Case, Cases};
+
+/// The training and testing cases for a run.
+#[derive(Debug)]
+pub struct Split<Input, Output = Input> {
+    pub training: Cases<Input, Output>,
+    pub testing: Cases<Input, Output>,
+}
+
+/// The number of cases out of `len` that go in the training set, rounding
+/// to the nearest case.
+fn num_training(len: usize, training_fraction: f64) -> Result<usize> {
+    ensure!(
+        (0.0..=1.0).contains(&training_fraction),
+        "The training fraction must be between 0 and 1, but was {training_fraction}"
+    );
+    // This can't fail because the fraction is between 0 and 1, so the
+    // result is between 0 and `len`.
+    Ok((len.to_f64().unwrap_or(0.0) * training_fraction)
+        .round()
+        .to_usize()
+        .unwrap_or(0)
+        .min(len))
+}
+
+impl<Input, Output> Cases<Input, Output> {
+    pub fn shuffle(&mut self, rng: &mut impl Rng) {
+        self.cases.shuffle(rng);
+    }
+
+    /// Randomly split the cases, putting (the nearest whole number to)
+    /// `training_fraction` of them in the training cases and the rest in
+    /// the testing cases.
+    ///
+    /// # Errors
+    ///
+    /// This fails if `training_fraction` isn't between 0 and 1.
+    pub fn split(
+        mut self,
+        training_fraction: f64,
+        rng: &mut impl Rng,
+    ) -> Result<Split<Input, Output>> {
+        let num_training = num_training(self.len(), training_fraction)?;
+        self.shuffle(rng);
+        let testing = self.cases.split_off(num_training);
+        Ok(Split {
+            training: self,
+            testing: Self { cases: testing },
+        })
+    }
+
+    /// Randomly split the cases like [`split`](Self::split), but separately
+    /// for each group of cases with the same `key`, so that each group is
+    /// represented in the same proportions in the training and testing
+    /// cases. The key is often the output, or some property of it, e.g.,
+    /// its sign.
+    ///
+    /// # Errors
+    ///
+    /// This fails if `training_fraction` isn't between 0 and 1.
+    pub fn split_stratified<K>(
+        self,
+        training_fraction: f64,
+        key: impl Fn(&Case<Input, Output>) -> K,
+        rng: &mut impl Rng,
+    ) -> Result<Split<Input, Output>>
+    where
+        K: Eq + Hash,
+    {
+        // We keep the groups in the order their keys first appear, so that
+        // the split only depends on the `rng`.
+        let mut group_indices = HashMap::new();
+        let mut groups = Vec::<Vec<_>>::new();
+        for case in self.cases {
+            let index = *group_indices.entry(key(&case)).or_insert_with(|| {
+                groups.push(Vec::new());
+                groups.len() - 1
+            });
+            groups[index].push(case);
+        }
+
+        let mut split = Split {
+            training: Self::new(),
+            testing: Self::new(),
+        };
+        for group in groups {
+            let group_split = Self { cases: group }.split(training_fraction, rng)?;
+            split.training.cases.extend(group_split.training);
+            split.testing.cases.extend(group_split.testing);
+        }
+        split.training.shuffle(rng);
+        split.testing.shuffle(rng);
+        Ok(split)
+    }
+
+    /// Randomly partition the cases into `k` folds of (as near as possible)
+    /// equal size, and return a split for each fold, where that fold is the
+    /// testing cases and the other folds are the training cases.
+    ///
+    /// # Errors
+    ///
+    /// This fails if `k` is less than 2 or more than the number of cases.
+    pub fn k_folds(&self, k: usize, rng: &mut impl Rng) -> Result<Vec<Split<Input, Output>>>
+    where
+        Input: Clone,
+        Output: Clone,
+    {
+        ensure!(
+            (2..=self.len()).contains(&k),
+            "The number of folds must be between 2 and the number of cases ({}), but was {k}",
+            self.len()
+        );
+        let mut indices = (0..self.len()).collect::<Vec<_>>();
+        indices.shuffle(rng);
+        // Index `i` in the shuffled order is in fold `i % k`.
+        Ok((0..k)
+            .map(|fold| {
+                let (testing, training): (Vec<_>, Vec<_>) = indices
+                    .iter()
+                    .enumerate()
+                    .partition(|(position, _)| position % k == fold);
+                let cases = |positions: Vec<(usize, &usize)>| {
+                    positions
+                        .into_iter()
+                        .map(|(_, &index)| self.cases[index].clone())
+                        .collect()
+                };
+                Split {
+                    training: cases(training),
+                    testing: cases(testing),
+                }
+            })
+            .collect())
+    }
+
+    /// A random sample of `num_cases` of the cases, without replacement.
+    ///
+    /// # Errors
+    ///
+    /// This fails if there are fewer than `num_cases` cases.
+    pub fn sample(&self, num_cases: usize, rng: &mut impl Rng) -> Result<Self>
+    where
+        Input: Clone,
+        Output: Clone,
+    {
+        ensure!(
+            num_cases <= self.len(),
+            "Can't sample {num_cases} cases from {} cases",
+            self.len()
+        );
+        Ok(self
+            .cases
+            .choose_multiple(rng, num_cases)
+            .cloned()
+            .collect())
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+    use crate::evaluation::cases::WithTarget;
+
+    fn squares(num_cases: i32) -> Cases<i32> {
+        (0..num_cases).with_target(|x| x * x)
+    }
+
+    fn inputs(cases: &Cases<i32>) -> Vec<i32> {
+        let mut inputs = cases.iter().map(|case| case.input).collect::<Vec<_>>();
+        inputs.sort_unstable();
+        inputs
+    }
+
+    #[test]
+    fn split_keeps_every_case_once() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let split = squares(10).split(0.75, &mut rng).unwrap();
+        // 7.5 rounds up to 8.
+        assert_eq!(split.training.len(), 8);
+        assert_eq!(split.testing.len(), 2);
+        let mut all = inputs(&split.training);
+        all.extend(inputs(&split.testing));
+        all.sort_unstable();
+        assert_eq!(all, (0..10).collect::<Vec<_>>());
+        assert!(
+            split
+                .training
+                .iter()
+                .all(|case| case.output == case.input * case.input)
+        );
+        assert!(squares(10).split(1.5, &mut rng).is_err());
+    }
+
+    #[test]
+    fn seeded_splits_are_repeatable() {
+        let first = squares(20)
+            .split(0.5, &mut StdRng::seed_from_u64(7))
+            .unwrap();
+        let second = squares(20)
+            .split(0.5, &mut StdRng::seed_from_u64(7))
+            .unwrap();
+        assert_eq!(
+            first.training.iter().collect::<Vec<_>>(),
+            second.training.iter().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn stratified_split_keeps_the_proportions() {
+        let mut rng = StdRng::seed_from_u64(1);
+        // 20 even and 10 odd inputs.
+        let cases = (0..30)
+            .map(|x| if x % 3 == 0 { 2 * x + 1 } else { 2 * x })
+            .with_target(|x| x % 2 == 1);
+        let split = cases
+            .split_stratified(0.5, |case| case.output, &mut rng)
+            .unwrap();
+        let num_odd = |cases: &Cases<i32, bool>| cases.iter().filter(|case| case.output).count();
+        assert_eq!(split.training.len(), 15);
+        assert_eq!(num_odd(&split.training), 5);
+        assert_eq!(num_odd(&split.testing), 5);
+    }
+
+    #[test]
+    fn folds_partition_the_cases() {
+        let mut rng = StdRng::seed_from_u64(2);
+        let cases = squares(10);
+        let folds = cases.k_folds(3, &mut rng).unwrap();
+        assert_eq!(folds.len(), 3);
+        let mut tested = Vec::new();
+        for fold in &folds {
+            assert_eq!(fold.training.len() + fold.testing.len(), 10);
+            assert!(
+                inputs(&fold.training)
+                    .iter()
+                    .all(|input| !inputs(&fold.testing).contains(input))
+            );
+            tested.extend(inputs(&fold.testing));
+        }
+        tested.sort_unstable();
+        assert_eq!(tested, (0..10).collect::<Vec<_>>());
+        assert!(cases.k_folds(1, &mut rng).is_err());
+        assert!(cases.k_folds(11, &mut rng).is_err());
+    }
+
+    #[test]
+    fn sample_without_replacement() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let cases = squares(10);
+        let sample = cases.sample(4, &mut rng).unwrap();
+        let mut sampled = inputs(&sample);
+        sampled.dedup();
+        assert_eq!(sampled.len(), 4);
+        assert!(cases.sample(11, &mut rng).is_err());
+    }
+}
@@ -0,0 +1,139 @@
+//! Tracking how well the best individual in each generation generalises to
+//! held-out cases that weren't used to select it, so that overfitting shows
+//! up during a run.
+
+use std::{
+    cmp::Reverse,
+    fmt::{Debug, Display},
+    ops::Sub,
+};
+
+use crate::{
+    individual::{scorer::Scorer, Individual},
+    test_results::{Error, TestResults},
+};
+
+/// The total results of one generation's best individual on the training
+/// cases and on the held-out testing cases.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Generalisation<R> {
+    pub generation: usize,
+    pub training: R,
+    pub testing: R,
+}
+
+impl<T> Generalisation<Error<T>>
+where
+    T: Clone + Sub<Output = T>,
+{
+    /// How much bigger the testing error is than the training error. A gap
+    /// that grows over a run is a sign of overfitting.
+    pub fn gap(&self) -> T {
+        self.testing.error.clone() - self.training.error.clone()
+    }
+}
+
+impl<R: Debug> Display for Generalisation<R> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Generation {}: training {:?}, testing {:?}",
+            self.generation, self.training, self.testing
+        )
+    }
+}
+
+/// Records the [`Generalisation`] of the best individual in each generation.
+///
+/// The testing scorer is typically the same kind of scorer as the one used
+/// in the run, but with the held-out cases.
+pub struct GeneralisationReport<S, R> {
+    testing_scorer: S,
+    history: Vec<Generalisation<R>>,
+}
+
+impl<S, R> GeneralisationReport<S, R> {
+    pub const fn new(testing_scorer: S) -> Self {
+        Self {
+            testing_scorer,
+            history: Vec::new(),
+        }
+    }
+
+    /// Score `best` on the held-out cases, and record that alongside its
+    /// training results as the results for `generation`.
+    pub fn record<I>(&mut self, generation: usize, best: &I) -> &Generalisation<R>
+    where
+        I: Individual<TestResults = TestResults<R>>,
+        S: Scorer<I::Genome, Score = TestResults<R>>,
+        R: Clone,
+    {
+        let testing = self.testing_scorer.score(best.genome()).total_result;
+        let training = best.test_results().total_result.clone();
+        self.history.push(Generalisation {
+            generation,
+            training,
+            testing,
+        });
+        self.history
+            .last()
+            .unwrap_or_else(|| unreachable!("We just added to the history, so it isn't empty"))
+    }
+
+    /// All the recorded results, in the order they were recorded.
+    pub fn history(&self) -> &[Generalisation<R>] {
+        &self.history
+    }
+
+    /// The recorded results with the best testing results, which is the
+    /// generation to stop at if we were stopping early. If several
+    /// generations tie, this is the earliest of them.
+    pub fn best_testing(&self) -> Option<&Generalisation<R>>
+    where
+        R: Ord,
+    {
+        self.history.iter().max_by(|first, second| {
+            (&first.testing, Reverse(first.generation))
+                .cmp(&(&second.testing, Reverse(second.generation)))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::individual::{ec::EcIndividual, scorer::FnScorer};
+
+    #[test]
+    fn records_training_and_testing_totals() {
+        // The "held-out cases" are the even numbers up to 10, and the error
+        // on each is its distance from the genome.
+        let testing_scorer = FnScorer(|&genome: &i64| -> TestResults<Error<i64>> {
+            (0..=10)
+                .step_by(2)
+                .map(|target: i64| (target - genome).abs())
+                .collect()
+        });
+        let mut report = GeneralisationReport::new(testing_scorer);
+
+        let first = EcIndividual::new(0, TestResults::from(vec![5]));
+        let recorded = report.record(0, &first);
+        assert_eq!(recorded.training, Error::from(5));
+        assert_eq!(recorded.testing, Error::from(2 + 4 + 6 + 8 + 10));
+        assert_eq!(recorded.gap(), 25);
+
+        let second = EcIndividual::new(5, TestResults::from(vec![1]));
+        report.record(1, &second);
+        assert_eq!(report.history().len(), 2);
+        assert_eq!(report.best_testing().map(|g| g.generation), Some(1));
+        assert_eq!(
+            report.history()[1].to_string(),
+            "Generation 1: training 1, testing 18"
+        );
+
+        // A later generation that's only as good doesn't replace it.
+        let third = EcIndividual::new(5, TestResults::from(vec![0]));
+        report.record(2, &third);
+        assert_eq!(report.best_testing().map(|g| g.generation), Some(1));
+    }
+}
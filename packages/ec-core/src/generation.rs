@@ -73,3 +73,156 @@ where
         Ok(())
     }
 }
+
+/// The input to the child maker in a [`OneToOneGeneration`]: the population,
+/// and the position of the _target_ individual that the child will compete
+/// with.
+pub struct Target<'pop, P> {
+    pub population: &'pop P,
+    pub index: usize,
+}
+
+// These are implemented by hand because deriving them would require `P` to
+// be `Clone` and `Copy`.
+impl<P> Clone for Target<'_, P> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<P> Copy for Target<'_, P> {}
+
+impl<'pop, P> Target<'pop, P> {
+    pub const fn new(population: &'pop P, index: usize) -> Self {
+        Self { population, index }
+    }
+}
+
+impl<'pop, I> Target<'pop, Vec<I>> {
+    /// The target individual.
+    ///
+    /// # Panics
+    ///
+    /// This panics if `index` is out of range for the population.
+    #[must_use]
+    pub fn individual(&self) -> &'pop I {
+        &self.population[self.index]
+    }
+}
+
+/// A generation with one-to-one replacement, as used in differential
+/// evolution.
+///
+/// Rather than making a whole new population from the old one like
+/// [`Generation`], each individual in the population is the target of one
+/// child, and the child replaces its target in the next generation if it's
+/// at least as good. The child maker is applied to a [`Target`], so it knows
+/// which individual it's competing with (see
+/// [`donors`](crate::operator::donors) for operators that use that).
+pub struct OneToOneGeneration<I, C> {
+    population: Vec<I>,
+    child_maker: C,
+}
+
+impl<I, C> OneToOneGeneration<I, C> {
+    pub const fn new(child_maker: C, population: Vec<I>) -> Self {
+        Self {
+            population,
+            child_maker,
+        }
+    }
+
+    pub const fn population(&self) -> &Vec<I> {
+        &self.population
+    }
+}
+
+impl<I, C> OneToOneGeneration<I, C>
+where
+    I: Ord,
+{
+    /// Replace every target that its child is at least as good as.
+    fn replace(&mut self, children: Vec<I>) {
+        let targets = std::mem::take(&mut self.population);
+        self.population = targets
+            .into_iter()
+            .zip(children)
+            .map(|(target, child)| if child >= target { child } else { target })
+            .collect();
+    }
+
+    /// Make the next generation serially.
+    ///
+    /// # Errors
+    ///
+    /// This can return errors if any aspect of creating the children fails.
+    /// That can include constructing or scoring the genomes.
+    pub fn serial_next(&mut self) -> anyhow::Result<()>
+    where
+        C: for<'a> Operator<Target<'a, Vec<I>>, Output = I>,
+    {
+        let mut rng = rand::thread_rng();
+        let children = (0..self.population.len())
+            .map(|index| {
+                self.child_maker
+                    .apply(Target::new(&self.population, index), &mut rng)
+            })
+            .collect::<Result<_>>()?;
+        self.replace(children);
+        Ok(())
+    }
+
+    /// Make the next generation using a Rayon parallel iterator.
+    ///
+    /// # Errors
+    ///
+    /// This can return errors if any aspect of creating the children fails.
+    /// That can include constructing or scoring the genomes.
+    pub fn par_next(&mut self) -> anyhow::Result<()>
+    where
+        I: Send + Sync,
+        C: for<'a> Operator<Target<'a, Vec<I>>, Output = I> + Send + Sync,
+    {
+        let children = (0..self.population.len())
+            .into_par_iter()
+            .map_init(rand::thread_rng, |rng, index| {
+                self.child_maker
+                    .apply(Target::new(&self.population, index), rng)
+            })
+            .collect::<Result<_>>()?;
+        self.replace(children);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::ThreadRng;
+
+    use super::*;
+    use crate::operator::Composable;
+
+    /// Make a child that's one more than the target's neighbour.
+    struct NeighbourPlusOne;
+
+    impl Operator<Target<'_, Vec<i32>>> for NeighbourPlusOne {
+        type Output = i32;
+
+        fn apply(&self, target: Target<'_, Vec<i32>>, _: &mut ThreadRng) -> Result<i32> {
+            let neighbour = (target.index + 1) % target.population.len();
+            Ok(target.population[neighbour] + 1)
+        }
+    }
+    impl Composable for NeighbourPlusOne {}
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn children_only_replace_worse_targets() {
+        let mut generation = OneToOneGeneration::new(NeighbourPlusOne, vec![5, 1, 3]);
+        generation.serial_next().unwrap();
+        // The children are [2, 4, 6], so the first target survives.
+        assert_eq!(generation.population(), &vec![5, 4, 6]);
+        generation.par_next().unwrap();
+        assert_eq!(generation.population(), &vec![5, 7, 6]);
+    }
+}
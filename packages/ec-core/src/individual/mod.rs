@@ -1,4 +1,5 @@
 pub mod ec;
+pub mod process_scorer;
 pub mod scorer;
 
 pub trait Individual {
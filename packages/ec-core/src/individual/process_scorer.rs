@@ -0,0 +1,490 @@
+/// This module contains `ProcessScorer`, a `Scorer` that hands genomes off to
+/// a pool of worker subprocesses so that evaluations can be written in other
+/// languages (or just isolated from the rest of the run).
+///
+/// # Protocol
+///
+/// Each worker is a long-running process that reads requests from its
+/// standard input and writes responses to its standard output. Every request
+/// and every response is a single line of JSON terminated by `\n`, and a
+/// worker must write exactly one response line for each request line it reads
+/// (in order). Anything a worker writes to standard error is passed through
+/// unchanged, so that can be used for logging.
+///
+/// A request has the form
+///
+/// ```text
+/// {"genome": <the genome, serialized with serde>}
+/// ```
+///
+/// and a response is either a list of per-case results
+///
+/// ```text
+/// {"results": [<result for case 0>, <result for case 1>, ...]}
+/// ```
+///
+/// or an error reported by the worker for this genome
+///
+/// ```text
+/// {"error": "some description of what went wrong"}
+/// ```
+///
+/// Each per-case result is deserialized into the value type `V`, and then
+/// converted into the `TestResults` element type `R` using `From`. A response
+/// with the wrong number of results is treated the same as an `error`
+/// response.
+///
+/// If a worker exits, closes its output, or writes something that isn't a
+/// valid response, it is killed and restarted and the request is retried (up
+/// to `max_restarts` times). If a genome still can't be scored, or the worker
+/// reports an error, the genome is given the `penalty` value for every test
+/// case.
+///
+/// If a timeout is set (see `ProcessScorer::with_timeout`) and a worker
+/// doesn't respond in time, it is killed (to be restarted for the next
+/// request) and the genome gets the penalty straight away, since retrying a
+/// genome that hangs the worker would most likely just hang it again.
+use std::{
+    ffi::{OsStr, OsString},
+    io::{self, BufRead, BufReader, BufWriter, Write},
+    iter::{repeat_n, Sum},
+    marker::PhantomData,
+    process::{Child, ChildStdin, ChildStdout, Command, Stdio},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc::{self, Receiver, RecvTimeoutError},
+        Mutex, MutexGuard, TryLockError,
+    },
+    thread,
+    time::Duration,
+};
+
+use anyhow::{bail, ensure, Context, Result};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use super::scorer::Scorer;
+use crate::test_results::TestResults;
+
+#[derive(Serialize)]
+struct Request<'a, G> {
+    genome: &'a G,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Response<V> {
+    Results { results: Vec<V> },
+    Error { error: String },
+}
+
+/// The outcome of sending a single request to a worker.
+enum Outcome<V> {
+    /// The worker returned the right number of results.
+    Scored(Vec<V>),
+    /// The worker is still healthy, but couldn't (or wouldn't) score the
+    /// genome, for the given reason.
+    Rejected(String),
+    /// The worker didn't respond within the timeout.
+    TimedOut,
+}
+
+/// A single running worker process, along with the pipes used to talk to it.
+/// Its output is read by a separate thread, so that we can stop waiting for
+/// a response that doesn't come.
+struct Worker {
+    child: Child,
+    stdin: BufWriter<ChildStdin>,
+    responses: Receiver<io::Result<String>>,
+}
+
+impl Worker {
+    fn spawn(program: &OsStr, args: &[OsString]) -> Result<Self> {
+        let mut child = Command::new(program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .spawn()
+            .with_context(|| {
+                format!(
+                    "Failed to start the worker process {}",
+                    program.to_string_lossy()
+                )
+            })?;
+        let stdin = child
+            .stdin
+            .take()
+            .context("The worker process had no standard input")?;
+        let stdout = child
+            .stdout
+            .take()
+            .context("The worker process had no standard output")?;
+        Ok(Self {
+            child,
+            stdin: BufWriter::new(stdin),
+            responses: Self::read_lines(stdout),
+        })
+    }
+
+    /// Start a thread that sends each line of `stdout` to the returned
+    /// channel, ending with an empty line when the output is closed. The
+    /// thread stops when the worker's output is closed, e.g., because it was
+    /// killed.
+    fn read_lines(stdout: ChildStdout) -> Receiver<io::Result<String>> {
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let mut stdout = BufReader::new(stdout);
+            loop {
+                let mut line = String::new();
+                let result = stdout.read_line(&mut line);
+                let closed = !matches!(result, Ok(num_bytes) if num_bytes > 0);
+                if sender.send(result.map(|_| line)).is_err() || closed {
+                    break;
+                }
+            }
+        });
+        receiver
+    }
+
+    /// Send one request line and wait (for at most `timeout`, if there is
+    /// one) for the matching response line.
+    ///
+    /// # Errors
+    ///
+    /// This fails if the worker has died or otherwise broken the protocol, in
+    /// which case the worker should be restarted.
+    fn evaluate<V>(
+        &mut self,
+        request: &str,
+        num_cases: usize,
+        timeout: Option<Duration>,
+    ) -> Result<Outcome<V>>
+    where
+        V: DeserializeOwned,
+    {
+        self.stdin
+            .write_all(request.as_bytes())
+            .and_then(|()| self.stdin.write_all(b"\n"))
+            .and_then(|()| self.stdin.flush())
+            .context("Failed to send a request to the worker")?;
+
+        let line = match timeout {
+            Some(timeout) => match self.responses.recv_timeout(timeout) {
+                Ok(line) => line,
+                Err(RecvTimeoutError::Timeout) => return Ok(Outcome::TimedOut),
+                Err(RecvTimeoutError::Disconnected) => bail!("The worker closed its output"),
+            },
+            None => self
+                .responses
+                .recv()
+                .context("The worker closed its output")?,
+        }
+        .context("Failed to read a response from the worker")?;
+        ensure!(!line.is_empty(), "The worker closed its output");
+
+        let response: Response<V> = serde_json::from_str(&line)
+            .with_context(|| format!("The worker sent an invalid response: {line}"))?;
+        Ok(match response {
+            Response::Results { results } if results.len() == num_cases => Outcome::Scored(results),
+            Response::Results { results } => Outcome::Rejected(format!(
+                "Expected {num_cases} results but the worker returned {}",
+                results.len()
+            )),
+            Response::Error { error } => Outcome::Rejected(error),
+        })
+    }
+}
+
+impl Drop for Worker {
+    fn drop(&mut self) {
+        // The worker may well have already exited, in which case these fail
+        // and there's nothing more to do.
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// A `Scorer` that scores genomes by sending them to a pool of worker
+/// subprocesses using the line-delimited JSON protocol described in the
+/// module documentation.
+///
+/// Workers are started lazily the first time they're needed, and each worker
+/// handles one genome at a time. Concurrent calls to `score()` (e.g., from
+/// `Generation::par_next()`) are spread across the pool, so there's
+/// parallelism across workers.
+pub struct ProcessScorer<V, R> {
+    program: OsString,
+    args: Vec<OsString>,
+    workers: Vec<Mutex<Option<Worker>>>,
+    next_worker: AtomicUsize,
+    num_cases: usize,
+    penalty: V,
+    max_restarts: usize,
+    timeout: Option<Duration>,
+    num_failures: AtomicUsize,
+    _p: PhantomData<fn() -> R>,
+}
+
+impl<V, R> ProcessScorer<V, R> {
+    /// Create a new `ProcessScorer` that runs `num_workers` copies of
+    /// `program`. Each genome is expected to have `num_cases` results, and
+    /// `penalty` is used for every case of a genome that can't be scored.
+    ///
+    /// # Errors
+    ///
+    /// This returns an error if `num_workers` is zero.
+    pub fn new(
+        program: impl Into<OsString>,
+        num_workers: usize,
+        num_cases: usize,
+        penalty: V,
+    ) -> Result<Self> {
+        ensure!(
+            num_workers > 0,
+            "A `ProcessScorer` needs at least one worker process"
+        );
+        Ok(Self {
+            program: program.into(),
+            args: Vec::new(),
+            workers: (0..num_workers).map(|_| Mutex::new(None)).collect(),
+            next_worker: AtomicUsize::new(0),
+            num_cases,
+            penalty,
+            max_restarts: 2,
+            timeout: None,
+            num_failures: AtomicUsize::new(0),
+            _p: PhantomData,
+        })
+    }
+
+    /// Adds a command line argument that will be passed to each worker when
+    /// it is started.
+    #[must_use]
+    pub fn with_arg(mut self, arg: impl Into<OsString>) -> Self {
+        self.args.push(arg.into());
+        self
+    }
+
+    /// Sets how many times a crashed worker will be restarted while trying
+    /// to score a single genome before giving up and using the penalty.
+    #[must_use]
+    pub const fn with_max_restarts(mut self, max_restarts: usize) -> Self {
+        self.max_restarts = max_restarts;
+        self
+    }
+
+    /// Sets how long to wait for a worker to score a genome. A worker that
+    /// takes longer is killed and restarted, and the genome is given the
+    /// penalty. By default there is no timeout.
+    #[must_use]
+    pub const fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// The number of worker processes in the pool.
+    #[must_use]
+    pub const fn num_workers(&self) -> usize {
+        self.workers.len()
+    }
+
+    /// The number of genomes that were given the penalty value because they
+    /// couldn't be scored.
+    #[must_use]
+    pub fn num_failures(&self) -> usize {
+        self.num_failures.load(Ordering::Relaxed)
+    }
+
+    /// Lock a worker slot, preferring one that isn't currently in use.
+    fn acquire_worker(&self) -> MutexGuard<'_, Option<Worker>> {
+        let num_workers = self.workers.len();
+        let start = self.next_worker.fetch_add(1, Ordering::Relaxed) % num_workers;
+        for offset in 0..num_workers {
+            match self.workers[(start + offset) % num_workers].try_lock() {
+                Ok(guard) => return guard,
+                Err(TryLockError::Poisoned(poisoned)) => return poisoned.into_inner(),
+                Err(TryLockError::WouldBlock) => {}
+            }
+        }
+        // Every worker is busy, so wait for our "assigned" one.
+        self.workers[start]
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    // We deliberately hold on to the worker for the whole evaluation, including
+    // any restarts, so no one else can grab it part way through.
+    #[allow(clippy::significant_drop_tightening)]
+    fn evaluate(&self, request: &str) -> Result<Vec<V>>
+    where
+        V: DeserializeOwned,
+    {
+        let mut slot = self.acquire_worker();
+        let mut last_error = None;
+        for _ in 0..=self.max_restarts {
+            let worker = match slot.as_mut() {
+                Some(worker) => worker,
+                None => slot.insert(Worker::spawn(&self.program, &self.args)?),
+            };
+            match worker.evaluate(request, self.num_cases, self.timeout) {
+                Ok(Outcome::Scored(results)) => return Ok(results),
+                Ok(Outcome::Rejected(reason)) => bail!("The worker rejected the genome: {reason}"),
+                Ok(Outcome::TimedOut) => {
+                    // Dropping the worker kills it, so it won't send a late
+                    // response to the next request.
+                    *slot = None;
+                    bail!("The worker didn't respond within {:?}", self.timeout);
+                }
+                Err(error) => {
+                    // Dropping the worker kills it; a fresh one is started on the
+                    // next attempt.
+                    *slot = None;
+                    last_error = Some(error);
+                }
+            }
+        }
+        Err(last_error.unwrap_or_else(|| anyhow::anyhow!("The worker was never started")))
+    }
+}
+
+impl<G, V, R> Scorer<G> for ProcessScorer<V, R>
+where
+    G: Serialize,
+    V: DeserializeOwned + Clone,
+    for<'a> R: From<V> + Sum<&'a R> + 'a,
+{
+    type Score = TestResults<R>;
+
+    fn score(&self, genome: &G) -> Self::Score {
+        let results = serde_json::to_string(&Request { genome })
+            .map_err(anyhow::Error::from)
+            .and_then(|request| self.evaluate(&request));
+        results.map_or_else(
+            |_| {
+                self.num_failures.fetch_add(1, Ordering::Relaxed);
+                repeat_n(self.penalty.clone(), self.num_cases).into()
+            },
+            Into::into,
+        )
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use std::{fs, process};
+
+    use super::*;
+    use crate::test_results;
+
+    // These tests use `sh` to play the role of a worker written in
+    // "some other language".
+    fn sh_scorer(script: &str, num_cases: usize) -> ProcessScorer<i64, test_results::Error<i64>> {
+        ProcessScorer::new("sh", 2, num_cases, 1_000)
+            .unwrap()
+            .with_arg("-c")
+            .with_arg(script)
+    }
+
+    #[test]
+    fn scores_with_worker_results() {
+        let scorer = sh_scorer(
+            r#"while read line; do echo '{"results": [1, 2, 3]}'; done"#,
+            3,
+        );
+        let results = scorer.score(&vec![true, false]);
+        assert_eq!(
+            results,
+            TestResults::<test_results::Error<i64>>::from(vec![1, 2, 3])
+        );
+        assert_eq!(scorer.num_failures(), 0);
+    }
+
+    #[test]
+    fn worker_errors_get_the_penalty() {
+        let scorer = sh_scorer(
+            r#"while read line; do echo '{"error": "no thanks"}'; done"#,
+            2,
+        );
+        let results = scorer.score(&"genome");
+        assert_eq!(
+            results,
+            TestResults::<test_results::Error<i64>>::from(vec![1_000, 1_000])
+        );
+        assert_eq!(scorer.num_failures(), 1);
+    }
+
+    #[test]
+    fn wrong_number_of_results_gets_the_penalty() {
+        let scorer = sh_scorer(r#"while read line; do echo '{"results": [1]}'; done"#, 2);
+        let results = scorer.score(&5);
+        assert_eq!(
+            results,
+            TestResults::<test_results::Error<i64>>::from(vec![1_000, 1_000])
+        );
+    }
+
+    #[test]
+    fn crashed_workers_are_restarted() {
+        // The first worker process crashes on its first request; every worker
+        // started after that behaves.
+        let marker = std::env::temp_dir().join(format!(
+            "ec-core-process-scorer-{}-{:?}",
+            process::id(),
+            std::thread::current().id()
+        ));
+        let _ = fs::remove_file(&marker);
+        let script = format!(
+            r#"if [ ! -e "{0}" ]; then read line; touch "{0}"; exit 1; fi
+               while read line; do echo '{{"results": [4, 5]}}'; done"#,
+            marker.display()
+        );
+        let scorer = sh_scorer(&script, 2);
+        let results = scorer.score(&[1, 2, 3]);
+        let _ = fs::remove_file(&marker);
+        assert_eq!(
+            results,
+            TestResults::<test_results::Error<i64>>::from(vec![4, 5])
+        );
+        assert_eq!(scorer.num_failures(), 0);
+    }
+
+    #[test]
+    fn workers_that_time_out_get_the_penalty_and_are_restarted() {
+        let scorer = sh_scorer(
+            r#"while read line; do
+                 case "$line" in *slow*) sleep 2;; esac
+                 echo '{"results": [7]}'
+               done"#,
+            1,
+        )
+        .with_timeout(Duration::from_millis(200));
+        let start = std::time::Instant::now();
+        let results = scorer.score(&"slow");
+        assert!(start.elapsed() < Duration::from_secs(2));
+        assert_eq!(
+            results,
+            TestResults::<test_results::Error<i64>>::from(vec![1_000])
+        );
+        assert_eq!(scorer.num_failures(), 1);
+        // Both workers are used again, including the restarted one.
+        for _ in 0..scorer.num_workers() {
+            assert_eq!(
+                scorer.score(&"fast"),
+                TestResults::<test_results::Error<i64>>::from(vec![7])
+            );
+        }
+        assert_eq!(scorer.num_failures(), 1);
+    }
+
+    #[test]
+    fn workers_that_always_crash_get_the_penalty() {
+        let scorer = sh_scorer("read line; exit 1", 1).with_max_restarts(1);
+        let results = scorer.score(&"genome");
+        assert_eq!(
+            results,
+            TestResults::<test_results::Error<i64>>::from(vec![1_000])
+        );
+        assert_eq!(scorer.num_failures(), 1);
+    }
+}
@@ -1,4 +1,5 @@
 pub mod child_maker;
+pub mod evaluation;
 pub mod generation;
 pub mod generator;
 pub mod genome;
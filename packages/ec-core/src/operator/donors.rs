@@ -0,0 +1,152 @@
+//! Operators that choose the _donor_ individuals that a differential
+//! evolution-style recombinator combines with a target individual.
+//!
+//! These are applied to a [`Target`] in a
+//! [`OneToOneGeneration`](crate::generation::OneToOneGeneration), and
+//! return references to the target and `N` donors. The donors are always
+//! distinct from each other and from the target (except that the best
+//! individual can also be the target), so a population needs at least
+//! `N + 1` individuals.
+
+use anyhow::{anyhow, ensure, Context, Result};
+use rand::{rngs::ThreadRng, seq::index};
+
+use super::{Composable, Operator};
+use crate::generation::Target;
+
+/// `count` distinct positions in `0..len`, chosen uniformly at random from
+/// those that aren't in `excluded` (which mustn't have duplicates).
+fn random_positions(
+    len: usize,
+    excluded: &[usize],
+    count: usize,
+    rng: &mut ThreadRng,
+) -> Result<Vec<usize>> {
+    ensure!(
+        len >= excluded.len() + count,
+        "A population of size {len} is too small to choose {count} donors that differ from {} \
+         other individuals",
+        excluded.len()
+    );
+    // Sampling `count + excluded.len()` positions guarantees that at least
+    // `count` of them aren't excluded, and dropping the excluded ones keeps
+    // the choice uniform.
+    Ok(index::sample(rng, len, count + excluded.len())
+        .into_iter()
+        .filter(|position| !excluded.contains(position))
+        .take(count)
+        .collect())
+}
+
+fn into_array<I, const N: usize>(donors: Vec<&I>) -> Result<[&I; N]> {
+    donors.try_into().map_err(|donors: Vec<_>| {
+        anyhow!("Expected to choose {N} donors but chose {}", donors.len())
+    })
+}
+
+/// Choose `N` donors uniformly at random, as in DE/rand/1, where the first
+/// donor is the base vector and the others make the difference vector.
+pub struct RandomDonors<const N: usize>;
+
+impl<'pop, I, const N: usize> Operator<Target<'pop, Vec<I>>> for RandomDonors<N> {
+    type Output = (&'pop I, [&'pop I; N]);
+
+    fn apply(&self, target: Target<'pop, Vec<I>>, rng: &mut ThreadRng) -> Result<Self::Output> {
+        let donors = random_positions(target.population.len(), &[target.index], N, rng)?
+            .into_iter()
+            .map(|position| &target.population[position])
+            .collect();
+        Ok((target.individual(), into_array(donors)?))
+    }
+}
+impl<const N: usize> Composable for RandomDonors<N> {}
+
+/// Choose the best individual in the population as the first donor, and the
+/// other `N - 1` donors uniformly at random, as in DE/best/1 and
+/// DE/current-to-best/1.
+pub struct BestDonors<const N: usize>;
+
+impl<'pop, I, const N: usize> Operator<Target<'pop, Vec<I>>> for BestDonors<N>
+where
+    I: Ord,
+{
+    type Output = (&'pop I, [&'pop I; N]);
+
+    fn apply(&self, target: Target<'pop, Vec<I>>, rng: &mut ThreadRng) -> Result<Self::Output> {
+        ensure!(N > 0, "BestDonors needs to choose at least one donor");
+        let population = target.population;
+        let best = population
+            .iter()
+            .enumerate()
+            .max_by(|(_, x), (_, y)| x.cmp(y))
+            .map(|(position, _)| position)
+            .context("The population was empty")?;
+        let excluded = if best == target.index {
+            vec![best]
+        } else {
+            vec![target.index, best]
+        };
+        let donors = std::iter::once(best)
+            .chain(random_positions(population.len(), &excluded, N - 1, rng)?)
+            .map(|position| &population[position])
+            .collect();
+        Ok((target.individual(), into_array(donors)?))
+    }
+}
+impl<const N: usize> Composable for BestDonors<N> {}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn random_donors_are_distinct_from_each_other_and_the_target() {
+        let mut rng = rand::thread_rng();
+        let population = (0..6).collect::<Vec<_>>();
+        for index in 0..population.len() {
+            for _ in 0..20 {
+                let target = Target::new(&population, index);
+                let (target, donors) = RandomDonors::<3>.apply(target, &mut rng).unwrap();
+                assert_eq!(*target, index);
+                let distinct = donors.iter().collect::<HashSet<_>>();
+                assert_eq!(distinct.len(), 3);
+                assert!(!distinct.contains(&target));
+            }
+        }
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn best_donors_start_with_the_best() {
+        let mut rng = rand::thread_rng();
+        let population = vec![3, 9, 1, 4, 0];
+        for index in 0..population.len() {
+            let target = Target::new(&population, index);
+            let (target, donors) = BestDonors::<3>.apply(target, &mut rng).unwrap();
+            assert_eq!(*donors[0], 9);
+            let distinct = donors.iter().collect::<HashSet<_>>();
+            assert_eq!(distinct.len(), 3);
+            if *target != 9 {
+                assert!(!distinct.contains(&target));
+            }
+        }
+    }
+
+    #[test]
+    fn small_populations_are_an_error() {
+        let mut rng = rand::thread_rng();
+        let population = vec![1, 2, 3];
+        assert!(RandomDonors::<3>
+            .apply(Target::new(&population, 0), &mut rng)
+            .is_err());
+        assert!(BestDonors::<3>
+            .apply(Target::new(&population, 0), &mut rng)
+            .is_err());
+        assert!(RandomDonors::<2>
+            .apply(Target::new(&population, 0), &mut rng)
+            .is_ok());
+    }
+}
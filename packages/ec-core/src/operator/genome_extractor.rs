@@ -22,3 +22,25 @@ where
     }
 }
 impl Composable for GenomeExtractor {}
+
+/// Returns _cloned_ copies of the genomes of a target individual and its
+/// donors, as chosen by the operators in
+/// [`donors`](crate::operator::donors).
+impl<I, const N: usize> Operator<(&I, [&I; N])> for GenomeExtractor
+where
+    I: Individual,
+    <I as Individual>::Genome: Clone,
+{
+    type Output = (I::Genome, [I::Genome; N]);
+
+    fn apply(
+        &self,
+        (target, donors): (&I, [&I; N]),
+        _: &mut rand::rngs::ThreadRng,
+    ) -> Result<Self::Output> {
+        Ok((
+            target.genome().clone(),
+            donors.map(|donor| donor.genome().clone()),
+        ))
+    }
+}
@@ -1,10 +1,7 @@
 use anyhow::Result;
 
 use super::{composable::Wrappable, Composable, Operator};
-use crate::{
-    individual::{ec::EcIndividual, scorer::Scorer},
-    population::Population,
-};
+use crate::individual::{ec::EcIndividual, scorer::Scorer};
 
 pub struct GenomeScorer<GM, S> {
     genome_maker: GM,
@@ -29,16 +26,18 @@ impl<G, S> Wrappable<G> for GenomeScorer<G, S> {
 }
 
 // scorer: &Genome -> TestResults<R>
-impl<'pop, GM, S, R, P> Operator<&'pop P> for GenomeScorer<GM, S>
+//
+// The input is usually a reference to the population, but can be anything
+// the genome maker accepts, e.g., a `Target` in a `OneToOneGeneration`.
+impl<Input, GM, S, R> Operator<Input> for GenomeScorer<GM, S>
 where
-    P: Population,
-    GM: Operator<&'pop P>,
+    GM: Operator<Input>,
     S: Scorer<GM::Output, Score = R>,
 {
     type Output = EcIndividual<GM::Output, S::Score>;
 
-    fn apply(&self, population: &'pop P, rng: &mut rand::rngs::ThreadRng) -> Result<Self::Output> {
-        let genome = self.genome_maker.apply(population, rng)?;
+    fn apply(&self, input: Input, rng: &mut rand::rngs::ThreadRng) -> Result<Self::Output> {
+        let genome = self.genome_maker.apply(input, rng)?;
         let score = self.scorer.score(&genome);
         // TODO: We probably don't want to bake in `EcIndividual` here, but instead
         //   have things be more general than that.
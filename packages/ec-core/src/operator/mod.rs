@@ -2,6 +2,7 @@ use anyhow::Result;
 use rand::rngs::ThreadRng;
 
 pub mod composable;
+pub mod donors;
 pub mod genome_extractor;
 pub mod genome_scorer;
 pub mod identity;
@@ -20,7 +20,7 @@ use std::{cmp::Ordering, fmt::Debug, iter::Sum};
 //   closer to where they're actually needed.
 
 /// Score implicitly follows a "bigger is better" model.
-#[derive(Eq, PartialEq, Ord, PartialOrd)]
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd)]
 pub struct Score<T> {
     pub score: T,
 }
@@ -69,7 +69,7 @@ where
 
 // TODO: Rewrite `Error` using the std::cmp::Reverse type
 //   to convert `Score` to `Error`.
-#[derive(Eq, PartialEq)]
+#[derive(Clone, Eq, PartialEq)]
 pub struct Error<T> {
     pub error: T,
 }
@@ -0,0 +1,38 @@
+use std::path::PathBuf;
+
+use clap::Parser;
+
+#[derive(clap::ValueEnum, Copy, Clone, Debug)]
+pub enum RunModel {
+    Serial,
+    Parallel,
+}
+
+/// Grammatical evolution symbolic regression in Rust
+#[derive(Parser, Debug, Clone)]
+#[clap(author, version, about, long_about = None)]
+pub struct Args {
+    /// Should we use parallelism when doing the run?
+    #[clap(short, long, value_enum, default_value_t = RunModel::Parallel)]
+    pub run_model: RunModel,
+
+    /// The BNF grammar file to use
+    #[clap(short, long, value_parser, default_value = concat!(env!("CARGO_MANIFEST_DIR"), "/examples/symbolic_regression/grammar.bnf"))]
+    pub grammar: PathBuf,
+
+    /// Population size
+    #[clap(short, long, value_parser, default_value_t = 500)]
+    pub population_size: usize,
+
+    /// Number of codons in each genome
+    #[clap(short = 'l', long, value_parser, default_value_t = 100)]
+    pub genome_length: usize,
+
+    /// Maximum number of times the mapping can wrap around the genome
+    #[clap(short = 'w', long, value_parser, default_value_t = 2)]
+    pub max_wraps: usize,
+
+    /// Number of generations to run
+    #[clap(short, long, value_parser, default_value_t = 100)]
+    pub num_generations: usize,
+}
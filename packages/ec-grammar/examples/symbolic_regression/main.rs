@@ -0,0 +1,170 @@
+pub mod args;
+
+use std::ops::Not;
+
+use anyhow::{ensure, Result};
+use clap::Parser;
+use ec_core::{
+    generation::Generation,
+    generator::{collection::ConvertToCollectionGenerator, Generator},
+    individual::ec::{EcIndividual, WithScorer},
+    operator::{
+        genome_extractor::GenomeExtractor,
+        genome_scorer::GenomeScorer,
+        mutator::Mutate,
+        recombinator::Recombine,
+        selector::{
+            best::Best, lexicase::Lexicase, tournament::Tournament, weighted::Weighted, Select,
+            Selector,
+        },
+        Composable,
+    },
+    test_results::{self, TestResults},
+};
+use ec_grammar::{
+    codon::RandomGenome,
+    grammar::Grammar,
+    mapper::{Derivation, DerivationNode, DerivationTree, InvalidIndividual, Mapper},
+    scorer::GrammarScorer,
+};
+use ec_linear::{
+    genome::vector::Vector, mutator::with_one_over_length::WithOneOverLength,
+    recombinator::two_point_xo::TwoPointXo,
+};
+use ordered_float::OrderedFloat;
+use rand::thread_rng;
+
+use crate::args::{Args, RunModel};
+
+/*
+ * This is the same "simple regression" problem as the PushGP version in
+ * `push/examples/simple_regression`, but solved with grammatical evolution
+ * using the grammar in `grammar.bnf`.
+ */
+
+/// Evaluate a derivation tree from our expression grammar directly, rather
+/// than parsing the generated string.
+fn evaluate(tree: &DerivationTree, x: f64) -> f64 {
+    match tree.children() {
+        // ( <expr> <op> <expr> )
+        [_, DerivationNode::NonTerminal(lhs), DerivationNode::NonTerminal(op), DerivationNode::NonTerminal(rhs), _] =>
+        {
+            let (lhs, rhs) = (evaluate(lhs, x), evaluate(rhs, x));
+            match op.to_string().as_str() {
+                "+" => lhs + rhs,
+                "-" => lhs - rhs,
+                "*" => lhs * rhs,
+                _ => f64::NAN,
+            }
+        }
+        // <var>
+        [DerivationNode::NonTerminal(var)] => evaluate(var, x),
+        [DerivationNode::Terminal(terminal)] if terminal == "x" => x,
+        [DerivationNode::Terminal(terminal)] => terminal.parse().unwrap_or(f64::NAN),
+        _ => f64::NAN,
+    }
+}
+
+fn main() -> Result<()> {
+    // Using `Error` in `TestResults<Error>` will have the run favor smaller
+    // values, where using `Score` (e.g., `TestResults<Score>`) will have the run
+    // favor larger values.
+    type Pop = Vec<EcIndividual<Vector<u8>, TestResults<test_results::Error<OrderedFloat<f64>>>>>;
+    // The error to use for invalid individuals, or when an expression's value
+    // isn't a number.
+    let penalty_value = OrderedFloat(1_000.0);
+
+    let args = Args::parse();
+
+    let grammar = Grammar::from_file(&args.grammar)?;
+    let mapper = Mapper::new(grammar, args.max_wraps);
+
+    // Inputs from -4 (inclusive) to 4 (exclusive) in increments of 0.25.
+    let training_inputs = (-4 * 4..4 * 4)
+        .map(|n| f64::from(n) / 4.0)
+        .collect::<Vec<_>>();
+    let num_test_cases = training_inputs.len();
+
+    /*
+     * The target polynomial is x^3 - 2x^2 - x
+     */
+    let scorer = GrammarScorer::new(
+        mapper,
+        |derivation: &Derivation| -> TestResults<test_results::Error<OrderedFloat<f64>>> {
+            training_inputs
+                .iter()
+                .map(|&x| {
+                    let expected = x.mul_add(x * (x - 2.0), -x);
+                    let error = (evaluate(&derivation.tree, x) - expected).abs();
+                    if error.is_nan() {
+                        penalty_value
+                    } else {
+                        OrderedFloat(error)
+                    }
+                })
+                .collect()
+        },
+        |_: &InvalidIndividual| -> TestResults<test_results::Error<OrderedFloat<f64>>> {
+            std::iter::repeat_n(penalty_value, num_test_cases).collect()
+        },
+    );
+
+    let lexicase = Lexicase::new(num_test_cases);
+    let binary_tournament = Tournament::new(2);
+
+    let selector: Weighted<Pop> = Weighted::new(Best, 1)
+        .with_selector(lexicase, 5)
+        .with_selector(binary_tournament, args.population_size - 1);
+
+    let mut rng = thread_rng();
+
+    let population = RandomGenome::new(args.genome_length)
+        .with_scorer::<_, Vector<u8>>(&scorer)
+        .into_collection_generator(args.population_size)
+        .generate(&mut rng)?;
+
+    ensure!(population.is_empty().not());
+
+    let best = Best.select(&population, &mut rng)?;
+    println!("Best initial individual is {best:?}");
+
+    let make_new_individual = Select::new(selector)
+        .apply_twice()
+        .then_map(GenomeExtractor)
+        .then(Recombine::new(TwoPointXo))
+        .then(Mutate::new(WithOneOverLength))
+        .wrap::<GenomeScorer<_, _>>(&scorer);
+
+    let mut generation = Generation::new(make_new_individual, population);
+
+    // TODO: It might be useful to insert some kind of logging system so we can
+    // make this less imperative in nature.
+
+    for generation_number in 0..args.num_generations {
+        match args.run_model {
+            RunModel::Serial => generation.serial_next()?,
+            RunModel::Parallel => generation.par_next()?,
+        }
+
+        let best = Best.select(generation.population(), &mut rng)?;
+        let phenotype = scorer.mapper().map(&best.genome.genes).map_or_else(
+            |error| error.to_string(),
+            |derivation| derivation.phenotype(),
+        );
+        // TODO: Change 2 to be the smallest number of digits needed for
+        // args.num_generations-1.
+        println!(
+            "Generation {generation_number:2} best is {phenotype} with total error {} ({} invalid \
+             individuals so far)",
+            best.test_results.total_result.error,
+            scorer.num_invalid()
+        );
+
+        if best.test_results.total_result.error < OrderedFloat(1e-6) {
+            println!("SUCCESS");
+            break;
+        }
+    }
+
+    Ok(())
+}
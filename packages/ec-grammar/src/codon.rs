@@ -0,0 +1,86 @@
+use ec_core::generator::Generator;
+use ec_linear::genome::vector::Vector;
+use rand::{
+    distributions::{Distribution, Standard},
+    rngs::ThreadRng,
+    Rng,
+};
+
+/// The integer types that can be used as codons in a grammatical evolution
+/// genome, typically `u8` or `u32`.
+///
+/// When mapping, the codon `c` picks production `c % n` of a rule with `n`
+/// productions.
+pub trait Codon: Copy + Into<u64> {}
+
+impl<C> Codon for C where C: Copy + Into<u64> {}
+
+/// The index of the production chosen by `codon` from `num_choices`
+/// productions.
+pub(crate) fn choose<C: Codon>(codon: C, num_choices: usize) -> usize {
+    debug_assert!(num_choices > 0);
+    // A `usize` always fits in a `u64` on the platforms we support, and the
+    // remainder is less than `num_choices` so it always fits back in a
+    // `usize`.
+    let num_choices_u64 = u64::try_from(num_choices).unwrap_or(u64::MAX);
+    usize::try_from(codon.into() % num_choices_u64).unwrap_or_default()
+}
+
+/// Generate uniformly random codons, e.g., as the gene generator for
+/// `Umad`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct UniformCodon;
+
+impl<C> Generator<C> for UniformCodon
+where
+    C: Codon,
+    Standard: Distribution<C>,
+{
+    fn generate(&self, rng: &mut ThreadRng) -> anyhow::Result<C> {
+        Ok(rng.gen())
+    }
+}
+
+/// Generate genomes with `length` uniformly random codons.
+#[derive(Debug, Clone, Copy)]
+pub struct RandomGenome {
+    pub length: usize,
+}
+
+impl RandomGenome {
+    #[must_use]
+    pub const fn new(length: usize) -> Self {
+        Self { length }
+    }
+}
+
+impl<C> Generator<Vector<C>> for RandomGenome
+where
+    C: Codon,
+    Standard: Distribution<C>,
+{
+    fn generate(&self, rng: &mut ThreadRng) -> anyhow::Result<Vector<C>> {
+        Ok((0..self.length).map(|_| rng.gen()).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn choose_wraps_around() {
+        assert_eq!(choose(7u8, 3), 1);
+        assert_eq!(choose(255u8, 2), 1);
+        assert_eq!(choose(u32::MAX, 10), 5);
+        assert_eq!(choose(0u32, 1), 0);
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn random_genome_has_requested_length() {
+        let mut rng = rand::thread_rng();
+        let genome: Vector<u32> = RandomGenome::new(17).generate(&mut rng).unwrap();
+        assert_eq!(genome.genes.len(), 17);
+    }
+}
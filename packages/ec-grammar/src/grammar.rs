@@ -0,0 +1,379 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+/// A context-free grammar, typically read from a BNF file.
+///
+/// The grammar file consists of rules of the form
+///
+/// ```text
+/// <expr> ::= "(" <expr> <op> <expr> ")" | <var>
+/// <op>   ::= "+" | "-" | "*"
+///          | "/"
+/// <var>  ::= x | y
+/// ```
+///
+/// where the first rule defines the start symbol. A rule's productions are
+/// separated by `|` and can be continued on following lines that start
+/// with `|`. Terminals are either quoted (with `"` or `'`) or bare runs of
+/// non-whitespace characters. Terminals are concatenated with nothing in
+/// between when building output strings, so any spaces that are needed in
+/// the output have to be quoted, e.g., `"if" " " <cond>`. Lines starting
+/// with `#` are comments.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grammar {
+    // The first rule is the start rule.
+    rules: Vec<Rule>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rule {
+    name: String,
+    productions: Vec<Production>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Production {
+    symbols: Vec<Symbol>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Symbol {
+    Terminal(String),
+    /// The index (in the grammar) of the rule for this non-terminal.
+    NonTerminal(usize),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum GrammarError {
+    #[error("Failed to read the grammar file {path:?}")]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error(
+        "Line {line}: expected a rule of the form `<name> ::= ...` or a continuation line \
+         starting with `|`"
+    )]
+    ExpectedRule { line: usize },
+    #[error("Line {line}: unterminated quoted terminal")]
+    UnterminatedQuote { line: usize },
+    #[error("Line {line}: unterminated non-terminal `<{name}`")]
+    UnterminatedNonTerminal { line: usize, name: String },
+    #[error("Line {line}: the rule <{name}> is defined more than once")]
+    DuplicateRule { line: usize, name: String },
+    #[error("Line {line}: the non-terminal <{name}> is never defined")]
+    UndefinedNonTerminal { line: usize, name: String },
+    #[error("The grammar doesn't contain any rules")]
+    Empty,
+}
+
+impl Grammar {
+    /// Read and parse a grammar from a BNF file.
+    ///
+    /// # Errors
+    ///
+    /// This fails if the file can't be read or isn't a valid grammar.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, GrammarError> {
+        let path = path.as_ref();
+        let contents = fs::read_to_string(path).map_err(|source| GrammarError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        contents.parse()
+    }
+
+    /// The index of the start rule, which is always the first rule in the
+    /// grammar.
+    #[must_use]
+    pub const fn start(&self) -> usize {
+        0
+    }
+
+    #[must_use]
+    pub fn rules(&self) -> &[Rule] {
+        &self.rules
+    }
+
+    /// # Panics
+    ///
+    /// This panics if `index` isn't the index of a rule in this grammar.
+    #[must_use]
+    pub fn rule(&self, index: usize) -> &Rule {
+        &self.rules[index]
+    }
+
+    /// The index of the rule with the given name, if there is one.
+    #[must_use]
+    pub fn rule_index(&self, name: &str) -> Option<usize> {
+        self.rules.iter().position(|rule| rule.name == name)
+    }
+}
+
+impl Rule {
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    #[must_use]
+    pub fn productions(&self) -> &[Production] {
+        &self.productions
+    }
+}
+
+impl Production {
+    #[must_use]
+    pub fn symbols(&self) -> &[Symbol] {
+        &self.symbols
+    }
+}
+
+/// A symbol as it appears in the grammar file, before we've checked that
+/// all the non-terminals are defined.
+enum RawSymbol {
+    Terminal(String),
+    NonTerminal(String),
+}
+
+struct RawRule {
+    name: String,
+    // Each symbol is paired with the line it appears on for error reporting.
+    productions: Vec<Vec<(RawSymbol, usize)>>,
+}
+
+impl FromStr for Grammar {
+    type Err = GrammarError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut raw_rules: Vec<RawRule> = Vec::new();
+        for (index, text) in s.lines().enumerate() {
+            let line = index + 1;
+            let text = text.trim();
+            if text.is_empty() || text.starts_with('#') {
+                continue;
+            }
+            if let Some(continuation) = text.strip_prefix('|') {
+                let rule = raw_rules
+                    .last_mut()
+                    .ok_or(GrammarError::ExpectedRule { line })?;
+                // The `|` at the start of the line ends the last production of
+                // the previous line and starts a new one.
+                rule.productions
+                    .extend(parse_productions(continuation, line)?);
+            } else {
+                let (name, productions) = text
+                    .split_once("::=")
+                    .ok_or(GrammarError::ExpectedRule { line })?;
+                let name = name
+                    .trim()
+                    .strip_prefix('<')
+                    .and_then(|name| name.strip_suffix('>'))
+                    .ok_or(GrammarError::ExpectedRule { line })?;
+                if raw_rules.iter().any(|rule| rule.name == name) {
+                    return Err(GrammarError::DuplicateRule {
+                        line,
+                        name: name.to_string(),
+                    });
+                }
+                raw_rules.push(RawRule {
+                    name: name.to_string(),
+                    productions: parse_productions(productions, line)?,
+                });
+            }
+        }
+        if raw_rules.is_empty() {
+            return Err(GrammarError::Empty);
+        }
+
+        let indices = raw_rules
+            .iter()
+            .enumerate()
+            .map(|(index, rule)| (rule.name.clone(), index))
+            .collect::<HashMap<_, _>>();
+        let rules = raw_rules
+            .into_iter()
+            .map(|raw_rule| {
+                let productions = raw_rule
+                    .productions
+                    .into_iter()
+                    .map(|symbols| {
+                        let symbols = symbols
+                            .into_iter()
+                            .map(|(symbol, line)| match symbol {
+                                RawSymbol::Terminal(text) => Ok(Symbol::Terminal(text)),
+                                RawSymbol::NonTerminal(name) => indices
+                                    .get(&name)
+                                    .map(|&index| Symbol::NonTerminal(index))
+                                    .ok_or(GrammarError::UndefinedNonTerminal { line, name }),
+                            })
+                            .collect::<Result<_, _>>()?;
+                        Ok(Production { symbols })
+                    })
+                    .collect::<Result<_, GrammarError>>()?;
+                Ok(Rule {
+                    name: raw_rule.name,
+                    productions,
+                })
+            })
+            .collect::<Result<_, GrammarError>>()?;
+        Ok(Self { rules })
+    }
+}
+
+/// Split the right-hand side of a rule into its `|`-separated productions.
+fn parse_productions(
+    text: &str,
+    line: usize,
+) -> Result<Vec<Vec<(RawSymbol, usize)>>, GrammarError> {
+    let mut productions = Vec::new();
+    let mut symbols = Vec::new();
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '|' => productions.push(std::mem::take(&mut symbols)),
+            '"' | '\'' => {
+                let mut terminal = String::new();
+                loop {
+                    match chars.next() {
+                        Some(next) if next == c => break,
+                        Some(next) => terminal.push(next),
+                        None => return Err(GrammarError::UnterminatedQuote { line }),
+                    }
+                }
+                symbols.push((RawSymbol::Terminal(terminal), line));
+            }
+            '<' => {
+                let mut name = String::new();
+                loop {
+                    match chars.next() {
+                        Some('>') => break,
+                        Some(next) => name.push(next),
+                        None => return Err(GrammarError::UnterminatedNonTerminal { line, name }),
+                    }
+                }
+                symbols.push((RawSymbol::NonTerminal(name), line));
+            }
+            c if c.is_whitespace() => {}
+            c => {
+                let mut terminal = String::from(c);
+                while let Some(&next) = chars.peek() {
+                    if next.is_whitespace() || matches!(next, '|' | '<' | '"' | '\'') {
+                        break;
+                    }
+                    terminal.push(next);
+                    chars.next();
+                }
+                symbols.push((RawSymbol::Terminal(terminal), line));
+            }
+        }
+    }
+    productions.push(symbols);
+    Ok(productions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GRAMMAR: &str = r#"
+        # A tiny expression grammar
+        <expr> ::= "(" <expr> <op> <expr> ")" | <var>
+        <op>   ::= + | - | *
+                 | '/'
+        <var>  ::= x | "1.0"
+    "#;
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn parse_expression_grammar() {
+        let grammar: Grammar = GRAMMAR.parse().unwrap();
+        assert_eq!(grammar.rules().len(), 3);
+        assert_eq!(grammar.rule(grammar.start()).name(), "expr");
+
+        let expr = grammar.rule(0);
+        assert_eq!(expr.productions().len(), 2);
+        assert_eq!(
+            expr.productions()[0].symbols(),
+            &[
+                Symbol::Terminal("(".to_string()),
+                Symbol::NonTerminal(0),
+                Symbol::NonTerminal(1),
+                Symbol::NonTerminal(0),
+                Symbol::Terminal(")".to_string()),
+            ]
+        );
+
+        let op = grammar.rule(1);
+        assert_eq!(op.productions().len(), 4);
+        assert_eq!(
+            op.productions()[3].symbols(),
+            &[Symbol::Terminal("/".to_string())]
+        );
+
+        assert_eq!(grammar.rule_index("var"), Some(2));
+        assert_eq!(
+            grammar.rule(2).productions()[1].symbols(),
+            &[Symbol::Terminal("1.0".to_string())]
+        );
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn quoted_terminals_can_contain_special_characters() {
+        let grammar: Grammar = r#"<s> ::= "a | <b>" ' '"#.parse().unwrap();
+        assert_eq!(
+            grammar.rule(0).productions()[0].symbols(),
+            &[
+                Symbol::Terminal("a | <b>".to_string()),
+                Symbol::Terminal(" ".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn undefined_non_terminal_is_an_error() {
+        let result = "<s> ::= <t>\n<u> ::= x".parse::<Grammar>();
+        assert!(matches!(
+            result,
+            Err(GrammarError::UndefinedNonTerminal { line: 1, name }) if name == "t"
+        ));
+    }
+
+    #[test]
+    fn duplicate_rule_is_an_error() {
+        let result = "<s> ::= x\n<s> ::= y".parse::<Grammar>();
+        assert!(matches!(
+            result,
+            Err(GrammarError::DuplicateRule { line: 2, .. })
+        ));
+    }
+
+    #[test]
+    fn malformed_lines_are_errors() {
+        assert!(matches!(
+            "s ::= x".parse::<Grammar>(),
+            Err(GrammarError::ExpectedRule { line: 1 })
+        ));
+        assert!(matches!(
+            "| x".parse::<Grammar>(),
+            Err(GrammarError::ExpectedRule { line: 1 })
+        ));
+        assert!(matches!(
+            "<s> ::= \"x".parse::<Grammar>(),
+            Err(GrammarError::UnterminatedQuote { line: 1 })
+        ));
+        assert!(matches!(
+            "<s> ::= <x".parse::<Grammar>(),
+            Err(GrammarError::UnterminatedNonTerminal { line: 1, .. })
+        ));
+        assert!(matches!(
+            "# nothing here".parse::<Grammar>(),
+            Err(GrammarError::Empty)
+        ));
+    }
+}
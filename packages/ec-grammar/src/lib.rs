@@ -0,0 +1,4 @@
+pub mod codon;
+pub mod grammar;
+pub mod mapper;
+pub mod scorer;
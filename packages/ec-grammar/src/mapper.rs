@@ -0,0 +1,302 @@
+use std::fmt::Display;
+
+use crate::{
+    codon::{choose, Codon},
+    grammar::{Grammar, Symbol},
+};
+
+/// Maps genomes (sequences of codons) to derivation trees using a grammar.
+///
+/// Mapping does a leftmost derivation from the grammar's start rule, using
+/// the next codon to choose a production whenever a rule has more than one
+/// production (rules with a single production don't consume a codon). If
+/// we run out of codons we wrap around to the start of the genome, up to
+/// `max_wraps` times; individuals that need more codons than that, or whose
+/// derivation trees get deeper than `max_depth`, are invalid.
+#[derive(Debug, Clone)]
+pub struct Mapper {
+    grammar: Grammar,
+    max_wraps: usize,
+    max_depth: usize,
+}
+
+/// The ways that mapping a genome can fail.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum InvalidIndividual {
+    #[error("The genome ran out of codons after wrapping {max_wraps} times")]
+    WrapsExceeded { max_wraps: usize },
+    #[error("The derivation tree exceeded the maximum depth of {max_depth}")]
+    DepthExceeded { max_depth: usize },
+}
+
+/// The result of successfully mapping a genome.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Derivation {
+    pub tree: DerivationTree,
+    /// The total number of codons used, including any re-used by wrapping.
+    pub codons_used: usize,
+    /// The number of times we wrapped around to the start of the genome.
+    pub wraps: usize,
+}
+
+/// The expansion of a single non-terminal: which rule it was, which of that
+/// rule's productions was chosen, and the expansion of each of the
+/// production's symbols.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DerivationTree {
+    rule: usize,
+    production: usize,
+    children: Vec<DerivationNode>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DerivationNode {
+    Terminal(String),
+    NonTerminal(DerivationTree),
+}
+
+impl Mapper {
+    /// The default maximum depth of derivation trees.
+    pub const DEFAULT_MAX_DEPTH: usize = 100;
+
+    #[must_use]
+    pub const fn new(grammar: Grammar, max_wraps: usize) -> Self {
+        Self {
+            grammar,
+            max_wraps,
+            max_depth: Self::DEFAULT_MAX_DEPTH,
+        }
+    }
+
+    #[must_use]
+    pub const fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    #[must_use]
+    pub const fn grammar(&self) -> &Grammar {
+        &self.grammar
+    }
+
+    /// Map a sequence of codons to a derivation tree.
+    ///
+    /// # Errors
+    ///
+    /// This returns an `InvalidIndividual` error if the codons (with
+    /// wrapping) run out before the derivation is complete, or the
+    /// derivation tree gets too deep.
+    pub fn map<C: Codon>(&self, codons: &[C]) -> Result<Derivation, InvalidIndividual> {
+        let mut codon_source = CodonSource {
+            codons,
+            position: 0,
+            max_wraps: self.max_wraps,
+        };
+        let tree = self.expand(self.grammar.start(), 0, &mut codon_source)?;
+        Ok(Derivation {
+            tree,
+            codons_used: codon_source.position,
+            wraps: codon_source.wraps(),
+        })
+    }
+
+    fn expand<C: Codon>(
+        &self,
+        rule_index: usize,
+        depth: usize,
+        codon_source: &mut CodonSource<C>,
+    ) -> Result<DerivationTree, InvalidIndividual> {
+        if depth > self.max_depth {
+            return Err(InvalidIndividual::DepthExceeded {
+                max_depth: self.max_depth,
+            });
+        }
+        let productions = self.grammar.rule(rule_index).productions();
+        let production = if productions.len() == 1 {
+            0
+        } else {
+            choose(codon_source.next()?, productions.len())
+        };
+        let children = productions[production]
+            .symbols()
+            .iter()
+            .map(|symbol| match symbol {
+                Symbol::Terminal(text) => Ok(DerivationNode::Terminal(text.clone())),
+                Symbol::NonTerminal(rule) => self
+                    .expand(*rule, depth + 1, codon_source)
+                    .map(DerivationNode::NonTerminal),
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(DerivationTree {
+            rule: rule_index,
+            production,
+            children,
+        })
+    }
+}
+
+struct CodonSource<'a, C> {
+    codons: &'a [C],
+    // The total number of codons consumed so far.
+    position: usize,
+    max_wraps: usize,
+}
+
+impl<C: Codon> CodonSource<'_, C> {
+    fn next(&mut self) -> Result<C, InvalidIndividual> {
+        let wraps_exceeded = InvalidIndividual::WrapsExceeded {
+            max_wraps: self.max_wraps,
+        };
+        if self.codons.is_empty() || self.position / self.codons.len() > self.max_wraps {
+            return Err(wraps_exceeded);
+        }
+        let codon = self.codons[self.position % self.codons.len()];
+        self.position += 1;
+        Ok(codon)
+    }
+
+    const fn wraps(&self) -> usize {
+        if self.codons.is_empty() {
+            0
+        } else {
+            self.position.saturating_sub(1) / self.codons.len()
+        }
+    }
+}
+
+impl Derivation {
+    /// The string generated by this derivation, i.e., the concatenation of
+    /// all the terminals in the derivation tree.
+    #[must_use]
+    pub fn phenotype(&self) -> String {
+        self.tree.to_string()
+    }
+}
+
+impl DerivationTree {
+    /// The index (in the grammar) of the rule that was expanded.
+    #[must_use]
+    pub const fn rule(&self) -> usize {
+        self.rule
+    }
+
+    /// The index of the production (in the rule) that was chosen.
+    #[must_use]
+    pub const fn production(&self) -> usize {
+        self.production
+    }
+
+    #[must_use]
+    pub fn children(&self) -> &[DerivationNode] {
+        &self.children
+    }
+
+    /// The depth of this tree, where a tree with no non-terminal children
+    /// has depth 0.
+    #[must_use]
+    pub fn depth(&self) -> usize {
+        self.children
+            .iter()
+            .filter_map(|child| match child {
+                DerivationNode::Terminal(_) => None,
+                DerivationNode::NonTerminal(tree) => Some(tree.depth() + 1),
+            })
+            .max()
+            .unwrap_or_default()
+    }
+}
+
+/// Display the string generated by the derivation tree.
+impl Display for DerivationTree {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.children.iter().try_for_each(|child| match child {
+            DerivationNode::Terminal(text) => write!(f, "{text}"),
+            DerivationNode::NonTerminal(tree) => write!(f, "{tree}"),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GRAMMAR: &str = r#"
+        <expr> ::= "(" <expr> <op> <expr> ")" | <var>
+        <op>   ::= + | - | *
+        <var>  ::= x | y
+        "#;
+
+    #[allow(clippy::unwrap_used)]
+    fn mapper(max_wraps: usize) -> Mapper {
+        Mapper::new(GRAMMAR.parse().unwrap(), max_wraps)
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn map_simple_genome() {
+        // <expr> -> 0: ( <expr> <op> <expr> )
+        //   <expr> -> 1: <var> -> 0: x
+        //   <op> -> 2: *
+        //   <expr> -> 1: <var> -> 1: y
+        let codons: [u8; 6] = [0, 1, 0, 2, 1, 1];
+        let derivation = mapper(0).map(&codons).unwrap();
+        assert_eq!(derivation.phenotype(), "(x*y)");
+        assert_eq!(derivation.codons_used, 6);
+        assert_eq!(derivation.wraps, 0);
+        assert_eq!(derivation.tree.rule(), 0);
+        assert_eq!(derivation.tree.production(), 0);
+        assert_eq!(derivation.tree.depth(), 2);
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn codons_are_taken_modulo_the_number_of_choices() {
+        let codons: [u32; 6] = [10, 7, 12, 5, 3, 201];
+        assert_eq!(mapper(0).map(&codons).unwrap().phenotype(), "(x*y)");
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn wrapping_reuses_codons() {
+        // (x+x) needs codons 0 1 0 0 1 0; with wrapping [0, 1, 0] is enough.
+        let codons: [u8; 3] = [0, 1, 0];
+        assert_eq!(
+            mapper(0).map(&codons),
+            Err(InvalidIndividual::WrapsExceeded { max_wraps: 0 })
+        );
+        let derivation = mapper(1).map(&codons).unwrap();
+        assert_eq!(derivation.phenotype(), "(x+x)");
+        assert_eq!(derivation.codons_used, 6);
+        assert_eq!(derivation.wraps, 1);
+    }
+
+    #[test]
+    fn endless_recursion_is_invalid() {
+        // Always choosing the recursive production never terminates, so we
+        // either run out of wraps or depth.
+        let codons: [u8; 1] = [0];
+        assert_eq!(
+            mapper(1_000).with_max_depth(10).map(&codons),
+            Err(InvalidIndividual::DepthExceeded { max_depth: 10 })
+        );
+    }
+
+    #[test]
+    fn empty_genome_is_invalid() {
+        let codons: [u8; 0] = [];
+        assert_eq!(
+            mapper(3).map(&codons),
+            Err(InvalidIndividual::WrapsExceeded { max_wraps: 3 })
+        );
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn single_production_rules_do_not_use_codons() {
+        let grammar = "<s> ::= <a> <a>\n<a> ::= \"a\"".parse().unwrap();
+        let codons: [u8; 0] = [];
+        let derivation = Mapper::new(grammar, 0).map(&codons).unwrap();
+        assert_eq!(derivation.phenotype(), "aa");
+        assert_eq!(derivation.codons_used, 0);
+    }
+}
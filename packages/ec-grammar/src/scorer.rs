@@ -0,0 +1,130 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use ec_core::individual::scorer::Scorer;
+use ec_linear::genome::vector::Vector;
+
+use crate::{
+    codon::Codon,
+    mapper::{Derivation, InvalidIndividual, Mapper},
+};
+
+/// Score grammatical evolution genomes by mapping them to derivations and
+/// then scoring the derivations with `scorer`.
+///
+/// Invalid individuals (those that can't be mapped) are scored by
+/// `invalid_scorer` instead, which is given the reason the individual was
+/// invalid, and are counted so runs can report how many there were.
+#[derive(Debug)]
+pub struct GrammarScorer<F, I> {
+    mapper: Mapper,
+    scorer: F,
+    invalid_scorer: I,
+    num_invalid: AtomicUsize,
+}
+
+impl<F, I> GrammarScorer<F, I> {
+    pub const fn new(mapper: Mapper, scorer: F, invalid_scorer: I) -> Self {
+        Self {
+            mapper,
+            scorer,
+            invalid_scorer,
+            num_invalid: AtomicUsize::new(0),
+        }
+    }
+
+    #[must_use]
+    pub const fn mapper(&self) -> &Mapper {
+        &self.mapper
+    }
+
+    /// The number of invalid individuals this scorer has seen so far.
+    pub fn num_invalid(&self) -> usize {
+        self.num_invalid.load(Ordering::Relaxed)
+    }
+}
+
+impl<C, F, I, S> Scorer<Vector<C>> for GrammarScorer<F, I>
+where
+    C: Codon,
+    F: Fn(&Derivation) -> S,
+    I: Fn(&InvalidIndividual) -> S,
+{
+    type Score = S;
+
+    fn score(&self, genome: &Vector<C>) -> Self::Score {
+        self.mapper.map(&genome.genes).map_or_else(
+            |invalid| {
+                self.num_invalid.fetch_add(1, Ordering::Relaxed);
+                (self.invalid_scorer)(&invalid)
+            },
+            |derivation| (self.scorer)(&derivation),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ec_core::{
+        generator::Generator,
+        operator::{mutator::Mutator, recombinator::Recombinator},
+    };
+    use ec_linear::{
+        mutator::{umad::Umad, with_one_over_length::WithOneOverLength},
+        recombinator::two_point_xo::TwoPointXo,
+    };
+
+    use super::*;
+    use crate::codon::{RandomGenome, UniformCodon};
+
+    const GRAMMAR: &str = r#"
+        <expr> ::= "(" <expr> <op> <expr> ")" | <var>
+        <op>   ::= + | - | *
+        <var>  ::= x | y
+        "#;
+
+    #[allow(clippy::unwrap_used)]
+    fn scorer() -> GrammarScorer<impl Fn(&Derivation) -> usize, impl Fn(&InvalidIndividual) -> usize>
+    {
+        let mapper = Mapper::new(GRAMMAR.parse().unwrap(), 0);
+        GrammarScorer::new(
+            mapper,
+            |derivation: &Derivation| derivation.phenotype().len(),
+            |_: &InvalidIndividual| usize::MAX,
+        )
+    }
+
+    #[test]
+    fn valid_and_invalid_individuals() {
+        let scorer = scorer();
+        let valid = Vector {
+            genes: vec![0u8, 1, 0, 2, 1, 1],
+        };
+        assert_eq!(scorer.score(&valid), "(x*y)".len());
+        assert_eq!(scorer.num_invalid(), 0);
+
+        let invalid = Vector { genes: vec![0u8] };
+        assert_eq!(scorer.score(&invalid), usize::MAX);
+        assert_eq!(scorer.num_invalid(), 1);
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn linear_operators_work_on_codon_genomes() {
+        let mut rng = rand::thread_rng();
+        let scorer = scorer();
+        let generator = RandomGenome::new(20);
+        let first: Vector<u8> = generator.generate(&mut rng).unwrap();
+        let second: Vector<u8> = generator.generate(&mut rng).unwrap();
+
+        let child = TwoPointXo.recombine([first, second], &mut rng).unwrap();
+        assert_eq!(child.genes.len(), 20);
+        let child = WithOneOverLength.mutate(child, &mut rng).unwrap();
+        assert_eq!(child.genes.len(), 20);
+        let child = Umad::new(0.1, 0.1, UniformCodon)
+            .mutate(child, &mut rng)
+            .unwrap();
+        // Every genome maps to something or is invalid, but scoring never
+        // fails.
+        let _ = scorer.score(&child);
+    }
+}
@@ -0,0 +1,111 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use ec_core::operator::{mutator::Mutator, recombinator::Recombinator};
+use ec_linear::{
+    benchmark::bitstring::hiff,
+    genome::bitstring::Bitstring,
+    mutator::with_one_over_length::WithOneOverLength,
+    recombinator::{two_point_xo::TwoPointXo, uniform_xo::UniformXo},
+};
+
+const NUM_BITS: [usize; 3] = [128, 4_096, 131_072];
+
+#[allow(clippy::unwrap_used)]
+fn random_bitstring(num_bits: usize) -> Bitstring {
+    Bitstring::random(num_bits, &mut rand::thread_rng()).unwrap()
+}
+
+fn compute_hiff(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Compute HIFF on a random bitstring");
+    for num_bits in NUM_BITS {
+        let bitstring = random_bitstring(num_bits);
+        group.bench_with_input(
+            BenchmarkId::from_parameter(num_bits),
+            &bitstring,
+            |b, bits| {
+                b.iter(|| hiff(black_box(bits)));
+            },
+        );
+    }
+    group.finish();
+}
+
+fn count_ones(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Count the ones in a random bitstring");
+    for num_bits in NUM_BITS {
+        let bitstring = random_bitstring(num_bits);
+        group.bench_with_input(
+            BenchmarkId::from_parameter(num_bits),
+            &bitstring,
+            |b, bits| {
+                b.iter(|| black_box(bits).count_ones());
+            },
+        );
+    }
+    group.finish();
+}
+
+fn hamming_distance(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Hamming distance between random bitstrings");
+    for num_bits in NUM_BITS {
+        let pair = (random_bitstring(num_bits), random_bitstring(num_bits));
+        group.bench_with_input(
+            BenchmarkId::from_parameter(num_bits),
+            &pair,
+            |b, (first, second)| {
+                b.iter(|| black_box(first).hamming_distance(black_box(second)));
+            },
+        );
+    }
+    group.finish();
+}
+
+#[allow(clippy::unwrap_used)]
+fn recombine(c: &mut Criterion) {
+    let mut rng = rand::thread_rng();
+    let mut group = c.benchmark_group("Recombine random bitstrings");
+    for num_bits in NUM_BITS {
+        let parents = [random_bitstring(num_bits), random_bitstring(num_bits)];
+        group.bench_with_input(
+            BenchmarkId::new("two point", num_bits),
+            &parents,
+            |b, parents| {
+                b.iter(|| TwoPointXo.recombine(parents.clone(), &mut rng).unwrap());
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("uniform", num_bits),
+            &parents,
+            |b, parents| {
+                b.iter(|| UniformXo.recombine(parents.clone(), &mut rng).unwrap());
+            },
+        );
+    }
+    group.finish();
+}
+
+#[allow(clippy::unwrap_used)]
+fn mutate(c: &mut Criterion) {
+    let mut rng = rand::thread_rng();
+    let mut group = c.benchmark_group("Mutate a random bitstring with rate 1/n");
+    for num_bits in NUM_BITS {
+        let bitstring = random_bitstring(num_bits);
+        group.bench_with_input(
+            BenchmarkId::from_parameter(num_bits),
+            &bitstring,
+            |b, bits| {
+                b.iter(|| WithOneOverLength.mutate(bits.clone(), &mut rng).unwrap());
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(
+    hiff_benches,
+    compute_hiff,
+    count_ones,
+    hamming_distance,
+    recombine,
+    mutate
+);
+criterion_main!(hiff_benches);
@@ -0,0 +1,52 @@
+use clap::Parser;
+use ec_linear::benchmark::continuous::ContinuousBenchmark;
+
+#[derive(clap::ValueEnum, Copy, Clone, Debug)]
+pub enum RunModel {
+    Serial,
+    Parallel,
+}
+
+#[derive(clap::ValueEnum, Copy, Clone, Debug)]
+pub enum Benchmark {
+    Sphere,
+    Rastrigin,
+    Rosenbrock,
+    Ackley,
+}
+
+impl From<Benchmark> for ContinuousBenchmark {
+    fn from(benchmark: Benchmark) -> Self {
+        match benchmark {
+            Benchmark::Sphere => Self::Sphere,
+            Benchmark::Rastrigin => Self::Rastrigin,
+            Benchmark::Rosenbrock => Self::Rosenbrock,
+            Benchmark::Ackley => Self::Ackley,
+        }
+    }
+}
+
+/// Continuous optimisation with real-valued vectors in Rust
+#[derive(Parser, Debug, Copy, Clone)]
+#[clap(author, version, about, long_about = None)]
+pub struct Args {
+    /// Should we use parallelism when doing the run?
+    #[clap(short, long, value_enum, default_value_t = RunModel::Parallel)]
+    pub run_model: RunModel,
+
+    /// The function to minimise
+    #[clap(short, long, value_enum, default_value_t = Benchmark::Rastrigin)]
+    pub benchmark: Benchmark,
+
+    /// The number of dimensions
+    #[clap(short, long, value_parser, default_value_t = 10)]
+    pub dimensions: usize,
+
+    /// Population size
+    #[clap(short, long, value_parser, default_value_t = 200)]
+    pub population_size: usize,
+
+    /// Number of generations to run
+    #[clap(short, long, value_parser, default_value_t = 200)]
+    pub num_generations: usize,
+}
@@ -0,0 +1,99 @@
+pub mod args;
+
+use std::ops::Not;
+
+use anyhow::{ensure, Result};
+use clap::Parser;
+use ec_core::{
+    generation::Generation,
+    generator::{collection::ConvertToCollectionGenerator, Generator},
+    individual::{
+        ec::{EcIndividual, WithScorer},
+        scorer::FnScorer,
+    },
+    operator::{
+        genome_extractor::GenomeExtractor,
+        genome_scorer::GenomeScorer,
+        mutator::Mutate,
+        recombinator::Recombine,
+        selector::{best::Best, tournament::Tournament, weighted::Weighted, Select, Selector},
+        Composable,
+    },
+    test_results::{self, TestResults},
+};
+use ec_linear::{
+    benchmark::continuous::ContinuousBenchmark, genome::vector::Vector,
+    mutator::polynomial::PolynomialMutation, recombinator::sbx::Sbx,
+};
+use num_traits::ToPrimitive;
+use ordered_float::OrderedFloat;
+use rand::thread_rng;
+
+use crate::args::{Args, RunModel};
+
+fn main() -> Result<()> {
+    // The genes are `OrderedFloat`s so that genomes (and thus individuals)
+    // can be totally ordered.
+    type Genome = Vector<OrderedFloat<f64>>;
+    type Pop = Vec<EcIndividual<Genome, TestResults<test_results::Error<OrderedFloat<f64>>>>>;
+
+    let args = Args::parse();
+    let benchmark = ContinuousBenchmark::from(args.benchmark);
+    let bounds = benchmark.standard_bounds(args.dimensions)?;
+
+    // There's just one "test case": the value of the benchmark function.
+    let scorer = FnScorer(|genome: &Genome| -> TestResults<test_results::Error<_>> {
+        std::iter::once(OrderedFloat(benchmark.evaluate(&genome.genes))).collect()
+    });
+
+    let selector: Weighted<Pop> =
+        Weighted::new(Best, 1).with_selector(Tournament::new(2), args.population_size - 1);
+
+    let mut rng = thread_rng();
+
+    let population = bounds
+        .clone()
+        .with_scorer::<_, Genome>(scorer)
+        .into_collection_generator(args.population_size)
+        .generate(&mut rng)?;
+
+    ensure!(population.is_empty().not());
+
+    let best = Best.select(&population, &mut rng)?;
+    println!(
+        "Best initial value of {benchmark:?} is {}",
+        best.test_results.total_result.error
+    );
+
+    let mutation_rate = 1.0 / args.dimensions.to_f64().unwrap_or(1.0);
+    let make_new_individual = Select::new(selector)
+        .apply_twice()
+        .then_map(GenomeExtractor)
+        .then(Recombine::new(Sbx::new(15.0)?.with_bounds(bounds.clone())))
+        .then(Mutate::new(PolynomialMutation::new(
+            bounds,
+            20.0,
+            mutation_rate,
+        )?))
+        .wrap::<GenomeScorer<_, _>>(scorer);
+
+    let mut generation = Generation::new(make_new_individual, population);
+
+    for generation_number in 0..args.num_generations {
+        match args.run_model {
+            RunModel::Serial => generation.serial_next()?,
+            RunModel::Parallel => generation.par_next()?,
+        }
+
+        let best = Best.select(generation.population(), &mut rng)?;
+        println!(
+            "Generation {generation_number:3} best value is {:.6}",
+            best.test_results.total_result.error
+        );
+    }
+
+    let best = Best.select(generation.population(), &mut rng)?;
+    println!("Best solution found:\n{:?}", best.genome.genes);
+
+    Ok(())
+}
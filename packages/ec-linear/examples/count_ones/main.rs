@@ -25,6 +25,7 @@ use ec_core::{
     test_results::{self, TestResults},
 };
 use ec_linear::{
+    benchmark::bitstring::one_max,
     genome::bitstring::{Bitstring, BoolGenerator},
     mutator::with_one_over_length::WithOneOverLength,
     recombinator::two_point_xo::TwoPointXo,
@@ -33,20 +34,15 @@ use rand::thread_rng;
 
 use crate::args::{Args, RunModel};
 
-#[must_use]
-pub fn count_ones(bits: &[bool]) -> TestResults<test_results::Score<i64>> {
-    bits.iter().map(|bit| i64::from(*bit)).collect()
-}
-
 fn main() -> Result<()> {
     // Using `Error` in `TestResults<Error>` will have the run favor smaller
     // values, where using `Score` (e.g., `TestResults<Score>`) will have the run
     // favor larger values.
-    type Pop = Vec<EcIndividual<Bitstring, TestResults<test_results::Score<i64>>>>;
+    type Pop = Vec<EcIndividual<Bitstring, TestResults<test_results::Score<usize>>>>;
 
     let args = Args::parse();
 
-    let scorer = FnScorer(|bitstring: &Bitstring| count_ones(&bitstring.bits));
+    let scorer = FnScorer(|bitstring: &Bitstring| one_max(bitstring));
 
     let num_test_cases = args.bit_length;
 
@@ -113,14 +109,13 @@ fn main() -> Result<()> {
 #[cfg(test)]
 mod test {
     use ec_core::test_results::{self, TestResults};
-
-    use super::count_ones;
+    use ec_linear::{benchmark::bitstring::one_max, genome::bitstring::Bitstring};
 
     #[test]
     fn non_empty() {
-        let input = [false, true, true, true, false, true];
-        let output: TestResults<test_results::Score<i64>> =
+        let input: Bitstring = [false, true, true, true, false, true].into_iter().collect();
+        let output: TestResults<test_results::Score<usize>> =
             [0, 1, 1, 1, 0, 1].into_iter().collect();
-        assert_eq!(output, count_ones(&input));
+        assert_eq!(output, one_max(&input));
     }
 }
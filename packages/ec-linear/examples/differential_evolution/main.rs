@@ -0,0 +1,126 @@
+pub mod args;
+
+use std::ops::Not;
+
+use anyhow::{ensure, Result};
+use clap::Parser;
+use ec_core::{
+    generation::{OneToOneGeneration, Target},
+    generator::{collection::ConvertToCollectionGenerator, Generator},
+    individual::{
+        ec::{EcIndividual, WithScorer},
+        scorer::FnScorer,
+    },
+    operator::{
+        donors::{BestDonors, RandomDonors},
+        genome_extractor::GenomeExtractor,
+        genome_scorer::GenomeScorer,
+        recombinator::Recombine,
+        selector::{best::Best, Selector},
+        Composable, Operator,
+    },
+    test_results::{self, TestResults},
+};
+use ec_linear::{
+    benchmark::continuous::ContinuousBenchmark,
+    genome::vector::Vector,
+    recombinator::differential::{CurrentToBestXo, DifferentialXo},
+};
+use ordered_float::OrderedFloat;
+use rand::thread_rng;
+
+use crate::args::{Args, RunModel, Variant};
+
+// The genes are `OrderedFloat`s so that genomes (and thus individuals) can
+// be totally ordered.
+type Genome = Vector<OrderedFloat<f64>>;
+type Individual = EcIndividual<Genome, TestResults<test_results::Error<OrderedFloat<f64>>>>;
+
+fn run<C>(make_trial: C, population: Vec<Individual>, args: &Args) -> Result<()>
+where
+    C: for<'a> Operator<Target<'a, Vec<Individual>>, Output = Individual> + Send + Sync,
+{
+    let mut rng = thread_rng();
+    let mut generation = OneToOneGeneration::new(make_trial, population);
+
+    for generation_number in 0..args.num_generations {
+        match args.run_model {
+            RunModel::Serial => generation.serial_next()?,
+            RunModel::Parallel => generation.par_next()?,
+        }
+
+        let best = Best.select(generation.population(), &mut rng)?;
+        println!(
+            "Generation {generation_number:3} best value is {:.6}",
+            best.test_results.total_result.error
+        );
+    }
+
+    let best = Best.select(generation.population(), &mut rng)?;
+    println!("Best solution found:\n{:?}", best.genome.genes);
+
+    Ok(())
+}
+
+fn main() -> Result<()> {
+    let args = Args::parse();
+    let benchmark = ContinuousBenchmark::from(args.benchmark);
+    let bounds = benchmark.standard_bounds(args.dimensions)?;
+
+    // There's just one "test case": the value of the benchmark function.
+    let scorer = FnScorer(|genome: &Genome| -> TestResults<test_results::Error<_>> {
+        std::iter::once(OrderedFloat(benchmark.evaluate(&genome.genes))).collect()
+    });
+
+    let mut rng = thread_rng();
+
+    let population = bounds
+        .clone()
+        .with_scorer::<_, Genome>(scorer)
+        .into_collection_generator(args.population_size)
+        .generate(&mut rng)?;
+
+    // Each trial needs three donors that differ from its target.
+    ensure!(
+        (population.len() >= 4),
+        "Differential evolution needs a population of at least 4"
+    );
+    ensure!(population.is_empty().not());
+
+    let best = Best.select(&population, &mut rng)?;
+    println!(
+        "Best initial value of {benchmark:?} is {}",
+        best.test_results.total_result.error
+    );
+
+    let (scale_factor, crossover_rate) = (args.scale_factor, args.crossover_rate);
+    match args.variant {
+        Variant::Rand1Bin => {
+            let make_trial = RandomDonors::<3>
+                .then(GenomeExtractor)
+                .then(Recombine::new(
+                    DifferentialXo::new(scale_factor, crossover_rate)?.with_bounds(bounds),
+                ))
+                .wrap::<GenomeScorer<_, _>>(scorer);
+            run(make_trial, population, &args)
+        }
+        Variant::Best1Bin => {
+            let make_trial = BestDonors::<3>
+                .then(GenomeExtractor)
+                .then(Recombine::new(
+                    DifferentialXo::new(scale_factor, crossover_rate)?.with_bounds(bounds),
+                ))
+                .wrap::<GenomeScorer<_, _>>(scorer);
+            run(make_trial, population, &args)
+        }
+        Variant::CurrentToBest1 => {
+            let make_trial = BestDonors::<3>
+                .then(GenomeExtractor)
+                .then(Recombine::new(
+                    CurrentToBestXo::new(scale_factor, crossover_rate)?.with_bounds(bounds),
+                ))
+                .wrap::<GenomeScorer<_, _>>(scorer);
+            run(make_trial, population, &args)
+        }
+    }
+}
@@ -0,0 +1,72 @@
+use clap::Parser;
+use ec_linear::benchmark::continuous::ContinuousBenchmark;
+
+#[derive(clap::ValueEnum, Copy, Clone, Debug)]
+pub enum RunModel {
+    Serial,
+    Parallel,
+}
+
+#[derive(clap::ValueEnum, Copy, Clone, Debug)]
+pub enum Strategy {
+    /// The (1+1)-ES with the one-fifth success rule
+    OnePlusOne,
+    /// The (μ/μ,λ)-ES with self-adaptive step sizes
+    SelfAdaptive,
+    /// The covariance matrix adaptation evolution strategy
+    CmaEs,
+}
+
+#[derive(clap::ValueEnum, Copy, Clone, Debug)]
+pub enum Benchmark {
+    Sphere,
+    Rastrigin,
+    Rosenbrock,
+    Ackley,
+}
+
+impl From<Benchmark> for ContinuousBenchmark {
+    fn from(benchmark: Benchmark) -> Self {
+        match benchmark {
+            Benchmark::Sphere => Self::Sphere,
+            Benchmark::Rastrigin => Self::Rastrigin,
+            Benchmark::Rosenbrock => Self::Rosenbrock,
+            Benchmark::Ackley => Self::Ackley,
+        }
+    }
+}
+
+/// Continuous optimisation with evolution strategies in Rust
+#[derive(Parser, Debug, Copy, Clone)]
+#[clap(author, version, about, long_about = None)]
+pub struct Args {
+    /// Should we use parallelism when doing the run?
+    #[clap(short, long, value_enum, default_value_t = RunModel::Parallel)]
+    pub run_model: RunModel,
+
+    /// The evolution strategy to use
+    #[clap(short, long, value_enum, default_value_t = Strategy::CmaEs)]
+    pub strategy: Strategy,
+
+    /// The function to minimise
+    #[clap(short, long, value_enum, default_value_t = Benchmark::Rastrigin)]
+    pub benchmark: Benchmark,
+
+    /// The number of dimensions
+    #[clap(short, long, value_parser, default_value_t = 10)]
+    pub dimensions: usize,
+
+    /// The number of children per generation (λ), which defaults to the
+    /// usual choice for each strategy. The (1+1)-ES ignores this.
+    #[clap(short, long, value_parser)]
+    pub population_size: Option<usize>,
+
+    /// The initial step size, as a fraction of the width of the benchmark's
+    /// standard bounds
+    #[clap(long, value_parser, default_value_t = 0.3)]
+    pub initial_step_size: f64,
+
+    /// Number of generations to run
+    #[clap(short, long, value_parser, default_value_t = 200)]
+    pub num_generations: usize,
+}
@@ -0,0 +1,133 @@
+pub mod args;
+
+use anyhow::Result;
+use clap::Parser;
+use ec_core::{
+    generator::Generator,
+    individual::{
+        ec::EcIndividual,
+        scorer::{FnScorer, Scorer},
+    },
+    test_results::{self, TestResults},
+};
+use ec_linear::{
+    benchmark::continuous::ContinuousBenchmark,
+    es::{cma_es::CmaEs, one_plus_one::OnePlusOneEs, self_adaptive::SelfAdaptiveEs},
+    genome::vector::Vector,
+};
+use ordered_float::OrderedFloat;
+use rand::thread_rng;
+
+use crate::args::{Args, RunModel, Strategy};
+
+type Genome = Vector<f64>;
+type Results = TestResults<test_results::Error<OrderedFloat<f64>>>;
+
+/// What we need from each of the evolution strategies to run them here.
+trait EvolutionStrategy {
+    fn next(&mut self, run_model: RunModel) -> Result<()>;
+    fn best(&self) -> &EcIndividual<Genome, Results>;
+}
+
+impl<S: Scorer<Genome, Score = Results>> EvolutionStrategy for OnePlusOneEs<f64, Results, S> {
+    // There's only one child per generation, so there's nothing to do in
+    // parallel.
+    fn next(&mut self, _: RunModel) -> Result<()> {
+        self.serial_next()
+    }
+
+    fn best(&self) -> &EcIndividual<Genome, Results> {
+        self.parent()
+    }
+}
+
+impl<S> EvolutionStrategy for SelfAdaptiveEs<f64, Results, S>
+where
+    S: Scorer<Genome, Score = Results> + Sync,
+{
+    fn next(&mut self, run_model: RunModel) -> Result<()> {
+        match run_model {
+            RunModel::Serial => self.serial_next(),
+            RunModel::Parallel => self.par_next(),
+        }
+    }
+
+    fn best(&self) -> &EcIndividual<Genome, Results> {
+        Self::best(self)
+    }
+}
+
+impl<S> EvolutionStrategy for CmaEs<f64, Results, S>
+where
+    S: Scorer<Genome, Score = Results> + Sync,
+{
+    fn next(&mut self, run_model: RunModel) -> Result<()> {
+        match run_model {
+            RunModel::Serial => self.serial_next(),
+            RunModel::Parallel => self.par_next(),
+        }
+    }
+
+    fn best(&self) -> &EcIndividual<Genome, Results> {
+        Self::best(self)
+    }
+}
+
+fn run(mut strategy: impl EvolutionStrategy, args: &Args) -> Result<()> {
+    println!(
+        "Initial value is {}",
+        strategy.best().test_results.total_result.error
+    );
+
+    for generation_number in 0..args.num_generations {
+        strategy.next(args.run_model)?;
+        println!(
+            "Generation {generation_number:3} best value is {:.6}",
+            strategy.best().test_results.total_result.error
+        );
+    }
+
+    println!("Best solution found:\n{:?}", strategy.best().genome.genes);
+
+    Ok(())
+}
+
+fn main() -> Result<()> {
+    let args = Args::parse();
+    let benchmark = ContinuousBenchmark::from(args.benchmark);
+    let bounds = benchmark.standard_bounds(args.dimensions)?;
+
+    // There's just one "test case": the value of the benchmark function,
+    // exactly as in the `continuous` GA example.
+    let scorer = FnScorer(|genome: &Genome| -> Results {
+        std::iter::once(OrderedFloat(benchmark.evaluate(&genome.genes))).collect()
+    });
+
+    // Start from a random point, with a step size that's a fraction of the
+    // width of the search space.
+    let initial: Genome = bounds.generate(&mut thread_rng())?;
+    let width = bounds.upper()[0] - bounds.lower()[0];
+    let sigma = args.initial_step_size * width;
+
+    println!("Minimising {benchmark:?} with {:?}", args.strategy);
+    match args.strategy {
+        Strategy::OnePlusOne => run(OnePlusOneEs::new(initial, sigma, scorer)?, &args),
+        Strategy::SelfAdaptive => {
+            let es = SelfAdaptiveEs::new(initial, sigma, scorer)?;
+            let es = match args.population_size {
+                // Keep the traditional ratio of about 1 parent to 7 children.
+                Some(lambda) => es.with_population_sizes((lambda / 7).max(1), lambda)?,
+                None => es,
+            };
+            run(es, &args)
+        }
+        Strategy::CmaEs => {
+            let es = CmaEs::new(initial, sigma, scorer)?;
+            let es = match args.population_size {
+                Some(lambda) => es.with_lambda(lambda)?,
+                None => es,
+            };
+            run(es, &args)
+        }
+    }
+}
@@ -1,6 +1,6 @@
 pub mod args;
 
-use std::{iter::once, ops::Not};
+use std::ops::Not;
 
 use anyhow::{ensure, Result};
 use clap::Parser;
@@ -25,6 +25,7 @@ use ec_core::{
     test_results::{self, TestResults},
 };
 use ec_linear::{
+    benchmark::bitstring::hiff,
     genome::bitstring::{Bitstring, BoolGenerator},
     mutator::with_one_over_length::WithOneOverLength,
     recombinator::two_point_xo::TwoPointXo,
@@ -33,32 +34,6 @@ use rand::thread_rng;
 
 use crate::args::{Args, RunModel};
 
-#[must_use]
-fn hiff(bits: &[bool]) -> (bool, TestResults<test_results::Score<usize>>) {
-    let len = bits.len();
-    if len < 2 {
-        (true, once(test_results::Score::from(len)).collect())
-    } else {
-        let half_len = len / 2;
-        let (left_all_same, left_score) = hiff(&bits[..half_len]);
-        let (right_all_same, right_score) = hiff(&bits[half_len..]);
-        let all_same = left_all_same && right_all_same && bits[0] == bits[half_len];
-        (
-            all_same,
-            left_score
-                .results
-                .into_iter()
-                .chain(right_score.results)
-                .chain(once(test_results::Score::from(if all_same {
-                    len
-                } else {
-                    0
-                })))
-                .collect(),
-        )
-    }
-}
-
 fn main() -> Result<()> {
     // Using `Error` in `TestResults<Error>` will have the run favor smaller
     // values, where using `Score` (e.g., `TestResults<Score>`) will have the run
@@ -66,8 +41,13 @@ fn main() -> Result<()> {
     type Pop = Vec<EcIndividual<Bitstring, TestResults<test_results::Score<usize>>>>;
 
     let args = Args::parse();
+    ensure!(
+        args.bit_length.is_power_of_two(),
+        "HIFF needs the number of bits to be a power of two, but got {}",
+        args.bit_length
+    );
 
-    let scorer = FnScorer(|bitstring: &Bitstring| hiff(&bitstring.bits).1);
+    let scorer = FnScorer(|bitstring: &Bitstring| hiff(bitstring));
 
     let num_test_cases = 2 * args.bit_length - 1;
 
@@ -0,0 +1,32 @@
+use clap::Parser;
+
+#[derive(clap::ValueEnum, Copy, Clone, Debug)]
+pub enum RunModel {
+    Serial,
+    Parallel,
+}
+
+/// Linear GP (register machine) symbolic regression in Rust
+#[derive(Parser, Debug, Copy, Clone)]
+#[clap(author, version, about, long_about = None)]
+pub struct Args {
+    /// Should we use parallelism when doing the run?
+    #[clap(short, long, value_enum, default_value_t = RunModel::Parallel)]
+    pub run_model: RunModel,
+
+    /// Population size
+    #[clap(short, long, value_parser, default_value_t = 200)]
+    pub population_size: usize,
+
+    /// Number of registers in the register machine
+    #[clap(short = 'g', long, value_parser, default_value_t = 4)]
+    pub num_registers: usize,
+
+    /// Maximum number of initial instructions
+    #[clap(short = 'i', long, value_parser, default_value_t = 20)]
+    pub max_initial_instructions: usize,
+
+    /// Number of generations to run
+    #[clap(short, long, value_parser, default_value_t = 100)]
+    pub num_generations: usize,
+}
@@ -0,0 +1,140 @@
+pub mod args;
+
+use std::ops::Not;
+
+use anyhow::{ensure, Result};
+use clap::Parser;
+use ec_core::{
+    evaluation::cases::Cases,
+    generation::Generation,
+    generator::{collection::ConvertToCollectionGenerator, Generator},
+    individual::ec::{EcIndividual, WithScorer},
+    operator::{
+        genome_extractor::GenomeExtractor,
+        genome_scorer::GenomeScorer,
+        mutator::Mutate,
+        selector::{
+            best::Best, lexicase::Lexicase, tournament::Tournament, weighted::Weighted, Select,
+            Selector,
+        },
+        Composable,
+    },
+    test_results::{self, TestResults},
+};
+use ec_linear::{
+    lgp::{
+        machine::RegisterMachine,
+        operator::ArithmeticOperator,
+        program::{InstructionGenerator, ProgramGenerator, RegisterProgram},
+        scorer::RegisterMachineScorer,
+    },
+    mutator::umad::Umad,
+};
+use ordered_float::OrderedFloat;
+use rand::thread_rng;
+
+use crate::args::{Args, RunModel};
+
+/*
+ * This is the same "simple regression" problem as the PushGP version in
+ * `push/examples/simple_regression`, solved with linear GP on a register
+ * machine to give a baseline to compare against.
+ */
+
+fn main() -> Result<()> {
+    // Using `Error` in `TestResults<Error>` will have the run favor smaller
+    // values, where using `Score` (e.g., `TestResults<Score>`) will have the run
+    // favor larger values.
+    type Program = RegisterProgram<ArithmeticOperator, OrderedFloat<f64>>;
+    type Pop = Vec<EcIndividual<Program, TestResults<test_results::Error<OrderedFloat<f64>>>>>;
+
+    let args = Args::parse();
+
+    // Inputs from -4 (inclusive) to 4 (exclusive) in increments of 0.25.
+    // The target polynomial is x^3 - 2x^2 - x
+    let training_cases = (-4 * 4..4 * 4)
+        .map(|n| {
+            let x = f64::from(n) / 4.0;
+            (
+                vec![OrderedFloat(x)],
+                OrderedFloat(x.mul_add(x * (x - 2.0), -x)),
+            )
+        })
+        .collect::<Cases<Vec<OrderedFloat<f64>>, OrderedFloat<f64>>>();
+    let num_test_cases = training_cases.len();
+
+    let machine = RegisterMachine::new(args.num_registers, OrderedFloat(0.0))?;
+    let scorer: RegisterMachineScorer<_, _, _, test_results::Error<OrderedFloat<f64>>> =
+        RegisterMachineScorer::new(
+            machine,
+            training_cases,
+            |actual: &OrderedFloat<f64>, expected: &OrderedFloat<f64>| {
+                let error = (actual.0 - expected.0).abs();
+                // The penalty value is also used when the program's result isn't
+                // a number.
+                OrderedFloat(if error.is_nan() { 1_000.0 } else { error })
+            },
+            OrderedFloat(1_000.0),
+        );
+
+    let lexicase = Lexicase::new(num_test_cases);
+    let binary_tournament = Tournament::new(2);
+
+    let selector: Weighted<Pop> = Weighted::new(Best, 1)
+        .with_selector(lexicase, 5)
+        .with_selector(binary_tournament, args.population_size - 1);
+
+    let mut rng = thread_rng();
+
+    let instruction_generator = InstructionGenerator::new(
+        args.num_registers,
+        ArithmeticOperator::ALL.to_vec(),
+        0.1,
+        vec![OrderedFloat(0.0), OrderedFloat(1.0), OrderedFloat(2.0)],
+    )?;
+
+    let population = ProgramGenerator::new(&instruction_generator, args.max_initial_instructions)
+        .with_scorer::<_, Program>(&scorer)
+        .into_collection_generator(args.population_size)
+        .generate(&mut rng)?;
+
+    ensure!(population.is_empty().not());
+
+    let best = Best.select(&population, &mut rng)?;
+    println!("Best initial individual is {best}");
+
+    let umad = Umad::new(0.1, 0.1, &instruction_generator);
+
+    let make_new_individual = Select::new(selector)
+        .then(GenomeExtractor)
+        .then(Mutate::new(umad))
+        .wrap::<GenomeScorer<_, _>>(&scorer);
+
+    let mut generation = Generation::new(make_new_individual, population);
+
+    // TODO: It might be useful to insert some kind of logging system so we can
+    // make this less imperative in nature.
+
+    for generation_number in 0..args.num_generations {
+        match args.run_model {
+            RunModel::Serial => generation.serial_next()?,
+            RunModel::Parallel => generation.par_next()?,
+        }
+
+        let best = Best.select(generation.population(), &mut rng)?;
+        // TODO: Change 2 to be the smallest number of digits needed for
+        // args.num_generations-1.
+        println!(
+            "Generation {generation_number:2} best (with introns removed) is\n{}total error: {}",
+            best.genome.without_introns(&[0]),
+            best.test_results.total_result.error
+        );
+
+        if best.test_results.total_result.error < OrderedFloat(1e-6) {
+            println!("SUCCESS");
+            break;
+        }
+    }
+
+    Ok(())
+}
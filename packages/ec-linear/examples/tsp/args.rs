@@ -0,0 +1,46 @@
+use std::path::PathBuf;
+
+use clap::Parser;
+
+#[derive(clap::ValueEnum, Copy, Clone, Debug)]
+pub enum RunModel {
+    Serial,
+    Parallel,
+}
+
+#[derive(clap::ValueEnum, Copy, Clone, Debug)]
+pub enum Crossover {
+    Pmx,
+    Order,
+    Cycle,
+    Edge,
+}
+
+/// Solving travelling salesperson problems with permutation genomes in Rust
+#[derive(Parser, Debug, Clone)]
+#[clap(author, version, about, long_about = None)]
+pub struct Args {
+    /// Should we use parallelism when doing the run?
+    #[clap(short, long, value_enum, default_value_t = RunModel::Parallel)]
+    pub run_model: RunModel,
+
+    /// The TSPLIB file to solve
+    #[clap(short, long, value_parser, default_value = concat!(env!("CARGO_MANIFEST_DIR"), "/examples/tsp/data/circle48.tsp"))]
+    pub tsp_file: PathBuf,
+
+    /// The crossover operator to use
+    #[clap(short, long, value_enum, default_value_t = Crossover::Edge)]
+    pub crossover: Crossover,
+
+    /// The probability of mutating each child with an inversion
+    #[clap(short, long, value_parser, default_value_t = 0.2)]
+    pub mutation_probability: f64,
+
+    /// Population size
+    #[clap(short, long, value_parser, default_value_t = 500)]
+    pub population_size: usize,
+
+    /// Number of generations to run
+    #[clap(short, long, value_parser, default_value_t = 200)]
+    pub num_generations: usize,
+}
@@ -0,0 +1,123 @@
+pub mod args;
+
+use std::ops::Not;
+
+use anyhow::{ensure, Result};
+use clap::Parser;
+use ec_core::{
+    generation::Generation,
+    generator::{collection::ConvertToCollectionGenerator, Generator},
+    individual::{
+        ec::{EcIndividual, WithScorer},
+        scorer::FnScorer,
+    },
+    operator::{
+        genome_extractor::GenomeExtractor,
+        genome_scorer::GenomeScorer,
+        mutator::{Mutate, Mutator},
+        recombinator::{Recombinator, Recombine},
+        selector::{best::Best, tournament::Tournament, weighted::Weighted, Select, Selector},
+        Composable,
+    },
+    test_results::{self, TestResults},
+};
+use ec_linear::{
+    benchmark::tsp::Tsp,
+    genome::permutation::{Permutation, RandomPermutation},
+    mutator::permutation::InversionMutation,
+    recombinator::permutation::{CycleXo, EdgeRecombination, OrderXo, Pmx},
+};
+use rand::{rngs::ThreadRng, thread_rng, Rng};
+
+use crate::args::{Args, Crossover, RunModel};
+
+type Pop = Vec<EcIndividual<Permutation, TestResults<test_results::Error<i64>>>>;
+
+fn main() -> Result<()> {
+    let args = Args::parse();
+    match args.crossover {
+        Crossover::Pmx => run(&args, Pmx),
+        Crossover::Order => run(&args, OrderXo),
+        Crossover::Cycle => run(&args, CycleXo),
+        Crossover::Edge => run(&args, EdgeRecombination),
+    }
+}
+
+fn run<R>(args: &Args, crossover: R) -> Result<()>
+where
+    R: Recombinator<[Permutation; 2], Output = Permutation> + Send + Sync,
+{
+    let tsp = Tsp::from_file(&args.tsp_file)?;
+    println!("Solving {} ({} cities)", tsp.name(), tsp.num_cities());
+
+    // The only "test case" is the length of the tour.
+    let scorer = FnScorer(
+        |tour: &Permutation| -> TestResults<test_results::Error<i64>> {
+            std::iter::once(tsp.tour_length(tour)).collect()
+        },
+    );
+
+    let selector: Weighted<Pop> =
+        Weighted::new(Best, 1).with_selector(Tournament::new(3), args.population_size - 1);
+
+    let mut rng = thread_rng();
+
+    let population = RandomPermutation {
+        size: tsp.num_cities(),
+    }
+    .with_scorer::<_, Permutation>(scorer)
+    .into_collection_generator(args.population_size)
+    .generate(&mut rng)?;
+
+    ensure!(population.is_empty().not());
+
+    let make_new_individual = Select::new(selector)
+        .apply_twice()
+        .then_map(GenomeExtractor)
+        .then(Recombine::new(crossover))
+        .then(Mutate::new(Sometimes {
+            mutator: InversionMutation,
+            probability: args.mutation_probability,
+        }))
+        .wrap::<GenomeScorer<_, _>>(scorer);
+
+    let mut generation = Generation::new(make_new_individual, population);
+
+    for generation_number in 0..args.num_generations {
+        match args.run_model {
+            RunModel::Serial => generation.serial_next()?,
+            RunModel::Parallel => generation.par_next()?,
+        }
+
+        let best = Best.select(generation.population(), &mut rng)?;
+        println!(
+            "Generation {generation_number:3} best tour length is {}",
+            best.test_results.total_result.error
+        );
+    }
+
+    let best = Best.select(generation.population(), &mut rng)?;
+    println!("Best tour found: {}", best.genome);
+
+    Ok(())
+}
+
+/// Applying an inversion to every child is too disruptive once the tours get
+/// good, so we only mutate some of them.
+struct Sometimes<M> {
+    mutator: M,
+    probability: f64,
+}
+
+impl<M> Mutator<Permutation> for Sometimes<M>
+where
+    M: Mutator<Permutation>,
+{
+    fn mutate(&self, genome: Permutation, rng: &mut ThreadRng) -> Result<Permutation> {
+        if rng.gen_bool(self.probability) {
+            self.mutator.mutate(genome, rng)
+        } else {
+            Ok(genome)
+        }
+    }
+}
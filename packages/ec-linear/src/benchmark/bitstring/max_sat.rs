@@ -0,0 +1,234 @@
+//! MAX-SAT problems, read from files in the
+//! [DIMACS CNF](https://www.cs.ubc.ca/~hoos/SATLIB/Benchmarks/SAT/satformat.ps)
+//! format (as used by, e.g., SATLIB).
+
+use std::{fs, path::Path, str::FromStr};
+
+use anyhow::{bail, ensure, Context, Result};
+use ec_core::test_results::{Score, TestResults};
+
+use crate::genome::bitstring::Bitstring;
+
+/// A variable, or its negation, in a clause.
+///
+/// Variables are numbered from 0 here, even though DIMACS files number them
+/// from 1, so that variable `i` is bit `i` of an assignment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Literal {
+    pub variable: usize,
+    pub negated: bool,
+}
+
+impl Literal {
+    fn is_satisfied_by(self, assignment: &Bitstring) -> bool {
+        assignment.get(self.variable) == Some(!self.negated)
+    }
+}
+
+/// A boolean formula in conjunctive normal form, where the goal is to
+/// satisfy as many clauses as possible.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MaxSat {
+    num_variables: usize,
+    clauses: Vec<Vec<Literal>>,
+}
+
+impl MaxSat {
+    /// Read and parse a DIMACS CNF file.
+    ///
+    /// # Errors
+    ///
+    /// This fails if the file can't be read or isn't a valid CNF file.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read the DIMACS CNF file {}", path.display()))?;
+        contents
+            .parse()
+            .with_context(|| format!("Failed to parse the DIMACS CNF file {}", path.display()))
+    }
+
+    #[must_use]
+    pub const fn num_variables(&self) -> usize {
+        self.num_variables
+    }
+
+    #[must_use]
+    pub fn clauses(&self) -> &[Vec<Literal>] {
+        &self.clauses
+    }
+
+    /// Whether each clause is satisfied by `assignment`, where bit `i` is the
+    /// value of variable `i`. There's one result for each clause, which is 1
+    /// if the clause is satisfied and 0 otherwise.
+    ///
+    /// # Panics
+    ///
+    /// This panics if `assignment` doesn't have one bit for each variable.
+    #[must_use]
+    pub fn evaluate(&self, assignment: &Bitstring) -> TestResults<Score<usize>> {
+        assert_eq!(
+            assignment.len(),
+            self.num_variables,
+            "This problem has {} variables, but the assignment has {} bits",
+            self.num_variables,
+            assignment.len()
+        );
+        self.clauses
+            .iter()
+            .map(|clause| {
+                usize::from(
+                    clause
+                        .iter()
+                        .any(|literal| literal.is_satisfied_by(assignment)),
+                )
+            })
+            .collect()
+    }
+}
+
+impl FromStr for MaxSat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut header = None;
+        let mut clauses = Vec::new();
+        let mut clause = Vec::new();
+        for (index, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('c') {
+                continue;
+            }
+            // SATLIB files end with a `%` line (followed by a stray `0`).
+            if line.starts_with('%') {
+                break;
+            }
+            if let Some(problem) = line.strip_prefix('p') {
+                ensure!(
+                    header.is_none(),
+                    "Line {}: there's more than one problem line",
+                    index + 1
+                );
+                header =
+                    Some(parse_header(problem).with_context(|| format!("Line {}", index + 1))?);
+                continue;
+            }
+            let (num_variables, _) = header.with_context(|| {
+                format!(
+                    "Line {}: the problem line must come before the clauses",
+                    index + 1
+                )
+            })?;
+            for token in line.split_whitespace() {
+                let literal = token
+                    .parse::<i64>()
+                    .with_context(|| format!("Line {}: invalid literal {token}", index + 1))?;
+                if literal == 0 {
+                    clauses.push(std::mem::take(&mut clause));
+                    continue;
+                }
+                let variable = usize::try_from(literal.unsigned_abs())?;
+                ensure!(
+                    variable <= num_variables,
+                    "Line {}: the variable {variable} is out of range since there are only \
+                     {num_variables} variables",
+                    index + 1
+                );
+                clause.push(Literal {
+                    variable: variable - 1,
+                    negated: literal < 0,
+                });
+            }
+        }
+        let Some((num_variables, num_clauses)) = header else {
+            bail!("There's no problem line");
+        };
+        ensure!(
+            clause.is_empty(),
+            "The last clause isn't terminated with a 0"
+        );
+        ensure!(
+            clauses.len() == num_clauses,
+            "The problem line says there are {num_clauses} clauses, but there are {}",
+            clauses.len()
+        );
+        Ok(Self {
+            num_variables,
+            clauses,
+        })
+    }
+}
+
+/// Parse the rest of a `p cnf <variables> <clauses>` problem line.
+fn parse_header(problem: &str) -> Result<(usize, usize)> {
+    let fields = problem.split_whitespace().collect::<Vec<_>>();
+    let ["cnf", num_variables, num_clauses] = fields[..] else {
+        bail!("Expected `p cnf <variables> <clauses>` but got `p{problem}`");
+    };
+    Ok((num_variables.parse()?, num_clauses.parse()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // (x1 ∨ ¬x3) ∧ (x2 ∨ x3 ∨ ¬x1) ∧ (¬x2)
+    const SMALL: &str = "
+        c A small example
+        p cnf 3 3
+        1 -3 0
+        2 3 -1 0
+        -2
+        0
+        %
+        0
+    ";
+
+    fn from_str(bits: &str) -> Bitstring {
+        bits.chars().map(|c| c == '1').collect()
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn parse_and_evaluate() {
+        let problem: MaxSat = SMALL.parse().unwrap();
+        assert_eq!(problem.num_variables(), 3);
+        assert_eq!(
+            problem.clauses()[0],
+            [
+                Literal {
+                    variable: 0,
+                    negated: false
+                },
+                Literal {
+                    variable: 2,
+                    negated: true
+                }
+            ]
+        );
+        let scores = |bits| {
+            problem
+                .evaluate(&from_str(bits))
+                .results
+                .into_iter()
+                .map(|score| score.score)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(scores("000"), [1, 1, 1]);
+        assert_eq!(scores("100"), [1, 0, 1]);
+        assert_eq!(scores("011"), [0, 1, 0]);
+    }
+
+    #[test]
+    fn errors() {
+        let fails = |text: &str| text.parse::<MaxSat>().is_err();
+        assert!(fails(&SMALL.replace("p cnf 3 3", "p cnf 3 4")));
+        assert!(fails(&SMALL.replace("p cnf 3 3", "p cnf 2 3")));
+        assert!(fails(&SMALL.replace("p cnf 3 3", "p dnf 3 3")));
+        assert!(fails(&SMALL.replace("-2\n", "-2 x\n")));
+        // The last clause isn't terminated.
+        assert!(fails("p cnf 2 1\n1 2"));
+        // There's no problem line.
+        assert!(fails("1 2 0"));
+    }
+}
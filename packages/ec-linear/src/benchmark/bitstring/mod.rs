@@ -0,0 +1,371 @@
+//! Benchmark problems on [`Bitstring`]s.
+//!
+//! These are all maximisation problems, and they all return
+//! [`TestResults`] of [`Score`]s. Where a problem breaks down naturally into
+//! parts (bits, blocks, levels, or clauses) there's one result for each
+//! part, which makes them suitable for lexicase selection as well as
+//! tournament selection on the total.
+
+use std::iter::{once, repeat_n};
+
+use ec_core::test_results::{Score, TestResults};
+use num_traits::ToPrimitive;
+
+use crate::genome::bitstring::Bitstring;
+
+pub mod max_sat;
+pub mod nk;
+
+/// The number of bits in each of a [`Bitstring`]'s words.
+const WORD_BITS: usize = 64;
+
+/// The `OneMax` problem, which just counts the ones.
+///
+/// There's one result for each bit, which is 1 if the bit is set and 0
+/// otherwise.
+#[must_use]
+pub fn one_max(bitstring: &Bitstring) -> TestResults<Score<usize>> {
+    bitstring.iter().copied().map(usize::from).collect()
+}
+
+/// The number of consecutive ones at the start of `bitstring`.
+fn count_leading_ones(bitstring: &Bitstring) -> usize {
+    let mut count = 0;
+    for word in bitstring.words() {
+        let ones = word.trailing_ones();
+        count += ones.to_usize().unwrap_or(WORD_BITS);
+        if ones < u64::BITS {
+            break;
+        }
+    }
+    count.min(bitstring.len())
+}
+
+/// The `LeadingOnes` problem, which counts the ones before the first zero.
+///
+/// There's one result for each bit, which is 1 if it's one of the leading
+/// ones and 0 otherwise. Only the first zero bit matters, so the later
+/// results are all 0 whatever the bits are.
+#[must_use]
+pub fn leading_ones(bitstring: &Bitstring) -> TestResults<Score<usize>> {
+    let leading = count_leading_ones(bitstring);
+    repeat_n(1, leading)
+        .chain(repeat_n(0, bitstring.len() - leading))
+        .collect()
+}
+
+/// The Jump problem with a gap of size `gap`.
+///
+/// This is `OneMax` shifted up by `gap`, except that bitstrings with fewer
+/// than `gap` zeros (but at least one) fall into a "gap" where the score
+/// gets worse as the number of ones increases. Reaching the optimum of all
+/// ones (which scores `len + gap`) from the edge of the gap needs `gap` bits
+/// to flip at once.
+///
+/// The score depends on the number of ones as a whole, so there's just one
+/// result.
+#[must_use]
+pub fn jump(bitstring: &Bitstring, gap: usize) -> TestResults<Score<usize>> {
+    let len = bitstring.len();
+    let ones = bitstring.count_ones();
+    let score = if ones == len || ones + gap <= len {
+        gap + ones
+    } else {
+        len - ones
+    };
+    once(score).collect()
+}
+
+/// The concatenated deceptive trap problem with blocks of `block_size` bits.
+///
+/// Each block scores `block_size` if all its bits are ones, and otherwise
+/// scores `block_size - 1 - u`, where `u` is the number of ones in the
+/// block. Within a block every bit flip that isn't the last one leads away
+/// from the optimum, so hill climbers are drawn towards all zeros. There's
+/// one result for each block.
+///
+/// # Panics
+///
+/// This panics if `block_size` is zero or doesn't divide the length of
+/// `bitstring`.
+#[must_use]
+pub fn deceptive_traps(bitstring: &Bitstring, block_size: usize) -> TestResults<Score<usize>> {
+    block_ones(bitstring, block_size)
+        .map(|ones| {
+            if ones == block_size {
+                block_size
+            } else {
+                block_size - 1 - ones
+            }
+        })
+        .collect()
+}
+
+/// The royal road problem with blocks of `block_size` bits.
+///
+/// Each block scores `block_size` if all its bits are ones, and 0
+/// otherwise, so there's no signal at all within a block. There's one
+/// result for each block.
+///
+/// # Panics
+///
+/// This panics if `block_size` is zero or doesn't divide the length of
+/// `bitstring`.
+#[must_use]
+pub fn royal_road(bitstring: &Bitstring, block_size: usize) -> TestResults<Score<usize>> {
+    block_ones(bitstring, block_size)
+        .map(|ones| if ones == block_size { block_size } else { 0 })
+        .collect()
+}
+
+/// The number of ones in each of the consecutive blocks of `block_size`
+/// bits of `bitstring`.
+fn block_ones(bitstring: &Bitstring, block_size: usize) -> impl Iterator<Item = usize> + '_ {
+    let len = bitstring.len();
+    assert!(
+        block_size > 0 && len.is_multiple_of(block_size),
+        "The block size {block_size} must be positive and divide the number of bits {len}"
+    );
+    (0..len)
+        .step_by(block_size)
+        .map(move |start| bitstring.count_ones_in(start..start + block_size))
+}
+
+/// The mask of the bits of a word that are at multiples of `step`.
+fn multiples_of(step: usize) -> u64 {
+    (0..WORD_BITS)
+        .step_by(step)
+        .fold(0, |mask, position| mask | (1 << position))
+}
+
+/// Watson's Hierarchical If-and-only-iF (HIFF) function.
+///
+/// The bitstring is treated as a complete binary tree of blocks: the
+/// individual bits, then adjacent pairs of bits, then adjacent pairs of
+/// pairs, and so on up to the whole bitstring. Every block whose bits are
+/// all the same (all zeros or all ones) scores its size, and other blocks
+/// score zero. There's one result for each block, ordered level by level
+/// from the individual bits up, and left to right within each level, so
+/// there are `2n - 1` results for `n` bits. The maximum total score is
+/// `n * (log2(n) + 1)`, which is reached by both the all zeros and all ones
+/// bitstrings.
+///
+/// This works a word at a time, checking all the blocks in a word with a
+/// few bitwise operations until blocks span whole words.
+///
+/// # Panics
+///
+/// This panics if the length of `bitstring` isn't a power of two.
+#[must_use]
+pub fn hiff(bitstring: &Bitstring) -> TestResults<Score<usize>> {
+    let len = bitstring.len();
+    assert!(
+        len.is_power_of_two(),
+        "HIFF needs a power of two bits, but got {len}"
+    );
+    let words = bitstring.words();
+    let mut results = Vec::with_capacity(2 * len - 1);
+    // Every bit is a block of size one, which is trivially uniform.
+    results.extend(repeat_n(1, len));
+
+    // Blocks that fit in a word. Bit `i` of `uniform[w]` is set if the
+    // block starting at bit `i` of word `w` is all the same.
+    let mut uniform = vec![u64::MAX; words.len()];
+    let mut block_size = 1;
+    while block_size < len.min(WORD_BITS) {
+        let doubled = 2 * block_size;
+        let starts = multiples_of(doubled);
+        for (uniform, &word) in uniform.iter_mut().zip(words) {
+            // A doubled block is uniform if both its halves are and their
+            // first bits match.
+            *uniform &= (*uniform >> block_size) & !(word ^ (word >> block_size)) & starts;
+            results.extend((0..len.min(WORD_BITS)).step_by(doubled).map(|start| {
+                if (*uniform >> start) & 1 == 1 {
+                    doubled
+                } else {
+                    0
+                }
+            }));
+        }
+        block_size = doubled;
+    }
+
+    // Blocks of whole words, each of which is either uniform with the given
+    // value or not uniform.
+    let mut blocks = words
+        .iter()
+        .zip(&uniform)
+        .map(|(&word, &uniform)| (uniform & 1 == 1).then_some(word & 1 == 1))
+        .collect::<Vec<_>>();
+    while blocks.len() > 1 {
+        block_size *= 2;
+        blocks = blocks
+            .chunks_exact(2)
+            .map(|pair| match (pair[0], pair[1]) {
+                (Some(left), Some(right)) if left == right => Some(left),
+                _ => None,
+            })
+            .collect();
+        results.extend(
+            blocks
+                .iter()
+                .map(|block| if block.is_some() { block_size } else { 0 }),
+        );
+    }
+
+    results.into_iter().map(Score::from).collect()
+}
+
+/// Watson's HIFF function with one result for each level of blocks.
+///
+/// This has the same total as [`hiff`], but each result is the total score
+/// of the blocks of one size, from the individual bits up to the whole
+/// bitstring, so there are `log2(n) + 1` results for `n` bits. That's a
+/// much smaller set of test cases for lexicase selection, and each case
+/// rewards a different scale of structure.
+///
+/// # Panics
+///
+/// This panics if the length of `bitstring` isn't a power of two.
+#[must_use]
+pub fn hiff_levels(bitstring: &Bitstring) -> TestResults<Score<usize>> {
+    let blocks = hiff(bitstring).results;
+    let mut levels = Vec::new();
+    let mut remaining = &blocks[..];
+    let mut level_size = bitstring.len();
+    while level_size > 0 {
+        let (level, rest) = remaining.split_at(level_size);
+        levels.push(level.iter().map(|block| block.score).sum::<usize>());
+        remaining = rest;
+        level_size /= 2;
+    }
+    levels.into_iter().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn from_str(bits: &str) -> Bitstring {
+        bits.chars().map(|c| c == '1').collect()
+    }
+
+    fn scores(results: TestResults<Score<usize>>) -> Vec<usize> {
+        results
+            .results
+            .into_iter()
+            .map(|score| score.score)
+            .collect()
+    }
+
+    #[test]
+    fn one_max_scores_each_bit() {
+        assert_eq!(scores(one_max(&from_str("011101"))), [0, 1, 1, 1, 0, 1]);
+        assert_eq!(one_max(&Bitstring::ones(200)).total_result.score, 200);
+    }
+
+    #[test]
+    fn leading_ones_stops_at_the_first_zero() {
+        assert_eq!(
+            scores(leading_ones(&from_str("110111"))),
+            [1, 1, 0, 0, 0, 0]
+        );
+        assert_eq!(leading_ones(&Bitstring::ones(130)).total_result.score, 130);
+        let mut bitstring = Bitstring::ones(200);
+        bitstring.set(150, false);
+        assert_eq!(leading_ones(&bitstring).total_result.score, 150);
+        assert_eq!(leading_ones(&Bitstring::zeros(10)).total_result.score, 0);
+    }
+
+    #[test]
+    fn jump_has_a_gap_before_the_optimum() {
+        let total = |bits| jump(&from_str(bits), 2).total_result.score;
+        assert_eq!(total("000000"), 2);
+        assert_eq!(total("111100"), 6);
+        // One zero left is in the gap.
+        assert_eq!(total("111110"), 1);
+        assert_eq!(total("111111"), 8);
+    }
+
+    #[test]
+    fn deceptive_traps_score_each_block() {
+        assert_eq!(
+            scores(deceptive_traps(&from_str("000011110100"), 4)),
+            [3, 4, 2]
+        );
+    }
+
+    #[test]
+    fn royal_road_only_rewards_complete_blocks() {
+        assert_eq!(scores(royal_road(&from_str("111101111111"), 4)), [4, 0, 4]);
+    }
+
+    #[test]
+    #[should_panic(expected = "must be positive and divide")]
+    fn blocks_must_divide_the_length() {
+        let _ = royal_road(&Bitstring::ones(10), 4);
+    }
+
+    /// The straightforward recursive definition of HIFF, with the results
+    /// for each level of blocks.
+    fn slow_hiff(bits: &[bool]) -> (bool, Vec<Vec<usize>>) {
+        if bits.len() == 1 {
+            return (true, vec![vec![1]]);
+        }
+        let half = bits.len() / 2;
+        let (left_same, left) = slow_hiff(&bits[..half]);
+        let (right_same, right) = slow_hiff(&bits[half..]);
+        let same = left_same && right_same && bits[0] == bits[half];
+        let mut levels = left
+            .into_iter()
+            .zip(right)
+            .map(|(mut left, right)| {
+                left.extend(right);
+                left
+            })
+            .collect::<Vec<_>>();
+        levels.push(vec![if same { bits.len() } else { 0 }]);
+        (same, levels)
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn matches_the_recursive_definition() {
+        let mut rng = rand::thread_rng();
+        for len in [1, 2, 4, 8, 64, 128, 512] {
+            for probability in [0.0, 0.1, 0.5, 1.0] {
+                let bitstring =
+                    Bitstring::random_with_probability(len, probability, &mut rng).unwrap();
+                let expected = slow_hiff(&bitstring.iter().copied().collect::<Vec<_>>())
+                    .1
+                    .concat();
+                let actual = hiff(&bitstring)
+                    .results
+                    .into_iter()
+                    .map(|score| score.score)
+                    .collect::<Vec<_>>();
+                assert_eq!(actual, expected, "{bitstring}");
+            }
+        }
+    }
+
+    #[test]
+    fn uniform_bitstrings_score_the_maximum() {
+        let results = hiff(&Bitstring::ones(128));
+        assert_eq!(results.results.len(), 255);
+        assert_eq!(results.total_result.score, 128 * 8);
+        assert_eq!(hiff(&Bitstring::zeros(128)).total_result.score, 128 * 8);
+    }
+
+    #[test]
+    fn hiff_levels_sum_each_level() {
+        let bitstring = from_str("00011111");
+        // Level totals: eight bits, then pairs 00 01 11 11, then quads 0001
+        // 1111, then the whole thing.
+        assert_eq!(scores(hiff_levels(&bitstring)), [8, 6, 4, 0]);
+        assert_eq!(
+            hiff_levels(&bitstring).total_result,
+            hiff(&bitstring).total_result
+        );
+    }
+}
@@ -0,0 +1,177 @@
+//! Kauffman's NK-landscapes, whose ruggedness can be tuned with `K`.
+
+use anyhow::{ensure, Result};
+use ec_core::test_results::{Score, TestResults};
+use rand::{rngs::StdRng, seq::index::sample, Rng, SeedableRng};
+
+use crate::genome::bitstring::Bitstring;
+
+/// Each locus contributes an integer below this. Integer contributions
+/// (rather than the usual reals in `[0, 1)`) keep the results totally
+/// ordered, which selection needs.
+pub const CONTRIBUTION_LIMIT: usize = 1 << 16;
+
+/// Which other loci each locus interacts with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Neighbourhood {
+    /// The `K` loci after it, wrapping around at the end.
+    Adjacent,
+    /// `K` distinct loci chosen at random.
+    Random,
+}
+
+/// An NK-landscape on bitstrings of `N` bits, where the contribution of
+/// each bit depends on its own value and the values of `K` other bits.
+///
+/// The contribution of each locus for each combination of its own bit and
+/// its neighbours' bits is drawn at random when the landscape is created.
+/// Those random choices come from a seed so that a landscape can be
+/// recreated exactly, e.g., to compare different algorithms on it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NkLandscape {
+    /// The neighbours of each locus, not including the locus itself.
+    neighbours: Vec<Vec<usize>>,
+    /// The contributions of each locus, indexed by its bit (as the lowest
+    /// bit) and its neighbours' bits (in order) read as a binary number.
+    contributions: Vec<Vec<usize>>,
+}
+
+impl NkLandscape {
+    /// Create a random NK-landscape from `seed`. The same arguments give the
+    /// same landscape (at least for a given version of `rand`).
+    ///
+    /// # Errors
+    ///
+    /// This fails unless `k < n`, since each locus needs `k` other loci to
+    /// interact with, or if `k` is so large that the contribution tables
+    /// wouldn't fit in memory.
+    pub fn new(n: usize, k: usize, neighbourhood: Neighbourhood, seed: u64) -> Result<Self> {
+        ensure!(
+            k < n,
+            "An NK-landscape needs K < N, but got N = {n} and K = {k}"
+        );
+        ensure!(
+            k < 32,
+            "K = {k} would need contribution tables with 2^{} entries",
+            k + 1
+        );
+        let mut rng = StdRng::seed_from_u64(seed);
+        let neighbours = (0..n)
+            .map(|locus| match neighbourhood {
+                Neighbourhood::Adjacent => (1..=k).map(|offset| (locus + offset) % n).collect(),
+                // Choose from the other `n - 1` loci, skipping over this one.
+                Neighbourhood::Random => sample(&mut rng, n - 1, k)
+                    .into_iter()
+                    .map(|other| if other < locus { other } else { other + 1 })
+                    .collect(),
+            })
+            .collect();
+        let contributions = (0..n)
+            .map(|_| {
+                (0..1 << (k + 1))
+                    .map(|_| rng.gen_range(0..CONTRIBUTION_LIMIT))
+                    .collect()
+            })
+            .collect();
+        Ok(Self {
+            neighbours,
+            contributions,
+        })
+    }
+
+    /// The number of bits, `N`.
+    #[must_use]
+    pub const fn n(&self) -> usize {
+        self.neighbours.len()
+    }
+
+    /// The number of neighbours of each bit, `K`.
+    #[must_use]
+    pub fn k(&self) -> usize {
+        self.neighbours.first().map_or(0, Vec::len)
+    }
+
+    /// The contribution of each locus, so there are `N` results.
+    ///
+    /// # Panics
+    ///
+    /// This panics if `bitstring` doesn't have `N` bits.
+    #[must_use]
+    pub fn evaluate(&self, bitstring: &Bitstring) -> TestResults<Score<usize>> {
+        assert_eq!(
+            bitstring.len(),
+            self.n(),
+            "This NK-landscape needs {} bits, but got {}",
+            self.n(),
+            bitstring.len()
+        );
+        let bit = |index| usize::from(bitstring.get(index) == Some(true));
+        self.neighbours
+            .iter()
+            .zip(&self.contributions)
+            .enumerate()
+            .map(|(locus, (neighbours, contributions))| {
+                let index = neighbours
+                    .iter()
+                    .enumerate()
+                    .fold(bit(locus), |index, (position, &neighbour)| {
+                        index | (bit(neighbour) << (position + 1))
+                    });
+                contributions[index]
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn seeds_determine_the_landscape() {
+        let first = NkLandscape::new(20, 3, Neighbourhood::Random, 42).unwrap();
+        let second = NkLandscape::new(20, 3, Neighbourhood::Random, 42).unwrap();
+        let third = NkLandscape::new(20, 3, Neighbourhood::Random, 43).unwrap();
+        assert_eq!(first, second);
+        assert_ne!(first, third);
+        assert_eq!((first.n(), first.k()), (20, 3));
+        let bitstring = Bitstring::ones(20);
+        assert_eq!(first.evaluate(&bitstring), second.evaluate(&bitstring));
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn neighbours_are_other_loci() {
+        for neighbourhood in [Neighbourhood::Adjacent, Neighbourhood::Random] {
+            let landscape = NkLandscape::new(10, 9, neighbourhood, 0).unwrap();
+            for (locus, neighbours) in landscape.neighbours.iter().enumerate() {
+                let mut sorted = neighbours.clone();
+                sorted.sort_unstable();
+                sorted.dedup();
+                assert_eq!(sorted.len(), 9);
+                assert!(!sorted.contains(&locus));
+            }
+        }
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn with_k_zero_each_bit_is_independent() {
+        let landscape = NkLandscape::new(8, 0, Neighbourhood::Adjacent, 7).unwrap();
+        let mut bitstring = Bitstring::zeros(8);
+        let before = landscape.evaluate(&bitstring).results;
+        bitstring.flip(3);
+        let after = landscape.evaluate(&bitstring).results;
+        for locus in (0..8).filter(|&locus| locus != 3) {
+            assert_eq!(before[locus], after[locus]);
+        }
+        assert_eq!(after[3].score, landscape.contributions[3][1]);
+        assert!(after.iter().all(|score| score.score < CONTRIBUTION_LIMIT));
+    }
+
+    #[test]
+    fn k_must_be_less_than_n() {
+        assert!(NkLandscape::new(5, 5, Neighbourhood::Adjacent, 0).is_err());
+    }
+}
@@ -0,0 +1,134 @@
+//! Benchmark functions for continuous optimisation. These are all to be
+//! minimised, and all have a global minimum of 0.
+
+use std::f64::consts::{E, TAU};
+
+use anyhow::Result;
+use num_traits::ToPrimitive;
+
+use crate::genome::real::{Bounds, Real};
+
+/// The sphere function, `Σ x²`, with its minimum at the origin. This is
+/// about as easy as continuous problems get.
+pub fn sphere<T: Real>(x: &[T]) -> f64 {
+    x.iter().map(|&x| x.into().powi(2)).sum()
+}
+
+/// The Rastrigin function, `10n + Σ (x² - 10 cos(2πx))`, which is highly
+/// multimodal with a regular grid of local minima. The global minimum is
+/// at the origin.
+pub fn rastrigin<T: Real>(x: &[T]) -> f64 {
+    x.iter()
+        .map(|&x| {
+            let x: f64 = x.into();
+            10.0f64.mul_add(-(TAU * x).cos(), x.powi(2)) + 10.0
+        })
+        .sum()
+}
+
+/// The Rosenbrock function, `Σ 100(x[i+1] - x[i]²)² + (1 - x[i])²`, whose
+/// minimum at `(1, ..., 1)` is at the bottom of a long, curved, nearly
+/// flat valley.
+pub fn rosenbrock<T: Real>(x: &[T]) -> f64 {
+    x.windows(2)
+        .map(|pair| {
+            let (x, next): (f64, f64) = (pair[0].into(), pair[1].into());
+            100.0f64.mul_add(x.mul_add(-x, next).powi(2), (1.0 - x).powi(2))
+        })
+        .sum()
+}
+
+/// The Ackley function, which is nearly flat far from the origin and has
+/// many shallow local minima, with a deep global minimum at the origin.
+pub fn ackley<T: Real>(x: &[T]) -> f64 {
+    if x.is_empty() {
+        return 0.0;
+    }
+    // Converting a `usize` to an `f64` can't fail, although it may lose
+    // precision for absurdly long vectors.
+    let n = x.len().to_f64().unwrap_or(f64::MAX);
+    let (sum_of_squares, sum_of_cosines) = x.iter().fold((0.0, 0.0), |(squares, cosines), &x| {
+        let x: f64 = x.into();
+        (x.mul_add(x, squares), cosines + (TAU * x).cos())
+    });
+    let distance_term = -20.0 * (-0.2 * (sum_of_squares / n).sqrt()).exp();
+    let cosine_term = -(sum_of_cosines / n).exp();
+    distance_term + cosine_term + 20.0 + E
+}
+
+/// The continuous benchmarks, so that programs can choose between them
+/// (e.g., from the command line).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ContinuousBenchmark {
+    Sphere,
+    Rastrigin,
+    Rosenbrock,
+    Ackley,
+}
+
+impl ContinuousBenchmark {
+    pub fn evaluate<T: Real>(self, x: &[T]) -> f64 {
+        match self {
+            Self::Sphere => sphere(x),
+            Self::Rastrigin => rastrigin(x),
+            Self::Rosenbrock => rosenbrock(x),
+            Self::Ackley => ackley(x),
+        }
+    }
+
+    /// The search domain that this benchmark is usually run on.
+    ///
+    /// # Errors
+    ///
+    /// This can't actually fail since the standard bounds are all valid.
+    pub fn standard_bounds(self, dimensions: usize) -> Result<Bounds> {
+        let limit = match self {
+            Self::Sphere | Self::Rastrigin => 5.12,
+            Self::Rosenbrock => 2.048,
+            Self::Ackley => 32.768,
+        };
+        Bounds::uniform(dimensions, -limit, limit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-9,
+            "Expected {expected} but got {actual}"
+        );
+    }
+
+    #[test]
+    fn minima_are_zero() {
+        assert_close(sphere(&[0.0; 5]), 0.0);
+        assert_close(rastrigin(&[0.0; 5]), 0.0);
+        assert_close(rosenbrock(&[1.0; 5]), 0.0);
+        assert_close(ackley(&[0.0; 5]), 0.0);
+    }
+
+    #[test]
+    fn known_values() {
+        assert_close(sphere(&[1.0, 2.0, 3.0]), 14.0);
+        // cos(2π) = 1, so each integer coordinate just adds x².
+        assert_close(rastrigin(&[1.0, 2.0]), 5.0);
+        assert_close(rosenbrock(&[0.0, 0.0]), 1.0);
+        assert!(ackley(&[1.0, 1.0]) > 1.0);
+    }
+
+    #[test]
+    fn benchmarks_are_positive_away_from_the_minimum() {
+        let x = [0.5, -1.5, 2.0];
+        for benchmark in [
+            ContinuousBenchmark::Sphere,
+            ContinuousBenchmark::Rastrigin,
+            ContinuousBenchmark::Rosenbrock,
+            ContinuousBenchmark::Ackley,
+        ] {
+            assert!(benchmark.evaluate(&x) > 0.0, "{benchmark:?}");
+        }
+    }
+}
@@ -0,0 +1,6 @@
+//! Standard benchmark problems for testing and comparing evolutionary
+//! algorithms.
+
+pub mod bitstring;
+pub mod continuous;
+pub mod tsp;
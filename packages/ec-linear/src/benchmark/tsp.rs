@@ -0,0 +1,280 @@
+//! Travelling salesperson problems, read from files in the
+//! [TSPLIB](http://comopt.ifi.uni-heidelberg.de/software/TSPLIB95/) format.
+
+use std::{fs, path::Path, str::FromStr};
+
+use anyhow::{bail, ensure, Context, Result};
+use num_traits::ToPrimitive;
+
+use crate::genome::permutation::Permutation;
+
+/// A symmetric TSP instance, with the (integer) distances between every
+/// pair of cities computed up front.
+///
+/// Cities are numbered from 0 here, even though TSPLIB files number them
+/// from 1, so that tours can be represented as [`Permutation`]s.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tsp {
+    name: String,
+    distances: Vec<Vec<i64>>,
+}
+
+/// How distances are computed from node coordinates, following the TSPLIB
+/// definitions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EdgeWeightType {
+    Euclidean,
+    CeilingEuclidean,
+    Pseudo,
+    Geographical,
+}
+
+impl FromStr for EdgeWeightType {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(match s {
+            "EUC_2D" => Self::Euclidean,
+            "CEIL_2D" => Self::CeilingEuclidean,
+            "ATT" => Self::Pseudo,
+            "GEO" => Self::Geographical,
+            _ => bail!(
+                "Unsupported EDGE_WEIGHT_TYPE {s}; only EUC_2D, CEIL_2D, ATT, and GEO are \
+                 supported"
+            ),
+        })
+    }
+}
+
+impl EdgeWeightType {
+    fn distance(self, (x1, y1): (f64, f64), (x2, y2): (f64, f64)) -> Option<i64> {
+        let (dx, dy) = (x1 - x2, y1 - y2);
+        match self {
+            Self::Euclidean => dx.hypot(dy).round().to_i64(),
+            Self::CeilingEuclidean => dx.hypot(dy).ceil().to_i64(),
+            Self::Pseudo => {
+                let distance = (dx.mul_add(dx, dy * dy) / 10.0).sqrt();
+                let rounded = distance.round();
+                if rounded < distance {
+                    rounded + 1.0
+                } else {
+                    rounded
+                }
+                .to_i64()
+            }
+            Self::Geographical => {
+                // TSPLIB's approximations of pi and the Earth's radius.
+                #[allow(clippy::approx_constant)]
+                const PI: f64 = 3.141_592;
+                const RADIUS: f64 = 6_378.388;
+                // Coordinates are `DDD.MM` (degrees and minutes).
+                let radians = |coordinate: f64| {
+                    let degrees = coordinate.trunc();
+                    let minutes = coordinate - degrees;
+                    PI * (degrees + 5.0 * minutes / 3.0) / 180.0
+                };
+                let (latitude1, longitude1) = (radians(x1), radians(y1));
+                let (latitude2, longitude2) = (radians(x2), radians(y2));
+                let q1 = (longitude1 - longitude2).cos();
+                let q2 = (latitude1 - latitude2).cos();
+                let q3 = (latitude1 + latitude2).cos();
+                let angle = (0.5 * (1.0 + q1).mul_add(q2, -(1.0 - q1) * q3)).acos();
+                RADIUS.mul_add(angle, 1.0).trunc().to_i64()
+            }
+        }
+    }
+}
+
+impl Tsp {
+    /// Read and parse a TSPLIB file.
+    ///
+    /// # Errors
+    ///
+    /// This fails if the file can't be read or isn't a symmetric TSP with
+    /// one of the supported coordinate-based edge weight types.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read the TSPLIB file {}", path.display()))?;
+        contents
+            .parse()
+            .with_context(|| format!("Failed to parse the TSPLIB file {}", path.display()))
+    }
+
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    #[must_use]
+    pub const fn num_cities(&self) -> usize {
+        self.distances.len()
+    }
+
+    /// The distance between cities `from` and `to`.
+    ///
+    /// # Panics
+    ///
+    /// This panics if either city is out of range.
+    #[must_use]
+    pub fn distance(&self, from: usize, to: usize) -> i64 {
+        self.distances[from][to]
+    }
+
+    /// The total length of the round trip that visits the cities in the
+    /// order given by `tour` and then returns to the start.
+    ///
+    /// # Panics
+    ///
+    /// This panics if `tour` has cities that aren't in this problem.
+    #[must_use]
+    pub fn tour_length(&self, tour: &Permutation) -> i64 {
+        let order = tour.order();
+        order
+            .iter()
+            .zip(order.iter().cycle().skip(1))
+            .map(|(&from, &to)| self.distance(from, to))
+            .sum()
+    }
+}
+
+impl FromStr for Tsp {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut name = String::new();
+        let mut dimension = None;
+        let mut edge_weight_type = None;
+        let mut coordinates = Vec::new();
+        let mut lines = s.lines().enumerate();
+        while let Some((index, line)) = lines.next() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            if line == "EOF" {
+                break;
+            }
+            if line == "NODE_COORD_SECTION" {
+                let dimension = dimension
+                    .context("The DIMENSION must be given before the NODE_COORD_SECTION")?;
+                for _ in 0..dimension {
+                    let (index, line) = lines
+                        .next()
+                        .context("The file ended in the middle of the NODE_COORD_SECTION")?;
+                    coordinates.push(
+                        parse_coordinates(line)
+                            .with_context(|| format!("Line {}: invalid node", index + 1))?,
+                    );
+                }
+                continue;
+            }
+            let (key, value) = line
+                .split_once(':')
+                .with_context(|| format!("Line {}: expected `KEY : VALUE`", index + 1))?;
+            let value = value.trim();
+            match key.trim() {
+                "NAME" => value.clone_into(&mut name),
+                "TYPE" => ensure!(
+                    value == "TSP",
+                    "Line {}: only symmetric TSP files are supported, not {value}",
+                    index + 1
+                ),
+                "DIMENSION" => {
+                    dimension = Some(value.parse::<usize>().with_context(|| {
+                        format!("Line {}: invalid DIMENSION {value}", index + 1)
+                    })?);
+                }
+                "EDGE_WEIGHT_TYPE" => {
+                    edge_weight_type = Some(
+                        value
+                            .parse::<EdgeWeightType>()
+                            .with_context(|| format!("Line {}", index + 1))?,
+                    );
+                }
+                // Other keys (like COMMENT) don't affect the problem.
+                _ => {}
+            }
+        }
+        let edge_weight_type = edge_weight_type.context("No EDGE_WEIGHT_TYPE was given")?;
+        ensure!(!coordinates.is_empty(), "No NODE_COORD_SECTION was given");
+        let distances = coordinates
+            .iter()
+            .map(|&from| {
+                coordinates
+                    .iter()
+                    .map(|&to| {
+                        edge_weight_type
+                            .distance(from, to)
+                            .context("A distance was too large to represent")
+                    })
+                    .collect()
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { name, distances })
+    }
+}
+
+/// Parse a `<node number> <x> <y>` line, ignoring the node number since
+/// the nodes are always numbered in order.
+fn parse_coordinates(line: &str) -> Result<(f64, f64)> {
+    let fields = line.split_whitespace().collect::<Vec<_>>();
+    let [_, x, y] = fields[..] else {
+        bail!("Expected `<node> <x> <y>` but got `{line}`");
+    };
+    Ok((x.parse()?, y.parse()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SQUARE: &str = "
+        NAME : square
+        COMMENT : The corners of a 3x4 rectangle
+        TYPE : TSP
+        DIMENSION : 4
+        EDGE_WEIGHT_TYPE : EUC_2D
+        NODE_COORD_SECTION
+        1 0 0
+        2 3 4
+        3 3 0
+        4 0 4
+        EOF
+    ";
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn parse_and_measure_tours() {
+        let tsp: Tsp = SQUARE.parse().unwrap();
+        assert_eq!(tsp.name(), "square");
+        assert_eq!(tsp.num_cities(), 4);
+        assert_eq!(tsp.distance(0, 1), 5);
+        let around = Permutation::new(vec![0, 2, 1, 3]).unwrap();
+        assert_eq!(tsp.tour_length(&around), 14);
+        let crossing = Permutation::new(vec![0, 1, 2, 3]).unwrap();
+        assert_eq!(tsp.tour_length(&crossing), 18);
+    }
+
+    #[test]
+    fn errors() {
+        assert!(SQUARE.replace("EUC_2D", "EXPLICIT").parse::<Tsp>().is_err());
+        assert!(SQUARE
+            .replace("DIMENSION : 4", "DIMENSION : 5")
+            .parse::<Tsp>()
+            .is_err());
+        assert!(SQUARE.replace("2 3 4", "2 3").parse::<Tsp>().is_err());
+        assert!(SQUARE
+            .replace("TYPE : TSP", "TYPE : ATSP")
+            .parse::<Tsp>()
+            .is_err());
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn pseudo_euclidean_distance_rounds_up() {
+        // sqrt(4^2 / 10) = 1.26..., which rounds to 1, so it's bumped up to 2.
+        let tsp: Tsp = SQUARE.replace("EUC_2D", "ATT").parse().unwrap();
+        assert_eq!(tsp.distance(0, 3), 2);
+    }
+}
@@ -0,0 +1,450 @@
+use anyhow::{ensure, Result};
+use ec_core::individual::{ec::EcIndividual, scorer::Scorer};
+use num_traits::ToPrimitive;
+use rand::rngs::ThreadRng;
+use rayon::prelude::{IntoParallelIterator, ParallelIterator};
+
+use super::linalg::{identity, multiply, multiply_transpose, norm, symmetric_eigen};
+use crate::genome::{
+    real::{standard_normal, Real},
+    vector::Vector,
+};
+
+/// The strategy parameters, which only depend on the number of dimensions
+/// and λ. These are the defaults from Hansen's "The CMA Evolution Strategy:
+/// A Tutorial" (2016).
+#[derive(Debug, Clone)]
+struct Parameters {
+    lambda: usize,
+    weights: Vec<f64>,
+    mu_eff: f64,
+    c_sigma: f64,
+    d_sigma: f64,
+    c_c: f64,
+    c_1: f64,
+    c_mu: f64,
+    /// The expected length of a standard normal vector.
+    chi_n: f64,
+}
+
+impl Parameters {
+    fn new(dimensions: usize, lambda: usize) -> Self {
+        let n = dimensions.to_f64().unwrap_or(1.0);
+        let mu = lambda / 2;
+        let raw_weights = (1..=mu)
+            .map(|i| {
+                let half = lambda.to_f64().unwrap_or(2.0).mul_add(0.5, 0.5).ln();
+                half - i.to_f64().unwrap_or(1.0).ln()
+            })
+            .collect::<Vec<_>>();
+        let total = raw_weights.iter().sum::<f64>();
+        let weights = raw_weights
+            .into_iter()
+            .map(|weight| weight / total)
+            .collect::<Vec<_>>();
+        let mu_eff = weights
+            .iter()
+            .map(|weight| weight * weight)
+            .sum::<f64>()
+            .recip();
+        let c_sigma = (mu_eff + 2.0) / (n + mu_eff + 5.0);
+        let d_sigma =
+            2.0f64.mul_add((((mu_eff - 1.0) / (n + 1.0)).sqrt() - 1.0).max(0.0), 1.0) + c_sigma;
+        let c_c = (4.0 + mu_eff / n) / (2.0f64.mul_add(mu_eff / n, n + 4.0));
+        let c_1 = 2.0 / (n + 1.3).mul_add(n + 1.3, mu_eff);
+        let c_mu = (1.0 - c_1)
+            .min(2.0 * (mu_eff - 2.0 + mu_eff.recip()) / (n + 2.0).mul_add(n + 2.0, mu_eff));
+        let chi_n = n.sqrt() * (1.0 - (4.0 * n).recip() + (21.0 * n * n).recip());
+        Self {
+            lambda,
+            weights,
+            mu_eff,
+            c_sigma,
+            d_sigma,
+            c_c,
+            c_1,
+            c_mu,
+            chi_n,
+        }
+    }
+}
+
+/// A sampled candidate solution, along with the step `y` (where the genome
+/// is `mean + sigma * y`) that the updates are computed from.
+struct Candidate<T, R> {
+    step: Vec<f64>,
+    individual: EcIndividual<Vector<T>, R>,
+}
+
+/// The covariance matrix adaptation evolution strategy (CMA-ES).
+///
+/// Each generation, λ candidates are sampled from the multivariate normal
+/// distribution `N(mean, sigma² C)`. The mean moves to a weighted average of
+/// the best μ = λ/2 candidates, the covariance matrix `C` is adapted towards
+/// the directions of the successful steps (with both the rank-one update
+/// from the evolution path and the rank-μ update from the current
+/// generation), and the overall step size `sigma` is adapted by cumulative
+/// step-size adaptation. This lets CMA-ES learn the scaling and rotation of
+/// ill-conditioned and non-separable problems like Rosenbrock.
+///
+/// The default λ is `4 + ⌊3 ln n⌋` for `n` dimensions, and all the other
+/// parameters are set from that as in Hansen's tutorial.
+pub struct CmaEs<T, R, S> {
+    parameters: Parameters,
+    mean: Vec<f64>,
+    sigma: f64,
+    /// The covariance matrix `C`, row-major.
+    covariance: Vec<f64>,
+    /// The eigenvectors of `C`, as columns.
+    eigenvectors: Vec<f64>,
+    /// The square roots of the eigenvalues of `C`.
+    scales: Vec<f64>,
+    sigma_path: Vec<f64>,
+    covariance_path: Vec<f64>,
+    generation: usize,
+    best: EcIndividual<Vector<T>, R>,
+    scorer: S,
+}
+
+impl<T, R, S> CmaEs<T, R, S>
+where
+    T: Real,
+    S: Scorer<Vector<T>, Score = R>,
+{
+    /// Start the search from the distribution centred on `mean`, with
+    /// initial step size `sigma` and the identity covariance matrix.
+    ///
+    /// # Errors
+    ///
+    /// This fails if `mean` is empty or `sigma` isn't positive and finite.
+    pub fn new(mean: Vector<T>, sigma: f64, scorer: S) -> Result<Self> {
+        ensure!(
+            !mean.genes.is_empty(),
+            "CMA-ES needs at least one dimension"
+        );
+        ensure!(
+            sigma > 0.0 && sigma.is_finite(),
+            "The initial step size {sigma} must be positive"
+        );
+        let n = mean.genes.len();
+        let lambda = 4
+            + (3.0 * n.to_f64().unwrap_or(1.0).ln())
+                .floor()
+                .to_usize()
+                .unwrap_or(0);
+        let test_results = scorer.score(&mean);
+        Ok(Self {
+            parameters: Parameters::new(n, lambda),
+            mean: mean.genes.iter().map(|&gene| gene.into()).collect(),
+            sigma,
+            covariance: identity(n),
+            eigenvectors: identity(n),
+            scales: vec![1.0; n],
+            sigma_path: vec![0.0; n],
+            covariance_path: vec![0.0; n],
+            generation: 0,
+            best: EcIndividual::new(mean, test_results),
+            scorer,
+        })
+    }
+
+    /// Use `lambda` candidates per generation instead of the default.
+    /// Larger populations make CMA-ES more robust on multimodal problems.
+    ///
+    /// # Errors
+    ///
+    /// This fails if `lambda` is less than 2.
+    pub fn with_lambda(mut self, lambda: usize) -> Result<Self> {
+        ensure!(
+            lambda >= 2,
+            "CMA-ES needs at least two candidates per generation"
+        );
+        self.parameters = Parameters::new(self.mean.len(), lambda);
+        Ok(self)
+    }
+}
+
+impl<T, R, S> CmaEs<T, R, S> {
+    /// The mean of the current search distribution.
+    pub fn mean(&self) -> &[f64] {
+        &self.mean
+    }
+
+    /// The current overall step size.
+    pub const fn sigma(&self) -> f64 {
+        self.sigma
+    }
+
+    /// The best individual seen so far.
+    pub const fn best(&self) -> &EcIndividual<Vector<T>, R> {
+        &self.best
+    }
+
+    /// The number of generations so far.
+    pub const fn generation(&self) -> usize {
+        self.generation
+    }
+
+    pub const fn lambda(&self) -> usize {
+        self.parameters.lambda
+    }
+
+    /// The condition number of the covariance matrix, i.e., the ratio of its
+    /// largest to smallest eigenvalue. This is useful for seeing how much
+    /// structure CMA-ES has learned, and very large values suggest it's
+    /// time to stop.
+    pub fn condition_number(&self) -> f64 {
+        let largest = self.scales.iter().copied().fold(f64::MIN, f64::max);
+        let smallest = self.scales.iter().copied().fold(f64::MAX, f64::min);
+        (largest / smallest).powi(2)
+    }
+}
+
+impl<T, R, S> CmaEs<T, R, S>
+where
+    T: Real,
+    R: Ord,
+    S: Scorer<Vector<T>, Score = R>,
+{
+    fn sample(&self, rng: &mut ThreadRng) -> Candidate<T, R> {
+        let scaled = self
+            .scales
+            .iter()
+            .map(|scale| scale * standard_normal(rng))
+            .collect::<Vec<_>>();
+        let step = multiply(&self.eigenvectors, &scaled);
+        let genome = self
+            .mean
+            .iter()
+            .zip(&step)
+            .map(|(&mean, &y)| T::from(self.sigma.mul_add(y, mean)))
+            .collect();
+        let test_results = self.scorer.score(&genome);
+        Candidate {
+            step,
+            individual: EcIndividual::new(genome, test_results),
+        }
+    }
+
+    /// Update the distribution from a generation of candidates.
+    fn update(&mut self, mut candidates: Vec<Candidate<T, R>>) {
+        let n = self.mean.len();
+        let p = &self.parameters;
+        candidates.sort_by(|x, y| y.individual.test_results.cmp(&x.individual.test_results));
+        let selected = &candidates[..p.weights.len()];
+
+        // Move the mean by the weighted average of the selected steps.
+        let mut mean_step = vec![0.0; n];
+        for (weight, candidate) in p.weights.iter().zip(selected) {
+            for (total, y) in mean_step.iter_mut().zip(&candidate.step) {
+                *total += weight * y;
+            }
+        }
+        for (mean, y) in self.mean.iter_mut().zip(&mean_step) {
+            *mean = self.sigma.mul_add(*y, *mean);
+        }
+
+        // Update the evolution path for sigma, which uses the step in the
+        // coordinate system where the distribution is isotropic, i.e.,
+        // `C^(-1/2) * mean_step`.
+        let whitened = multiply(
+            &self.eigenvectors,
+            &multiply_transpose(&self.eigenvectors, &mean_step)
+                .into_iter()
+                .zip(&self.scales)
+                .map(|(x, scale)| x / scale)
+                .collect::<Vec<_>>(),
+        );
+        let sigma_rate = (p.c_sigma * (2.0 - p.c_sigma) * p.mu_eff).sqrt();
+        for (path, x) in self.sigma_path.iter_mut().zip(&whitened) {
+            *path = (1.0 - p.c_sigma).mul_add(*path, sigma_rate * x);
+        }
+
+        // Stall the covariance path when sigma's path is long, which stops C
+        // from growing too fast when sigma is too small.
+        self.generation += 1;
+        let generations = i32::try_from(self.generation).unwrap_or(i32::MAX);
+        let sigma_path_length = norm(&self.sigma_path);
+        let correction = (1.0 - (1.0 - p.c_sigma).powi(2 * generations)).sqrt();
+        let threshold = (1.4 + 2.0 / (n.to_f64().unwrap_or(1.0) + 1.0)) * p.chi_n;
+        let stalled = sigma_path_length / correction >= threshold;
+        let covariance_rate = if stalled {
+            0.0
+        } else {
+            (p.c_c * (2.0 - p.c_c) * p.mu_eff).sqrt()
+        };
+        for (path, y) in self.covariance_path.iter_mut().zip(&mean_step) {
+            *path = (1.0 - p.c_c).mul_add(*path, covariance_rate * y);
+        }
+
+        // The rank-one and rank-μ updates of the covariance matrix. When the
+        // path is stalled, the `stall_correction` term makes up for the
+        // variance that the path would have added.
+        let stall_correction = if stalled {
+            p.c_1 * p.c_c * (2.0 - p.c_c)
+        } else {
+            0.0
+        };
+        let decay = 1.0 - p.c_1 - p.c_mu + stall_correction;
+        for row in 0..n {
+            for column in 0..n {
+                let rank_one = self.covariance_path[row] * self.covariance_path[column];
+                let rank_mu = p
+                    .weights
+                    .iter()
+                    .zip(selected)
+                    .map(|(weight, candidate)| {
+                        weight * candidate.step[row] * candidate.step[column]
+                    })
+                    .sum::<f64>();
+                let entry = &mut self.covariance[row * n + column];
+                *entry = p
+                    .c_mu
+                    .mul_add(rank_mu, decay.mul_add(*entry, p.c_1 * rank_one));
+            }
+        }
+
+        // Cumulative step-size adaptation: grow sigma if the path is longer
+        // than expected for random steps, and shrink it if it's shorter.
+        self.sigma *= ((p.c_sigma / p.d_sigma) * (sigma_path_length / p.chi_n - 1.0)).exp();
+
+        self.decompose();
+
+        if candidates[0].individual.test_results > self.best.test_results {
+            self.best = candidates.swap_remove(0).individual;
+        }
+    }
+
+    /// Recompute the eigendecomposition of the covariance matrix, after
+    /// making sure it's exactly symmetric and keeping its eigenvalues
+    /// positive in the face of rounding errors.
+    fn decompose(&mut self) {
+        let n = self.mean.len();
+        for row in 0..n {
+            for column in row + 1..n {
+                let average = f64::midpoint(
+                    self.covariance[row * n + column],
+                    self.covariance[column * n + row],
+                );
+                self.covariance[row * n + column] = average;
+                self.covariance[column * n + row] = average;
+            }
+        }
+        let (values, vectors) = symmetric_eigen(&self.covariance, n);
+        let largest = values.iter().copied().fold(f64::MIN_POSITIVE, f64::max);
+        self.scales = values
+            .into_iter()
+            .map(|value| value.max(largest * 1e-20).sqrt())
+            .collect();
+        self.eigenvectors = vectors;
+    }
+
+    /// Sample and score the next generation of candidates serially, and
+    /// update the search distribution.
+    ///
+    /// # Errors
+    ///
+    /// This can't currently fail, but returns a `Result` to match the other
+    /// strategies and `Generation`.
+    pub fn serial_next(&mut self) -> Result<()> {
+        let mut rng = rand::thread_rng();
+        let candidates = (0..self.parameters.lambda)
+            .map(|_| self.sample(&mut rng))
+            .collect();
+        self.update(candidates);
+        Ok(())
+    }
+
+    /// Sample and score the next generation of candidates using a Rayon
+    /// parallel iterator, and update the search distribution.
+    ///
+    /// # Errors
+    ///
+    /// This can't currently fail, but returns a `Result` to match the other
+    /// strategies and `Generation`.
+    pub fn par_next(&mut self) -> Result<()>
+    where
+        T: Send + Sync,
+        R: Send + Sync,
+        S: Sync,
+    {
+        let candidates = (0..self.parameters.lambda)
+            .into_par_iter()
+            .map_init(rand::thread_rng, |rng, _| self.sample(rng))
+            .collect();
+        self.update(candidates);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ec_core::{individual::scorer::FnScorer, test_results::Error};
+    use ordered_float::OrderedFloat;
+
+    use super::*;
+    use crate::benchmark::continuous::{rosenbrock, sphere};
+
+    #[test]
+    fn default_parameters() {
+        let parameters = Parameters::new(10, 10);
+        assert_eq!(parameters.weights.len(), 5);
+        assert!((parameters.weights.iter().sum::<f64>() - 1.0).abs() < 1e-12);
+        assert!(parameters.weights.windows(2).all(|pair| pair[0] > pair[1]));
+        assert!(parameters.c_1 + parameters.c_mu <= 1.0);
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn solves_sphere() {
+        let scorer =
+            FnScorer(|genome: &Vector<f64>| Error::from(OrderedFloat(sphere(&genome.genes))));
+        let mut es = CmaEs::new(
+            Vector {
+                genes: vec![3.0; 5],
+            },
+            1.0,
+            scorer,
+        )
+        .unwrap();
+        assert_eq!(es.lambda(), 8);
+        for _ in 0..300 {
+            es.serial_next().unwrap();
+        }
+        assert!(es.best().test_results.error < OrderedFloat(1e-10));
+        assert_eq!(es.generation(), 300);
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn solves_rosenbrock() {
+        // Rosenbrock's curved valley needs the covariance matrix to keep
+        // adapting, which the isotropic strategies struggle with.
+        let scorer =
+            FnScorer(|genome: &Vector<f64>| Error::from(OrderedFloat(rosenbrock(&genome.genes))));
+        let mut es = CmaEs::new(
+            Vector {
+                genes: vec![0.0; 4],
+            },
+            0.5,
+            scorer,
+        )
+        .unwrap();
+        for _ in 0..1_000 {
+            es.par_next().unwrap();
+        }
+        assert!(es.best().test_results.error < OrderedFloat(1e-6));
+        assert!(es.condition_number() > 1.0);
+    }
+
+    #[test]
+    fn bad_parameters_are_rejected() {
+        let scorer =
+            FnScorer(|genome: &Vector<f64>| Error::from(OrderedFloat(sphere(&genome.genes))));
+        assert!(CmaEs::new(Vector { genes: vec![] }, 1.0, scorer).is_err());
+        assert!(CmaEs::new(Vector { genes: vec![1.0] }, -1.0, scorer).is_err());
+        assert!(CmaEs::new(Vector { genes: vec![1.0] }, 1.0, scorer)
+            .and_then(|es| es.with_lambda(1))
+            .is_err());
+    }
+}
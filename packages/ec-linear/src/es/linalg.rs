@@ -0,0 +1,123 @@
+//! The little bit of linear algebra that CMA-ES needs. Matrices are square
+//! and stored as flat vectors in row-major order.
+
+/// The eigenvalues and eigenvectors of the symmetric `n`x`n` matrix
+/// `matrix`, computed with the cyclic Jacobi method.
+///
+/// The eigenvectors are the columns of the returned matrix, in the same
+/// order as the eigenvalues.
+pub fn symmetric_eigen(matrix: &[f64], n: usize) -> (Vec<f64>, Vec<f64>) {
+    debug_assert_eq!(matrix.len(), n * n);
+    let mut rotated = matrix.to_vec();
+    let mut vectors = identity(n);
+    let scale = rotated.iter().map(|x| x * x).sum::<f64>();
+    for _ in 0..100 {
+        let off_diagonal = (0..n)
+            .flat_map(|row| {
+                (0..n)
+                    .filter(move |&column| column != row)
+                    .map(move |column| (row, column))
+            })
+            .map(|(row, column)| rotated[row * n + column].powi(2))
+            .sum::<f64>();
+        if off_diagonal <= 1e-24 * scale {
+            break;
+        }
+        for p in 0..n {
+            for q in p + 1..n {
+                let apq = rotated[p * n + q];
+                if apq == 0.0 {
+                    continue;
+                }
+                let theta = (rotated[q * n + q] - rotated[p * n + p]) / (2.0 * apq);
+                let tangent = theta.signum() / (theta.abs() + theta.hypot(1.0));
+                let cos = tangent.hypot(1.0).recip();
+                let sin = tangent * cos;
+                for k in 0..n {
+                    let (akp, akq) = (rotated[k * n + p], rotated[k * n + q]);
+                    rotated[k * n + p] = cos * akp - sin * akq;
+                    rotated[k * n + q] = sin * akp + cos * akq;
+                }
+                for k in 0..n {
+                    let (apk, aqk) = (rotated[p * n + k], rotated[q * n + k]);
+                    rotated[p * n + k] = cos * apk - sin * aqk;
+                    rotated[q * n + k] = sin * apk + cos * aqk;
+                }
+                for k in 0..n {
+                    let (vkp, vkq) = (vectors[k * n + p], vectors[k * n + q]);
+                    vectors[k * n + p] = cos * vkp - sin * vkq;
+                    vectors[k * n + q] = sin * vkp + cos * vkq;
+                }
+            }
+        }
+    }
+    let values = (0..n).map(|i| rotated[i * n + i]).collect();
+    (values, vectors)
+}
+
+/// The `n`x`n` identity matrix.
+pub fn identity(n: usize) -> Vec<f64> {
+    let mut matrix = vec![0.0; n * n];
+    for i in 0..n {
+        matrix[i * n + i] = 1.0;
+    }
+    matrix
+}
+
+/// The product of the `n`x`n` matrix `matrix` and the vector `vector`.
+pub fn multiply(matrix: &[f64], vector: &[f64]) -> Vec<f64> {
+    matrix
+        .chunks_exact(vector.len())
+        .map(|row| row.iter().zip(vector).map(|(a, b)| a * b).sum())
+        .collect()
+}
+
+/// The product of the transpose of the `n`x`n` matrix `matrix` and the
+/// vector `vector`.
+pub fn multiply_transpose(matrix: &[f64], vector: &[f64]) -> Vec<f64> {
+    let n = vector.len();
+    (0..n)
+        .map(|column| {
+            (0..n)
+                .map(|row| matrix[row * n + column] * vector[row])
+                .sum()
+        })
+        .collect()
+}
+
+pub fn norm(vector: &[f64]) -> f64 {
+    vector.iter().map(|x| x * x).sum::<f64>().sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn eigen_decomposition_reconstructs_the_matrix() {
+        let matrix = [4.0, 1.0, 2.0, 1.0, 3.0, 0.5, 2.0, 0.5, 5.0];
+        let (values, vectors) = symmetric_eigen(&matrix, 3);
+        // Check that `matrix * v = lambda * v` for each eigenpair.
+        for (index, value) in values.iter().enumerate() {
+            let vector = (0..3)
+                .map(|row| vectors[row * 3 + index])
+                .collect::<Vec<_>>();
+            let product = multiply(&matrix, &vector);
+            for (p, v) in product.iter().zip(&vector) {
+                assert!(
+                    (p - value * v).abs() < 1e-9,
+                    "{product:?} vs {value} * {vector:?}"
+                );
+            }
+        }
+        let trace = values.iter().sum::<f64>();
+        assert!((trace - 12.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn diagonal_matrices_are_already_decomposed() {
+        let (values, vectors) = symmetric_eigen(&[2.0, 0.0, 0.0, 3.0], 2);
+        assert_eq!(values, vec![2.0, 3.0]);
+        assert_eq!(vectors, identity(2));
+    }
+}
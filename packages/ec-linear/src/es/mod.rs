@@ -0,0 +1,21 @@
+//! Evolution strategies for real-valued vectors.
+//!
+//! These maintain their own (small) populations and step sizes rather than
+//! being built out of selectors and operators like a GA, but they're driven
+//! the same way as [`Generation`](ec_core::generation::Generation): call
+//! `serial_next()` (or `par_next()` for the strategies with more than one
+//! child per generation) once per generation, and inspect the best
+//! individual found so far in between. They use the same
+//! [`Scorer`](ec_core::individual::scorer::Scorer)s as GAs on
+//! [`Vector`](crate::genome::vector::Vector)s, so it's easy to compare them
+//! on the same problems.
+//!
+//! - [`one_plus_one`]: the (1+1)-ES with the one-fifth success rule.
+//! - [`self_adaptive`]: the (μ/μ,λ)-ES with a step size per gene stored in (and
+//!   evolved with) the genome.
+//! - [`cma_es`]: the covariance matrix adaptation evolution strategy.
+
+pub mod cma_es;
+mod linalg;
+pub mod one_plus_one;
+pub mod self_adaptive;
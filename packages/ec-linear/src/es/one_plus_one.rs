@@ -0,0 +1,146 @@
+use anyhow::{ensure, Result};
+use ec_core::individual::{ec::EcIndividual, scorer::Scorer};
+
+use crate::genome::{
+    real::{standard_normal, Real},
+    vector::Vector,
+};
+
+/// The (1+1) evolution strategy with the one-fifth success rule.
+///
+/// Each generation the parent is mutated by adding normally distributed
+/// noise with standard deviation `sigma` to every gene, and the child
+/// replaces the parent if it's at least as good. The step size is
+/// multiplied by `step_factor` after every success and by
+/// `step_factor^(-1/4)` after every failure, so it only stays steady when
+/// one in five children succeeds.
+pub struct OnePlusOneEs<T, R, S> {
+    parent: EcIndividual<Vector<T>, R>,
+    sigma: f64,
+    step_factor: f64,
+    scorer: S,
+}
+
+impl<T, R, S> OnePlusOneEs<T, R, S>
+where
+    T: Real,
+    S: Scorer<Vector<T>, Score = R>,
+{
+    pub const DEFAULT_STEP_FACTOR: f64 = 1.5;
+
+    /// Start the search from `initial`, with initial step size `sigma`.
+    ///
+    /// # Errors
+    ///
+    /// This fails if `sigma` isn't positive and finite.
+    pub fn new(initial: Vector<T>, sigma: f64, scorer: S) -> Result<Self> {
+        ensure!(
+            sigma > 0.0 && sigma.is_finite(),
+            "The initial step size {sigma} must be positive"
+        );
+        let test_results = scorer.score(&initial);
+        Ok(Self {
+            parent: EcIndividual::new(initial, test_results),
+            sigma,
+            step_factor: Self::DEFAULT_STEP_FACTOR,
+            scorer,
+        })
+    }
+
+    /// # Errors
+    ///
+    /// This fails if `step_factor` isn't greater than 1.
+    pub fn with_step_factor(mut self, step_factor: f64) -> Result<Self> {
+        ensure!(
+            step_factor > 1.0 && step_factor.is_finite(),
+            "The step factor {step_factor} must be greater than 1"
+        );
+        self.step_factor = step_factor;
+        Ok(self)
+    }
+}
+
+impl<T, R, S> OnePlusOneEs<T, R, S> {
+    /// The current parent, which is always the best individual found so far.
+    pub const fn parent(&self) -> &EcIndividual<Vector<T>, R> {
+        &self.parent
+    }
+
+    /// The current step size.
+    pub const fn sigma(&self) -> f64 {
+        self.sigma
+    }
+}
+
+impl<T, R, S> OnePlusOneEs<T, R, S>
+where
+    T: Real,
+    R: Ord,
+    S: Scorer<Vector<T>, Score = R>,
+{
+    /// Make and score the next child, and adapt the step size.
+    ///
+    /// # Errors
+    ///
+    /// This can't currently fail, but returns a `Result` to match the other
+    /// strategies and `Generation`.
+    pub fn serial_next(&mut self) -> Result<()> {
+        let mut rng = rand::thread_rng();
+        let child = self
+            .parent
+            .genome
+            .genes
+            .iter()
+            .map(|&gene| T::from(self.sigma.mul_add(standard_normal(&mut rng), gene.into())))
+            .collect::<Vector<T>>();
+        let test_results = self.scorer.score(&child);
+        if test_results >= self.parent.test_results {
+            self.parent = EcIndividual::new(child, test_results);
+            self.sigma *= self.step_factor;
+        } else {
+            self.sigma *= self.step_factor.powf(-0.25);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ec_core::{individual::scorer::FnScorer, test_results::Error};
+    use ordered_float::OrderedFloat;
+
+    use super::*;
+    use crate::benchmark::continuous::sphere;
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn solves_sphere() {
+        let scorer =
+            FnScorer(|genome: &Vector<f64>| Error::from(OrderedFloat(sphere(&genome.genes))));
+        let mut es = OnePlusOneEs::new(
+            Vector {
+                genes: vec![3.0; 5],
+            },
+            1.0,
+            scorer,
+        )
+        .unwrap();
+        for _ in 0..2_000 {
+            es.serial_next().unwrap();
+        }
+        assert!(es.parent().test_results.error < OrderedFloat(1e-8));
+        // The one-fifth rule should have shrunk the step size along with the
+        // distance to the optimum.
+        assert!(es.sigma() < 1e-3);
+    }
+
+    #[test]
+    fn bad_parameters_are_rejected() {
+        let scorer =
+            FnScorer(|genome: &Vector<f64>| Error::from(OrderedFloat(sphere(&genome.genes))));
+        assert!(OnePlusOneEs::new(Vector { genes: vec![0.0] }, 0.0, scorer).is_err());
+        assert!(OnePlusOneEs::new(Vector { genes: vec![0.0] }, 1.0, scorer)
+            .and_then(|es| es.with_step_factor(0.5))
+            .is_err());
+    }
+}
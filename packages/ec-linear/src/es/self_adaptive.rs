@@ -0,0 +1,367 @@
+use anyhow::{ensure, Result};
+use ec_core::{
+    genome::Genome,
+    individual::{ec::EcIndividual, scorer::Scorer},
+    operator::mutator::Mutator,
+};
+use num_traits::ToPrimitive;
+use rand::rngs::ThreadRng;
+use rayon::prelude::{IntoParallelIterator, ParallelIterator};
+
+use crate::genome::{
+    real::{standard_normal, Real},
+    vector::Vector,
+};
+
+/// A real-valued vector together with its own step size for each gene.
+///
+/// The step sizes are evolved along with the values (see
+/// [`SelfAdaptiveMutation`]), so the search learns how far to move in each
+/// direction rather than having that set by the user.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SelfAdaptive<T> {
+    pub values: Vector<T>,
+    pub step_sizes: Vector<T>,
+}
+
+impl<T> Genome for SelfAdaptive<T> {
+    type Gene = T;
+}
+
+impl<T: Real> SelfAdaptive<T> {
+    /// Give every gene of `values` the same initial `step_size`.
+    ///
+    /// # Errors
+    ///
+    /// This fails if `step_size` isn't positive and finite.
+    pub fn new(values: Vector<T>, step_size: f64) -> Result<Self> {
+        ensure!(
+            step_size > 0.0 && step_size.is_finite(),
+            "The initial step size {step_size} must be positive"
+        );
+        let step_sizes = values.genes.iter().map(|_| T::from(step_size)).collect();
+        Ok(Self { values, step_sizes })
+    }
+}
+
+/// Self-adaptive mutation with one step size per gene.
+///
+/// Each step size is first multiplied by `exp(τ'·N + τ·N_i)`, where `N` is
+/// shared by all the genes and `N_i` is drawn for each gene, and then each
+/// value gets normally distributed noise with its new step size added to
+/// it. The learning rates are the usual `τ' = 1/√(2n)` and
+/// `τ = 1/√(2√n)` for `n` genes. Step sizes are kept above
+/// `min_step_size` so the search can't freeze completely.
+#[derive(Debug, Clone, Copy)]
+pub struct SelfAdaptiveMutation {
+    min_step_size: f64,
+}
+
+impl SelfAdaptiveMutation {
+    pub const DEFAULT_MIN_STEP_SIZE: f64 = 1e-300;
+
+    /// # Errors
+    ///
+    /// This fails if `min_step_size` isn't positive and finite.
+    pub fn new(min_step_size: f64) -> Result<Self> {
+        ensure!(
+            min_step_size > 0.0 && min_step_size.is_finite(),
+            "The minimum step size {min_step_size} must be positive"
+        );
+        Ok(Self { min_step_size })
+    }
+}
+
+impl Default for SelfAdaptiveMutation {
+    fn default() -> Self {
+        Self {
+            min_step_size: Self::DEFAULT_MIN_STEP_SIZE,
+        }
+    }
+}
+
+impl<T: Real> Mutator<SelfAdaptive<T>> for SelfAdaptiveMutation {
+    fn mutate(&self, genome: SelfAdaptive<T>, rng: &mut ThreadRng) -> Result<SelfAdaptive<T>> {
+        ensure!(
+            genome.values.genes.len() == genome.step_sizes.genes.len(),
+            "A genome with {} values can't have {} step sizes",
+            genome.values.genes.len(),
+            genome.step_sizes.genes.len()
+        );
+        let n = genome.values.genes.len().to_f64().unwrap_or(1.0).max(1.0);
+        let global_rate = (2.0 * n).sqrt().recip();
+        let local_rate = (2.0 * n.sqrt()).sqrt().recip();
+        let global_change = global_rate * standard_normal(rng);
+        let (values, step_sizes) = genome
+            .values
+            .genes
+            .iter()
+            .zip(&genome.step_sizes.genes)
+            .map(|(&value, &step_size)| {
+                let step_size = (step_size.into()
+                    * local_rate
+                        .mul_add(standard_normal(rng), global_change)
+                        .exp())
+                .max(self.min_step_size);
+                let value = step_size.mul_add(standard_normal(rng), value.into());
+                (T::from(value), T::from(step_size))
+            })
+            .unzip::<_, _, Vec<_>, Vec<_>>();
+        Ok(SelfAdaptive {
+            values: Vector { genes: values },
+            step_sizes: Vector { genes: step_sizes },
+        })
+    }
+}
+
+/// The (μ/μ,λ) evolution strategy with self-adaptive step sizes.
+///
+/// Each generation, the μ parents are combined by global intermediate
+/// recombination (averaging all their values and all their step sizes), and
+/// λ children are made by applying [`SelfAdaptiveMutation`] to that
+/// recombinant. The best μ children become the next parents; the old parents
+/// are always discarded ("comma" selection), which keeps the step sizes from
+/// getting stuck at values that happened to work once. The best individual
+/// ever seen is kept separately. Since test results can't be cloned, the
+/// best child is scored a second time whenever it becomes the best ever.
+///
+/// The scorer only sees the values, so the same scorers can be used here as
+/// for GAs on [`Vector`]s.
+pub struct SelfAdaptiveEs<T, R, S> {
+    parents: Vec<EcIndividual<SelfAdaptive<T>, R>>,
+    best: EcIndividual<Vector<T>, R>,
+    mutator: SelfAdaptiveMutation,
+    scorer: S,
+    mu: usize,
+    lambda: usize,
+}
+
+impl<T, R, S> SelfAdaptiveEs<T, R, S>
+where
+    T: Real,
+    S: Scorer<Vector<T>, Score = R>,
+{
+    pub const DEFAULT_MU: usize = 15;
+    pub const DEFAULT_LAMBDA: usize = 100;
+
+    /// Start the search from `initial`, with every gene having the initial
+    /// step size `step_size`. The initial genome is the only parent of the
+    /// first generation.
+    ///
+    /// # Errors
+    ///
+    /// This fails if `step_size` isn't positive and finite.
+    pub fn new(initial: Vector<T>, step_size: f64, scorer: S) -> Result<Self> {
+        let genome = SelfAdaptive::new(initial, step_size)?;
+        let best = EcIndividual::new(genome.values.clone(), scorer.score(&genome.values));
+        let test_results = scorer.score(&genome.values);
+        Ok(Self {
+            parents: vec![EcIndividual::new(genome, test_results)],
+            best,
+            mutator: SelfAdaptiveMutation::default(),
+            scorer,
+            mu: Self::DEFAULT_MU,
+            lambda: Self::DEFAULT_LAMBDA,
+        })
+    }
+
+    /// Set the number of parents `mu` and children `lambda`. A ratio of
+    /// about 1 to 7 is traditional.
+    ///
+    /// # Errors
+    ///
+    /// This fails if `mu` is zero or greater than `lambda`.
+    pub fn with_population_sizes(mut self, mu: usize, lambda: usize) -> Result<Self> {
+        ensure!(
+            0 < mu && mu <= lambda,
+            "A (μ/μ,λ) strategy needs 0 < μ <= λ, but μ = {mu} and λ = {lambda}"
+        );
+        self.mu = mu;
+        self.lambda = lambda;
+        Ok(self)
+    }
+
+    #[must_use]
+    pub const fn with_mutator(mut self, mutator: SelfAdaptiveMutation) -> Self {
+        self.mutator = mutator;
+        self
+    }
+}
+
+impl<T, R, S> SelfAdaptiveEs<T, R, S> {
+    /// The current parents, best first.
+    pub fn parents(&self) -> &[EcIndividual<SelfAdaptive<T>, R>] {
+        &self.parents
+    }
+
+    /// The best individual seen so far, which may no longer be a parent.
+    pub const fn best(&self) -> &EcIndividual<Vector<T>, R> {
+        &self.best
+    }
+
+    pub const fn mu(&self) -> usize {
+        self.mu
+    }
+
+    pub const fn lambda(&self) -> usize {
+        self.lambda
+    }
+}
+
+impl<T, R, S> SelfAdaptiveEs<T, R, S>
+where
+    T: Real,
+    R: Ord,
+    S: Scorer<Vector<T>, Score = R>,
+{
+    /// Average the values and step sizes of all the parents.
+    fn recombinant(&self) -> SelfAdaptive<T> {
+        let count = self.parents.len().to_f64().unwrap_or(1.0);
+        let average = |genes: fn(&SelfAdaptive<T>) -> &Vector<T>| -> Vector<T> {
+            let mut sums = vec![0.0; genes(&self.parents[0].genome).genes.len()];
+            for parent in &self.parents {
+                for (sum, &gene) in sums.iter_mut().zip(&genes(&parent.genome).genes) {
+                    *sum += gene.into();
+                }
+            }
+            sums.into_iter().map(|sum| T::from(sum / count)).collect()
+        };
+        SelfAdaptive {
+            values: average(|genome| &genome.values),
+            step_sizes: average(|genome| &genome.step_sizes),
+        }
+    }
+
+    fn make_child(
+        &self,
+        recombinant: &SelfAdaptive<T>,
+        rng: &mut ThreadRng,
+    ) -> Result<EcIndividual<SelfAdaptive<T>, R>> {
+        let genome = self.mutator.mutate(recombinant.clone(), rng)?;
+        let test_results = self.scorer.score(&genome.values);
+        Ok(EcIndividual::new(genome, test_results))
+    }
+
+    fn select_parents(&mut self, mut children: Vec<EcIndividual<SelfAdaptive<T>, R>>) {
+        children.sort_by(|x, y| y.test_results.cmp(&x.test_results));
+        children.truncate(self.mu);
+        if let Some(best_child) = children.first() {
+            if best_child.test_results > self.best.test_results {
+                let values = best_child.genome.values.clone();
+                let test_results = self.scorer.score(&values);
+                self.best = EcIndividual::new(values, test_results);
+            }
+        }
+        self.parents = children;
+    }
+
+    /// Make and score the next `lambda` children serially, and select the
+    /// next parents from them.
+    ///
+    /// # Errors
+    ///
+    /// This can return errors if mutating any of the children fails.
+    pub fn serial_next(&mut self) -> Result<()> {
+        let mut rng = rand::thread_rng();
+        let recombinant = self.recombinant();
+        let children = (0..self.lambda)
+            .map(|_| self.make_child(&recombinant, &mut rng))
+            .collect::<Result<_>>()?;
+        self.select_parents(children);
+        Ok(())
+    }
+
+    /// Make and score the next `lambda` children using a Rayon parallel
+    /// iterator, and select the next parents from them.
+    ///
+    /// # Errors
+    ///
+    /// This can return errors if mutating any of the children fails.
+    pub fn par_next(&mut self) -> Result<()>
+    where
+        T: Send + Sync,
+        R: Send + Sync,
+        S: Sync,
+    {
+        let recombinant = self.recombinant();
+        let children = (0..self.lambda)
+            .into_par_iter()
+            .map_init(rand::thread_rng, |rng, _| {
+                self.make_child(&recombinant, rng)
+            })
+            .collect::<Result<_>>()?;
+        self.select_parents(children);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ec_core::{individual::scorer::FnScorer, test_results::Error};
+    use ordered_float::OrderedFloat;
+
+    use super::*;
+    use crate::benchmark::continuous::sphere;
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn step_sizes_stay_positive() {
+        let mut rng = rand::thread_rng();
+        let mut genome = SelfAdaptive::new(
+            Vector {
+                genes: vec![0.0; 4],
+            },
+            1.0,
+        )
+        .unwrap();
+        for _ in 0..100 {
+            genome = SelfAdaptiveMutation::default()
+                .mutate(genome, &mut rng)
+                .unwrap();
+            assert!(genome.step_sizes.genes.iter().all(|&s: &f64| s > 0.0));
+        }
+        let mismatched = SelfAdaptive {
+            values: Vector {
+                genes: vec![0.0; 2],
+            },
+            step_sizes: Vector { genes: vec![1.0] },
+        };
+        assert!(SelfAdaptiveMutation::default()
+            .mutate(mismatched, &mut rng)
+            .is_err());
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn solves_sphere() {
+        let scorer =
+            FnScorer(|genome: &Vector<f64>| Error::from(OrderedFloat(sphere(&genome.genes))));
+        let mut es = SelfAdaptiveEs::new(
+            Vector {
+                genes: vec![3.0; 5],
+            },
+            1.0,
+            scorer,
+        )
+        .unwrap()
+        .with_population_sizes(5, 35)
+        .unwrap();
+        for _ in 0..300 {
+            es.par_next().unwrap();
+        }
+        assert!(es.best().test_results.error < OrderedFloat(1e-6));
+        assert_eq!(es.mu(), 5);
+        assert_eq!(es.parents().len(), 5);
+    }
+
+    #[test]
+    fn bad_population_sizes_are_rejected() {
+        let scorer =
+            FnScorer(|genome: &Vector<f64>| Error::from(OrderedFloat(sphere(&genome.genes))));
+        let es = || SelfAdaptiveEs::new(Vector { genes: vec![0.0] }, 1.0, scorer);
+        assert!(es().and_then(|es| es.with_population_sizes(0, 10)).is_err());
+        assert!(es()
+            .and_then(|es| es.with_population_sizes(11, 10))
+            .is_err());
+    }
+}
@@ -1,17 +1,24 @@
-use std::fmt::Display;
+use std::{
+    cell::Cell,
+    cmp::Ordering,
+    fmt::{Debug, Display},
+    hash::Hash,
+    ops::{Deref, DerefMut, Index, Range},
+};
 
-use anyhow::bail;
+use anyhow::{ensure, Result};
 use ec_core::{
     generator::{collection::CollectionGenerator, Generator},
     genome::Genome,
 };
+use num_traits::ToPrimitive;
 use rand::{rngs::ThreadRng, Rng};
 
 use super::Linear;
 use crate::recombinator::crossover::Crossover;
 
-// TODO: Ought to have `LinearGenome<T>` so that `Bitstring` is just
-//   `LinearGenome<bool>`.
+/// The number of bits in each word.
+const WORD_BITS: usize = 64;
 
 pub struct BoolGenerator {
     pub p: f64,
@@ -23,9 +30,25 @@ impl Generator<bool> for BoolGenerator {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+/// The bits of a [`Bitstring`], packed 64 to a word.
+///
+/// This has the same API as the `Vec<bool>` that bitstrings used to store,
+/// except that mutable access goes through [`BitMut`] proxies, since there's
+/// no `bool` in memory to borrow.
+///
+/// Bit `i` is bit `i % 64` (counting from the least significant bit) of
+/// word `i / 64`, and the unused bits of the last word are always zero. This
+/// lets us count, compare, and recombine bits a word at a time, which is
+/// much faster than working with one `bool` per bit.
+#[derive(Clone, Default, PartialEq, Eq, Hash)]
+pub struct Bits {
+    words: Vec<u64>,
+    len: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Bitstring {
-    pub bits: Vec<bool>,
+    pub bits: Bits,
 }
 
 impl<BG> Generator<Bitstring> for CollectionGenerator<BG>
@@ -33,12 +56,253 @@ where
     BG: Generator<bool>,
 {
     fn generate(&self, rng: &mut ThreadRng) -> anyhow::Result<Bitstring> {
-        let bits = self.generate(rng)?;
-        Ok(Bitstring { bits })
+        (0..self.size)
+            .map(|_| self.element_generator.generate(rng))
+            .collect()
+    }
+}
+
+/// The mask with the lowest `count` bits set.
+const fn low_bits(count: usize) -> u64 {
+    if count >= WORD_BITS {
+        u64::MAX
+    } else {
+        (1 << count) - 1
+    }
+}
+
+/// The mask for bit `index` within its word.
+const fn bit_mask(index: usize) -> u64 {
+    1 << (index % WORD_BITS)
+}
+
+/// A reference to `bit`, so that we can hand out `&bool`s without storing
+/// any.
+const fn bool_ref(bit: bool) -> &'static bool {
+    if bit { &true } else { &false }
+}
+
+fn popcount(word: u64) -> usize {
+    word.count_ones().to_usize().unwrap_or(0)
+}
+
+/// The index of each word that overlaps the range of bits `range`, along
+/// with the mask of the bits of that word that are in `range`.
+fn word_masks(range: Range<usize>) -> impl Iterator<Item = (usize, u64)> {
+    let words = if range.is_empty() {
+        0..0
+    } else {
+        range.start / WORD_BITS..(range.end - 1) / WORD_BITS + 1
+    };
+    words.map(move |word| {
+        let word_start = word * WORD_BITS;
+        let start = range.start.saturating_sub(word_start);
+        let end = (range.end - word_start).min(WORD_BITS);
+        (word, low_bits(end) & !low_bits(start))
+    })
+}
+
+impl Bits {
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            words: Vec::new(),
+            len: 0,
+        }
+    }
+
+    /// `num_bits` copies of `bit`.
+    fn repeat(bit: bool, num_bits: usize) -> Self {
+        let word = if bit { u64::MAX } else { 0 };
+        let mut bits = Self {
+            words: vec![word; num_bits.div_ceil(WORD_BITS)],
+            len: num_bits,
+        };
+        bits.clear_unused_bits();
+        bits
+    }
+
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    #[must_use]
+    pub fn get(&self, index: usize) -> Option<&bool> {
+        (index < self.len).then(|| bool_ref(self.bit(index)))
+    }
+
+    pub fn get_mut(&mut self, index: usize) -> Option<BitMut<'_>> {
+        (index < self.len)
+            .then(|| BitMut::new(Cell::from_mut(&mut self.words[index / WORD_BITS]), index))
+    }
+
+    pub fn push(&mut self, value: bool) {
+        if self.len.is_multiple_of(WORD_BITS) {
+            self.words.push(0);
+        }
+        self.len += 1;
+        self.set(self.len - 1, value);
+    }
+
+    #[must_use]
+    pub const fn iter(&self) -> Iter<'_> {
+        Iter {
+            bits: self,
+            range: 0..self.len,
+        }
+    }
+
+    pub const fn iter_mut(&mut self) -> IterMut<'_> {
+        IterMut {
+            range: 0..self.len,
+            words: Cell::from_mut(self.words.as_mut_slice()).as_slice_of_cells(),
+        }
+    }
+
+    /// The bit at `index`, which must be in range.
+    fn bit(&self, index: usize) -> bool {
+        self.words[index / WORD_BITS] & bit_mask(index) != 0
+    }
+
+    /// Set the bit at `index`, which must be in range, to `value`.
+    fn set(&mut self, index: usize, value: bool) {
+        if value {
+            self.words[index / WORD_BITS] |= bit_mask(index);
+        } else {
+            self.words[index / WORD_BITS] &= !bit_mask(index);
+        }
+    }
+
+    /// Restore the invariant that the unused bits of the last word are zero.
+    fn clear_unused_bits(&mut self) {
+        let used = self.len % WORD_BITS;
+        if used > 0 {
+            if let Some(last) = self.words.last_mut() {
+                *last &= low_bits(used);
+            }
+        }
+    }
+}
+
+impl Index<usize> for Bits {
+    type Output = bool;
+
+    fn index(&self, index: usize) -> &bool {
+        self.get(index).unwrap_or_else(|| {
+            panic!(
+                "Index {index} is out of range for a bitstring of length {}",
+                self.len
+            )
+        })
+    }
+}
+
+/// Bits are ordered lexicographically, like the `Vec<bool>`s they used to
+/// be.
+impl PartialOrd for Bits {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Bits {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.iter().cmp(other.iter())
+    }
+}
+
+impl Debug for Bits {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+impl From<Vec<bool>> for Bits {
+    fn from(bits: Vec<bool>) -> Self {
+        bits.into_iter().collect()
+    }
+}
+
+impl FromIterator<bool> for Bits {
+    fn from_iter<T: IntoIterator<Item = bool>>(iter: T) -> Self {
+        let mut bits = Self::new();
+        for bit in iter {
+            bits.push(bit);
+        }
+        bits
+    }
+}
+
+/// A mutable reference to one bit of a [`Bits`].
+///
+/// It dereferences to a `bool`, and any change is written back to the
+/// bitstring when it's dropped.
+#[derive(Debug)]
+pub struct BitMut<'a> {
+    word: &'a Cell<u64>,
+    mask: u64,
+    value: bool,
+}
+
+impl<'a> BitMut<'a> {
+    const fn new(word: &'a Cell<u64>, index: usize) -> Self {
+        let mask = bit_mask(index);
+        Self {
+            word,
+            mask,
+            value: word.get() & mask != 0,
+        }
+    }
+}
+
+impl Deref for BitMut<'_> {
+    type Target = bool;
+
+    fn deref(&self) -> &bool {
+        &self.value
+    }
+}
+
+impl DerefMut for BitMut<'_> {
+    fn deref_mut(&mut self) -> &mut bool {
+        &mut self.value
+    }
+}
+
+impl Drop for BitMut<'_> {
+    fn drop(&mut self) {
+        let word = self.word.get();
+        self.word.set(if self.value {
+            word | self.mask
+        } else {
+            word & !self.mask
+        });
     }
 }
 
 impl Bitstring {
+    /// A bitstring of `num_bits` zeros.
+    #[must_use]
+    pub fn zeros(num_bits: usize) -> Self {
+        Self {
+            bits: Bits::repeat(false, num_bits),
+        }
+    }
+
+    /// A bitstring of `num_bits` ones.
+    #[must_use]
+    pub fn ones(num_bits: usize) -> Self {
+        Self {
+            bits: Bits::repeat(true, num_bits),
+        }
+    }
+
     /// # Errors
     ///
     /// This returns an `anyhow::Result<>` as required by the `Generate` trait.
@@ -47,7 +311,16 @@ impl Bitstring {
     // TODO: I think that the `!` type could be used here to indicate that this
     // can't fail, but that's still nightly.
     pub fn random(num_bits: usize, rng: &mut ThreadRng) -> anyhow::Result<Self> {
-        Self::random_with_probability(num_bits, 0.5, rng)
+        // Every bit of a random word is equally likely to be 0 or 1, so we
+        // can generate a word at a time.
+        let mut bits = Bits {
+            words: (0..num_bits.div_ceil(WORD_BITS))
+                .map(|_| rng.gen())
+                .collect(),
+            len: num_bits,
+        };
+        bits.clear_unused_bits();
+        Ok(Self { bits })
     }
 
     /// # Errors
@@ -69,18 +342,126 @@ impl Bitstring {
         .generate(rng)
     }
 
-    pub fn iter(&self) -> std::slice::Iter<bool> {
+    /// The number of bits.
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.bits.len
+    }
+
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.bits.is_empty()
+    }
+
+    /// The packed words, as described in the [`Bits`] docs.
+    #[must_use]
+    pub fn words(&self) -> &[u64] {
+        &self.bits.words
+    }
+
+    /// The bit at `index`, or `None` if `index` is out of range.
+    #[must_use]
+    pub fn get(&self, index: usize) -> Option<bool> {
+        self.bits.get(index).copied()
+    }
+
+    /// Set the bit at `index` to `value`.
+    ///
+    /// # Panics
+    ///
+    /// This panics if `index` is out of range.
+    pub fn set(&mut self, index: usize, value: bool) {
+        self.check_index(index);
+        self.bits.set(index, value);
+    }
+
+    /// Flip the bit at `index`.
+    ///
+    /// # Panics
+    ///
+    /// This panics if `index` is out of range.
+    pub fn flip(&mut self, index: usize) {
+        self.check_index(index);
+        self.bits.words[index / WORD_BITS] ^= bit_mask(index);
+    }
+
+    /// Add a bit to the end.
+    pub fn push(&mut self, value: bool) {
+        self.bits.push(value);
+    }
+
+    /// The number of bits that are set.
+    #[must_use]
+    pub fn count_ones(&self) -> usize {
+        self.bits.words.iter().copied().map(popcount).sum()
+    }
+
+    /// The number of bits in the range of positions `range` that are set.
+    ///
+    /// # Panics
+    ///
+    /// This panics if `range` extends past the end of the bitstring.
+    #[must_use]
+    pub fn count_ones_in(&self, range: Range<usize>) -> usize {
+        assert!(
+            range.end <= self.len(),
+            "The range {range:?} is out of range for a bitstring of length {}",
+            self.len()
+        );
+        word_masks(range)
+            .map(|(word, mask)| popcount(self.bits.words[word] & mask))
+            .sum()
+    }
+
+    /// The number of bits that aren't set.
+    #[must_use]
+    pub fn count_zeros(&self) -> usize {
+        self.len() - self.count_ones()
+    }
+
+    /// The number of positions where this and `other` differ. If they have
+    /// different lengths, the extra bits of the longer one all count as
+    /// differences.
+    #[must_use]
+    pub fn hamming_distance(&self, other: &Self) -> usize {
+        let (words, other_words) = (&self.bits.words, &other.bits.words);
+        let common = word_masks(0..self.len().min(other.len()))
+            .map(|(word, mask)| popcount((words[word] ^ other_words[word]) & mask))
+            .sum::<usize>();
+        common + self.len().abs_diff(other.len())
+    }
+
+    #[must_use]
+    pub const fn iter(&self) -> Iter<'_> {
         self.bits.iter()
     }
 
-    pub fn iter_mut(&mut self) -> std::slice::IterMut<bool> {
+    pub const fn iter_mut(&mut self) -> IterMut<'_> {
         self.bits.iter_mut()
     }
+
+    fn check_index(&self, index: usize) {
+        assert!(
+            index < self.len(),
+            "Index {index} is out of range for a bitstring of length {}",
+            self.len()
+        );
+    }
+
+    /// Swap the bits selected by `mask` in word `word` of this and `other`.
+    fn swap_bits(&mut self, other: &mut Self, word: usize, mask: u64) {
+        // Swapping the bits where the two words differ is the same as
+        // swapping all the bits.
+        let (word, other_word) = (&mut self.bits.words[word], &mut other.bits.words[word]);
+        let difference = (*word ^ *other_word) & mask;
+        *word ^= difference;
+        *other_word ^= difference;
+    }
 }
 
 impl Display for Bitstring {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        for bit in &self.bits {
+        for bit in self {
             write!(f, "{}", u8::from(*bit))?;
         }
         Ok(())
@@ -100,9 +481,126 @@ where
     }
 }
 
+/// An iterator over references to the bits of a [`Bits`].
+#[derive(Clone)]
+pub struct Iter<'a> {
+    bits: &'a Bits,
+    range: Range<usize>,
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = &'a bool;
+
+    fn next(&mut self) -> Option<&'a bool> {
+        self.range
+            .next()
+            .map(|index| bool_ref(self.bits.bit(index)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.range.size_hint()
+    }
+}
+
+impl<'a> DoubleEndedIterator for Iter<'a> {
+    fn next_back(&mut self) -> Option<&'a bool> {
+        self.range
+            .next_back()
+            .map(|index| bool_ref(self.bits.bit(index)))
+    }
+}
+
+impl ExactSizeIterator for Iter<'_> {}
+
+/// An iterator over [`BitMut`] references to the bits of a [`Bits`].
+pub struct IterMut<'a> {
+    words: &'a [Cell<u64>],
+    range: Range<usize>,
+}
+
+impl<'a> Iterator for IterMut<'a> {
+    type Item = BitMut<'a>;
+
+    fn next(&mut self) -> Option<BitMut<'a>> {
+        self.range
+            .next()
+            .map(|index| BitMut::new(&self.words[index / WORD_BITS], index))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.range.size_hint()
+    }
+}
+
+impl<'a> DoubleEndedIterator for IterMut<'a> {
+    fn next_back(&mut self) -> Option<BitMut<'a>> {
+        self.range
+            .next_back()
+            .map(|index| BitMut::new(&self.words[index / WORD_BITS], index))
+    }
+}
+
+impl ExactSizeIterator for IterMut<'_> {}
+
+/// An iterator that owns a [`Bits`] and returns its bits.
+pub struct IntoIter {
+    bits: Bits,
+    range: Range<usize>,
+}
+
+impl Iterator for IntoIter {
+    type Item = bool;
+
+    fn next(&mut self) -> Option<bool> {
+        self.range.next().map(|index| self.bits.bit(index))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.range.size_hint()
+    }
+}
+
+impl DoubleEndedIterator for IntoIter {
+    fn next_back(&mut self) -> Option<bool> {
+        self.range.next_back().map(|index| self.bits.bit(index))
+    }
+}
+
+impl ExactSizeIterator for IntoIter {}
+
+impl IntoIterator for Bits {
+    type Item = bool;
+    type IntoIter = IntoIter;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter {
+            range: 0..self.len,
+            bits: self,
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a Bits {
+    type Item = &'a bool;
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a mut Bits {
+    type Item = BitMut<'a>;
+    type IntoIter = IterMut<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
 impl IntoIterator for Bitstring {
     type Item = bool;
-    type IntoIter = std::vec::IntoIter<Self::Item>;
+    type IntoIter = IntoIter;
 
     fn into_iter(self) -> Self::IntoIter {
         self.bits.into_iter()
@@ -111,7 +609,7 @@ impl IntoIterator for Bitstring {
 
 impl<'a> IntoIterator for &'a Bitstring {
     type Item = &'a bool;
-    type IntoIter = std::slice::Iter<'a, bool>;
+    type IntoIter = Iter<'a>;
 
     fn into_iter(self) -> Self::IntoIter {
         self.bits.iter()
@@ -119,8 +617,8 @@ impl<'a> IntoIterator for &'a Bitstring {
 }
 
 impl<'a> IntoIterator for &'a mut Bitstring {
-    type Item = &'a mut bool;
-    type IntoIter = std::slice::IterMut<'a, bool>;
+    type Item = BitMut<'a>;
+    type IntoIter = IterMut<'a>;
 
     fn into_iter(self) -> Self::IntoIter {
         self.bits.iter_mut()
@@ -132,37 +630,243 @@ impl Genome for Bitstring {
 }
 
 impl Linear for Bitstring {
+    type GeneMut<'a> = BitMut<'a>;
+
     fn size(&self) -> usize {
-        self.bits.len()
+        self.len()
     }
 
-    fn gene_mut(&mut self, index: usize) -> Option<&mut Self::Gene> {
+    fn gene_mut(&mut self, index: usize) -> Option<BitMut<'_>> {
         self.bits.get_mut(index)
     }
+
+    /// Rather than drawing a random number for every bit, this jumps
+    /// straight to the next bit to flip, using the fact that the gaps
+    /// between flipped bits are geometrically distributed. That makes the
+    /// usual low mutation rates much faster on long bitstrings.
+    fn flip_genes(mut self, rate: f32, rng: &mut ThreadRng) -> Self {
+        let rate = f64::from(rate);
+        if rate <= 0.0 || self.is_empty() {
+            return self;
+        }
+        if rate >= 1.0 {
+            for word in &mut self.bits.words {
+                *word = !*word;
+            }
+            self.bits.clear_unused_bits();
+            return self;
+        }
+        let log_keep = (1.0 - rate).ln();
+        let mut index: usize = 0;
+        loop {
+            // `gen` returns values in [0, 1), so this is in (0, 1] and safe
+            // to take the log of.
+            let u = 1.0 - rng.gen::<f64>();
+            let gap = (u.ln() / log_keep).floor().to_usize().unwrap_or(usize::MAX);
+            match index.checked_add(gap) {
+                Some(next) if next < self.len() => {
+                    self.flip(next);
+                    index = next + 1;
+                }
+                _ => return self,
+            }
+        }
+    }
 }
 
 impl Crossover for Bitstring {
-    fn crossover_gene(&mut self, other: &mut Self, index: usize) -> anyhow::Result<()> {
-        if let (Some(lhs), Some(rhs)) = (self.gene_mut(index), other.gene_mut(index)) {
-            std::mem::swap(lhs, rhs);
-            Ok(())
-        } else {
-            bail!("Crossing {self} and {other} at position {index} failed")
+    fn crossover_gene(&mut self, other: &mut Self, index: usize) -> Result<()> {
+        ensure!(
+            index < self.len() && index < other.len(),
+            "Crossing bitstrings of lengths {} and {} at position {index} failed",
+            self.len(),
+            other.len()
+        );
+        self.swap_bits(other, index / WORD_BITS, bit_mask(index));
+        Ok(())
+    }
+
+    fn crossover_segment(&mut self, other: &mut Self, range: Range<usize>) -> Result<()> {
+        ensure!(
+            range.start <= range.end && range.end <= self.len().min(other.len()),
+            "Crossing bitstrings of lengths {} and {} with range {range:?} failed",
+            self.len(),
+            other.len()
+        );
+        for (word, mask) in word_masks(range) {
+            self.swap_bits(other, word, mask);
         }
+        Ok(())
     }
 
-    fn crossover_segment(
-        &mut self,
-        other: &mut Self,
-        range: std::ops::Range<usize>,
-    ) -> anyhow::Result<()> {
-        let lhs = &mut self.bits[range.clone()];
-        let rhs = &mut other.bits[range.clone()];
-        if lhs.len() == rhs.len() {
-            lhs.swap_with_slice(rhs);
-            Ok(())
-        } else {
-            bail!("Crossing {self} and {other} with range {range:?} failed")
+    fn crossover_uniform(&mut self, other: &mut Self, rng: &mut ThreadRng) -> Result<()> {
+        ensure!(
+            self.len() == other.len(),
+            "Attempted to perform uniform crossover on bitstrings of different lengths {} and {}",
+            self.len(),
+            other.len()
+        );
+        // Each bit of a random word is a fair coin flip for whether to swap
+        // that position, and the padding bits are zero in both bitstrings so
+        // swapping them changes nothing.
+        for word in 0..self.bits.words.len() {
+            self.swap_bits(other, word, rng.gen());
         }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn from_str(bits: &str) -> Bitstring {
+        bits.chars().map(|c| c == '1').collect()
+    }
+
+    #[test]
+    fn round_trips_through_bools() {
+        let bits = (0..150).map(|i| i % 3 == 0).collect::<Vec<_>>();
+        let bitstring = bits.iter().copied().collect::<Bitstring>();
+        assert_eq!(bitstring.len(), 150);
+        assert_eq!(bitstring.words().len(), 3);
+        assert_eq!(bitstring.iter().copied().collect::<Vec<_>>(), bits);
+        assert_eq!(bitstring.clone().into_iter().collect::<Vec<_>>(), bits);
+        assert_eq!(bitstring.iter().next_back(), Some(&bits[149]));
+        assert_eq!(bitstring.get(3), Some(true));
+        assert_eq!(bitstring.get(150), None);
+        assert_eq!(bitstring.count_ones(), 50);
+        assert_eq!(bitstring.count_zeros(), 100);
+    }
+
+    #[test]
+    fn display_and_ordering_match_bools() {
+        let (low, high) = (from_str("0110"), from_str("1000"));
+        assert_eq!(low.to_string(), "0110");
+        assert_eq!(
+            format!("{high:?}"),
+            "Bitstring { bits: [true, false, false, false] }"
+        );
+        assert!(low < high);
+        assert!(from_str("011") < low);
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn bits_can_be_used_like_a_vec_of_bools() {
+        let mut bitstring = Bitstring {
+            bits: vec![false; 100].into(),
+        };
+        assert_eq!(bitstring.bits.len(), 100);
+        *bitstring.gene_mut(70).unwrap() = true;
+        *bitstring.bits.get_mut(3).unwrap() = true;
+        assert!(bitstring.gene_mut(100).is_none());
+        assert!(bitstring.bits[70] && bitstring.bits[3] && !bitstring.bits[4]);
+        for mut bit in &mut bitstring {
+            *bit = !*bit;
+        }
+        bitstring
+            .iter_mut()
+            .rev()
+            .take(10)
+            .for_each(|mut bit| *bit = true);
+        assert_eq!(bitstring.count_ones(), 98);
+        assert_eq!(bitstring.get(70), Some(false));
+        assert_eq!(bitstring.get(4), Some(true));
+        assert_eq!(bitstring.words()[1] >> 36, 0);
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn random_bitstrings_keep_the_padding_clear() {
+        let mut rng = rand::thread_rng();
+        let bitstring = Bitstring::random(70, &mut rng).unwrap();
+        assert_eq!(bitstring.words()[1] >> 6, 0);
+        assert_eq!(Bitstring::ones(70).count_ones(), 70);
+        assert_eq!(Bitstring::ones(70).words()[1], 0b11_1111);
+    }
+
+    #[test]
+    fn set_and_flip() {
+        let mut bitstring = Bitstring::zeros(100);
+        bitstring.set(99, true);
+        bitstring.flip(64);
+        bitstring.flip(99);
+        assert_eq!(bitstring.count_ones(), 1);
+        assert_eq!(bitstring.get(64), Some(true));
+    }
+
+    #[test]
+    fn count_ones_in_ranges() {
+        let bitstring = (0..200).map(|i| i % 2 == 0).collect::<Bitstring>();
+        assert_eq!(bitstring.count_ones_in(0..200), 100);
+        assert_eq!(bitstring.count_ones_in(60..140), 40);
+        assert_eq!(bitstring.count_ones_in(63..65), 1);
+        assert_eq!(bitstring.count_ones_in(10..10), 0);
+    }
+
+    #[test]
+    fn hamming_distance() {
+        let (first, second) = (Bitstring::zeros(130), Bitstring::ones(130));
+        assert_eq!(first.hamming_distance(&second), 130);
+        assert_eq!(first.hamming_distance(&first), 0);
+        assert_eq!(from_str("0110").hamming_distance(&from_str("0101")), 2);
+        // The extra bits of the longer one all count.
+        assert_eq!(from_str("01").hamming_distance(&from_str("0100")), 2);
+        assert_eq!(
+            Bitstring::ones(10).hamming_distance(&Bitstring::zeros(200)),
+            200
+        );
+        assert_eq!(
+            Bitstring::zeros(0).hamming_distance(&Bitstring::ones(65)),
+            65
+        );
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn crossover_segment_swaps_exactly_the_range() {
+        let (mut first, mut second) = (Bitstring::zeros(200), Bitstring::ones(200));
+        first.crossover_segment(&mut second, 60..140).unwrap();
+        for index in 0..200 {
+            let in_range = (60..140).contains(&index);
+            assert_eq!(first.get(index), Some(in_range));
+            assert_eq!(second.get(index), Some(!in_range));
+        }
+        assert!(first.crossover_segment(&mut second, 0..201).is_err());
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn uniform_crossover_conserves_bits() {
+        let mut rng = rand::thread_rng();
+        let (mut first, mut second) = (Bitstring::zeros(1_000), Bitstring::ones(1_000));
+        first.crossover_uniform(&mut second, &mut rng).unwrap();
+        assert_eq!(first.count_ones() + second.count_ones(), 1_000);
+        assert_eq!(first.hamming_distance(&second), 1_000);
+        // There's essentially no chance of fewer than 400 swaps.
+        assert!(first.count_ones() > 400 && first.count_ones() < 600);
+    }
+
+    #[test]
+    fn flip_genes_at_extreme_rates() {
+        let mut rng = rand::thread_rng();
+        let bitstring = Bitstring::zeros(100);
+        assert_eq!(bitstring.clone().flip_genes(0.0, &mut rng), bitstring);
+        assert_eq!(bitstring.flip_genes(1.0, &mut rng), Bitstring::ones(100));
+    }
+
+    #[test]
+    fn flip_genes_flips_about_the_right_number() {
+        let mut rng = rand::thread_rng();
+        let flips = (0..100)
+            .map(|_| {
+                Bitstring::zeros(1_000)
+                    .flip_genes(0.01, &mut rng)
+                    .count_ones()
+            })
+            .sum::<usize>();
+        // We expect 1,000 flips, with a standard deviation of about 31.
+        assert!((800..1_200).contains(&flips), "There were {flips} flips");
     }
 }
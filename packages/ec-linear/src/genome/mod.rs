@@ -1,10 +1,40 @@
+use std::ops::{DerefMut, Not};
+
 use ec_core::genome::Genome;
+use rand::{rngs::ThreadRng, Rng};
 
 pub mod bitstring;
+pub mod permutation;
+pub mod real;
 pub mod vector;
 
 pub trait Linear: Genome {
+    /// A mutable reference to a gene. Genomes that pack their genes (like
+    /// [`Bitstring`](bitstring::Bitstring)) can't lend out a `&mut` to one,
+    /// so they use a proxy that writes the gene back when it's dropped.
+    type GeneMut<'a>: DerefMut<Target = Self::Gene>
+    where
+        Self: 'a;
+
     fn size(&self) -> usize;
 
-    fn gene_mut(&mut self, index: usize) -> Option<&mut Self::Gene>;
+    fn gene_mut(&mut self, index: usize) -> Option<Self::GeneMut<'_>>;
+
+    /// Flip (negate) each gene independently with probability `rate`, which
+    /// is what [`WithRate`](crate::mutator::with_rate::WithRate) does.
+    /// Genomes that can flip genes faster than one at a time (like
+    /// [`Bitstring`](bitstring::Bitstring)) override this.
+    #[must_use]
+    fn flip_genes(self, rate: f32, rng: &mut ThreadRng) -> Self
+    where
+        Self: Sized + FromIterator<Self::Gene> + IntoIterator<Item = Self::Gene>,
+        Self::Gene: Not<Output = Self::Gene>,
+    {
+        self.into_iter()
+            .map(|gene| {
+                let r: f32 = rng.gen();
+                if r < rate { !gene } else { gene }
+            })
+            .collect()
+    }
 }
@@ -0,0 +1,158 @@
+use std::fmt::Display;
+
+use anyhow::{ensure, Result};
+use ec_core::{generator::Generator, genome::Genome};
+use rand::{rngs::ThreadRng, seq::SliceRandom};
+
+/// A permutation of `0..n`, e.g., the order in which to visit the cities in
+/// a travelling salesperson problem.
+///
+/// `Permutation` deliberately doesn't implement [`Linear`](super::Linear) or
+/// [`Crossover`](crate::recombinator::crossover::Crossover), since the
+/// general purpose operators that use those would duplicate and drop
+/// elements. Use the order-preserving operators in
+/// [`mutator::permutation`](crate::mutator::permutation) and
+/// [`recombinator::permutation`](crate::recombinator::permutation) instead.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Permutation {
+    order: Vec<usize>,
+}
+
+impl Genome for Permutation {
+    type Gene = usize;
+}
+
+impl Permutation {
+    /// # Errors
+    ///
+    /// This fails if `order` isn't a permutation of `0..order.len()`.
+    pub fn new(order: Vec<usize>) -> Result<Self> {
+        let mut seen = vec![false; order.len()];
+        for &element in &order {
+            ensure!(
+                element < order.len(),
+                "{element} is out of range for a permutation of length {}",
+                order.len()
+            );
+            ensure!(!seen[element], "{element} appears more than once");
+            seen[element] = true;
+        }
+        Ok(Self { order })
+    }
+
+    /// The permutation `0, 1, ..., size - 1`.
+    #[must_use]
+    pub fn identity(size: usize) -> Self {
+        Self {
+            order: (0..size).collect(),
+        }
+    }
+
+    /// A uniformly random permutation of `0..size`.
+    pub fn random(size: usize, rng: &mut ThreadRng) -> Self {
+        let mut permutation = Self::identity(size);
+        permutation.order.shuffle(rng);
+        permutation
+    }
+
+    /// Wrap an order that the operators in this crate have built, which we
+    /// know is a valid permutation.
+    pub(crate) fn from_valid_order(order: Vec<usize>) -> Self {
+        debug_assert!(Self::new(order.clone()).is_ok());
+        Self { order }
+    }
+
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.order.len()
+    }
+
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.order.is_empty()
+    }
+
+    #[must_use]
+    pub fn order(&self) -> &[usize] {
+        &self.order
+    }
+
+    /// The mutable order, which only the operators in this crate can use
+    /// since they have to keep it a permutation.
+    pub(crate) fn order_mut(&mut self) -> &mut [usize] {
+        &mut self.order
+    }
+
+    /// `positions()[element]` is the position of `element` in this
+    /// permutation, i.e., the inverse permutation.
+    #[must_use]
+    pub fn positions(&self) -> Vec<usize> {
+        let mut positions = vec![0; self.order.len()];
+        for (position, &element) in self.order.iter().enumerate() {
+            positions[element] = position;
+        }
+        positions
+    }
+}
+
+impl Display for Permutation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let elements = self
+            .order
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        write!(f, "[{}]", elements.join(" "))
+    }
+}
+
+impl IntoIterator for Permutation {
+    type Item = usize;
+    type IntoIter = std::vec::IntoIter<usize>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.order.into_iter()
+    }
+}
+
+/// Generate uniformly random permutations of `0..size`.
+pub struct RandomPermutation {
+    pub size: usize,
+}
+
+impl Generator<Permutation> for RandomPermutation {
+    fn generate(&self, rng: &mut ThreadRng) -> Result<Permutation> {
+        Ok(Permutation::random(self.size, rng))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validation() {
+        assert!(Permutation::new(vec![2, 0, 1]).is_ok());
+        assert!(Permutation::new(vec![]).is_ok());
+        assert!(Permutation::new(vec![0, 0, 1]).is_err());
+        assert!(Permutation::new(vec![0, 3, 1]).is_err());
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn random_permutations_are_valid() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..20 {
+            let permutation = RandomPermutation { size: 10 }.generate(&mut rng).unwrap();
+            assert!(Permutation::new(permutation.order().to_vec()).is_ok());
+        }
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn positions_are_the_inverse() {
+        let permutation = Permutation::new(vec![2, 0, 3, 1]).unwrap();
+        assert_eq!(permutation.positions(), vec![1, 3, 0, 2]);
+        assert_eq!(permutation.to_string(), "[2 0 3 1]");
+    }
+}
@@ -0,0 +1,166 @@
+use std::f64::consts::TAU;
+
+use anyhow::{ensure, Result};
+use ec_core::generator::Generator;
+use rand::{rngs::ThreadRng, Rng};
+
+use super::vector::Vector;
+
+/// The gene types that can be used in real-valued vectors.
+///
+/// The numeric operators do all their arithmetic in `f64`, so anything that
+/// converts to and from `f64` works. That includes `f64` itself and
+/// `OrderedFloat<f64>`, which is handy since individuals need totally
+/// ordered genomes to be compared.
+pub trait Real: Copy + From<f64> + Into<f64> {}
+
+impl<T> Real for T where T: Copy + From<f64> + Into<f64> {}
+
+/// Lower and upper bounds (both inclusive) for each dimension of a
+/// real-valued vector.
+///
+/// Bounds are used to generate random vectors and by the operators that
+/// need to keep genes in range. As a generator, `Bounds` samples each gene
+/// uniformly from its range.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bounds {
+    lower: Vec<f64>,
+    upper: Vec<f64>,
+}
+
+impl Bounds {
+    /// # Errors
+    ///
+    /// This fails if `lower` and `upper` have different lengths, if any
+    /// bound isn't finite, or if any lower bound is above its upper bound.
+    pub fn new(lower: Vec<f64>, upper: Vec<f64>) -> Result<Self> {
+        ensure!(
+            lower.len() == upper.len(),
+            "There are {} lower bounds but {} upper bounds",
+            lower.len(),
+            upper.len()
+        );
+        for (index, (low, high)) in lower.iter().zip(&upper).enumerate() {
+            ensure!(
+                low.is_finite() && high.is_finite() && low <= high,
+                "The bounds [{low}, {high}] for dimension {index} aren't a valid range"
+            );
+        }
+        Ok(Self { lower, upper })
+    }
+
+    /// The same bounds for each of `dimensions` dimensions.
+    ///
+    /// # Errors
+    ///
+    /// This fails if the bounds aren't finite or `lower` is above `upper`.
+    pub fn uniform(dimensions: usize, lower: f64, upper: f64) -> Result<Self> {
+        Self::new(vec![lower; dimensions], vec![upper; dimensions])
+    }
+
+    #[must_use]
+    pub const fn dimensions(&self) -> usize {
+        self.lower.len()
+    }
+
+    #[must_use]
+    pub fn lower(&self) -> &[f64] {
+        &self.lower
+    }
+
+    #[must_use]
+    pub fn upper(&self) -> &[f64] {
+        &self.upper
+    }
+
+    /// Make sure a genome has the right number of genes for these bounds.
+    ///
+    /// # Errors
+    ///
+    /// This fails if `size` isn't the number of dimensions.
+    pub fn check_size(&self, size: usize) -> Result<()> {
+        ensure!(
+            size == self.dimensions(),
+            "A genome with {size} genes doesn't fit bounds with {} dimensions",
+            self.dimensions()
+        );
+        Ok(())
+    }
+
+    /// Is every gene of `genome` within its bounds?
+    #[must_use]
+    pub fn contains<T: Real>(&self, genome: &Vector<T>) -> bool {
+        genome.genes.len() == self.dimensions()
+            && genome
+                .genes
+                .iter()
+                .zip(self.lower.iter().zip(&self.upper))
+                .all(|(&gene, (&low, &high))| (low..=high).contains(&gene.into()))
+    }
+
+    /// Clamp `value` to the bounds of dimension `index`.
+    ///
+    /// # Panics
+    ///
+    /// This panics if `index` isn't less than the number of dimensions.
+    #[must_use]
+    pub fn clamp(&self, index: usize, value: f64) -> f64 {
+        value.clamp(self.lower[index], self.upper[index])
+    }
+}
+
+impl<T: Real> Generator<Vector<T>> for Bounds {
+    fn generate(&self, rng: &mut ThreadRng) -> Result<Vector<T>> {
+        Ok(self
+            .lower
+            .iter()
+            .zip(&self.upper)
+            .map(|(&low, &high)| T::from(rng.gen_range(low..=high)))
+            .collect())
+    }
+}
+
+/// A sample from the standard normal distribution (mean 0, standard
+/// deviation 1), using the Box-Muller transform.
+pub fn standard_normal(rng: &mut ThreadRng) -> f64 {
+    // `gen` returns values in [0, 1), so this is in (0, 1] and safe to
+    // take the log of.
+    let u1 = 1.0 - rng.gen::<f64>();
+    let u2 = rng.gen::<f64>();
+    (-2.0 * u1.ln()).sqrt() * (TAU * u2).cos()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn generated_vectors_are_in_bounds() {
+        let mut rng = rand::thread_rng();
+        let bounds = Bounds::new(vec![-1.0, 0.0, 5.0], vec![1.0, 0.0, 10.0]).unwrap();
+        for _ in 0..100 {
+            let genome: Vector<f64> = bounds.generate(&mut rng).unwrap();
+            assert!(bounds.contains(&genome));
+        }
+    }
+
+    #[test]
+    fn invalid_bounds_are_rejected() {
+        assert!(Bounds::new(vec![0.0], vec![1.0, 2.0]).is_err());
+        assert!(Bounds::uniform(3, 1.0, 0.0).is_err());
+        assert!(Bounds::uniform(3, 0.0, f64::INFINITY).is_err());
+    }
+
+    #[test]
+    fn standard_normal_is_roughly_standard() {
+        let mut rng = rand::thread_rng();
+        let samples = (0..10_000)
+            .map(|_| standard_normal(&mut rng))
+            .collect::<Vec<_>>();
+        let mean = samples.iter().sum::<f64>() / 10_000.0;
+        let variance = samples.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / 10_000.0;
+        assert!(mean.abs() < 0.1, "The mean was {mean}");
+        assert!((variance - 1.0).abs() < 0.1, "The variance was {variance}");
+    }
+}
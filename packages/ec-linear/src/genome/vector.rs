@@ -1,8 +1,10 @@
+use anyhow::bail;
 use ec_core::genome::Genome;
 
 use super::Linear;
+use crate::recombinator::crossover::Crossover;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Vector<T> {
     pub genes: Vec<T>,
 }
@@ -12,6 +14,11 @@ impl<T> Genome for Vector<T> {
 }
 
 impl<T> Linear for Vector<T> {
+    type GeneMut<'a>
+        = &'a mut T
+    where
+        T: 'a;
+
     fn size(&self) -> usize {
         self.genes.len()
     }
@@ -21,6 +28,42 @@ impl<T> Linear for Vector<T> {
     }
 }
 
+impl<T> Crossover for Vector<T> {
+    fn crossover_gene(&mut self, other: &mut Self, index: usize) -> anyhow::Result<()> {
+        if let (Some(lhs), Some(rhs)) = (self.gene_mut(index), other.gene_mut(index)) {
+            std::mem::swap(lhs, rhs);
+            Ok(())
+        } else {
+            bail!(
+                "Crossing vectors of lengths {} and {} at position {index} failed",
+                self.size(),
+                other.size()
+            )
+        }
+    }
+
+    fn crossover_segment(
+        &mut self,
+        other: &mut Self,
+        range: std::ops::Range<usize>,
+    ) -> anyhow::Result<()> {
+        let (self_size, other_size) = (self.size(), other.size());
+        match (
+            self.genes.get_mut(range.clone()),
+            other.genes.get_mut(range.clone()),
+        ) {
+            (Some(lhs), Some(rhs)) => {
+                lhs.swap_with_slice(rhs);
+                Ok(())
+            }
+            _ => bail!(
+                "Crossing vectors of lengths {self_size} and {other_size} with range {range:?} \
+                 failed"
+            ),
+        }
+    }
+}
+
 impl<T> FromIterator<T> for Vector<T> {
     fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
         Self {
@@ -38,3 +81,43 @@ impl<T> IntoIterator for Vector<T> {
         self.genes.into_iter()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use ec_core::operator::recombinator::Recombinator;
+
+    use super::*;
+    use crate::recombinator::{two_point_xo::TwoPointXo, uniform_xo::UniformXo};
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn two_point_xo_keeps_genes_in_place() {
+        let mut rng = rand::thread_rng();
+        let first = (0..10).collect::<Vector<u8>>();
+        let second = (10..20).collect::<Vector<u8>>();
+        let child = TwoPointXo.recombine([first, second], &mut rng).unwrap();
+        assert_eq!(child.size(), 10);
+        for (index, gene) in child.into_iter().enumerate() {
+            assert_eq!(usize::from(gene) % 10, index);
+        }
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn uniform_xo_keeps_genes_in_place() {
+        let mut rng = rand::thread_rng();
+        let first = (0..10).collect::<Vector<u8>>();
+        let second = (10..20).collect::<Vector<u8>>();
+        let child = UniformXo.recombine((first, second), &mut rng).unwrap();
+        for (index, gene) in child.into_iter().enumerate() {
+            assert_eq!(usize::from(gene) % 10, index);
+        }
+    }
+
+    #[test]
+    fn crossover_segment_out_of_range_is_an_error() {
+        let mut first = (0..5).collect::<Vector<u8>>();
+        let mut second = (0..3).collect::<Vector<u8>>();
+        assert!(first.crossover_segment(&mut second, 2..5).is_err());
+    }
+}
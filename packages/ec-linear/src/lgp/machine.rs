@@ -0,0 +1,169 @@
+use anyhow::{ensure, Context, Result};
+
+use super::{
+    operator::RegisterOperator,
+    program::{Instruction, Operand, RegisterProgram},
+};
+
+/// An interpreter for register programs.
+///
+/// Before a run, the inputs are loaded into the first registers and every
+/// other register is set to `initial_value`. The instructions are then
+/// executed in order, and the result is whatever is left in
+/// `output_register`.
+#[derive(Debug, Clone)]
+pub struct RegisterMachine<T> {
+    num_registers: usize,
+    initial_value: T,
+    output_register: usize,
+}
+
+impl<T: Clone> RegisterMachine<T> {
+    /// Create a machine with `num_registers` registers, the output in
+    /// register 0, and unused registers initialized to `initial_value`.
+    ///
+    /// # Errors
+    ///
+    /// This fails if `num_registers` is 0.
+    pub fn new(num_registers: usize, initial_value: T) -> Result<Self> {
+        ensure!(num_registers > 0, "A register machine needs registers");
+        Ok(Self {
+            num_registers,
+            initial_value,
+            output_register: 0,
+        })
+    }
+
+    /// # Errors
+    ///
+    /// This fails if `output_register` isn't one of this machine's
+    /// registers.
+    pub fn with_output_register(mut self, output_register: usize) -> Result<Self> {
+        ensure!(
+            output_register < self.num_registers,
+            "The output register {output_register} doesn't exist in a machine with {} registers",
+            self.num_registers
+        );
+        self.output_register = output_register;
+        Ok(self)
+    }
+
+    #[must_use]
+    pub const fn num_registers(&self) -> usize {
+        self.num_registers
+    }
+
+    #[must_use]
+    pub const fn output_register(&self) -> usize {
+        self.output_register
+    }
+
+    /// Run `program` on `inputs`, returning the final values of all the
+    /// registers.
+    ///
+    /// # Errors
+    ///
+    /// This fails if there are more inputs than registers, or if the
+    /// program refers to a register this machine doesn't have.
+    pub fn run_registers<O>(&self, program: &RegisterProgram<O, T>, inputs: &[T]) -> Result<Vec<T>>
+    where
+        O: RegisterOperator<T>,
+    {
+        ensure!(
+            inputs.len() <= self.num_registers,
+            "Can't load {} inputs into a machine with {} registers",
+            inputs.len(),
+            self.num_registers
+        );
+        let mut registers = inputs.to_vec();
+        registers.resize(self.num_registers, self.initial_value.clone());
+        for Instruction {
+            destination,
+            operator,
+            lhs,
+            rhs,
+        } in &program.instructions
+        {
+            let result = operator.apply(Self::read(&registers, lhs)?, Self::read(&registers, rhs)?);
+            *registers
+                .get_mut(*destination)
+                .with_context(|| format!("There is no destination register {destination}"))? =
+                result;
+        }
+        Ok(registers)
+    }
+
+    /// Run `program` on `inputs`, returning the final value of the output
+    /// register.
+    ///
+    /// # Errors
+    ///
+    /// This fails if there are more inputs than registers, or if the
+    /// program refers to a register this machine doesn't have.
+    pub fn run<O>(&self, program: &RegisterProgram<O, T>, inputs: &[T]) -> Result<T>
+    where
+        O: RegisterOperator<T>,
+    {
+        let mut registers = self.run_registers(program, inputs)?;
+        Ok(registers.swap_remove(self.output_register))
+    }
+
+    fn read(registers: &[T], operand: &Operand<T>) -> Result<T> {
+        match operand {
+            Operand::Register(register) => registers
+                .get(*register)
+                .cloned()
+                .with_context(|| format!("There is no source register {register}")),
+            Operand::Constant(value) => Ok(value.clone()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lgp::operator::ArithmeticOperator::{self, Add, Multiply};
+
+    #[allow(clippy::unwrap_used)]
+    fn square_plus_one() -> RegisterProgram<ArithmeticOperator, f64> {
+        [
+            Instruction::new(1, Multiply, Operand::Register(0), Operand::Register(0)),
+            Instruction::new(0, Add, Operand::Register(1), Operand::Constant(1.0)),
+        ]
+        .into_iter()
+        .collect()
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used, clippy::float_cmp)]
+    fn run_program() {
+        let machine = RegisterMachine::new(3, 0.0).unwrap();
+        assert_eq!(machine.run(&square_plus_one(), &[3.0]).unwrap(), 10.0);
+        assert_eq!(
+            machine.run_registers(&square_plus_one(), &[3.0]).unwrap(),
+            vec![10.0, 9.0, 0.0]
+        );
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used, clippy::float_cmp)]
+    fn output_register_is_configurable() {
+        let machine = RegisterMachine::new(3, 0.0)
+            .unwrap()
+            .with_output_register(1)
+            .unwrap();
+        assert_eq!(machine.run(&square_plus_one(), &[3.0]).unwrap(), 9.0);
+        assert!(RegisterMachine::new(3, 0.0)
+            .unwrap()
+            .with_output_register(3)
+            .is_err());
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn missing_registers_are_errors() {
+        let machine = RegisterMachine::new(1, 0.0).unwrap();
+        assert!(machine.run(&square_plus_one(), &[3.0]).is_err());
+        assert!(machine.run(&square_plus_one(), &[3.0, 4.0]).is_err());
+    }
+}
@@ -0,0 +1,11 @@
+//! Linear genetic programming (LGP) with register machines.
+//!
+//! Programs are sequences of instructions of the form `r[i] = r[j] op r[k]`
+//! (where either operand can also be a constant), run in order on a bank of
+//! registers. The inputs are loaded into the first registers, and the answer
+//! is read from an output register at the end.
+
+pub mod machine;
+pub mod operator;
+pub mod program;
+pub mod scorer;
@@ -0,0 +1,59 @@
+use num_traits::Float;
+
+/// An operator that can be used in register machine instructions.
+pub trait RegisterOperator<T> {
+    fn apply(&self, lhs: T, rhs: T) -> T;
+}
+
+/// The usual arithmetic operators for floating point registers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ArithmeticOperator {
+    Add,
+    Subtract,
+    Multiply,
+    /// Division that returns 1 when dividing by zero, as in Push.
+    ProtectedDivide,
+}
+
+impl ArithmeticOperator {
+    /// All the arithmetic operators, which is a convenient default operator
+    /// set.
+    pub const ALL: [Self; 4] = [
+        Self::Add,
+        Self::Subtract,
+        Self::Multiply,
+        Self::ProtectedDivide,
+    ];
+}
+
+impl<T: Float> RegisterOperator<T> for ArithmeticOperator {
+    fn apply(&self, lhs: T, rhs: T) -> T {
+        match self {
+            Self::Add => lhs + rhs,
+            Self::Subtract => lhs - rhs,
+            Self::Multiply => lhs * rhs,
+            Self::ProtectedDivide => {
+                if rhs.is_zero() {
+                    T::one()
+                } else {
+                    lhs / rhs
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn arithmetic() {
+        assert_eq!(ArithmeticOperator::Add.apply(3.0, 2.0), 5.0);
+        assert_eq!(ArithmeticOperator::Subtract.apply(3.0, 2.0), 1.0);
+        assert_eq!(ArithmeticOperator::Multiply.apply(3.0, 2.0), 6.0);
+        assert_eq!(ArithmeticOperator::ProtectedDivide.apply(3.0, 2.0), 1.5);
+        assert_eq!(ArithmeticOperator::ProtectedDivide.apply(3.0, 0.0), 1.0);
+    }
+}
@@ -0,0 +1,354 @@
+use std::fmt::{Debug, Display};
+
+use anyhow::{bail, ensure, Context, Result};
+use ec_core::{generator::Generator, genome::Genome};
+use rand::{rngs::ThreadRng, seq::SliceRandom, Rng};
+
+use crate::{genome::Linear, recombinator::crossover::Crossover};
+
+/// One of the arguments of an instruction: either the value in a register
+/// or a constant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Operand<T> {
+    Register(usize),
+    Constant(T),
+}
+
+/// A single register machine instruction, `r[destination] = lhs operator
+/// rhs`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Instruction<O, T> {
+    pub destination: usize,
+    pub operator: O,
+    pub lhs: Operand<T>,
+    pub rhs: Operand<T>,
+}
+
+impl<O, T> Instruction<O, T> {
+    pub const fn new(destination: usize, operator: O, lhs: Operand<T>, rhs: Operand<T>) -> Self {
+        Self {
+            destination,
+            operator,
+            lhs,
+            rhs,
+        }
+    }
+
+    /// The registers this instruction reads from.
+    pub fn source_registers(&self) -> impl Iterator<Item = usize> + '_ {
+        [&self.lhs, &self.rhs]
+            .into_iter()
+            .filter_map(|operand| match operand {
+                Operand::Register(register) => Some(*register),
+                Operand::Constant(_) => None,
+            })
+    }
+}
+
+impl<T: Display> Display for Operand<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Register(register) => write!(f, "r[{register}]"),
+            Self::Constant(value) => write!(f, "{value}"),
+        }
+    }
+}
+
+impl<O: Debug, T: Display> Display for Instruction<O, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "r[{}] = {:?}({}, {})",
+            self.destination, self.operator, self.lhs, self.rhs
+        )
+    }
+}
+
+/// A linear GP program: a sequence of register machine instructions.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RegisterProgram<O, T> {
+    pub instructions: Vec<Instruction<O, T>>,
+}
+
+impl<O, T> Genome for RegisterProgram<O, T> {
+    type Gene = Instruction<O, T>;
+}
+
+impl<O, T> Linear for RegisterProgram<O, T> {
+    type GeneMut<'a>
+        = &'a mut Instruction<O, T>
+    where
+        Self: 'a;
+
+    fn size(&self) -> usize {
+        self.instructions.len()
+    }
+
+    fn gene_mut(&mut self, index: usize) -> Option<&mut Self::Gene> {
+        self.instructions.get_mut(index)
+    }
+}
+
+impl<O, T> Crossover for RegisterProgram<O, T> {
+    fn crossover_gene(&mut self, other: &mut Self, index: usize) -> Result<()> {
+        if let (Some(lhs), Some(rhs)) = (self.gene_mut(index), other.gene_mut(index)) {
+            std::mem::swap(lhs, rhs);
+            Ok(())
+        } else {
+            bail!(
+                "Crossing programs of lengths {} and {} at position {index} failed",
+                self.size(),
+                other.size()
+            )
+        }
+    }
+}
+
+impl<O, T> RegisterProgram<O, T> {
+    /// Find the effective instructions in this program, i.e., those that can
+    /// affect the values of the given output registers at the end of the run.
+    /// The rest of the instructions are introns, and removing them doesn't
+    /// change the program's behavior.
+    ///
+    /// The result has one entry for each instruction, which is `true` if that
+    /// instruction is effective.
+    #[must_use]
+    pub fn effective_instructions(&self, output_registers: &[usize]) -> Vec<bool> {
+        // We work backwards from the end of the program, keeping track of
+        // which registers are still going to be read by effective code.
+        let mut effective_registers = output_registers.to_vec();
+        let mut effective = vec![false; self.instructions.len()];
+        for (index, instruction) in self.instructions.iter().enumerate().rev() {
+            if let Some(position) = effective_registers
+                .iter()
+                .position(|&register| register == instruction.destination)
+            {
+                effective[index] = true;
+                effective_registers.swap_remove(position);
+                for register in instruction.source_registers() {
+                    if !effective_registers.contains(&register) {
+                        effective_registers.push(register);
+                    }
+                }
+            }
+        }
+        effective
+    }
+
+    /// A copy of this program with all the introns (with respect to the
+    /// given output registers) removed.
+    #[must_use]
+    pub fn without_introns(&self, output_registers: &[usize]) -> Self
+    where
+        O: Clone,
+        T: Clone,
+    {
+        self.instructions
+            .iter()
+            .zip(self.effective_instructions(output_registers))
+            .filter(|(_, effective)| *effective)
+            .map(|(instruction, _)| instruction.clone())
+            .collect()
+    }
+}
+
+impl<O: Debug, T: Display> Display for RegisterProgram<O, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for instruction in &self.instructions {
+            writeln!(f, "{instruction}")?;
+        }
+        Ok(())
+    }
+}
+
+impl<O, T> FromIterator<Instruction<O, T>> for RegisterProgram<O, T> {
+    fn from_iter<I: IntoIterator<Item = Instruction<O, T>>>(iter: I) -> Self {
+        Self {
+            instructions: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl<O, T> IntoIterator for RegisterProgram<O, T> {
+    type Item = Instruction<O, T>;
+    type IntoIter = std::vec::IntoIter<Self::Item>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.instructions.into_iter()
+    }
+}
+
+/// Generate random instructions for a machine with `num_registers`
+/// registers, using the given operators.
+///
+/// Each operand is a constant (from
+/// `constant_generator`) with probability `constant_probability`, and a
+/// random register otherwise.
+pub struct InstructionGenerator<O, CG> {
+    num_registers: usize,
+    operators: Vec<O>,
+    constant_probability: f64,
+    constant_generator: CG,
+}
+
+impl<O, CG> InstructionGenerator<O, CG> {
+    /// # Errors
+    ///
+    /// This fails if there are no registers or no operators, or if
+    /// `constant_probability` isn't a probability.
+    pub fn new(
+        num_registers: usize,
+        operators: Vec<O>,
+        constant_probability: f64,
+        constant_generator: CG,
+    ) -> Result<Self> {
+        ensure!(num_registers > 0, "A register machine needs registers");
+        ensure!(!operators.is_empty(), "The operator set can't be empty");
+        ensure!(
+            (0.0..=1.0).contains(&constant_probability),
+            "The constant probability {constant_probability} must be between 0 and 1"
+        );
+        Ok(Self {
+            num_registers,
+            operators,
+            constant_probability,
+            constant_generator,
+        })
+    }
+
+    fn operand<T>(&self, rng: &mut ThreadRng) -> Result<Operand<T>>
+    where
+        CG: Generator<T>,
+    {
+        Ok(if rng.gen_bool(self.constant_probability) {
+            Operand::Constant(self.constant_generator.generate(rng)?)
+        } else {
+            Operand::Register(rng.gen_range(0..self.num_registers))
+        })
+    }
+}
+
+impl<O, T, CG> Generator<Instruction<O, T>> for InstructionGenerator<O, CG>
+where
+    O: Clone,
+    CG: Generator<T>,
+{
+    fn generate(&self, rng: &mut ThreadRng) -> Result<Instruction<O, T>> {
+        let operator = self
+            .operators
+            .choose(rng)
+            .context("The operator set was unexpectedly empty")?
+            .clone();
+        Ok(Instruction {
+            destination: rng.gen_range(0..self.num_registers),
+            operator,
+            lhs: self.operand(rng)?,
+            rhs: self.operand(rng)?,
+        })
+    }
+}
+
+/// Generate random programs with `length` instructions, each generated by
+/// `instruction_generator`.
+pub struct ProgramGenerator<IG> {
+    pub instruction_generator: IG,
+    pub length: usize,
+}
+
+impl<IG> ProgramGenerator<IG> {
+    pub const fn new(instruction_generator: IG, length: usize) -> Self {
+        Self {
+            instruction_generator,
+            length,
+        }
+    }
+}
+
+impl<O, T, IG> Generator<RegisterProgram<O, T>> for ProgramGenerator<IG>
+where
+    IG: Generator<Instruction<O, T>>,
+{
+    fn generate(&self, rng: &mut ThreadRng) -> Result<RegisterProgram<O, T>> {
+        (0..self.length)
+            .map(|_| self.instruction_generator.generate(rng))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lgp::operator::ArithmeticOperator::{self, Add, Multiply, Subtract};
+
+    fn register(register: usize) -> Operand<f64> {
+        Operand::Register(register)
+    }
+
+    #[test]
+    fn introns_are_detected() {
+        let program: RegisterProgram<ArithmeticOperator, f64> = [
+            // Effective: r[1] is read by the fourth and fifth instructions.
+            Instruction::new(1, Add, register(0), Operand::Constant(1.0)),
+            // Intron: r[2] is never read.
+            Instruction::new(2, Multiply, register(0), register(0)),
+            // Intron: r[3] is overwritten by the next instruction before it's
+            // read.
+            Instruction::new(3, Subtract, register(0), register(1)),
+            // Effective: r[3] is read by the last instruction.
+            Instruction::new(3, Multiply, register(1), register(1)),
+            // Effective: r[0] is the output.
+            Instruction::new(0, Add, register(3), register(1)),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(
+            program.effective_instructions(&[0]),
+            vec![true, false, false, true, true]
+        );
+        let stripped = program.without_introns(&[0]);
+        assert_eq!(stripped.instructions.len(), 3);
+        assert_eq!(stripped.instructions[2].destination, 0);
+    }
+
+    #[test]
+    fn overwritten_registers_are_introns() {
+        let program: RegisterProgram<ArithmeticOperator, f64> = [
+            Instruction::new(0, Add, register(0), register(0)),
+            Instruction::new(0, Add, register(1), register(1)),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(program.effective_instructions(&[0]), vec![false, true]);
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn generated_instructions_are_in_range() {
+        let mut rng = rand::thread_rng();
+        let generator =
+            InstructionGenerator::new(4, ArithmeticOperator::ALL.to_vec(), 0.5, vec![1.0, 2.0])
+                .unwrap();
+        for _ in 0..100 {
+            let instruction: Instruction<ArithmeticOperator, f64> =
+                generator.generate(&mut rng).unwrap();
+            assert!(instruction.destination < 4);
+            assert!(instruction.source_registers().all(|register| register < 4));
+        }
+    }
+
+    #[test]
+    fn generator_rejects_bad_configurations() {
+        let constants = vec![1.0];
+        assert!(InstructionGenerator::new(0, vec![Add], 0.5, constants.clone()).is_err());
+        assert!(
+            InstructionGenerator::<ArithmeticOperator, _>::new(
+                2,
+                Vec::new(),
+                0.5,
+                constants.clone()
+            )
+            .is_err()
+        );
+        assert!(InstructionGenerator::new(2, vec![Add], 1.5, constants).is_err());
+    }
+}
@@ -0,0 +1,146 @@
+use std::{iter::Sum, marker::PhantomData};
+
+use ec_core::{evaluation::cases::Cases, individual::scorer::Scorer, test_results::TestResults};
+
+use super::{machine::RegisterMachine, operator::RegisterOperator, program::RegisterProgram};
+
+/// Score register programs by running them on each of a set of `Cases`,
+/// and comparing the value in the output register to the expected output
+/// with `error_fn`.
+///
+/// If a program can't be run (because it uses registers the machine doesn't
+/// have), every case gets the `penalty` value.
+pub struct RegisterMachineScorer<T, F, V, R> {
+    machine: RegisterMachine<T>,
+    cases: Cases<Vec<T>, T>,
+    error_fn: F,
+    penalty: V,
+    _result: PhantomData<fn() -> R>,
+}
+
+impl<T, F, V, R> RegisterMachineScorer<T, F, V, R> {
+    pub const fn new(
+        machine: RegisterMachine<T>,
+        cases: Cases<Vec<T>, T>,
+        error_fn: F,
+        penalty: V,
+    ) -> Self {
+        Self {
+            machine,
+            cases,
+            error_fn,
+            penalty,
+            _result: PhantomData,
+        }
+    }
+
+    #[must_use]
+    pub const fn cases(&self) -> &Cases<Vec<T>, T> {
+        &self.cases
+    }
+}
+
+impl<O, T, F, V, R> Scorer<RegisterProgram<O, T>> for RegisterMachineScorer<T, F, V, R>
+where
+    O: RegisterOperator<T>,
+    T: Clone,
+    F: Fn(&T, &T) -> V,
+    V: Clone,
+    for<'a> R: From<V> + Sum<&'a R> + 'a,
+{
+    type Score = TestResults<R>;
+
+    fn score(&self, program: &RegisterProgram<O, T>) -> Self::Score {
+        self.cases
+            .iter()
+            .map(|case| {
+                self.machine.run(program, &case.input).map_or_else(
+                    |_| self.penalty.clone(),
+                    |actual| (self.error_fn)(&actual, &case.output),
+                )
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ec_core::test_results::Error;
+
+    use super::*;
+    use crate::lgp::{
+        operator::ArithmeticOperator::{self, Multiply},
+        program::{Instruction, Operand},
+    };
+
+    // `ArithmeticOperator` needs floating point registers, so we use a tiny
+    // integer operator for some of these tests.
+    struct Times;
+
+    impl RegisterOperator<i64> for Times {
+        fn apply(&self, lhs: i64, rhs: i64) -> i64 {
+            lhs * rhs
+        }
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn score_square() {
+        let cases = (0..5)
+            .map(|x| (vec![x], x * x))
+            .collect::<Cases<Vec<i64>, i64>>();
+        let machine = RegisterMachine::new(2, 0).unwrap();
+        let scorer: RegisterMachineScorer<_, _, _, Error<i64>> = RegisterMachineScorer::new(
+            machine,
+            cases,
+            |actual: &i64, expected: &i64| (actual - expected).abs(),
+            1_000,
+        );
+        let square = RegisterProgram {
+            instructions: vec![Instruction::new(
+                0,
+                Times,
+                Operand::Register(0),
+                Operand::Register(0),
+            )],
+        };
+        assert_eq!(scorer.score(&square).total_result.error, 0);
+
+        // Register 5 doesn't exist, so every case gets the penalty.
+        let broken = RegisterProgram {
+            instructions: vec![Instruction::new(
+                0,
+                Times,
+                Operand::Register(5),
+                Operand::Register(0),
+            )],
+        };
+        assert_eq!(scorer.score(&broken).total_result.error, 5_000);
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn score_with_floats() {
+        let cases = [1.0, 2.0, 3.0]
+            .into_iter()
+            .map(|x| (vec![x], 2.0 * x))
+            .collect::<Cases<Vec<f64>, f64>>();
+        let machine = RegisterMachine::new(2, 0.0).unwrap();
+        let scorer: RegisterMachineScorer<_, _, _, Error<i64>> = RegisterMachineScorer::new(
+            machine,
+            cases,
+            // Count the cases we get wrong.
+            |actual: &f64, expected: &f64| i64::from((actual - expected).abs() > 1e-9),
+            1,
+        );
+        let times_two: RegisterProgram<ArithmeticOperator, f64> = RegisterProgram {
+            instructions: vec![Instruction::new(
+                0,
+                Multiply,
+                Operand::Register(0),
+                Operand::Constant(2.0),
+            )],
+        };
+        assert_eq!(scorer.score(&times_two).total_result.error, 0);
+    }
+}
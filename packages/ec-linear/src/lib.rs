@@ -1,3 +1,6 @@
+pub mod benchmark;
+pub mod es;
 pub mod genome;
+pub mod lgp;
 pub mod mutator;
 pub mod recombinator;
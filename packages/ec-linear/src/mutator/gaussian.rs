@@ -0,0 +1,103 @@
+use anyhow::{ensure, Result};
+use ec_core::operator::mutator::Mutator;
+use rand::{rngs::ThreadRng, Rng};
+
+use crate::genome::{
+    real::{standard_normal, Bounds, Real},
+    vector::Vector,
+};
+
+/// Add normally distributed noise (with mean 0 and standard deviation
+/// `sigma`) to each gene with probability `mutation_rate`.
+///
+/// If bounds are given, mutated genes are clamped to them.
+pub struct GaussianMutation {
+    sigma: f64,
+    mutation_rate: f64,
+    bounds: Option<Bounds>,
+}
+
+impl GaussianMutation {
+    /// # Errors
+    ///
+    /// This fails if `sigma` isn't positive and finite, or if
+    /// `mutation_rate` isn't a probability.
+    pub fn new(sigma: f64, mutation_rate: f64) -> Result<Self> {
+        ensure!(
+            sigma > 0.0 && sigma.is_finite(),
+            "The standard deviation {sigma} must be positive"
+        );
+        ensure!(
+            (0.0..=1.0).contains(&mutation_rate),
+            "The mutation rate {mutation_rate} must be between 0 and 1"
+        );
+        Ok(Self {
+            sigma,
+            mutation_rate,
+            bounds: None,
+        })
+    }
+
+    #[must_use]
+    pub fn with_bounds(mut self, bounds: Bounds) -> Self {
+        self.bounds = Some(bounds);
+        self
+    }
+}
+
+impl<T: Real> Mutator<Vector<T>> for GaussianMutation {
+    fn mutate(&self, mut genome: Vector<T>, rng: &mut ThreadRng) -> Result<Vector<T>> {
+        if let Some(bounds) = &self.bounds {
+            bounds.check_size(genome.genes.len())?;
+        }
+        for (index, gene) in genome.genes.iter_mut().enumerate() {
+            if rng.gen_bool(self.mutation_rate) {
+                let value = self.sigma.mul_add(standard_normal(rng), (*gene).into());
+                *gene = T::from(
+                    self.bounds
+                        .as_ref()
+                        .map_or(value, |bounds| bounds.clamp(index, value)),
+                );
+            }
+        }
+        Ok(genome)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn mutation_stays_in_bounds() {
+        let mut rng = rand::thread_rng();
+        let bounds = Bounds::uniform(10, -1.0, 1.0).unwrap();
+        let mutator = GaussianMutation::new(10.0, 1.0)
+            .unwrap()
+            .with_bounds(bounds.clone());
+        let genome = Vector {
+            genes: vec![0.0; 10],
+        };
+        let child = mutator.mutate(genome.clone(), &mut rng).unwrap();
+        assert_ne!(child, genome);
+        assert!(bounds.contains(&child));
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn zero_rate_changes_nothing() {
+        let mut rng = rand::thread_rng();
+        let mutator = GaussianMutation::new(1.0, 0.0).unwrap();
+        let genome = Vector {
+            genes: vec![1.0, 2.0, 3.0],
+        };
+        assert_eq!(mutator.mutate(genome.clone(), &mut rng).unwrap(), genome);
+    }
+
+    #[test]
+    fn bad_parameters_are_rejected() {
+        assert!(GaussianMutation::new(0.0, 0.5).is_err());
+        assert!(GaussianMutation::new(1.0, 1.5).is_err());
+    }
+}
@@ -0,0 +1,127 @@
+//! Mutations that keep permutations valid by moving elements around rather
+//! than replacing them.
+
+use anyhow::Result;
+use ec_core::operator::mutator::Mutator;
+use rand::{rngs::ThreadRng, seq::SliceRandom, Rng};
+
+use crate::genome::permutation::Permutation;
+
+/// A random segment `start..end` of a sequence of length `len`, with at
+/// least two elements (so reversing or shuffling it can change something).
+/// `len` must be at least 2.
+fn random_segment(len: usize, rng: &mut ThreadRng) -> (usize, usize) {
+    let start = rng.gen_range(0..len - 1);
+    let end = rng.gen_range(start + 2..=len);
+    (start, end)
+}
+
+/// Swap two randomly chosen elements.
+pub struct SwapMutation;
+
+impl Mutator<Permutation> for SwapMutation {
+    fn mutate(&self, mut genome: Permutation, rng: &mut ThreadRng) -> Result<Permutation> {
+        if genome.len() >= 2 {
+            let first = rng.gen_range(0..genome.len());
+            let second = rng.gen_range(0..genome.len());
+            genome.order_mut().swap(first, second);
+        }
+        Ok(genome)
+    }
+}
+
+/// Move a randomly chosen element to a random new position, shifting the
+/// elements in between over by one.
+pub struct InsertMutation;
+
+impl Mutator<Permutation> for InsertMutation {
+    fn mutate(&self, mut genome: Permutation, rng: &mut ThreadRng) -> Result<Permutation> {
+        if genome.len() >= 2 {
+            let from = rng.gen_range(0..genome.len());
+            let to = rng.gen_range(0..genome.len());
+            let order = genome.order_mut();
+            if from < to {
+                order[from..=to].rotate_left(1);
+            } else {
+                order[to..=from].rotate_right(1);
+            }
+        }
+        Ok(genome)
+    }
+}
+
+/// Reverse a random segment. For tours, this is the classic 2-opt move.
+pub struct InversionMutation;
+
+impl Mutator<Permutation> for InversionMutation {
+    fn mutate(&self, mut genome: Permutation, rng: &mut ThreadRng) -> Result<Permutation> {
+        if genome.len() >= 2 {
+            let (start, end) = random_segment(genome.len(), rng);
+            genome.order_mut()[start..end].reverse();
+        }
+        Ok(genome)
+    }
+}
+
+/// Randomly shuffle a random segment.
+pub struct ScrambleMutation;
+
+impl Mutator<Permutation> for ScrambleMutation {
+    fn mutate(&self, mut genome: Permutation, rng: &mut ThreadRng) -> Result<Permutation> {
+        if genome.len() >= 2 {
+            let (start, end) = random_segment(genome.len(), rng);
+            genome.order_mut()[start..end].shuffle(rng);
+        }
+        Ok(genome)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_mutant_is_a_permutation<M: Mutator<Permutation>>(mutator: &M) {
+        let mut rng = rand::thread_rng();
+        let mut genome = Permutation::identity(10);
+        for _ in 0..100 {
+            #[allow(clippy::unwrap_used)]
+            let mutant = mutator.mutate(genome, &mut rng).unwrap();
+            assert!(Permutation::new(mutant.order().to_vec()).is_ok());
+            genome = mutant;
+        }
+    }
+
+    #[test]
+    fn mutants_are_permutations() {
+        assert_mutant_is_a_permutation(&SwapMutation);
+        assert_mutant_is_a_permutation(&InsertMutation);
+        assert_mutant_is_a_permutation(&InversionMutation);
+        assert_mutant_is_a_permutation(&ScrambleMutation);
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn inversion_changes_the_permutation() {
+        let mut rng = rand::thread_rng();
+        let genome = Permutation::identity(5);
+        let mutant = InversionMutation.mutate(genome.clone(), &mut rng).unwrap();
+        assert_ne!(mutant, genome);
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn tiny_permutations_are_unchanged() {
+        let mut rng = rand::thread_rng();
+        for size in 0..2 {
+            let genome = Permutation::identity(size);
+            assert_eq!(
+                ScrambleMutation.mutate(genome.clone(), &mut rng).unwrap(),
+                genome
+            );
+            assert_eq!(
+                InsertMutation.mutate(genome.clone(), &mut rng).unwrap(),
+                genome
+            );
+        }
+    }
+}
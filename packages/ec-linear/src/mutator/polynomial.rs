@@ -0,0 +1,108 @@
+use anyhow::{ensure, Result};
+use ec_core::operator::mutator::Mutator;
+use rand::{rngs::ThreadRng, Rng};
+
+use crate::genome::{
+    real::{Bounds, Real},
+    vector::Vector,
+};
+
+/// Deb and Goyal's polynomial mutation, as used in NSGA-II.
+///
+/// Each gene is mutated with probability `mutation_rate` by a perturbation
+/// drawn from a polynomial distribution that is scaled to the gene's bounds.
+///
+/// Larger values of the distribution index `eta` make small changes more
+/// likely; values between 20 and 100 are typical.
+pub struct PolynomialMutation {
+    bounds: Bounds,
+    distribution_index: f64,
+    mutation_rate: f64,
+}
+
+impl PolynomialMutation {
+    /// # Errors
+    ///
+    /// This fails if the distribution index is negative, or if
+    /// `mutation_rate` isn't a probability.
+    pub fn new(bounds: Bounds, distribution_index: f64, mutation_rate: f64) -> Result<Self> {
+        ensure!(
+            distribution_index >= 0.0 && distribution_index.is_finite(),
+            "The distribution index {distribution_index} can't be negative"
+        );
+        ensure!(
+            (0.0..=1.0).contains(&mutation_rate),
+            "The mutation rate {mutation_rate} must be between 0 and 1"
+        );
+        Ok(Self {
+            bounds,
+            distribution_index,
+            mutation_rate,
+        })
+    }
+
+    fn mutate_gene(&self, value: f64, lower: f64, upper: f64, rng: &mut ThreadRng) -> f64 {
+        let range = upper - lower;
+        if range <= 0.0 {
+            return lower;
+        }
+        let exponent = self.distribution_index + 1.0;
+        let r = rng.gen::<f64>();
+        let delta = if r < 0.5 {
+            let below = 1.0 - (value - lower) / range;
+            let v = 2.0f64
+                .mul_add(-r, 1.0)
+                .mul_add(below.powf(exponent), 2.0 * r);
+            v.powf(exponent.recip()) - 1.0
+        } else {
+            let above = 1.0 - (upper - value) / range;
+            let v = (2.0 * (r - 0.5)).mul_add(above.powf(exponent), 2.0 * (1.0 - r));
+            1.0 - v.powf(exponent.recip())
+        };
+        delta.mul_add(range, value).clamp(lower, upper)
+    }
+}
+
+impl<T: Real> Mutator<Vector<T>> for PolynomialMutation {
+    fn mutate(&self, mut genome: Vector<T>, rng: &mut ThreadRng) -> Result<Vector<T>> {
+        self.bounds.check_size(genome.genes.len())?;
+        for (index, gene) in genome.genes.iter_mut().enumerate() {
+            if rng.gen_bool(self.mutation_rate) {
+                let lower = self.bounds.lower()[index];
+                let upper = self.bounds.upper()[index];
+                *gene = T::from(self.mutate_gene((*gene).into(), lower, upper, rng));
+            }
+        }
+        Ok(genome)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn mutation_stays_in_bounds() {
+        let mut rng = rand::thread_rng();
+        let bounds = Bounds::new(vec![0.0, -5.0, 3.0], vec![1.0, 5.0, 3.0]).unwrap();
+        let mutator = PolynomialMutation::new(bounds.clone(), 1.0, 1.0).unwrap();
+        let mut genome = Vector {
+            genes: vec![0.0, 5.0, 3.0],
+        };
+        for _ in 0..100 {
+            genome = mutator.mutate(genome, &mut rng).unwrap();
+            assert!(bounds.contains(&genome));
+        }
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn genome_must_match_bounds() {
+        let mut rng = rand::thread_rng();
+        let mutator =
+            PolynomialMutation::new(Bounds::uniform(3, 0.0, 1.0).unwrap(), 20.0, 0.5).unwrap();
+        let genome = Vector { genes: vec![0.5] };
+        assert!(mutator.mutate(genome, &mut rng).is_err());
+    }
+}
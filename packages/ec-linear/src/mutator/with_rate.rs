@@ -36,13 +36,7 @@ where
     T::Gene: Not<Output = T::Gene>,
 {
     fn mutate(&self, genome: T, rng: &mut ThreadRng) -> Result<T> {
-        Ok(genome
-            .into_iter()
-            .map(|bit| {
-                let r: f32 = rng.gen();
-                if r < self.mutation_rate { !bit } else { bit }
-            })
-            .collect())
+        Ok(genome.flip_genes(self.mutation_rate, rng))
     }
 }
 
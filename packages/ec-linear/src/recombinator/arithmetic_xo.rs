@@ -0,0 +1,113 @@
+use anyhow::{ensure, Result};
+use ec_core::operator::recombinator::Recombinator;
+use rand::{rngs::ThreadRng, Rng};
+
+use crate::genome::{real::Real, vector::Vector};
+
+/// Whole arithmetic crossover: the child is the weighted average
+/// `weight * first + (1 - weight) * second` of its parents.
+///
+/// The default is to choose a new random weight in `[0, 1]` for every
+/// child; use [`ArithmeticXo::with_weight`] to fix the weight instead (e.g.,
+/// 0.5 to always take the midpoint). Since the child always lies between its
+/// parents, it's within any bounds they're both within.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ArithmeticXo {
+    weight: Option<f64>,
+}
+
+impl ArithmeticXo {
+    /// # Errors
+    ///
+    /// This fails if `weight` isn't between 0 and 1.
+    pub fn with_weight(weight: f64) -> Result<Self> {
+        ensure!(
+            (0.0..=1.0).contains(&weight),
+            "The crossover weight {weight} must be between 0 and 1"
+        );
+        Ok(Self {
+            weight: Some(weight),
+        })
+    }
+}
+
+impl<T: Real> Recombinator<[Vector<T>; 2]> for ArithmeticXo {
+    type Output = Vector<T>;
+
+    fn recombine(
+        &self,
+        [first_genome, second_genome]: [Vector<T>; 2],
+        rng: &mut ThreadRng,
+    ) -> Result<Self::Output> {
+        ensure!(
+            first_genome.genes.len() == second_genome.genes.len(),
+            "Attempted to perform ArithmeticXo on genomes of different lengths {} and {}",
+            first_genome.genes.len(),
+            second_genome.genes.len()
+        );
+        let weight = self.weight.unwrap_or_else(|| rng.gen_range(0.0..=1.0));
+        Ok(first_genome
+            .into_iter()
+            .zip(second_genome)
+            .map(|(first, second)| {
+                let (first, second): (f64, f64) = (first.into(), second.into());
+                T::from(weight.mul_add(first - second, second))
+            })
+            .collect())
+    }
+}
+
+impl<T: Real> Recombinator<(Vector<T>, Vector<T>)> for ArithmeticXo {
+    type Output = Vector<T>;
+
+    fn recombine(
+        &self,
+        genomes: (Vector<T>, Vector<T>),
+        rng: &mut ThreadRng,
+    ) -> Result<Self::Output> {
+        self.recombine(<[Vector<T>; 2]>::from(genomes), rng)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn midpoint() {
+        let mut rng = rand::thread_rng();
+        let child: Vector<f64> = ArithmeticXo::with_weight(0.5)
+            .unwrap()
+            .recombine(
+                [
+                    Vector {
+                        genes: vec![0.0, 2.0],
+                    },
+                    Vector {
+                        genes: vec![4.0, -2.0],
+                    },
+                ],
+                &mut rng,
+            )
+            .unwrap();
+        assert!((child.genes[0] - 2.0).abs() < f64::EPSILON);
+        assert!(child.genes[1].abs() < f64::EPSILON);
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn random_weight_stays_between_parents() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..100 {
+            let child: Vector<f64> = ArithmeticXo::default()
+                .recombine(
+                    (Vector { genes: vec![1.0] }, Vector { genes: vec![3.0] }),
+                    &mut rng,
+                )
+                .unwrap();
+            assert!((1.0..=3.0).contains(&child.genes[0]));
+        }
+        assert!(ArithmeticXo::with_weight(1.5).is_err());
+    }
+}
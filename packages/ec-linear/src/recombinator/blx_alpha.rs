@@ -0,0 +1,123 @@
+use anyhow::{ensure, Result};
+use ec_core::operator::recombinator::Recombinator;
+use rand::{rngs::ThreadRng, Rng};
+
+use crate::genome::{
+    real::{Bounds, Real},
+    vector::Vector,
+};
+
+/// Blend crossover (BLX-α): each of the child's genes is chosen uniformly
+/// from the interval spanned by the parents' genes, extended on both sides
+/// by `alpha` times the interval's length.
+///
+/// An `alpha` of 0.5 is the usual choice. If bounds are given, the child's
+/// genes are clamped to them.
+pub struct BlxAlpha {
+    alpha: f64,
+    bounds: Option<Bounds>,
+}
+
+impl BlxAlpha {
+    /// # Errors
+    ///
+    /// This fails if `alpha` is negative.
+    pub fn new(alpha: f64) -> Result<Self> {
+        ensure!(
+            alpha >= 0.0 && alpha.is_finite(),
+            "BLX-alpha needs a non-negative alpha, not {alpha}"
+        );
+        Ok(Self {
+            alpha,
+            bounds: None,
+        })
+    }
+
+    #[must_use]
+    pub fn with_bounds(mut self, bounds: Bounds) -> Self {
+        self.bounds = Some(bounds);
+        self
+    }
+}
+
+impl<T: Real> Recombinator<[Vector<T>; 2]> for BlxAlpha {
+    type Output = Vector<T>;
+
+    fn recombine(
+        &self,
+        [first_genome, second_genome]: [Vector<T>; 2],
+        rng: &mut ThreadRng,
+    ) -> Result<Self::Output> {
+        ensure!(
+            first_genome.genes.len() == second_genome.genes.len(),
+            "Attempted to perform BLX-alpha on genomes of different lengths {} and {}",
+            first_genome.genes.len(),
+            second_genome.genes.len()
+        );
+        if let Some(bounds) = &self.bounds {
+            bounds.check_size(first_genome.genes.len())?;
+        }
+        Ok(first_genome
+            .into_iter()
+            .zip(second_genome)
+            .enumerate()
+            .map(|(index, (first, second))| {
+                let (first, second): (f64, f64) = (first.into(), second.into());
+                let (low, high) = (first.min(second), first.max(second));
+                let extension = self.alpha * (high - low);
+                let value = rng.gen_range(low - extension..=high + extension);
+                T::from(
+                    self.bounds
+                        .as_ref()
+                        .map_or(value, |bounds| bounds.clamp(index, value)),
+                )
+            })
+            .collect())
+    }
+}
+
+impl<T: Real> Recombinator<(Vector<T>, Vector<T>)> for BlxAlpha {
+    type Output = Vector<T>;
+
+    fn recombine(
+        &self,
+        genomes: (Vector<T>, Vector<T>),
+        rng: &mut ThreadRng,
+    ) -> Result<Self::Output> {
+        self.recombine(<[Vector<T>; 2]>::from(genomes), rng)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn children_are_in_the_extended_interval() {
+        let mut rng = rand::thread_rng();
+        let blx = BlxAlpha::new(0.5).unwrap();
+        for _ in 0..100 {
+            let child: Vector<f64> = blx
+                .recombine(
+                    [
+                        Vector {
+                            genes: vec![0.0, 5.0],
+                        },
+                        Vector {
+                            genes: vec![2.0, 5.0],
+                        },
+                    ],
+                    &mut rng,
+                )
+                .unwrap();
+            assert!((-1.0..=3.0).contains(&child.genes[0]));
+            assert!((child.genes[1] - 5.0).abs() < f64::EPSILON);
+        }
+    }
+
+    #[test]
+    fn negative_alpha_is_an_error() {
+        assert!(BlxAlpha::new(-0.1).is_err());
+    }
+}
@@ -1,5 +1,7 @@
 use std::ops::Range;
 
+use rand::{rngs::ThreadRng, Rng};
+
 use crate::genome::Linear;
 
 // TODO: Does `Crossover` need to be visible outside
@@ -26,4 +28,21 @@ pub trait Crossover: Linear {
         }
         Ok(())
     }
+
+    /// Swaps each gene of this and the `other` genome with probability 1/2,
+    /// destructively modifying both. This is what
+    /// [`UniformXo`](super::uniform_xo::UniformXo) does, and genomes that
+    /// can swap many genes at once (like
+    /// [`Bitstring`](crate::genome::bitstring::Bitstring)) override it.
+    ///
+    /// # Errors
+    /// This can fail if the two genomes have different sizes.
+    fn crossover_uniform(&mut self, other: &mut Self, rng: &mut ThreadRng) -> anyhow::Result<()> {
+        for index in 0..self.size() {
+            if rng.gen_bool(0.5) {
+                self.crossover_gene(other, index)?;
+            }
+        }
+        Ok(())
+    }
 }
@@ -0,0 +1,283 @@
+//! Differential evolution (DE) recombinators, which make a _trial_ vector
+//! from a target vector and several donor vectors from the population.
+//!
+//! The donors are chosen by one of the operators in
+//! [`ec_core::operator::donors`], and the trial competes with the target in
+//! a [`OneToOneGeneration`](ec_core::generation::OneToOneGeneration). The
+//! usual DE variants are:
+//!
+//! - DE/rand/1/bin: [`DifferentialXo`] with
+//!   [`RandomDonors<3>`](ec_core::operator::donors::RandomDonors).
+//! - DE/best/1/bin: [`DifferentialXo`] with
+//!   [`BestDonors<3>`](ec_core::operator::donors::BestDonors).
+//! - DE/current-to-best/1: [`CurrentToBestXo`] with
+//!   [`BestDonors<3>`](ec_core::operator::donors::BestDonors).
+
+use anyhow::{ensure, Result};
+use ec_core::operator::recombinator::Recombinator;
+use rand::{rngs::ThreadRng, Rng};
+
+use crate::genome::{
+    real::{Bounds, Real},
+    vector::Vector,
+};
+
+/// The settings that all the DE recombinators share.
+struct Settings {
+    scale_factor: f64,
+    crossover_rate: f64,
+    bounds: Option<Bounds>,
+}
+
+impl Settings {
+    fn new(scale_factor: f64, crossover_rate: f64) -> Result<Self> {
+        ensure!(
+            scale_factor > 0.0 && scale_factor <= 2.0,
+            "The scale factor {scale_factor} must be in (0, 2]"
+        );
+        ensure!(
+            (0.0..=1.0).contains(&crossover_rate),
+            "The crossover rate {crossover_rate} must be in [0, 1]"
+        );
+        Ok(Self {
+            scale_factor,
+            crossover_rate,
+            bounds: None,
+        })
+    }
+
+    fn check_sizes<T, const N: usize>(
+        &self,
+        target: &Vector<T>,
+        donors: &[Vector<T>; N],
+    ) -> Result<()> {
+        let size = target.genes.len();
+        for donor in donors {
+            ensure!(
+                donor.genes.len() == size,
+                "Attempted to perform differential evolution with a target of length {size} and a \
+                 donor of length {}",
+                donor.genes.len()
+            );
+        }
+        if let Some(bounds) = &self.bounds {
+            bounds.check_size(size)?;
+        }
+        Ok(())
+    }
+
+    /// Binomial crossover between the target and the mutant vector (whose
+    /// genes are computed by `mutant`). Each gene comes from the mutant with
+    /// probability `crossover_rate`, and one randomly chosen gene always
+    /// does so the trial differs from the target.
+    fn binomial_crossover<T: Real>(
+        &self,
+        target: Vector<T>,
+        mutant: impl Fn(usize, f64) -> f64,
+        rng: &mut ThreadRng,
+    ) -> Vector<T> {
+        if target.genes.is_empty() {
+            return target;
+        }
+        let forced = rng.gen_range(0..target.genes.len());
+        target
+            .into_iter()
+            .enumerate()
+            .map(|(index, gene)| {
+                let gene: f64 = gene.into();
+                if index != forced && rng.gen::<f64>() >= self.crossover_rate {
+                    return T::from(gene);
+                }
+                let value = mutant(index, gene);
+                T::from(
+                    self.bounds
+                        .as_ref()
+                        .map_or(value, |bounds| bounds.clamp(index, value)),
+                )
+            })
+            .collect()
+    }
+}
+
+/// The classic DE recombination with one difference vector and binomial
+/// crossover.
+///
+/// Given a target and donors `[base, first, second]`, the mutant vector is
+/// `base + scale_factor * (first - second)`, and the trial takes each gene
+/// from the mutant with probability `crossover_rate` (and from the target
+/// otherwise). Whether this is DE/rand/1/bin or DE/best/1/bin depends on
+/// how the base donor was chosen. If bounds are given, the trial's genes are
+/// clamped to them.
+pub struct DifferentialXo {
+    settings: Settings,
+}
+
+impl DifferentialXo {
+    /// Scale factors (`F`) around 0.5 and crossover rates (`CR`) around
+    /// 0.9 are common starting points.
+    ///
+    /// # Errors
+    ///
+    /// This fails if `scale_factor` isn't in (0, 2] or `crossover_rate`
+    /// isn't in [0, 1].
+    pub fn new(scale_factor: f64, crossover_rate: f64) -> Result<Self> {
+        Ok(Self {
+            settings: Settings::new(scale_factor, crossover_rate)?,
+        })
+    }
+
+    #[must_use]
+    pub fn with_bounds(mut self, bounds: Bounds) -> Self {
+        self.settings.bounds = Some(bounds);
+        self
+    }
+}
+
+impl<T: Real> Recombinator<(Vector<T>, [Vector<T>; 3])> for DifferentialXo {
+    type Output = Vector<T>;
+
+    fn recombine(
+        &self,
+        (target, donors): (Vector<T>, [Vector<T>; 3]),
+        rng: &mut ThreadRng,
+    ) -> Result<Self::Output> {
+        self.settings.check_sizes(&target, &donors)?;
+        let [base, first, second] = &donors;
+        let scale_factor = self.settings.scale_factor;
+        Ok(self.settings.binomial_crossover(
+            target,
+            |index, _| {
+                let difference = first.genes[index].into() - second.genes[index].into();
+                scale_factor.mul_add(difference, base.genes[index].into())
+            },
+            rng,
+        ))
+    }
+}
+
+/// DE/current-to-best/1, which moves the target towards the best individual
+/// as well as along a random difference vector.
+///
+/// Given a target and donors `[best, first, second]`, the mutant vector is
+/// `target + scale_factor * (best - target) + scale_factor * (first -
+/// second)`, which is then combined with the target by binomial crossover
+/// as in [`DifferentialXo`]. A crossover rate of 1 gives the variant
+/// without crossover.
+pub struct CurrentToBestXo {
+    settings: Settings,
+}
+
+impl CurrentToBestXo {
+    /// # Errors
+    ///
+    /// This fails if `scale_factor` isn't in (0, 2] or `crossover_rate`
+    /// isn't in [0, 1].
+    pub fn new(scale_factor: f64, crossover_rate: f64) -> Result<Self> {
+        Ok(Self {
+            settings: Settings::new(scale_factor, crossover_rate)?,
+        })
+    }
+
+    #[must_use]
+    pub fn with_bounds(mut self, bounds: Bounds) -> Self {
+        self.settings.bounds = Some(bounds);
+        self
+    }
+}
+
+impl<T: Real> Recombinator<(Vector<T>, [Vector<T>; 3])> for CurrentToBestXo {
+    type Output = Vector<T>;
+
+    fn recombine(
+        &self,
+        (target, donors): (Vector<T>, [Vector<T>; 3]),
+        rng: &mut ThreadRng,
+    ) -> Result<Self::Output> {
+        self.settings.check_sizes(&target, &donors)?;
+        let [best, first, second] = &donors;
+        let scale_factor = self.settings.scale_factor;
+        Ok(self.settings.binomial_crossover(
+            target,
+            |index, current| {
+                let to_best = best.genes[index].into() - current;
+                let difference = first.genes[index].into() - second.genes[index].into();
+                scale_factor.mul_add(to_best + difference, current)
+            },
+            rng,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vector(genes: &[f64]) -> Vector<f64> {
+        Vector {
+            genes: genes.to_vec(),
+        }
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used, clippy::float_cmp)]
+    fn full_crossover_gives_the_mutant() {
+        let mut rng = rand::thread_rng();
+        let target = vector(&[0.0, 0.0]);
+        let donors = [
+            vector(&[1.0, 2.0]),
+            vector(&[3.0, 3.0]),
+            vector(&[1.0, 2.0]),
+        ];
+        let trial = DifferentialXo::new(0.5, 1.0)
+            .unwrap()
+            .recombine((target.clone(), donors.clone()), &mut rng)
+            .unwrap();
+        // base + 0.5 * (first - second) = [1, 2] + 0.5 * [2, 1]
+        assert_eq!(trial, vector(&[2.0, 2.5]));
+        let trial = CurrentToBestXo::new(0.5, 1.0)
+            .unwrap()
+            .recombine((target, donors), &mut rng)
+            .unwrap();
+        // target + 0.5 * (best - target) + 0.5 * (first - second)
+        assert_eq!(trial, vector(&[1.5, 1.5]));
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used, clippy::float_cmp)]
+    fn no_crossover_still_changes_one_gene() {
+        let mut rng = rand::thread_rng();
+        let target = vector(&[0.0; 5]);
+        let donors = [vector(&[1.0; 5]), vector(&[0.0; 5]), vector(&[0.0; 5])];
+        for _ in 0..20 {
+            let trial = DifferentialXo::new(0.5, 0.0)
+                .unwrap()
+                .recombine((target.clone(), donors.clone()), &mut rng)
+                .unwrap();
+            assert_eq!(trial.genes.iter().filter(|&&gene| gene == 1.0).count(), 1);
+        }
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn trials_are_clamped() {
+        let mut rng = rand::thread_rng();
+        let bounds = Bounds::uniform(3, -1.0, 1.0).unwrap();
+        let xo = DifferentialXo::new(2.0, 1.0)
+            .unwrap()
+            .with_bounds(bounds.clone());
+        let donors = [vector(&[1.0; 3]), vector(&[1.0; 3]), vector(&[-1.0; 3])];
+        let trial = xo.recombine((vector(&[0.0; 3]), donors), &mut rng).unwrap();
+        assert!(bounds.contains(&trial));
+    }
+
+    #[test]
+    fn bad_settings_and_sizes_are_errors() {
+        let mut rng = rand::thread_rng();
+        assert!(DifferentialXo::new(0.0, 0.5).is_err());
+        assert!(CurrentToBestXo::new(0.5, 1.5).is_err());
+        let donors = [vector(&[1.0]), vector(&[1.0, 2.0]), vector(&[1.0])];
+        assert!(DifferentialXo::new(0.5, 0.5)
+            .and_then(|xo| xo.recombine((vector(&[0.0]), donors), &mut rng))
+            .is_err());
+    }
+}
@@ -1,3 +1,8 @@
+pub mod arithmetic_xo;
+pub mod blx_alpha;
 pub mod crossover;
+pub mod differential;
+pub mod permutation;
+pub mod sbx;
 pub mod two_point_xo;
 pub mod uniform_xo;
@@ -0,0 +1,351 @@
+//! Crossovers for permutations, each of which makes a child that is a
+//! valid permutation and inherits some of each parent's structure.
+
+use anyhow::{ensure, Context, Result};
+use ec_core::operator::recombinator::Recombinator;
+use rand::{rngs::ThreadRng, seq::SliceRandom, Rng};
+
+use crate::genome::permutation::Permutation;
+
+fn check_lengths(name: &str, first: &Permutation, second: &Permutation) -> Result<()> {
+    ensure!(
+        first.len() == second.len(),
+        "Attempted to perform {name} on permutations of different lengths {} and {}",
+        first.len(),
+        second.len()
+    );
+    Ok(())
+}
+
+/// Two random cut points `start <= end` for a sequence of length `len`.
+fn random_cut_points(len: usize, rng: &mut ThreadRng) -> (usize, usize) {
+    let first = rng.gen_range(0..=len);
+    let second = rng.gen_range(0..=len);
+    (first.min(second), first.max(second))
+}
+
+/// The part of a permutation crossover that actually builds the child's
+/// order from the parents, which have already been checked to be the same
+/// length.
+trait PermutationCrossover {
+    fn crossover(
+        &self,
+        first: &Permutation,
+        second: &Permutation,
+        rng: &mut ThreadRng,
+    ) -> Result<Vec<usize>>;
+}
+
+/// Implement `Recombinator` for pairs of permutations, both as arrays and
+/// as tuples, in terms of `PermutationCrossover`.
+macro_rules! permutation_recombinator {
+    ($name:ident) => {
+        impl Recombinator<[Permutation; 2]> for $name {
+            type Output = Permutation;
+
+            fn recombine(
+                &self,
+                [first, second]: [Permutation; 2],
+                rng: &mut ThreadRng,
+            ) -> Result<Self::Output> {
+                check_lengths(stringify!($name), &first, &second)?;
+                Ok(Permutation::from_valid_order(
+                    self.crossover(&first, &second, rng)?,
+                ))
+            }
+        }
+
+        impl Recombinator<(Permutation, Permutation)> for $name {
+            type Output = Permutation;
+
+            fn recombine(
+                &self,
+                genomes: (Permutation, Permutation),
+                rng: &mut ThreadRng,
+            ) -> Result<Self::Output> {
+                self.recombine(<[Permutation; 2]>::from(genomes), rng)
+            }
+        }
+    };
+}
+
+/// Partially mapped crossover (PMX).
+///
+/// The child gets a random segment from the first parent, and everything
+/// else from the second parent in the same positions where possible. When an
+/// element from the second parent is already in the copied segment, the
+/// mapping between the two parents' segments is followed to find a
+/// replacement.
+pub struct Pmx;
+
+impl PermutationCrossover for Pmx {
+    fn crossover(
+        &self,
+        first: &Permutation,
+        second: &Permutation,
+        rng: &mut ThreadRng,
+    ) -> Result<Vec<usize>> {
+        let (start, end) = random_cut_points(first.len(), rng);
+        let first_positions = first.positions();
+        let in_segment = |element: usize| (start..end).contains(&first_positions[element]);
+        Ok(second
+            .order()
+            .iter()
+            .enumerate()
+            .map(|(position, &element)| {
+                if (start..end).contains(&position) {
+                    return first.order()[position];
+                }
+                let mut element = element;
+                while in_segment(element) {
+                    element = second.order()[first_positions[element]];
+                }
+                element
+            })
+            .collect())
+    }
+}
+
+permutation_recombinator!(Pmx);
+
+/// Order crossover (OX).
+///
+/// The child gets a random segment from the first parent in the same
+/// positions, and the remaining elements in the order they appear in the
+/// second parent, starting after the segment and wrapping around.
+pub struct OrderXo;
+
+impl PermutationCrossover for OrderXo {
+    fn crossover(
+        &self,
+        first: &Permutation,
+        second: &Permutation,
+        rng: &mut ThreadRng,
+    ) -> Result<Vec<usize>> {
+        let len = first.len();
+        let (start, end) = random_cut_points(len, rng);
+        let mut child = first.order().to_vec();
+        let mut in_segment = vec![false; len];
+        for &element in &first.order()[start..end] {
+            in_segment[element] = true;
+        }
+        let mut remaining = second
+            .order()
+            .iter()
+            .cycle()
+            .skip(end)
+            .take(len)
+            .filter(|&&element| !in_segment[element]);
+        for position in (end..len).chain(0..start) {
+            if let Some(&element) = remaining.next() {
+                child[position] = element;
+            }
+        }
+        Ok(child)
+    }
+}
+
+permutation_recombinator!(OrderXo);
+
+/// Cycle crossover (CX).
+///
+/// The positions are split into the cycles of the mapping between the two
+/// parents, and the child takes the elements of alternate cycles from
+/// alternate parents. Every element ends up in a position it held in one of
+/// the parents.
+pub struct CycleXo;
+
+impl PermutationCrossover for CycleXo {
+    fn crossover(
+        &self,
+        first: &Permutation,
+        second: &Permutation,
+        _: &mut ThreadRng,
+    ) -> Result<Vec<usize>> {
+        let len = first.len();
+        let first_positions = first.positions();
+        let mut child = vec![0; len];
+        let mut assigned = vec![false; len];
+        let mut from_first = true;
+        for start in 0..len {
+            if assigned[start] {
+                continue;
+            }
+            let parent = if from_first { first } else { second };
+            let mut position = start;
+            while !assigned[position] {
+                assigned[position] = true;
+                child[position] = parent.order()[position];
+                position = first_positions[second.order()[position]];
+            }
+            from_first = !from_first;
+        }
+        Ok(child)
+    }
+}
+
+permutation_recombinator!(CycleXo);
+
+/// Edge recombination crossover (ERX).
+///
+/// ERX tries to build a child whose neighbouring elements were neighbours in
+/// one of the parents. This makes it a good fit for problems like the TSP where
+/// the adjacencies matter more than the absolute positions.
+///
+/// Both parents are treated as cycles (i.e., the last element is adjacent to
+/// the first). Starting from the first parent's first element, we always
+/// move to the unvisited neighbour with the fewest unvisited neighbours of
+/// its own, breaking ties randomly, and jump to a random unvisited element
+/// if there are no unvisited neighbours.
+pub struct EdgeRecombination;
+
+impl PermutationCrossover for EdgeRecombination {
+    fn crossover(
+        &self,
+        first: &Permutation,
+        second: &Permutation,
+        rng: &mut ThreadRng,
+    ) -> Result<Vec<usize>> {
+        let len = first.len();
+        if len == 0 {
+            return Ok(Vec::new());
+        }
+        // The edge table: the (at most four) neighbours of each element.
+        let mut neighbours = vec![Vec::with_capacity(4); len];
+        for parent in [first, second] {
+            let order = parent.order();
+            for (index, &element) in order.iter().enumerate() {
+                let next = order[(index + 1) % len];
+                if next != element && !neighbours[element].contains(&next) {
+                    neighbours[element].push(next);
+                    neighbours[next].push(element);
+                }
+            }
+        }
+
+        let mut visited = vec![false; len];
+        let mut child = Vec::with_capacity(len);
+        let mut current = first.order()[0];
+        loop {
+            child.push(current);
+            visited[current] = true;
+            if child.len() == len {
+                return Ok(child);
+            }
+            // Visited elements are removed from the edge table, so all the
+            // neighbours of `current` are unvisited.
+            let current_neighbours = std::mem::take(&mut neighbours[current]);
+            for &neighbour in &current_neighbours {
+                neighbours[neighbour].retain(|&element| element != current);
+            }
+            let candidates = current_neighbours
+                .iter()
+                .map(|&n| neighbours[n].len())
+                .min()
+                .map_or_else(
+                    || (0..len).filter(|&element| !visited[element]).collect(),
+                    |fewest| {
+                        current_neighbours
+                            .iter()
+                            .copied()
+                            .filter(|&neighbour| neighbours[neighbour].len() == fewest)
+                            .collect::<Vec<_>>()
+                    },
+                );
+            current = *candidates
+                .choose(rng)
+                .context("There were no unvisited elements left")?;
+        }
+    }
+}
+
+permutation_recombinator!(EdgeRecombination);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[allow(clippy::unwrap_used)]
+    fn parents() -> [Permutation; 2] {
+        [
+            Permutation::new(vec![0, 1, 2, 3, 4, 5, 6, 7, 8]).unwrap(),
+            Permutation::new(vec![8, 2, 6, 7, 1, 5, 4, 0, 3]).unwrap(),
+        ]
+    }
+
+    fn assert_children_are_permutations<R>(recombinator: &R)
+    where
+        R: Recombinator<[Permutation; 2], Output = Permutation>,
+    {
+        let mut rng = rand::thread_rng();
+        for _ in 0..100 {
+            #[allow(clippy::unwrap_used)]
+            let child = recombinator.recombine(parents(), &mut rng).unwrap();
+            assert!(Permutation::new(child.order().to_vec()).is_ok());
+        }
+    }
+
+    #[test]
+    fn children_are_permutations() {
+        assert_children_are_permutations(&Pmx);
+        assert_children_are_permutations(&OrderXo);
+        assert_children_are_permutations(&CycleXo);
+        assert_children_are_permutations(&EdgeRecombination);
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn cycle_xo_keeps_positions() {
+        let mut rng = rand::thread_rng();
+        let [first, second] = parents();
+        let child = CycleXo.recombine(parents(), &mut rng).unwrap();
+        for (position, &element) in child.order().iter().enumerate() {
+            assert!(element == first.order()[position] || element == second.order()[position]);
+        }
+        // The first cycle is positions 0, 8, 3, 7 (elements 0, 8, 3, 7),
+        // which come from the first parent.
+        for position in [0, 3, 7, 8] {
+            assert_eq!(child.order()[position], position);
+        }
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn identical_parents_make_identical_children() {
+        let mut rng = rand::thread_rng();
+        let [parent, _] = parents();
+        let pair = || [parent.clone(), parent.clone()];
+        assert_eq!(Pmx.recombine(pair(), &mut rng).unwrap(), parent);
+        assert_eq!(OrderXo.recombine(pair(), &mut rng).unwrap(), parent);
+        assert_eq!(CycleXo.recombine(pair(), &mut rng).unwrap(), parent);
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn edge_recombination_keeps_parent_edges() {
+        let mut rng = rand::thread_rng();
+        // With identical parents every edge of the child (as a cycle) should
+        // be an edge of the parent, except where we got stuck, which can't
+        // happen when following a single cycle.
+        let [parent, _] = parents();
+        let child = EdgeRecombination
+            .recombine([parent.clone(), parent.clone()], &mut rng)
+            .unwrap();
+        let positions = parent.positions();
+        let len = parent.len();
+        for pair in child.order().windows(2) {
+            let distance = (positions[pair[0]] + len - positions[pair[1]]) % len;
+            assert!(distance == 1 || distance == len - 1);
+        }
+    }
+
+    #[test]
+    fn different_lengths_are_an_error() {
+        let mut rng = rand::thread_rng();
+        let result = Pmx.recombine(
+            [Permutation::identity(3), Permutation::identity(4)],
+            &mut rng,
+        );
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,166 @@
+use anyhow::{ensure, Result};
+use ec_core::operator::recombinator::Recombinator;
+use rand::{rngs::ThreadRng, Rng};
+
+use crate::genome::{
+    real::{Bounds, Real},
+    vector::Vector,
+};
+
+/// Simulated binary crossover (SBX), which mimics the spread of children
+/// around their parents that single-point crossover has on binary-encoded
+/// numbers.
+///
+/// For each gene, SBX makes two values that are symmetric around the
+/// parents' mean, and the child gets one of them at random. Larger values of
+/// the distribution index `eta` keep children closer to their parents;
+/// values between 2 and 20 are typical. If bounds are given, the child's
+/// genes are clamped to them.
+pub struct Sbx {
+    distribution_index: f64,
+    bounds: Option<Bounds>,
+}
+
+impl Sbx {
+    /// # Errors
+    ///
+    /// This fails if the distribution index is negative.
+    pub fn new(distribution_index: f64) -> Result<Self> {
+        ensure!(
+            distribution_index >= 0.0 && distribution_index.is_finite(),
+            "The distribution index {distribution_index} can't be negative"
+        );
+        Ok(Self {
+            distribution_index,
+            bounds: None,
+        })
+    }
+
+    #[must_use]
+    pub fn with_bounds(mut self, bounds: Bounds) -> Self {
+        self.bounds = Some(bounds);
+        self
+    }
+
+    fn spread_factor(&self, rng: &mut ThreadRng) -> f64 {
+        let u = rng.gen::<f64>();
+        let exponent = (self.distribution_index + 1.0).recip();
+        if u <= 0.5 {
+            (2.0 * u).powf(exponent)
+        } else {
+            (2.0 * (1.0 - u)).recip().powf(exponent)
+        }
+    }
+}
+
+impl<T: Real> Recombinator<[Vector<T>; 2]> for Sbx {
+    type Output = Vector<T>;
+
+    fn recombine(
+        &self,
+        [first_genome, second_genome]: [Vector<T>; 2],
+        rng: &mut ThreadRng,
+    ) -> Result<Self::Output> {
+        ensure!(
+            first_genome.genes.len() == second_genome.genes.len(),
+            "Attempted to perform SBX on genomes of different lengths {} and {}",
+            first_genome.genes.len(),
+            second_genome.genes.len()
+        );
+        if let Some(bounds) = &self.bounds {
+            bounds.check_size(first_genome.genes.len())?;
+        }
+        Ok(first_genome
+            .into_iter()
+            .zip(second_genome)
+            .enumerate()
+            .map(|(index, (first, second))| {
+                let (first, second): (f64, f64) = (first.into(), second.into());
+                let beta = self.spread_factor(rng);
+                let mean = f64::midpoint(first, second);
+                let half_difference = (second - first) / 2.0;
+                // The two children are `mean - beta * half_difference` and
+                // `mean + beta * half_difference`.
+                let sign = if rng.gen_bool(0.5) { -1.0 } else { 1.0 };
+                let value = (sign * beta).mul_add(half_difference, mean);
+                T::from(
+                    self.bounds
+                        .as_ref()
+                        .map_or(value, |bounds| bounds.clamp(index, value)),
+                )
+            })
+            .collect())
+    }
+}
+
+impl<T: Real> Recombinator<(Vector<T>, Vector<T>)> for Sbx {
+    type Output = Vector<T>;
+
+    fn recombine(
+        &self,
+        genomes: (Vector<T>, Vector<T>),
+        rng: &mut ThreadRng,
+    ) -> Result<Self::Output> {
+        self.recombine(<[Vector<T>; 2]>::from(genomes), rng)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[allow(clippy::unwrap_used, clippy::float_cmp)]
+    fn identical_parents_make_identical_children() {
+        let mut rng = rand::thread_rng();
+        let parent = Vector {
+            genes: vec![1.0, -2.0, 3.5],
+        };
+        let child = Sbx::new(2.0)
+            .unwrap()
+            .recombine([parent.clone(), parent.clone()], &mut rng)
+            .unwrap();
+        assert_eq!(child, parent);
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn children_are_clamped() {
+        let mut rng = rand::thread_rng();
+        let bounds = Bounds::uniform(2, 0.0, 1.0).unwrap();
+        // A distribution index of 0 spreads children out a lot.
+        let sbx = Sbx::new(0.0).unwrap().with_bounds(bounds.clone());
+        for _ in 0..100 {
+            let child = sbx
+                .recombine(
+                    (
+                        Vector {
+                            genes: vec![0.0, 0.0],
+                        },
+                        Vector {
+                            genes: vec![1.0, 1.0],
+                        },
+                    ),
+                    &mut rng,
+                )
+                .unwrap();
+            assert!(bounds.contains(&child));
+        }
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn different_lengths_are_an_error() {
+        let mut rng = rand::thread_rng();
+        let result = Sbx::new(2.0).unwrap().recombine(
+            [
+                Vector { genes: vec![0.0] },
+                Vector {
+                    genes: vec![0.0, 1.0],
+                },
+            ],
+            &mut rng,
+        );
+        assert!(result.is_err());
+    }
+}
@@ -60,12 +60,7 @@ where
             first_genome.size(),
             second_genome.size()
         );
-        let len = first_genome.size();
-        for i in 0..len {
-            if rng.gen_bool(0.5) {
-                first_genome.crossover_gene(&mut second_genome, i)?;
-            }
-        }
+        first_genome.crossover_uniform(&mut second_genome, rng)?;
 
         Ok(first_genome)
     }
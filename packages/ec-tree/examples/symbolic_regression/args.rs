@@ -0,0 +1,32 @@
+use clap::Parser;
+
+#[derive(clap::ValueEnum, Copy, Clone, Debug)]
+pub enum RunModel {
+    Serial,
+    Parallel,
+}
+
+/// Tree-based symbolic regression in Rust
+#[derive(Parser, Debug, Copy, Clone)]
+#[clap(author, version, about, long_about = None)]
+pub struct Args {
+    /// Should we use parallelism when doing the run?
+    #[clap(short, long, value_enum, default_value_t = RunModel::Parallel)]
+    pub run_model: RunModel,
+
+    /// Population size
+    #[clap(short, long, value_parser, default_value_t = 500)]
+    pub population_size: usize,
+
+    /// Maximum depth of the trees in the initial population
+    #[clap(short = 'i', long, value_parser, default_value_t = 6)]
+    pub max_initial_depth: usize,
+
+    /// Maximum depth of trees created by crossover and mutation
+    #[clap(short, long, value_parser, default_value_t = 17)]
+    pub max_depth: usize,
+
+    /// Number of generations to run
+    #[clap(short, long, value_parser, default_value_t = 50)]
+    pub num_generations: usize,
+}
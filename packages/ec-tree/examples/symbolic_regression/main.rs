@@ -0,0 +1,200 @@
+pub mod args;
+
+use std::{fmt::Display, ops::Not};
+
+use anyhow::{ensure, Result};
+use clap::Parser;
+use ec_core::{
+    generation::Generation,
+    generator::{collection::ConvertToCollectionGenerator, Generator},
+    individual::{
+        ec::{EcIndividual, WithScorer},
+        scorer::FnScorer,
+    },
+    operator::{
+        genome_extractor::GenomeExtractor,
+        genome_scorer::GenomeScorer,
+        mutator::Mutate,
+        recombinator::Recombine,
+        selector::{
+            best::Best, lexicase::Lexicase, tournament::Tournament, weighted::Weighted, Select,
+            Selector,
+        },
+        Composable,
+    },
+    test_results::{self, TestResults},
+};
+use ec_tree::{
+    generator::RampedHalfAndHalf,
+    limits::TreeLimits,
+    mutator::point::PointMutation,
+    primitive::{Primitive, PrimitiveSet},
+    recombinator::subtree_xo::SubtreeXo,
+    tree::Tree,
+};
+use ordered_float::OrderedFloat;
+use rand::thread_rng;
+
+use crate::args::{Args, RunModel};
+
+/*
+ * This is the same "simple regression" problem as the PushGP version in
+ * `push/examples/simple_regression`, but solved with tree-based GP.
+ */
+
+/// The functions and terminals for our trees. There's only one type
+/// (floating point numbers) so we use `()` as the type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Add,
+    Subtract,
+    Multiply,
+    ProtectedDivide,
+    X,
+    One,
+}
+
+impl Primitive for Op {
+    type Type = ();
+    type Value = f64;
+    // The value of the input `x`.
+    type Context = f64;
+
+    fn return_type(&self) -> Self::Type {}
+
+    fn argument_types(&self) -> &[Self::Type] {
+        match self {
+            Self::Add | Self::Subtract | Self::Multiply | Self::ProtectedDivide => &[(), ()],
+            Self::X | Self::One => &[],
+        }
+    }
+
+    fn evaluate(&self, arguments: &[f64], x: &f64) -> f64 {
+        match self {
+            Self::Add => arguments[0] + arguments[1],
+            Self::Subtract => arguments[0] - arguments[1],
+            Self::Multiply => arguments[0] * arguments[1],
+            Self::ProtectedDivide => {
+                if arguments[1].abs() < f64::EPSILON {
+                    1.0
+                } else {
+                    arguments[0] / arguments[1]
+                }
+            }
+            Self::X => *x,
+            Self::One => 1.0,
+        }
+    }
+}
+
+impl Display for Op {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Add => "+",
+            Self::Subtract => "-",
+            Self::Multiply => "*",
+            Self::ProtectedDivide => "%",
+            Self::X => "x",
+            Self::One => "1",
+        };
+        write!(f, "{name}")
+    }
+}
+
+fn main() -> Result<()> {
+    // Using `Error` in `TestResults<Error>` will have the run favor smaller
+    // values, where using `Score` (e.g., `TestResults<Score>`) will have the run
+    // favor larger values.
+    type Pop = Vec<EcIndividual<Tree<Op>, TestResults<test_results::Error<OrderedFloat<f64>>>>>;
+
+    let args = Args::parse();
+
+    // Inputs from -4 (inclusive) to 4 (exclusive) in increments of 0.25.
+    let training_inputs = (-4 * 4..4 * 4)
+        .map(|n| f64::from(n) / 4.0)
+        .collect::<Vec<_>>();
+
+    /*
+     * The `scorer` evaluates a tree on all the training inputs, collecting
+     * together the errors, i.e., the absolute difference between the
+     * returned value and the expected value.
+     *
+     * The target polynomial is x^3 - 2x^2 - x
+     */
+    let scorer = FnScorer(
+        |tree: &Tree<Op>| -> TestResults<test_results::Error<OrderedFloat<f64>>> {
+            training_inputs
+                .iter()
+                .map(|&x| {
+                    let expected = x.mul_add(x * (x - 2.0), -x);
+                    OrderedFloat((tree.evaluate(&x) - expected).abs())
+                })
+                .collect()
+        },
+    );
+
+    let num_test_cases = training_inputs.len();
+    let lexicase = Lexicase::new(num_test_cases);
+    let binary_tournament = Tournament::new(2);
+
+    let selector: Weighted<Pop> = Weighted::new(Best, 1)
+        .with_selector(lexicase, 5)
+        .with_selector(binary_tournament, args.population_size - 1);
+
+    let mut rng = thread_rng();
+
+    let primitive_set = PrimitiveSet::new([
+        Op::Add,
+        Op::Subtract,
+        Op::Multiply,
+        Op::ProtectedDivide,
+        Op::X,
+        Op::One,
+    ])?;
+
+    let tree_generator =
+        RampedHalfAndHalf::new(primitive_set.clone(), (), 2..=args.max_initial_depth)?;
+
+    let population = tree_generator
+        .with_scorer(scorer)
+        .into_collection_generator(args.population_size)
+        .generate(&mut rng)?;
+
+    ensure!(population.is_empty().not());
+
+    let best = Best.select(&population, &mut rng)?;
+    println!("Best initial individual is {best}");
+
+    let limits = TreeLimits::new(args.max_depth, usize::MAX);
+
+    let make_new_individual = Select::new(selector)
+        .apply_twice()
+        .then_map(GenomeExtractor)
+        .then(Recombine::new(SubtreeXo::default().with_limits(limits)))
+        .then(Mutate::new(PointMutation::new(primitive_set, 0.05)?))
+        .wrap::<GenomeScorer<_, _>>(scorer);
+
+    let mut generation = Generation::new(make_new_individual, population);
+
+    // TODO: It might be useful to insert some kind of logging system so we can
+    // make this less imperative in nature.
+
+    for generation_number in 0..args.num_generations {
+        match args.run_model {
+            RunModel::Serial => generation.serial_next()?,
+            RunModel::Parallel => generation.par_next()?,
+        }
+
+        let best = Best.select(generation.population(), &mut rng)?;
+        // TODO: Change 2 to be the smallest number of digits needed for
+        // args.num_generations-1.
+        println!("Generation {generation_number:2} best is {best}");
+
+        if best.test_results.total_result.error < OrderedFloat(1e-6) {
+            println!("SUCCESS");
+            break;
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,165 @@
+use std::ops::RangeInclusive;
+
+use anyhow::{ensure, Result};
+use ec_core::generator::Generator;
+use rand::{rngs::ThreadRng, Rng};
+
+use crate::{
+    primitive::{Primitive, PrimitiveSet},
+    tree::Tree,
+};
+
+/// Generate random trees with the "grow" method, where nodes are chosen
+/// from all the functions and terminals of the right type, so the trees
+/// can have very different shapes and sizes (up to `max_depth`).
+#[derive(Debug, Clone)]
+pub struct Grow<P: Primitive> {
+    primitive_set: PrimitiveSet<P>,
+    return_type: P::Type,
+    max_depth: usize,
+}
+
+impl<P: Primitive> Grow<P> {
+    pub const fn new(
+        primitive_set: PrimitiveSet<P>,
+        return_type: P::Type,
+        max_depth: usize,
+    ) -> Self {
+        Self {
+            primitive_set,
+            return_type,
+            max_depth,
+        }
+    }
+}
+
+impl<P: Primitive> Generator<Tree<P>> for Grow<P> {
+    fn generate(&self, rng: &mut ThreadRng) -> Result<Tree<P>> {
+        self.primitive_set
+            .grow(self.return_type, self.max_depth, rng)
+    }
+}
+
+/// Generate random trees with the "full" method, where every branch is
+/// (type constraints permitting) extended all the way to `depth`.
+#[derive(Debug, Clone)]
+pub struct Full<P: Primitive> {
+    primitive_set: PrimitiveSet<P>,
+    return_type: P::Type,
+    depth: usize,
+}
+
+impl<P: Primitive> Full<P> {
+    pub const fn new(primitive_set: PrimitiveSet<P>, return_type: P::Type, depth: usize) -> Self {
+        Self {
+            primitive_set,
+            return_type,
+            depth,
+        }
+    }
+}
+
+impl<P: Primitive> Generator<Tree<P>> for Full<P> {
+    fn generate(&self, rng: &mut ThreadRng) -> Result<Tree<P>> {
+        self.primitive_set.full(self.return_type, self.depth, rng)
+    }
+}
+
+/// Generate random trees with Koza's "ramped half-and-half" method.
+///
+/// Each tree gets a random maximum depth from the given range, and then is
+/// generated with either the grow or the full method (with equal
+/// probability). This gives a good mix of shapes and sizes in an initial
+/// population.
+#[derive(Debug, Clone)]
+pub struct RampedHalfAndHalf<P: Primitive> {
+    primitive_set: PrimitiveSet<P>,
+    return_type: P::Type,
+    depths: RangeInclusive<usize>,
+}
+
+impl<P: Primitive> RampedHalfAndHalf<P> {
+    /// # Errors
+    ///
+    /// This fails if the range of depths is empty.
+    pub fn new(
+        primitive_set: PrimitiveSet<P>,
+        return_type: P::Type,
+        depths: RangeInclusive<usize>,
+    ) -> Result<Self> {
+        ensure!(
+            !depths.is_empty(),
+            "The range of depths {depths:?} for ramped half-and-half is empty"
+        );
+        Ok(Self {
+            primitive_set,
+            return_type,
+            depths,
+        })
+    }
+}
+
+impl<P: Primitive> Generator<Tree<P>> for RampedHalfAndHalf<P> {
+    fn generate(&self, rng: &mut ThreadRng) -> Result<Tree<P>> {
+        let depth = rng.gen_range(self.depths.clone());
+        if rng.gen_bool(0.5) {
+            self.primitive_set.grow(self.return_type, depth, rng)
+        } else {
+            self.primitive_set.full(self.return_type, depth, rng)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::thread_rng;
+
+    use super::*;
+    use crate::test_primitive::{primitive_set, Type};
+
+    #[test]
+    fn grow_respects_max_depth() {
+        let mut rng = thread_rng();
+        let generator = Grow::new(primitive_set(), Type::Real, 4);
+        for _ in 0..100 {
+            #[allow(clippy::unwrap_used)]
+            let tree = generator.generate(&mut rng).unwrap();
+            assert!(tree.depth() <= 4);
+            assert_eq!(tree.return_type(), Type::Real);
+            assert!(Tree::from_prefix(tree.nodes().to_vec()).is_ok());
+        }
+    }
+
+    #[test]
+    fn full_reaches_depth() {
+        let mut rng = thread_rng();
+        let generator = Full::new(primitive_set(), Type::Real, 3);
+        for _ in 0..100 {
+            #[allow(clippy::unwrap_used)]
+            let tree = generator.generate(&mut rng).unwrap();
+            // Every primitive in our test set has a function for each type,
+            // so full trees always have exactly the requested depth.
+            assert_eq!(tree.depth(), 3);
+            assert!(Tree::from_prefix(tree.nodes().to_vec()).is_ok());
+        }
+    }
+
+    #[test]
+    fn ramped_half_and_half_stays_in_range() {
+        let mut rng = thread_rng();
+        #[allow(clippy::unwrap_used)]
+        let generator = RampedHalfAndHalf::new(primitive_set(), Type::Bool, 2..=5).unwrap();
+        for _ in 0..100 {
+            #[allow(clippy::unwrap_used)]
+            let tree = generator.generate(&mut rng).unwrap();
+            assert!(tree.depth() <= 5);
+            assert_eq!(tree.return_type(), Type::Bool);
+        }
+    }
+
+    #[test]
+    #[allow(clippy::reversed_empty_ranges)]
+    fn empty_depth_range_is_an_error() {
+        assert!(RampedHalfAndHalf::new(primitive_set(), Type::Real, 5..=2).is_err());
+    }
+}
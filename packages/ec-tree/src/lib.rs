@@ -0,0 +1,9 @@
+pub mod generator;
+pub mod limits;
+pub mod mutator;
+pub mod primitive;
+pub mod recombinator;
+pub mod tree;
+
+#[cfg(test)]
+pub(crate) mod test_primitive;
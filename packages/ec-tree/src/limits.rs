@@ -0,0 +1,35 @@
+use crate::{primitive::Primitive, tree::Tree};
+
+/// Limits on the shape of trees that the variation operators in this crate
+/// will produce.
+///
+/// Operators that would create a tree that violates these
+/// limits try again and, if they keep failing, return their (first) parent
+/// unchanged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TreeLimits {
+    pub max_depth: usize,
+    pub max_size: usize,
+}
+
+impl TreeLimits {
+    #[must_use]
+    pub const fn new(max_depth: usize, max_size: usize) -> Self {
+        Self {
+            max_depth,
+            max_size,
+        }
+    }
+
+    #[must_use]
+    pub fn permits<P: Primitive>(&self, tree: &Tree<P>) -> bool {
+        tree.size() <= self.max_size && tree.depth() <= self.max_depth
+    }
+}
+
+/// The traditional (Koza-style) limit of depth 17 and no limit on size.
+impl Default for TreeLimits {
+    fn default() -> Self {
+        Self::new(17, usize::MAX)
+    }
+}
@@ -0,0 +1,69 @@
+use anyhow::Result;
+use ec_core::operator::mutator::Mutator;
+use rand::{rngs::ThreadRng, seq::IteratorRandom};
+
+use crate::{primitive::Primitive, tree::Tree};
+
+/// Replace the whole tree with one of its own (non-root) subtrees that has
+/// the same type as the tree. This can only make trees smaller, which makes
+/// it a useful counterweight to bloat.
+///
+/// If there is no such subtree, the tree is returned unchanged.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HoistMutation;
+
+impl<P: Primitive> Mutator<Tree<P>> for HoistMutation {
+    fn mutate(&self, genome: Tree<P>, rng: &mut ThreadRng) -> Result<Tree<P>> {
+        let return_type = genome.return_type();
+        let point = genome
+            .nodes()
+            .iter()
+            .enumerate()
+            .skip(1)
+            .filter(|(_, node)| node.return_type() == return_type)
+            .map(|(index, _)| index)
+            .choose(rng);
+        match point {
+            Some(point) => Ok(genome.subtree(point)),
+            None => Ok(genome),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::thread_rng;
+
+    use super::*;
+    use crate::test_primitive::{
+        Prim::{If, Lt, One, Plus, X},
+        Type,
+    };
+
+    #[test]
+    fn hoisted_tree_is_a_subtree() {
+        let mut rng = thread_rng();
+        #[allow(clippy::unwrap_used)]
+        let tree = Tree::from_prefix([If, Lt, X, One, Plus, X, One, X]).unwrap();
+        for _ in 0..20 {
+            #[allow(clippy::unwrap_used)]
+            let child = HoistMutation.mutate(tree.clone(), &mut rng).unwrap();
+            assert_eq!(child.return_type(), Type::Real);
+            assert!(child.size() < tree.size());
+            assert!(tree
+                .nodes()
+                .windows(child.size())
+                .any(|window| window == child.nodes()));
+        }
+    }
+
+    #[test]
+    fn single_node_is_unchanged() {
+        let mut rng = thread_rng();
+        #[allow(clippy::unwrap_used)]
+        let tree = Tree::from_prefix([X]).unwrap();
+        #[allow(clippy::unwrap_used)]
+        let child = HoistMutation.mutate(tree.clone(), &mut rng).unwrap();
+        assert_eq!(child, tree);
+    }
+}
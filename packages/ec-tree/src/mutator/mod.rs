@@ -0,0 +1,8 @@
+pub mod hoist;
+pub mod point;
+pub mod subtree;
+
+/// How many times the mutators in this module will try to produce a child
+/// that satisfies their `TreeLimits` before giving up and returning the
+/// parent unchanged.
+pub(crate) const MAX_ATTEMPTS: usize = 10;
@@ -0,0 +1,94 @@
+use anyhow::{ensure, Result};
+use ec_core::operator::mutator::Mutator;
+use rand::{rngs::ThreadRng, Rng};
+
+use crate::{
+    primitive::{Primitive, PrimitiveSet},
+    tree::Tree,
+};
+
+/// Independently replace each node, with probability `mutation_rate`, by a
+/// random primitive with the same return type and argument types.
+///
+/// This never
+/// changes the shape of the tree, so it can't violate any size or depth
+/// limits.
+#[derive(Debug, Clone)]
+pub struct PointMutation<P> {
+    primitive_set: PrimitiveSet<P>,
+    mutation_rate: f64,
+}
+
+impl<P> PointMutation<P> {
+    /// # Errors
+    ///
+    /// This fails if `mutation_rate` isn't a probability.
+    pub fn new(primitive_set: PrimitiveSet<P>, mutation_rate: f64) -> Result<Self> {
+        ensure!(
+            (0.0..=1.0).contains(&mutation_rate),
+            "The mutation rate {mutation_rate} must be between 0 and 1"
+        );
+        Ok(Self {
+            primitive_set,
+            mutation_rate,
+        })
+    }
+}
+
+impl<P: Primitive> Mutator<Tree<P>> for PointMutation<P> {
+    fn mutate(&self, genome: Tree<P>, rng: &mut ThreadRng) -> Result<Tree<P>> {
+        let nodes = genome
+            .nodes()
+            .iter()
+            .map(|node| {
+                if rng.gen_bool(self.mutation_rate) {
+                    self.primitive_set.random_replacement(node, rng)
+                } else {
+                    node.clone()
+                }
+            })
+            .collect();
+        Ok(Tree::from_valid_nodes(nodes))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::thread_rng;
+
+    use super::*;
+    use crate::test_primitive::{primitive_set, Type};
+
+    #[test]
+    fn shape_is_preserved() {
+        let mut rng = thread_rng();
+        #[allow(clippy::unwrap_used)]
+        let mutation = PointMutation::new(primitive_set(), 1.0).unwrap();
+        #[allow(clippy::unwrap_used)]
+        let tree = primitive_set().grow(Type::Real, 5, &mut rng).unwrap();
+        #[allow(clippy::unwrap_used)]
+        let child = mutation.mutate(tree.clone(), &mut rng).unwrap();
+        assert_eq!(child.size(), tree.size());
+        assert_eq!(child.node_depths(), tree.node_depths());
+        assert!(Tree::from_prefix(child.nodes().to_vec()).is_ok());
+    }
+
+    #[test]
+    fn zero_rate_changes_nothing() {
+        let mut rng = thread_rng();
+        #[allow(clippy::unwrap_used)]
+        let mutation = PointMutation::new(primitive_set(), 0.0).unwrap();
+        #[allow(clippy::unwrap_used)]
+        let tree = primitive_set().grow(Type::Real, 5, &mut rng).unwrap();
+        #[allow(clippy::unwrap_used)]
+        let child = mutation.mutate(tree.clone(), &mut rng).unwrap();
+        assert_eq!(child, tree);
+    }
+
+    #[test]
+    fn rate_must_be_a_probability() {
+        assert!(PointMutation::new(primitive_set(), 1.5).is_err());
+        assert!(PointMutation::new(primitive_set(), -0.1).is_err());
+        assert!(PointMutation::new(primitive_set(), f64::NAN).is_err());
+    }
+}
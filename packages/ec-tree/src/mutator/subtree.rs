@@ -0,0 +1,80 @@
+use anyhow::Result;
+use ec_core::operator::mutator::Mutator;
+use rand::{rngs::ThreadRng, Rng};
+
+use super::MAX_ATTEMPTS;
+use crate::{
+    limits::TreeLimits,
+    primitive::{Primitive, PrimitiveSet},
+    tree::Tree,
+};
+
+/// Replace a randomly chosen subtree with a new, randomly "grown" subtree of
+/// the same type.
+#[derive(Debug, Clone)]
+pub struct SubtreeMutation<P> {
+    primitive_set: PrimitiveSet<P>,
+    max_subtree_depth: usize,
+    limits: TreeLimits,
+}
+
+impl<P> SubtreeMutation<P> {
+    #[must_use]
+    pub fn new(primitive_set: PrimitiveSet<P>, max_subtree_depth: usize) -> Self {
+        Self {
+            primitive_set,
+            max_subtree_depth,
+            limits: TreeLimits::default(),
+        }
+    }
+
+    #[must_use]
+    pub const fn with_limits(mut self, limits: TreeLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+}
+
+impl<P: Primitive> Mutator<Tree<P>> for SubtreeMutation<P> {
+    fn mutate(&self, genome: Tree<P>, rng: &mut ThreadRng) -> Result<Tree<P>> {
+        for _ in 0..MAX_ATTEMPTS {
+            let point = rng.gen_range(0..genome.size());
+            let replacement = self.primitive_set.grow(
+                genome.nodes()[point].return_type(),
+                self.max_subtree_depth,
+                rng,
+            )?;
+            let mut child = genome.clone();
+            child.replace_subtree(point, &replacement)?;
+            if self.limits.permits(&child) {
+                return Ok(child);
+            }
+        }
+        Ok(genome)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::thread_rng;
+
+    use super::*;
+    use crate::test_primitive::{primitive_set, Prim, Type};
+
+    #[test]
+    fn children_are_valid_and_within_limits() {
+        let mut rng = thread_rng();
+        let limits = TreeLimits::new(4, 20);
+        let mutation = SubtreeMutation::new(primitive_set(), 3).with_limits(limits);
+        #[allow(clippy::unwrap_used)]
+        let mut tree = primitive_set().full(Type::Real, 2, &mut rng).unwrap();
+        for _ in 0..100 {
+            #[allow(clippy::unwrap_used)]
+            let child = mutation.mutate(tree.clone(), &mut rng).unwrap();
+            assert!(limits.permits(&child));
+            assert_eq!(child.return_type(), Type::Real);
+            assert!(Tree::<Prim>::from_prefix(child.nodes().to_vec()).is_ok());
+            tree = child;
+        }
+    }
+}
@@ -0,0 +1,195 @@
+use std::fmt::Debug;
+
+use anyhow::{ensure, Context, Result};
+use rand::{rngs::ThreadRng, seq::SliceRandom, Rng};
+
+use crate::tree::Tree;
+
+/// A single node type (function or terminal) that can appear in a [`Tree`].
+///
+/// Trees are _typed_: every primitive has a return type and a (possibly
+/// empty) list of argument types, and a primitive can only be used as the
+/// `i`th argument of a function if its return type matches that function's
+/// `i`th argument type. Problems that only need one type can just use `()`
+/// as their `Type`.
+///
+/// Primitives with no arguments are terminals; everything else is a
+/// function.
+pub trait Primitive: Clone {
+    /// The type system used to constrain which primitives can be combined.
+    type Type: Copy + Eq + Debug;
+    /// The type of the values computed when a tree is evaluated.
+    type Value;
+    /// Whatever extra information is needed to evaluate a tree, such as the
+    /// values of any input variables.
+    type Context: ?Sized;
+
+    /// The type of the value returned by this primitive.
+    fn return_type(&self) -> Self::Type;
+
+    /// The types of each of the arguments of this primitive, in order.
+    fn argument_types(&self) -> &[Self::Type];
+
+    /// The number of arguments (children) this primitive takes.
+    fn arity(&self) -> usize {
+        self.argument_types().len()
+    }
+
+    /// Compute the value of this primitive given the values of its
+    /// arguments.
+    fn evaluate(&self, arguments: &[Self::Value], context: &Self::Context) -> Self::Value;
+}
+
+/// The user-supplied set of functions and terminals that trees are built
+/// from.
+#[derive(Debug, Clone)]
+pub struct PrimitiveSet<P> {
+    functions: Vec<P>,
+    terminals: Vec<P>,
+}
+
+impl<P: Primitive> PrimitiveSet<P> {
+    /// Create a new `PrimitiveSet`, sorting the given primitives into
+    /// functions and terminals based on their arity.
+    ///
+    /// # Errors
+    ///
+    /// This fails if there are no terminals, since no tree could ever
+    /// be completed.
+    pub fn new(primitives: impl IntoIterator<Item = P>) -> Result<Self> {
+        let (terminals, functions): (Vec<_>, Vec<_>) =
+            primitives.into_iter().partition(|p| p.arity() == 0);
+        ensure!(
+            !terminals.is_empty(),
+            "A primitive set needs at least one terminal"
+        );
+        Ok(Self {
+            functions,
+            terminals,
+        })
+    }
+
+    #[must_use]
+    pub fn functions(&self) -> &[P] {
+        &self.functions
+    }
+
+    #[must_use]
+    pub fn terminals(&self) -> &[P] {
+        &self.terminals
+    }
+
+    fn functions_of_type(&self, return_type: P::Type) -> impl Iterator<Item = &P> {
+        self.functions
+            .iter()
+            .filter(move |p| p.return_type() == return_type)
+    }
+
+    fn terminals_of_type(&self, return_type: P::Type) -> impl Iterator<Item = &P> {
+        self.terminals
+            .iter()
+            .filter(move |p| p.return_type() == return_type)
+    }
+
+    /// Choose a random terminal with the given return type.
+    ///
+    /// # Errors
+    ///
+    /// This fails if there are no terminals of that type.
+    pub fn random_terminal(&self, return_type: P::Type, rng: &mut ThreadRng) -> Result<&P> {
+        self.terminals_of_type(return_type)
+            .collect::<Vec<_>>()
+            .choose(rng)
+            .copied()
+            .with_context(|| format!("There are no terminals of type {return_type:?}"))
+    }
+
+    /// Choose a random primitive (function or terminal) that could be used
+    /// in place of `primitive`, i.e., that has the same return type and the
+    /// same argument types. If there aren't any (e.g., because `primitive`
+    /// isn't actually in this set), `primitive` itself is returned.
+    pub fn random_replacement(&self, primitive: &P, rng: &mut ThreadRng) -> P {
+        let candidates = if primitive.arity() == 0 {
+            &self.terminals
+        } else {
+            &self.functions
+        };
+        candidates
+            .iter()
+            .filter(|p| {
+                p.return_type() == primitive.return_type()
+                    && p.argument_types() == primitive.argument_types()
+            })
+            .collect::<Vec<_>>()
+            .choose(rng)
+            .copied()
+            .cloned()
+            .unwrap_or_else(|| primitive.clone())
+    }
+
+    /// Generate a random tree using the "grow" method: every node is chosen
+    /// from all the primitives with the right type until we reach
+    /// `max_depth`, at which point only terminals are chosen.
+    ///
+    /// # Errors
+    ///
+    /// This fails if we need a terminal of some type and there aren't any.
+    pub fn grow(
+        &self,
+        return_type: P::Type,
+        max_depth: usize,
+        rng: &mut ThreadRng,
+    ) -> Result<Tree<P>> {
+        let mut nodes = Vec::new();
+        self.build(return_type, max_depth, false, rng, &mut nodes)?;
+        Ok(Tree::from_valid_nodes(nodes))
+    }
+
+    /// Generate a random tree using the "full" method: every node is a
+    /// function until we reach `max_depth`, at which point only terminals
+    /// are chosen. If there are no functions of a needed type then a terminal
+    /// is used early.
+    ///
+    /// # Errors
+    ///
+    /// This fails if we need a terminal of some type and there aren't any.
+    pub fn full(
+        &self,
+        return_type: P::Type,
+        max_depth: usize,
+        rng: &mut ThreadRng,
+    ) -> Result<Tree<P>> {
+        let mut nodes = Vec::new();
+        self.build(return_type, max_depth, true, rng, &mut nodes)?;
+        Ok(Tree::from_valid_nodes(nodes))
+    }
+
+    fn build(
+        &self,
+        return_type: P::Type,
+        depth_remaining: usize,
+        full: bool,
+        rng: &mut ThreadRng,
+        nodes: &mut Vec<P>,
+    ) -> Result<()> {
+        let functions = self.functions_of_type(return_type).collect::<Vec<_>>();
+        let use_function = depth_remaining > 0
+            && !functions.is_empty()
+            && (full || {
+                let num_terminals = self.terminals_of_type(return_type).count();
+                rng.gen_range(0..functions.len() + num_terminals) < functions.len()
+            });
+        if use_function {
+            let function = functions
+                .choose(rng)
+                .context("The set of functions was unexpectedly empty")?;
+            nodes.push((*function).clone());
+            for &argument_type in function.argument_types() {
+                self.build(argument_type, depth_remaining - 1, full, rng, nodes)?;
+            }
+        } else {
+            nodes.push(self.random_terminal(return_type, rng)?.clone());
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,171 @@
+use anyhow::{ensure, Result};
+use ec_core::operator::recombinator::Recombinator;
+use rand::{rngs::ThreadRng, seq::IteratorRandom, Rng};
+
+use crate::{limits::TreeLimits, primitive::Primitive, tree::Tree};
+
+/// How many times we'll try to find a pair of crossover points that
+/// produce a child within the limits before giving up.
+const MAX_ATTEMPTS: usize = 10;
+
+/// Standard (typed) subtree crossover: replace a randomly chosen subtree of
+/// the first parent with a randomly chosen subtree of the second parent
+/// that has the same type.
+///
+/// As suggested by Koza, crossover points are chosen from the internal
+/// (function) nodes with probability `internal_node_probability` and from
+/// the leaves otherwise, since otherwise most crossovers just swap leaves.
+#[derive(Debug, Clone, Copy)]
+pub struct SubtreeXo {
+    internal_node_probability: f64,
+    limits: TreeLimits,
+}
+
+impl SubtreeXo {
+    /// # Errors
+    ///
+    /// This fails if `internal_node_probability` isn't a probability.
+    pub fn new(internal_node_probability: f64) -> Result<Self> {
+        ensure!(
+            (0.0..=1.0).contains(&internal_node_probability),
+            "The internal node probability {internal_node_probability} must be between 0 and 1"
+        );
+        Ok(Self {
+            internal_node_probability,
+            limits: TreeLimits::default(),
+        })
+    }
+
+    #[must_use]
+    pub const fn with_limits(mut self, limits: TreeLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Choose a random node of `tree`, optionally requiring a particular
+    /// return type, preferring internal nodes with probability
+    /// `internal_node_probability` (if there are any suitable internal
+    /// nodes).
+    fn choose_point<P: Primitive>(
+        &self,
+        tree: &Tree<P>,
+        return_type: Option<P::Type>,
+        rng: &mut ThreadRng,
+    ) -> Option<usize> {
+        let want_internal = rng.gen_bool(self.internal_node_probability);
+        let candidates = || {
+            tree.nodes().iter().enumerate().filter(move |(_, node)| {
+                return_type.is_none_or(|return_type| node.return_type() == return_type)
+            })
+        };
+        candidates()
+            .filter(|(_, node)| (node.arity() > 0) == want_internal)
+            .map(|(index, _)| index)
+            .choose(rng)
+            .or_else(|| candidates().map(|(index, _)| index).choose(rng))
+    }
+}
+
+impl Default for SubtreeXo {
+    fn default() -> Self {
+        Self {
+            internal_node_probability: 0.9,
+            limits: TreeLimits::default(),
+        }
+    }
+}
+
+impl<P: Primitive> Recombinator<[Tree<P>; 2]> for SubtreeXo {
+    type Output = Tree<P>;
+
+    fn recombine(
+        &self,
+        [first_parent, second_parent]: [Tree<P>; 2],
+        rng: &mut ThreadRng,
+    ) -> Result<Self::Output> {
+        for _ in 0..MAX_ATTEMPTS {
+            let Some(first_point) = self.choose_point(&first_parent, None, rng) else {
+                continue;
+            };
+            let return_type = first_parent.nodes()[first_point].return_type();
+            let Some(second_point) = self.choose_point(&second_parent, Some(return_type), rng)
+            else {
+                continue;
+            };
+            let mut child = first_parent.clone();
+            child.replace_subtree(first_point, &second_parent.subtree(second_point))?;
+            if self.limits.permits(&child) {
+                return Ok(child);
+            }
+        }
+        Ok(first_parent)
+    }
+}
+
+impl<P: Primitive> Recombinator<(Tree<P>, Tree<P>)> for SubtreeXo {
+    type Output = Tree<P>;
+
+    fn recombine(&self, genomes: (Tree<P>, Tree<P>), rng: &mut ThreadRng) -> Result<Self::Output> {
+        self.recombine(<[Tree<P>; 2]>::from(genomes), rng)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::thread_rng;
+
+    use super::*;
+    use crate::test_primitive::{
+        primitive_set,
+        Prim::{One, Plus, True, X},
+        Type,
+    };
+
+    #[test]
+    fn children_are_valid_and_within_limits() {
+        let mut rng = thread_rng();
+        let limits = TreeLimits::new(5, 30);
+        let xo = SubtreeXo::default().with_limits(limits);
+        let primitive_set = primitive_set();
+        // If no crossover gives a child within the limits, the first parent
+        // is returned unchanged, so the parents have to be within the limits
+        // too. These random trees can have up to 121 and 40 nodes, so we
+        // generate them until they're small enough.
+        let within_limits = |generate: &mut dyn FnMut() -> Result<Tree<_>>| {
+            #[allow(clippy::unwrap_used)]
+            std::iter::repeat_with(|| generate().unwrap())
+                .find(|tree| limits.permits(tree))
+                .unwrap()
+        };
+        for _ in 0..100 {
+            let first = within_limits(&mut || primitive_set.grow(Type::Real, 4, &mut rng));
+            let second = within_limits(&mut || primitive_set.full(Type::Real, 3, &mut rng));
+            #[allow(clippy::unwrap_used)]
+            let child = xo.recombine([first, second], &mut rng).unwrap();
+            assert!(limits.permits(&child));
+            assert_eq!(child.return_type(), Type::Real);
+            assert!(Tree::from_prefix(child.nodes().to_vec()).is_ok());
+        }
+    }
+
+    #[test]
+    fn types_must_match() {
+        let mut rng = thread_rng();
+        // The second parent has no `Real` nodes, so the only possible
+        // crossovers would be ill-typed and the first parent is returned.
+        #[allow(clippy::unwrap_used)]
+        let first = Tree::from_prefix([Plus, X, One]).unwrap();
+        #[allow(clippy::unwrap_used)]
+        let second = Tree::from_prefix([True]).unwrap();
+        #[allow(clippy::unwrap_used)]
+        let child = SubtreeXo::default()
+            .recombine((first.clone(), second), &mut rng)
+            .unwrap();
+        assert_eq!(child, first);
+    }
+
+    #[test]
+    fn invalid_probability_is_an_error() {
+        assert!(SubtreeXo::new(1.5).is_err());
+    }
+}
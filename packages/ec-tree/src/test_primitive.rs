@@ -0,0 +1,87 @@
+//! A small, two-typed primitive set used by the tests in this crate.
+
+use std::fmt::Display;
+
+use crate::primitive::{Primitive, PrimitiveSet};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Type {
+    Real,
+    Bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Prim {
+    If,
+    Lt,
+    Plus,
+    X,
+    One,
+    True,
+}
+
+impl Primitive for Prim {
+    type Type = Type;
+    // To keep things simple we represent booleans as 0.0 and 1.0.
+    type Value = f64;
+    // The value of `x`.
+    type Context = f64;
+
+    fn return_type(&self) -> Type {
+        match self {
+            Self::Lt | Self::True => Type::Bool,
+            Self::If | Self::Plus | Self::X | Self::One => Type::Real,
+        }
+    }
+
+    fn argument_types(&self) -> &[Type] {
+        match self {
+            Self::If => &[Type::Bool, Type::Real, Type::Real],
+            Self::Lt | Self::Plus => &[Type::Real, Type::Real],
+            Self::X | Self::One | Self::True => &[],
+        }
+    }
+
+    fn evaluate(&self, arguments: &[f64], x: &f64) -> f64 {
+        match self {
+            Self::If => {
+                if arguments[0] > 0.5 {
+                    arguments[1]
+                } else {
+                    arguments[2]
+                }
+            }
+            Self::Lt => f64::from(u8::from(arguments[0] < arguments[1])),
+            Self::Plus => arguments[0] + arguments[1],
+            Self::X => *x,
+            Self::One | Self::True => 1.0,
+        }
+    }
+}
+
+impl Display for Prim {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::If => "if",
+            Self::Lt => "<",
+            Self::Plus => "+",
+            Self::X => "x",
+            Self::One => "1",
+            Self::True => "true",
+        };
+        write!(f, "{name}")
+    }
+}
+
+pub fn primitive_set() -> PrimitiveSet<Prim> {
+    #[allow(clippy::unwrap_used)]
+    PrimitiveSet::new([
+        Prim::If,
+        Prim::Lt,
+        Prim::Plus,
+        Prim::X,
+        Prim::One,
+        Prim::True,
+    ])
+    .unwrap()
+}
@@ -0,0 +1,314 @@
+use std::{fmt::Display, ops::Range};
+
+use anyhow::{bail, ensure, Result};
+use ec_core::genome::Genome;
+
+use crate::primitive::Primitive;
+
+/// An expression tree, stored as a flat vector of primitives in prefix
+/// (pre-order) order.
+///
+/// Storing trees this way (instead of as boxed nodes) makes cloning cheap
+/// and turns subtrees into contiguous ranges of nodes, which is all that
+/// crossover and mutation really need. The tree `(+ x (* x 1))`, for
+/// example, is stored as `[+, x, *, x, 1]`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Tree<P> {
+    nodes: Vec<P>,
+}
+
+impl<P> Genome for Tree<P> {
+    type Gene = P;
+}
+
+impl<P: Primitive> Tree<P> {
+    /// Build a tree from a sequence of primitives in prefix order.
+    ///
+    /// # Errors
+    ///
+    /// This fails if the nodes don't form exactly one complete tree, or if
+    /// any primitive's return type doesn't match the argument type its parent
+    /// expects.
+    pub fn from_prefix(nodes: impl IntoIterator<Item = P>) -> Result<Self> {
+        let nodes = nodes.into_iter().collect::<Vec<_>>();
+        ensure!(!nodes.is_empty(), "A tree must have at least one node");
+        // The argument types we still need to fill, with the next one needed
+        // on top of the stack.
+        let mut expected_types = Vec::new();
+        for (index, node) in nodes.iter().enumerate() {
+            if index > 0 {
+                let Some(expected_type) = expected_types.pop() else {
+                    bail!("The tree was complete after {index} nodes, but there were more nodes");
+                };
+                ensure!(
+                    node.return_type() == expected_type,
+                    "Node {index} returns type {:?} but its parent expects type {expected_type:?}",
+                    node.return_type()
+                );
+            }
+            expected_types.extend(node.argument_types().iter().rev());
+        }
+        ensure!(
+            expected_types.is_empty(),
+            "The tree is incomplete: {} more nodes are needed",
+            expected_types.len()
+        );
+        Ok(Self { nodes })
+    }
+
+    /// Wrap nodes we already know form a valid tree (e.g., because we just
+    /// generated them) without re-checking them.
+    pub(crate) fn from_valid_nodes(nodes: Vec<P>) -> Self {
+        debug_assert!(!nodes.is_empty());
+        Self { nodes }
+    }
+
+    #[must_use]
+    pub fn nodes(&self) -> &[P] {
+        &self.nodes
+    }
+
+    /// The primitive at the root of this tree.
+    #[must_use]
+    pub fn root(&self) -> &P {
+        &self.nodes[0]
+    }
+
+    /// The type of the value returned when this tree is evaluated.
+    #[must_use]
+    pub fn return_type(&self) -> P::Type {
+        self.root().return_type()
+    }
+
+    /// The number of nodes in this tree.
+    #[must_use]
+    pub const fn size(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// The depth of this tree, where a tree that is a single terminal has
+    /// depth 0.
+    #[must_use]
+    pub fn depth(&self) -> usize {
+        self.node_depths().into_iter().max().unwrap_or_default()
+    }
+
+    /// The depth of each node in this tree, in prefix order; the root is at
+    /// depth 0.
+    #[must_use]
+    pub fn node_depths(&self) -> Vec<usize> {
+        let mut depths = Vec::with_capacity(self.nodes.len());
+        // The number of children still to come for each open ancestor of
+        // the current node.
+        let mut open_children: Vec<usize> = Vec::new();
+        for node in &self.nodes {
+            depths.push(open_children.len());
+            if let Some(remaining) = open_children.last_mut() {
+                *remaining -= 1;
+            }
+            if node.arity() > 0 {
+                open_children.push(node.arity());
+            }
+            while open_children.last() == Some(&0) {
+                open_children.pop();
+            }
+        }
+        depths
+    }
+
+    /// The range of node indices making up the subtree rooted at `start`.
+    ///
+    /// # Panics
+    ///
+    /// This panics if `start` isn't a valid node index.
+    #[must_use]
+    pub fn subtree_range(&self, start: usize) -> Range<usize> {
+        let mut end = start;
+        let mut needed = 1;
+        while needed > 0 {
+            needed = needed + self.nodes[end].arity() - 1;
+            end += 1;
+        }
+        start..end
+    }
+
+    /// A copy of the subtree rooted at `start`.
+    ///
+    /// # Panics
+    ///
+    /// This panics if `start` isn't a valid node index.
+    #[must_use]
+    pub fn subtree(&self, start: usize) -> Self {
+        Self {
+            nodes: self.nodes[self.subtree_range(start)].to_vec(),
+        }
+    }
+
+    /// Replace the subtree rooted at `start` with `replacement`.
+    ///
+    /// # Errors
+    ///
+    /// This fails if `start` isn't a valid node index or if `replacement`
+    /// doesn't have the same return type as the subtree it replaces.
+    pub fn replace_subtree(&mut self, start: usize, replacement: &Self) -> Result<()> {
+        ensure!(
+            start < self.size(),
+            "Attempted to replace the subtree at index {start} in a tree with only {} nodes",
+            self.size()
+        );
+        ensure!(
+            self.nodes[start].return_type() == replacement.return_type(),
+            "Attempted to replace a subtree of type {:?} with one of type {:?}",
+            self.nodes[start].return_type(),
+            replacement.return_type()
+        );
+        let range = self.subtree_range(start);
+        self.nodes.splice(range, replacement.nodes.iter().cloned());
+        Ok(())
+    }
+
+    /// Evaluate this tree in the given context.
+    ///
+    /// # Panics
+    ///
+    /// This only panics if the tree is malformed, which the constructors
+    /// rule out.
+    pub fn evaluate(&self, context: &P::Context) -> P::Value {
+        // Evaluating the nodes from last to first means that the arguments
+        // of each function have always been evaluated (and are on the top of
+        // `values`, first argument on top) by the time we get to the
+        // function.
+        let mut values = Vec::new();
+        for node in self.nodes.iter().rev() {
+            let mut arguments = values.split_off(values.len() - node.arity());
+            arguments.reverse();
+            values.push(node.evaluate(&arguments, context));
+        }
+        debug_assert_eq!(values.len(), 1);
+        #[allow(clippy::unwrap_used)]
+        // A valid tree always leaves exactly one value behind.
+        values.pop().unwrap()
+    }
+}
+
+/// Display trees as S-expressions, e.g., `(+ x (* x 1))`.
+impl<P> Display for Tree<P>
+where
+    P: Primitive + Display,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut open_children: Vec<usize> = Vec::new();
+        for (index, node) in self.nodes.iter().enumerate() {
+            if index > 0 {
+                write!(f, " ")?;
+            }
+            if let Some(remaining) = open_children.last_mut() {
+                *remaining -= 1;
+            }
+            if node.arity() > 0 {
+                write!(f, "({node}")?;
+                open_children.push(node.arity());
+            } else {
+                write!(f, "{node}")?;
+            }
+            while open_children.last() == Some(&0) {
+                open_children.pop();
+                write!(f, ")")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_primitive::Prim::{self, If, Lt, One, Plus, True, X};
+
+    // (if (< x 1) (+ x 1) x)
+    fn example() -> Tree<Prim> {
+        #[allow(clippy::unwrap_used)]
+        Tree::from_prefix([If, Lt, X, One, Plus, X, One, X]).unwrap()
+    }
+
+    #[test]
+    fn size_and_depth() {
+        let tree = example();
+        assert_eq!(tree.size(), 8);
+        assert_eq!(tree.depth(), 2);
+        assert_eq!(tree.node_depths(), vec![0, 1, 2, 2, 1, 2, 2, 1]);
+    }
+
+    #[test]
+    fn single_terminal_has_depth_zero() {
+        #[allow(clippy::unwrap_used)]
+        let tree = Tree::from_prefix([X]).unwrap();
+        assert_eq!(tree.depth(), 0);
+        assert_eq!(tree.size(), 1);
+    }
+
+    #[test]
+    fn subtree_ranges() {
+        let tree = example();
+        assert_eq!(tree.subtree_range(0), 0..8);
+        assert_eq!(tree.subtree_range(1), 1..4);
+        assert_eq!(tree.subtree_range(4), 4..7);
+        assert_eq!(tree.subtree_range(7), 7..8);
+    }
+
+    #[test]
+    fn display_as_s_expression() {
+        assert_eq!(example().to_string(), "(if (< x 1) (+ x 1) x)");
+    }
+
+    #[test]
+    fn evaluate() {
+        let tree = example();
+        assert!((tree.evaluate(&0.5) - 1.5).abs() < f64::EPSILON);
+        assert!((tree.evaluate(&3.0) - 3.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn incomplete_trees_are_rejected() {
+        assert!(Tree::from_prefix([Plus, X]).is_err());
+        assert!(Tree::<Prim>::from_prefix([]).is_err());
+    }
+
+    #[test]
+    fn extra_nodes_are_rejected() {
+        assert!(Tree::from_prefix([X, One]).is_err());
+    }
+
+    #[test]
+    fn ill_typed_trees_are_rejected() {
+        assert!(Tree::from_prefix([Plus, True, X]).is_err());
+        assert!(Tree::from_prefix([If, X, One, One]).is_err());
+    }
+
+    #[test]
+    fn replace_subtree() {
+        let mut tree = example();
+        #[allow(clippy::unwrap_used)]
+        tree.replace_subtree(4, &Tree::from_prefix([One]).unwrap())
+            .unwrap();
+        assert_eq!(tree.to_string(), "(if (< x 1) 1 x)");
+    }
+
+    #[test]
+    fn replace_subtree_checks_types() {
+        let mut tree = example();
+        #[allow(clippy::unwrap_used)]
+        let result = tree.replace_subtree(4, &Tree::from_prefix([True]).unwrap());
+        assert!(result.is_err());
+        assert_eq!(tree, example());
+    }
+
+    #[test]
+    fn subtree_of_every_node_is_valid() {
+        let tree = example();
+        for start in 0..tree.size() {
+            let subtree = tree.subtree(start);
+            assert!(Tree::from_prefix(subtree.nodes().to_vec()).is_ok());
+        }
+    }
+}
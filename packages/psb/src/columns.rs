@@ -0,0 +1,204 @@
+//! Converting the columns of rows in the PSB datasets to and from Rust
+//! values.
+//!
+//! Each row of a dataset is a JSON object with `input1`, `input2`, ...
+//! and `output1`, `output2`, ... fields. A problem's inputs (and outputs)
+//! are a single [`Column`] when there's one of them, and a tuple of
+//! [`Column`]s when there are several.
+
+use anyhow::{bail, ensure, Context, Result};
+use ordered_float::OrderedFloat;
+use serde_json::Value;
+
+/// A value that's stored in a single column of a dataset.
+pub trait Column: Sized {
+    /// # Errors
+    ///
+    /// This fails if `value` isn't a JSON representation of this type.
+    fn from_json(value: &Value) -> Result<Self>;
+
+    fn to_json(&self) -> Value;
+}
+
+impl Column for i64 {
+    fn from_json(value: &Value) -> Result<Self> {
+        value
+            .as_i64()
+            .with_context(|| format!("Expected an integer but got {value}"))
+    }
+
+    fn to_json(&self) -> Value {
+        Value::from(*self)
+    }
+}
+
+impl Column for OrderedFloat<f64> {
+    fn from_json(value: &Value) -> Result<Self> {
+        value
+            .as_f64()
+            .map(OrderedFloat)
+            .with_context(|| format!("Expected a float but got {value}"))
+    }
+
+    fn to_json(&self) -> Value {
+        Value::from(self.0)
+    }
+}
+
+impl Column for bool {
+    fn from_json(value: &Value) -> Result<Self> {
+        value
+            .as_bool()
+            .with_context(|| format!("Expected a boolean but got {value}"))
+    }
+
+    fn to_json(&self) -> Value {
+        Value::from(*self)
+    }
+}
+
+impl Column for String {
+    fn from_json(value: &Value) -> Result<Self> {
+        value
+            .as_str()
+            .map(ToString::to_string)
+            .with_context(|| format!("Expected a string but got {value}"))
+    }
+
+    fn to_json(&self) -> Value {
+        Value::from(self.as_str())
+    }
+}
+
+/// Characters are stored as strings of length one.
+impl Column for char {
+    fn from_json(value: &Value) -> Result<Self> {
+        let string = String::from_json(value)?;
+        let mut chars = string.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Ok(c),
+            _ => bail!("Expected a single character but got {value}"),
+        }
+    }
+
+    fn to_json(&self) -> Value {
+        Value::from(self.to_string())
+    }
+}
+
+impl<T: Column> Column for Vec<T> {
+    fn from_json(value: &Value) -> Result<Self> {
+        value
+            .as_array()
+            .with_context(|| format!("Expected an array but got {value}"))?
+            .iter()
+            .map(T::from_json)
+            .collect()
+    }
+
+    fn to_json(&self) -> Value {
+        Value::Array(self.iter().map(Column::to_json).collect())
+    }
+}
+
+/// All the inputs, or all the outputs, of a case.
+pub trait Columns: Sized {
+    /// The number of columns.
+    const WIDTH: usize;
+
+    /// # Errors
+    ///
+    /// This fails if there aren't exactly [`WIDTH`](Self::WIDTH) values or
+    /// any of them has the wrong type.
+    fn from_columns(values: &[Value]) -> Result<Self>;
+
+    fn to_columns(&self) -> Vec<Value>;
+}
+
+macro_rules! single_column {
+    ($($t:ty),*) => {
+        $(
+            impl Columns for $t {
+                const WIDTH: usize = 1;
+
+                fn from_columns(values: &[Value]) -> Result<Self> {
+                    let [value] = values else {
+                        bail!("Expected one column but got {}", values.len());
+                    };
+                    Column::from_json(value)
+                }
+
+                fn to_columns(&self) -> Vec<Value> {
+                    vec![Column::to_json(self)]
+                }
+            }
+        )*
+    };
+}
+
+single_column!(i64, OrderedFloat<f64>, bool, String, char, Vec<i64>);
+
+macro_rules! tuple_columns {
+    ($width:literal; $($t:ident $index:tt),*) => {
+        impl<$($t: Column),*> Columns for ($($t,)*) {
+            const WIDTH: usize = $width;
+
+            fn from_columns(values: &[Value]) -> Result<Self> {
+                ensure!(
+                    values.len() == $width,
+                    "Expected {} columns but got {}",
+                    $width,
+                    values.len()
+                );
+                Ok(($(
+                    $t::from_json(&values[$index])
+                        .with_context(|| format!("In column {}", $index + 1))?,
+                )*))
+            }
+
+            fn to_columns(&self) -> Vec<Value> {
+                vec![$(self.$index.to_json()),*]
+            }
+        }
+    };
+}
+
+tuple_columns!(2; A 0, B 1);
+tuple_columns!(3; A 0, B 1, C 2);
+tuple_columns!(4; A 0, B 1, C 2, D 3);
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn round_trip_single_columns() {
+        assert_eq!(i64::from_columns(&[json!(5)]).unwrap(), 5);
+        assert_eq!(
+            Vec::<i64>::from_columns(&[json!([1, 2, 3])]).unwrap(),
+            [1, 2, 3]
+        );
+        assert_eq!(char::from_columns(&[json!("x")]).unwrap(), 'x');
+        assert_eq!(String::from("hi").to_columns(), [json!("hi")]);
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn round_trip_tuples() {
+        let values = [json!(3), json!(2.5), json!("three")];
+        let columns = <(i64, OrderedFloat<f64>, String)>::from_columns(&values).unwrap();
+        assert_eq!(columns, (3, OrderedFloat(2.5), String::from("three")));
+        assert_eq!(columns.to_columns(), values);
+    }
+
+    #[test]
+    fn errors() {
+        assert!(i64::from_columns(&[json!("5")]).is_err());
+        assert!(char::from_columns(&[json!("xy")]).is_err());
+        assert!(i64::from_columns(&[json!(1), json!(2)]).is_err());
+        assert!(<(i64, bool)>::from_columns(&[json!(1), json!(2)]).is_err());
+    }
+}
@@ -0,0 +1,109 @@
+//! Default instruction sets for the benchmark problems.
+//!
+//! Following the PSB papers, every problem gets the integer and boolean
+//! instructions, along with the instructions for the stacks that its inputs
+//! and outputs use. Stacks that our interpreter doesn't have yet (see
+//! [`StackKind::is_supported`]) are left out, as are the exec instructions,
+//! which the interpreter can't perform yet.
+
+use push::instruction::{
+    variable_name::VariableName, BoolInstruction, FloatInstruction, IntInstruction, PushInstruction,
+};
+use strum::IntoEnumIterator;
+
+use crate::stack_kind::StackKind;
+
+/// The name of the `index`th (counting from 0) input variable, which
+/// matches the column name in the datasets, so the first input is `input1`.
+#[must_use]
+pub fn input_name(index: usize) -> VariableName {
+    VariableName::from(format!("input{}", index + 1).as_str())
+}
+
+/// All the instructions for the stack of kind `kind`, except the ones that
+/// push constants.
+#[must_use]
+pub fn stack_instructions(kind: StackKind) -> Vec<PushInstruction> {
+    match kind {
+        StackKind::Integer => IntInstruction::iter()
+            .filter(|instruction| !matches!(instruction, IntInstruction::Push(_)))
+            .map(Into::into)
+            .collect(),
+        StackKind::Float => FloatInstruction::iter()
+            .filter(|instruction| !matches!(instruction, FloatInstruction::Push(_)))
+            .map(Into::into)
+            .collect(),
+        StackKind::Boolean => BoolInstruction::iter()
+            .filter(|instruction| !matches!(instruction, BoolInstruction::Push(_)))
+            .map(Into::into)
+            .collect(),
+        StackKind::Char | StackKind::String | StackKind::VectorInteger => Vec::new(),
+    }
+}
+
+/// The default instruction set for a problem whose inputs and outputs are
+/// on the given stacks, followed by an instruction for each (supported)
+/// input, followed by the problem's `constants`.
+#[must_use]
+pub fn default_instruction_set(
+    inputs: &[StackKind],
+    outputs: &[StackKind],
+    constants: impl IntoIterator<Item = PushInstruction>,
+) -> Vec<PushInstruction> {
+    let mut kinds = vec![StackKind::Integer, StackKind::Boolean];
+    for &kind in inputs.iter().chain(outputs) {
+        if !kinds.contains(&kind) {
+            kinds.push(kind);
+        }
+    }
+    kinds
+        .into_iter()
+        .flat_map(stack_instructions)
+        .chain(
+            inputs
+                .iter()
+                .enumerate()
+                .filter(|(_, kind)| kind.is_supported())
+                .map(|(index, _)| input_name(index).into()),
+        )
+        .chain(constants)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn includes_the_io_stacks_and_inputs() {
+        let instructions = default_instruction_set(
+            &[StackKind::Integer, StackKind::Float],
+            &[StackKind::Float],
+            [PushInstruction::push_int(7)],
+        );
+        assert!(instructions.contains(&IntInstruction::Add.into()));
+        assert!(instructions.contains(&BoolInstruction::And.into()));
+        assert!(instructions.contains(&FloatInstruction::Multiply.into()));
+        assert!(instructions.contains(&input_name(0).into()));
+        assert!(instructions.contains(&input_name(1).into()));
+        assert_eq!(instructions.last(), Some(&PushInstruction::push_int(7)));
+        // The constants are only there once, at the end.
+        assert_eq!(
+            instructions
+                .iter()
+                .filter(|instruction| matches!(
+                    instruction,
+                    PushInstruction::IntInstruction(IntInstruction::Push(_))
+                ))
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn leaves_out_unsupported_stacks() {
+        let instructions = default_instruction_set(&[StackKind::String], &[StackKind::Boolean], []);
+        assert!(!instructions.contains(&input_name(0).into()));
+        assert!(!instructions.contains(&FloatInstruction::Add.into()));
+    }
+}
@@ -0,0 +1,17 @@
+//! The general program synthesis benchmark suites,
+//! [PSB1](https://doi.org/10.1145/2739480.2754769) and
+//! [PSB2](https://doi.org/10.1145/3449639.3459285), for Push.
+//!
+//! Each problem implements [`Problem`](problem::Problem), which knows how to
+//! generate the problem's edge cases and random cases, its training and
+//! testing sizes, the stack types of its inputs and outputs, and a default
+//! instruction set. Problems can also be loaded from local copies of the
+//! official datasets (in their JSON Lines format) so that runs use exactly
+//! the same cases as other systems.
+
+pub mod columns;
+pub mod instructions;
+pub mod problem;
+pub mod psb1;
+pub mod psb2;
+pub mod stack_kind;
@@ -0,0 +1,197 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{ensure, Context, Result};
+use ec_core::evaluation::cases::{split::Split, Case};
+use push::instruction::PushInstruction;
+use rand::{seq::SliceRandom, Rng};
+use serde_json::Value;
+
+use crate::{columns::Columns, instructions::default_instruction_set, stack_kind::StackKind};
+
+/// Which benchmark suite a problem comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Suite {
+    Psb1,
+    Psb2,
+}
+
+/// A program synthesis benchmark problem.
+pub trait Problem {
+    type Input: Columns;
+    type Output: Columns;
+
+    /// The problem's name in the datasets, e.g., `"number-io"`.
+    const NAME: &'static str;
+    const SUITE: Suite;
+    /// The stacks that each of the inputs goes on, in order.
+    const INPUTS: &'static [StackKind];
+    /// The stacks that each of the outputs should be read from, in order.
+    const OUTPUTS: &'static [StackKind];
+    /// The number of training cases, including all the edge cases.
+    const NUM_TRAINING: usize;
+    const NUM_TESTING: usize;
+
+    /// The hand-chosen inputs that are always in the training cases.
+    fn edge_inputs() -> Vec<Self::Input>;
+
+    /// A random input, following the distribution in the problem's
+    /// specification.
+    fn random_input(rng: &mut impl Rng) -> Self::Input;
+
+    /// The correct output for `input`.
+    fn target(input: &Self::Input) -> Self::Output;
+
+    /// The constants in the problem's default instruction set.
+    #[must_use]
+    fn constants() -> Vec<PushInstruction> {
+        Vec::new()
+    }
+
+    /// The default instruction set for this problem; see
+    /// [`default_instruction_set`].
+    #[must_use]
+    fn instruction_set() -> Vec<PushInstruction> {
+        default_instruction_set(Self::INPUTS, Self::OUTPUTS, Self::constants())
+    }
+
+    #[must_use]
+    fn case(input: Self::Input) -> Case<Self::Input, Self::Output> {
+        let output = Self::target(&input);
+        Case::new(input, output)
+    }
+
+    /// Generate the training and testing cases, where the training cases are
+    /// the edge cases followed by enough random cases to make
+    /// [`NUM_TRAINING`](Self::NUM_TRAINING) in total.
+    #[must_use]
+    fn generate(rng: &mut impl Rng) -> Split<Self::Input, Self::Output> {
+        let edge_inputs = Self::edge_inputs();
+        let num_random = Self::NUM_TRAINING.saturating_sub(edge_inputs.len());
+        let training = edge_inputs
+            .into_iter()
+            .chain((0..num_random).map(|_| Self::random_input(rng)))
+            .map(Self::case)
+            .collect();
+        let testing = (0..Self::NUM_TESTING)
+            .map(|_| Self::case(Self::random_input(rng)))
+            .collect();
+        Split { training, testing }
+    }
+
+    /// Load the training and testing cases from a local copy of the official
+    /// datasets, where `datasets` is the directory with a subdirectory for
+    /// each problem, so that the cases are read from
+    /// `datasets/<NAME>/<NAME>-edge.json` and
+    /// `datasets/<NAME>/<NAME>-random.json`.
+    ///
+    /// The training cases are all the edge cases, followed by random cases
+    /// chosen from the random file to make
+    /// [`NUM_TRAINING`](Self::NUM_TRAINING) in total. The testing cases are
+    /// chosen from the remaining random cases.
+    ///
+    /// # Errors
+    ///
+    /// This fails if either file can't be read or parsed, or if there
+    /// aren't enough random cases.
+    fn load(
+        datasets: impl AsRef<Path>,
+        rng: &mut impl Rng,
+    ) -> Result<Split<Self::Input, Self::Output>> {
+        let directory = datasets.as_ref().join(Self::NAME);
+        let edge =
+            read_cases::<Self::Input, Self::Output>(&dataset_file(&directory, Self::NAME, "edge"))?;
+        let mut random = read_cases(&dataset_file(&directory, Self::NAME, "random"))?;
+        let num_random = Self::NUM_TRAINING.saturating_sub(edge.len());
+        ensure!(
+            random.len() >= num_random + Self::NUM_TESTING,
+            "{} needs {} random cases, but there are only {}",
+            Self::NAME,
+            num_random + Self::NUM_TESTING,
+            random.len()
+        );
+        random.shuffle(rng);
+        let mut random = random.into_iter();
+        let training = edge
+            .into_iter()
+            .chain(random.by_ref().take(num_random))
+            .collect();
+        let testing = random.take(Self::NUM_TESTING).collect();
+        Ok(Split { training, testing })
+    }
+}
+
+fn dataset_file(directory: &Path, name: &str, kind: &str) -> PathBuf {
+    directory.join(format!("{name}-{kind}.json"))
+}
+
+/// Read all the cases in a dataset file, which has one JSON object per
+/// line.
+///
+/// # Errors
+///
+/// This fails if the file can't be read, or if any line isn't a JSON object
+/// with the right `input<n>` and `output<n>` fields.
+pub fn read_cases<Input, Output>(path: &Path) -> Result<Vec<Case<Input, Output>>>
+where
+    Input: Columns,
+    Output: Columns,
+{
+    let contents = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read the dataset file {}", path.display()))?;
+    contents
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| {
+            parse_case(line).with_context(|| format!("{}, line {}", path.display(), index + 1))
+        })
+        .collect()
+}
+
+/// Parse a single line of a dataset file.
+///
+/// # Errors
+///
+/// This fails if `line` isn't a JSON object with the right `input<n>` and
+/// `output<n>` fields.
+pub fn parse_case<Input, Output>(line: &str) -> Result<Case<Input, Output>>
+where
+    Input: Columns,
+    Output: Columns,
+{
+    let row: Value = serde_json::from_str(line)?;
+    let row = row.as_object().context("Expected a JSON object")?;
+    let columns = |prefix: &str, width: usize| {
+        (1..=width)
+            .map(|index| {
+                let name = format!("{prefix}{index}");
+                row.get(&name)
+                    .cloned()
+                    .with_context(|| format!("There's no {name} field"))
+            })
+            .collect::<Result<Vec<_>>>()
+    };
+    let input = Input::from_columns(&columns("input", Input::WIDTH)?).context("Invalid inputs")?;
+    let output =
+        Output::from_columns(&columns("output", Output::WIDTH)?).context("Invalid outputs")?;
+    Ok(Case::new(input, output))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn parse_cases() {
+        let case: Case<(i64, String), bool> =
+            parse_case(r#"{"input1": 3, "input2": "abc", "output1": true}"#).unwrap();
+        assert_eq!(case, Case::new((3, String::from("abc")), true));
+        assert!(parse_case::<i64, bool>(r#"{"input1": 3}"#).is_err());
+        assert!(parse_case::<i64, bool>(r#"{"input1": 3, "output1": 4}"#).is_err());
+        assert!(parse_case::<i64, bool>("[3, true]").is_err());
+    }
+}
@@ -0,0 +1,344 @@
+//! Problems from the first program synthesis benchmark suite, described in
+//! Helmuth and Spector, "General Program Synthesis Benchmark Suite" (GECCO
+//! 2015).
+
+use num_traits::ToPrimitive;
+use ordered_float::OrderedFloat;
+use rand::Rng;
+
+use crate::{
+    problem::{Problem, Suite},
+    stack_kind::StackKind,
+};
+
+/// Given an integer and a float, return their sum as a float.
+pub struct NumberIo;
+
+impl Problem for NumberIo {
+    type Input = (i64, OrderedFloat<f64>);
+    type Output = OrderedFloat<f64>;
+
+    const NAME: &'static str = "number-io";
+    const SUITE: Suite = Suite::Psb1;
+    const INPUTS: &'static [StackKind] = &[StackKind::Integer, StackKind::Float];
+    const OUTPUTS: &'static [StackKind] = &[StackKind::Float];
+    const NUM_TRAINING: usize = 25;
+    const NUM_TESTING: usize = 1_000;
+
+    fn edge_inputs() -> Vec<Self::Input> {
+        Vec::new()
+    }
+
+    fn random_input(rng: &mut impl Rng) -> Self::Input {
+        (
+            rng.gen_range(-100..=100),
+            OrderedFloat(rng.gen_range(-100.0..=100.0)),
+        )
+    }
+
+    fn target(&(integer, float): &Self::Input) -> Self::Output {
+        // Converting an `i64` to an `f64` can't fail, although it may lose
+        // precision for integers much larger than the ones in this problem.
+        float + integer.to_f64().unwrap_or(f64::NAN)
+    }
+}
+
+/// Given an integer `n`, return `"small"` if `n < 1000`, `"large"` if
+/// `n >= 2000`, and the empty string otherwise.
+pub struct SmallOrLarge;
+
+impl Problem for SmallOrLarge {
+    type Input = i64;
+    type Output = String;
+
+    const NAME: &'static str = "small-or-large";
+    const SUITE: Suite = Suite::Psb1;
+    const INPUTS: &'static [StackKind] = &[StackKind::Integer];
+    const OUTPUTS: &'static [StackKind] = &[StackKind::String];
+    const NUM_TRAINING: usize = 100;
+    const NUM_TESTING: usize = 1_000;
+
+    /// All the integers near the two boundaries.
+    fn edge_inputs() -> Vec<Self::Input> {
+        (980..=1_020).chain(1_980..=2_020).collect()
+    }
+
+    fn random_input(rng: &mut impl Rng) -> Self::Input {
+        rng.gen_range(-10_000..=10_000)
+    }
+
+    fn target(&n: &Self::Input) -> Self::Output {
+        if n < 1_000 {
+            "small"
+        } else if n >= 2_000 {
+            "large"
+        } else {
+            ""
+        }
+        .to_string()
+    }
+}
+
+/// Given three strings, return whether their lengths are strictly
+/// increasing.
+pub struct CompareStringLengths;
+
+impl CompareStringLengths {
+    fn random_string(rng: &mut impl Rng) -> String {
+        let len = rng.gen_range(0..50);
+        (0..len).map(|_| rng.gen_range(' '..='~')).collect()
+    }
+}
+
+impl Problem for CompareStringLengths {
+    type Input = (String, String, String);
+    type Output = bool;
+
+    const NAME: &'static str = "compare-string-lengths";
+    const SUITE: Suite = Suite::Psb1;
+    const INPUTS: &'static [StackKind] = &[StackKind::String, StackKind::String, StackKind::String];
+    const OUTPUTS: &'static [StackKind] = &[StackKind::Boolean];
+    const NUM_TRAINING: usize = 100;
+    const NUM_TESTING: usize = 1_000;
+
+    /// Empty strings, equal lengths, and every ordering of three different
+    /// lengths.
+    fn edge_inputs() -> Vec<Self::Input> {
+        [
+            ("", "", ""),
+            ("a", "b", "c"),
+            ("", "", "a"),
+            ("a", "bc", "bc"),
+            ("", "a", "bc"),
+            ("", "bc", "a"),
+            ("a", "", "bc"),
+            ("a", "bc", ""),
+            ("bc", "", "a"),
+            ("bc", "a", ""),
+        ]
+        .into_iter()
+        .map(|(first, second, third)| (first.to_string(), second.to_string(), third.to_string()))
+        .collect()
+    }
+
+    fn random_input(rng: &mut impl Rng) -> Self::Input {
+        (
+            Self::random_string(rng),
+            Self::random_string(rng),
+            Self::random_string(rng),
+        )
+    }
+
+    fn target((first, second, third): &Self::Input) -> Self::Output {
+        first.chars().count() < second.chars().count()
+            && second.chars().count() < third.chars().count()
+    }
+}
+
+/// Given four integers, return the smallest.
+pub struct Smallest;
+
+impl Problem for Smallest {
+    type Input = (i64, i64, i64, i64);
+    type Output = i64;
+
+    const NAME: &'static str = "smallest";
+    const SUITE: Suite = Suite::Psb1;
+    const INPUTS: &'static [StackKind] = &[
+        StackKind::Integer,
+        StackKind::Integer,
+        StackKind::Integer,
+        StackKind::Integer,
+    ];
+    const OUTPUTS: &'static [StackKind] = &[StackKind::Integer];
+    const NUM_TRAINING: usize = 100;
+    const NUM_TESTING: usize = 1_000;
+
+    /// All the same value, and the smallest value in each position.
+    fn edge_inputs() -> Vec<Self::Input> {
+        vec![
+            (0, 0, 0, 0),
+            (-100, -100, -100, -100),
+            (100, 100, 100, 100),
+            (-100, 100, 100, 100),
+            (100, -100, 100, 100),
+            (100, 100, -100, 100),
+            (100, 100, 100, -100),
+        ]
+    }
+
+    fn random_input(rng: &mut impl Rng) -> Self::Input {
+        (
+            rng.gen_range(-100..=100),
+            rng.gen_range(-100..=100),
+            rng.gen_range(-100..=100),
+            rng.gen_range(-100..=100),
+        )
+    }
+
+    fn target(&(a, b, c, d): &Self::Input) -> Self::Output {
+        a.min(b).min(c).min(d)
+    }
+}
+
+/// Given three integers, return the median.
+pub struct Median;
+
+impl Problem for Median {
+    type Input = (i64, i64, i64);
+    type Output = i64;
+
+    const NAME: &'static str = "median";
+    const SUITE: Suite = Suite::Psb1;
+    const INPUTS: &'static [StackKind] =
+        &[StackKind::Integer, StackKind::Integer, StackKind::Integer];
+    const OUTPUTS: &'static [StackKind] = &[StackKind::Integer];
+    const NUM_TRAINING: usize = 100;
+    const NUM_TESTING: usize = 1_000;
+
+    /// All the same value, two the same, and every ordering of three
+    /// different values.
+    fn edge_inputs() -> Vec<Self::Input> {
+        vec![
+            (0, 0, 0),
+            (-100, -100, -100),
+            (100, 100, 100),
+            (1, 1, 2),
+            (1, 2, 1),
+            (2, 1, 1),
+            (1, 2, 3),
+            (1, 3, 2),
+            (2, 1, 3),
+            (2, 3, 1),
+            (3, 1, 2),
+            (3, 2, 1),
+        ]
+    }
+
+    fn random_input(rng: &mut impl Rng) -> Self::Input {
+        (
+            rng.gen_range(-100..=100),
+            rng.gen_range(-100..=100),
+            rng.gen_range(-100..=100),
+        )
+    }
+
+    fn target(&(a, b, c): &Self::Input) -> Self::Output {
+        a.max(b).min(a.min(b).max(c))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, process};
+
+    use ec_core::evaluation::cases::Case;
+    use push::instruction::{variable_name::VariableName, FloatInstruction, PushInstruction};
+
+    use super::*;
+
+    #[test]
+    fn targets() {
+        assert_eq!(NumberIo::target(&(3, OrderedFloat(0.5))), OrderedFloat(3.5));
+        assert_eq!(SmallOrLarge::target(&999), "small");
+        assert_eq!(SmallOrLarge::target(&1_000), "");
+        assert_eq!(SmallOrLarge::target(&2_000), "large");
+        assert!(CompareStringLengths::target(&(
+            String::new(),
+            String::from("a"),
+            String::from("bc")
+        )));
+        assert!(!CompareStringLengths::target(&(
+            String::from("a"),
+            String::from("b"),
+            String::from("cd")
+        )));
+        assert_eq!(Smallest::target(&(5, -3, 7, -2)), -3);
+        for (a, b, c) in Median::edge_inputs() {
+            let mut sorted = [a, b, c];
+            sorted.sort_unstable();
+            assert_eq!(Median::target(&(a, b, c)), sorted[1]);
+        }
+    }
+
+    #[test]
+    fn generated_splits_include_the_edge_cases() {
+        let mut rng = rand::thread_rng();
+        let split = SmallOrLarge::generate(&mut rng);
+        assert_eq!(split.training.len(), SmallOrLarge::NUM_TRAINING);
+        assert_eq!(split.testing.len(), SmallOrLarge::NUM_TESTING);
+        let first = split.training.iter().next();
+        assert_eq!(first, Some(&Case::new(980, String::from("small"))));
+        assert!(
+            split
+                .testing
+                .iter()
+                .all(|case| (-10_000..=10_000).contains(&case.input))
+        );
+
+        let split = CompareStringLengths::generate(&mut rng);
+        assert!(
+            split
+                .training
+                .iter()
+                .all(|case| case.input.0.len() < 50 && case.input.2.len() < 50)
+        );
+    }
+
+    #[test]
+    fn instruction_sets() {
+        let instructions = NumberIo::instruction_set();
+        assert!(instructions.contains(&FloatInstruction::Add.into()));
+        assert!(instructions.contains(&VariableName::from("input1").into()));
+        assert!(instructions.contains(&VariableName::from("input2").into()));
+        // The string inputs aren't supported yet.
+        assert!(
+            !CompareStringLengths::instruction_set()
+                .contains(&PushInstruction::from(VariableName::from("input1")))
+        );
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn load_from_dataset_files() {
+        let datasets = std::env::temp_dir().join(format!("psb-datasets-{}", process::id()));
+        let directory = datasets.join(Median::NAME);
+        fs::create_dir_all(&directory).unwrap();
+        let line = |(a, b, c): (i64, i64, i64)| {
+            format!(
+                r#"{{"input1": {a}, "input2": {b}, "input3": {c}, "output1": {}}}"#,
+                Median::target(&(a, b, c))
+            )
+        };
+        let edge = [(1, 2, 3), (3, 2, 1)].map(line).join("\n");
+        fs::write(directory.join("median-edge.json"), edge).unwrap();
+        let random = (0..1_200).map(|i| line((i, -i, 2 * i))).collect::<Vec<_>>();
+        fs::write(directory.join("median-random.json"), random.join("\n")).unwrap();
+
+        let mut rng = rand::thread_rng();
+        let split = Median::load(&datasets, &mut rng).unwrap();
+        assert_eq!(split.training.len(), 100);
+        assert_eq!(split.testing.len(), 1_000);
+        assert_eq!(
+            split.training.iter().take(2).copied().collect::<Vec<_>>(),
+            [Case::new((1, 2, 3), 2), Case::new((3, 2, 1), 2)]
+        );
+        assert!(
+            split
+                .training
+                .iter()
+                .chain(&split.testing)
+                .all(|case| Median::target(&case.input) == case.output)
+        );
+
+        // There aren't enough random cases for the PSB1 sizes if we
+        // drop most of them.
+        fs::write(
+            directory.join("median-random.json"),
+            random[..500].join("\n"),
+        )
+        .unwrap();
+        assert!(Median::load(&datasets, &mut rng).is_err());
+        fs::remove_dir_all(&datasets).unwrap();
+    }
+}
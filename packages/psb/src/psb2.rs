@@ -0,0 +1,196 @@
+//! Problems from the second program synthesis benchmark suite, described in
+//! Helmuth and Kelly, "PSB2: The Second Program Synthesis Benchmark Suite"
+//! (GECCO 2021).
+
+use push::instruction::PushInstruction;
+use rand::Rng;
+
+use crate::{
+    problem::{Problem, Suite},
+    stack_kind::StackKind,
+};
+
+/// Given an integer `n`, return `"FizzBuzz"` if it's divisible by 15,
+/// `"Fizz"` if it's divisible by 3, `"Buzz"` if it's divisible by 5, and
+/// `n` as a string otherwise.
+pub struct FizzBuzz;
+
+impl Problem for FizzBuzz {
+    type Input = i64;
+    type Output = String;
+
+    const NAME: &'static str = "fizz-buzz";
+    const SUITE: Suite = Suite::Psb2;
+    const INPUTS: &'static [StackKind] = &[StackKind::Integer];
+    const OUTPUTS: &'static [StackKind] = &[StackKind::String];
+    const NUM_TRAINING: usize = 200;
+    const NUM_TESTING: usize = 2_000;
+
+    /// The first few integers, which cover all the outputs, and the largest
+    /// integers in range with each output.
+    fn edge_inputs() -> Vec<Self::Input> {
+        (1..=15)
+            .chain([999_990, 999_998, 999_999, 1_000_000])
+            .collect()
+    }
+
+    fn random_input(rng: &mut impl Rng) -> Self::Input {
+        rng.gen_range(1..=1_000_000)
+    }
+
+    fn target(&n: &Self::Input) -> Self::Output {
+        match (n % 3, n % 5) {
+            (0, 0) => "FizzBuzz".to_string(),
+            (0, _) => "Fizz".to_string(),
+            (_, 0) => "Buzz".to_string(),
+            _ => n.to_string(),
+        }
+    }
+
+    fn constants() -> Vec<PushInstruction> {
+        [0, 3, 5].map(PushInstruction::push_int).to_vec()
+    }
+}
+
+/// Given a vector of masses, return the total fuel needed for them, where
+/// the fuel for a mass is the mass divided by three (rounding down), minus
+/// two.
+pub struct FuelCost;
+
+impl Problem for FuelCost {
+    type Input = Vec<i64>;
+    type Output = i64;
+
+    const NAME: &'static str = "fuel-cost";
+    const SUITE: Suite = Suite::Psb2;
+    const INPUTS: &'static [StackKind] = &[StackKind::VectorInteger];
+    const OUTPUTS: &'static [StackKind] = &[StackKind::Integer];
+    const NUM_TRAINING: usize = 200;
+    const NUM_TESTING: usize = 2_000;
+
+    /// Single masses around the smallest ones, the largest mass, and the
+    /// longest vectors of the smallest and largest masses.
+    fn edge_inputs() -> Vec<Self::Input> {
+        (6..=11)
+            .map(|mass| vec![mass])
+            .chain([vec![100_000], vec![6; 20], vec![100_000; 20]])
+            .collect()
+    }
+
+    fn random_input(rng: &mut impl Rng) -> Self::Input {
+        let len = rng.gen_range(1..=20);
+        (0..len).map(|_| rng.gen_range(6..=100_000)).collect()
+    }
+
+    fn target(masses: &Self::Input) -> Self::Output {
+        masses.iter().map(|mass| mass / 3 - 2).sum()
+    }
+
+    fn constants() -> Vec<PushInstruction> {
+        [0, 1, 2, 3].map(PushInstruction::push_int).to_vec()
+    }
+}
+
+/// Given two positive integers, return their greatest common divisor.
+pub struct Gcd;
+
+impl Gcd {
+    const MAX: i64 = 1_000_000;
+}
+
+impl Problem for Gcd {
+    type Input = (i64, i64);
+    type Output = i64;
+
+    const NAME: &'static str = "gcd";
+    const SUITE: Suite = Suite::Psb2;
+    const INPUTS: &'static [StackKind] = &[StackKind::Integer, StackKind::Integer];
+    const OUTPUTS: &'static [StackKind] = &[StackKind::Integer];
+    const NUM_TRAINING: usize = 200;
+    const NUM_TESTING: usize = 2_000;
+
+    /// Small values, a shared factor in both orders, and the largest values.
+    fn edge_inputs() -> Vec<Self::Input> {
+        vec![
+            (1, 1),
+            (1, 2),
+            (2, 1),
+            (2, 2),
+            (4, 6),
+            (6, 4),
+            (1, Self::MAX),
+            (Self::MAX, 1),
+            (Self::MAX, Self::MAX),
+        ]
+    }
+
+    /// Most pairs of random integers have a GCD of 1, so half the time we
+    /// multiply both integers by a random common factor.
+    fn random_input(rng: &mut impl Rng) -> Self::Input {
+        let factor = if rng.gen_bool(0.5) {
+            rng.gen_range(2..=1_000)
+        } else {
+            1
+        };
+        let limit = Self::MAX / factor;
+        (
+            rng.gen_range(1..=limit) * factor,
+            rng.gen_range(1..=limit) * factor,
+        )
+    }
+
+    fn target(&(mut a, mut b): &Self::Input) -> Self::Output {
+        while b != 0 {
+            (a, b) = (b, a % b);
+        }
+        a
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn targets() {
+        let fizz_buzz = (1..=15).map(|n| FizzBuzz::target(&n)).collect::<Vec<_>>();
+        assert_eq!(
+            fizz_buzz,
+            [
+                "1", "2", "Fizz", "4", "Buzz", "Fizz", "7", "8", "Fizz", "Buzz", "11", "Fizz",
+                "13", "14", "FizzBuzz"
+            ]
+        );
+        assert_eq!(FuelCost::target(&vec![6]), 0);
+        assert_eq!(FuelCost::target(&vec![9, 14, 100_000]), 1 + 2 + 33_331);
+        assert_eq!(Gcd::target(&(4, 6)), 2);
+        assert_eq!(Gcd::target(&(17, 5)), 1);
+        assert_eq!(Gcd::target(&(Gcd::MAX, Gcd::MAX)), Gcd::MAX);
+    }
+
+    #[test]
+    fn random_inputs_are_in_range() {
+        let mut rng = rand::thread_rng();
+        let split = Gcd::generate(&mut rng);
+        assert_eq!(split.training.len(), 200);
+        assert!(
+            split
+                .testing
+                .iter()
+                .all(|case| (1..=Gcd::MAX).contains(&case.input.0)
+                    && (1..=Gcd::MAX).contains(&case.input.1))
+        );
+        let split = FuelCost::generate(&mut rng);
+        assert!(split.testing.iter().all(|case| {
+            (1..=20).contains(&case.input.len())
+                && case.input.iter().all(|mass| (6..=100_000).contains(mass))
+        }));
+    }
+
+    #[test]
+    fn constants_are_in_the_instruction_set() {
+        let instructions = FuelCost::instruction_set();
+        assert!(instructions.contains(&PushInstruction::push_int(3)));
+        assert!(!instructions.contains(&PushInstruction::push_int(5)));
+    }
+}
@@ -0,0 +1,36 @@
+use std::fmt::Display;
+
+/// The kinds of Push stacks that benchmark inputs and outputs live on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StackKind {
+    Integer,
+    Float,
+    Boolean,
+    Char,
+    String,
+    VectorInteger,
+}
+
+impl StackKind {
+    /// Whether our Push interpreter has this stack yet. Problems whose
+    /// inputs or outputs use other stacks can still be generated and loaded,
+    /// but can't be solved until the interpreter supports those stacks.
+    #[must_use]
+    pub const fn is_supported(self) -> bool {
+        matches!(self, Self::Integer | Self::Float | Self::Boolean)
+    }
+}
+
+impl Display for StackKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Integer => "integer",
+            Self::Float => "float",
+            Self::Boolean => "boolean",
+            Self::Char => "char",
+            Self::String => "string",
+            Self::VectorInteger => "vector_integer",
+        };
+        write!(f, "{name}")
+    }
+}
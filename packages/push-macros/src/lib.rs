@@ -2,7 +2,13 @@ use push_state::printing::generate_builder::generate_builder;
 use quote::quote;
 use syn::{spanned::Spanned, DeriveInput};
 
-use crate::push_state::{parsing::parse_fields, printing::derive_has_stack::derive_has_stack};
+use crate::push_state::{
+    parsing::parse_fields,
+    printing::{
+        derive_clear_state::derive_clear_state, derive_has_stack::derive_has_stack,
+        derive_size_budget::derive_size_budget,
+    },
+};
 
 mod doctest_tokenstream;
 mod push_state;
@@ -24,6 +30,27 @@ mod push_state;
 /// You need to indicate which fields are stacks
 /// using the `#[stack]` attribute on the corresponding field.
 ///
+/// A stack marked `#[stack(code)]` holds the same type of values as the exec
+/// stack (like Push's code stack), so HasStack isn't derived for it, and it
+/// has no input builder functions. It is still cleared and gets the other
+/// builder functions.
+///
+/// ## ClearState (disabled by default)
+/// This implements the ClearState trait, which empties all the stacks
+/// (including the exec stack) and the `#[input_instructions]` field so that
+/// the state can be reused.
+///
+/// ## SizeBudget (disabled by default)
+/// This implements the SizeBudgetState trait, which shares one size budget
+/// between all the stacks (including the exec stack), so that the state can
+/// be given one with `with_size_budget`. Each stack counts its values as one
+/// item each, unless you set a function to measure them with
+/// `#[stack(size_fn = <path>)]`, e.g. `#[stack(exec, size_fn =
+/// PushProgram::size)]`.
+///
+/// This also implements Clone (so don't derive it), cloning every field and
+/// giving the clone its own budget with the same limit.
+///
 /// ## Builder (disabled by default)
 /// This creates a builder for this state.
 /// You need to indicate which fields are stacks using the `#[stack]`
@@ -41,6 +68,10 @@ mod push_state;
 /// can use the `ignore_doctests` flag to annotate every code example of the
 /// stack with the `ignore` attribute.
 ///
+/// The input instructions are stored in the field marked
+/// `#[input_instructions]`, which should be a
+/// `push::push_vm::input_slots::InputSlots` of the input instruction type.
+///
 /// # Example
 /// ```ignore
 /// #[push_state::push_state(builder)]
@@ -51,7 +82,7 @@ mod push_state;
 ///     int: Stack<MyInteger>,
 ///
 ///     #[input_instructions]
-///     input_instructions: HashMap<VariableName, MyInput>
+///     input_instructions: InputSlots<MyInput>
 /// }
 ///
 /// fn main() -> Result<(), StackError> {
@@ -96,10 +127,31 @@ pub fn push_state(
 
     let (stacks, exec_stack, input_instructions) = parse_fields(fields, macro_span, &macro_flags)?;
 
-    let has_stack_derives = macro_flags
+    let has_stack = macro_flags
         .has_stack
         .then(|| derive_has_stack(struct_ident, &stacks, &exec_stack));
 
+    let clear_state = macro_flags
+        .clear_state
+        .then(|| derive_clear_state(struct_ident, &stacks, &exec_stack, &input_instructions));
+
+    let size_budget = macro_flags
+        .size_budget
+        .then(|| {
+            let field_idents = fields
+                .iter()
+                .filter_map(|field| field.ident.clone())
+                .collect::<Vec<_>>();
+            derive_size_budget(
+                macro_span,
+                struct_ident,
+                &field_idents,
+                &stacks,
+                &exec_stack,
+            )
+        })
+        .transpose()?;
+
     let builder = macro_flags
         .builder
         .then(|| {
@@ -117,7 +169,9 @@ pub fn push_state(
 
     Ok(quote! {
         #struct_defn
-        #has_stack_derives
+        #has_stack
+        #clear_state
+        #size_budget
         #builder
     })
 }
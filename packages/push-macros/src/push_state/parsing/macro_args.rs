@@ -6,6 +6,8 @@ use super::FlagsValue;
 pub struct PushStateFlags {
     pub builder: bool,
     pub has_stack: bool,
+    pub clear_state: bool,
+    pub size_budget: bool,
 }
 
 impl Default for PushStateFlags {
@@ -13,15 +15,21 @@ impl Default for PushStateFlags {
         Self {
             builder: false,
             has_stack: true,
+            clear_state: false,
+            size_budget: false,
         }
     }
 }
 syn::custom_keyword!(builder);
 syn::custom_keyword!(has_stack);
+syn::custom_keyword!(clear_state);
+syn::custom_keyword!(size_budget);
 
 pub enum PushStateFlagsKw {
     Builder(builder),
     HasStack(has_stack),
+    ClearState(clear_state),
+    SizeBudget(size_budget),
 }
 
 impl ToTokens for PushStateFlagsKw {
@@ -29,6 +37,8 @@ impl ToTokens for PushStateFlagsKw {
         match self {
             Self::Builder(t) => t.to_tokens(tokens),
             Self::HasStack(t) => t.to_tokens(tokens),
+            Self::ClearState(t) => t.to_tokens(tokens),
+            Self::SizeBudget(t) => t.to_tokens(tokens),
         }
     }
 }
@@ -39,6 +49,10 @@ impl Parse for PushStateFlagsKw {
             PushStateFlagsKw::Builder(input.parse()?)
         } else if input.peek(has_stack) {
             PushStateFlagsKw::HasStack(input.parse()?)
+        } else if input.peek(clear_state) {
+            PushStateFlagsKw::ClearState(input.parse()?)
+        } else if input.peek(size_budget) {
+            PushStateFlagsKw::SizeBudget(input.parse()?)
         } else {
             return Err(input.error("Expected flag"));
         })
@@ -53,6 +67,8 @@ impl Parse for PushStateFlags {
 
         let mut builder_flag_set = false;
         let mut has_stack_flag_set = false;
+        let mut clear_state_flag_set = false;
+        let mut size_budget_flag_set = false;
 
         let mut current_flags = PushStateFlags::default();
         for flag in parsed_flags_list {
@@ -87,6 +103,34 @@ impl Parse for PushStateFlags {
                     has_stack_flag_set = true;
                     current_flags.has_stack = set_to;
                 }
+                PushStateFlagsKw::ClearState(_) if default_flags.clear_state == set_to => {
+                    return Err(syn::Error::new_spanned(
+                        flag,
+                        "Redundant flag, this is disabled by default. Maybe you meant to use \
+                         flag to enable it?",
+                    ));
+                }
+                PushStateFlagsKw::ClearState(_) if clear_state_flag_set => {
+                    return Err(syn::Error::new_spanned(flag, "Flag already set."));
+                }
+                PushStateFlagsKw::ClearState(_) => {
+                    clear_state_flag_set = true;
+                    current_flags.clear_state = set_to;
+                }
+                PushStateFlagsKw::SizeBudget(_) if default_flags.size_budget == set_to => {
+                    return Err(syn::Error::new_spanned(
+                        flag,
+                        "Redundant flag, this is disabled by default. Maybe you meant to use \
+                         flag to enable it?",
+                    ));
+                }
+                PushStateFlagsKw::SizeBudget(_) if size_budget_flag_set => {
+                    return Err(syn::Error::new_spanned(flag, "Flag already set."));
+                }
+                PushStateFlagsKw::SizeBudget(_) => {
+                    size_budget_flag_set = true;
+                    current_flags.size_budget = set_to;
+                }
             }
         }
 
@@ -55,6 +55,8 @@ pub fn parse_fields(
     PushStateFlags {
         builder: generate_builder,
         has_stack: derive_has_stack,
+        clear_state: derive_clear_state,
+        size_budget: derive_size_budget,
     }: &PushStateFlags,
 ) -> syn::Result<(StacksInput, ExecStackInput, InputInstructionsInput)> {
     let mut stacks: BTreeMap<Ident, (StackMarkerFlags, Type)> = BTreeMap::new();
@@ -125,11 +127,16 @@ pub fn parse_fields(
 
                     let marker_flags: StackMarkerFlags = syn::parse2(l.tokens)?;
                     if *marker_flags.is_exec {
-                        if !generate_builder && !derive_has_stack {
+                        if !generate_builder
+                            && !derive_has_stack
+                            && !derive_clear_state
+                            && !derive_size_budget
+                        {
                             return Err(syn::Error::new(
                                 marker_flags.is_exec.span,
-                                "Unknown flag exec. Maybe you meant to enable the builder or \
-                                 has_stack feature of the push_state macro?",
+                                "Unknown flag exec. Maybe you meant to enable the builder, \
+                                 has_stack, clear_state, or size_budget feature of the \
+                                 push_state macro?",
                             ));
                         }
                         if *stack_marker_flags.is_exec {
@@ -148,6 +155,21 @@ pub fn parse_fields(
                             stack_marker_flags.is_exec = marker_flags.is_exec.clone();
                         }
                     }
+                    if *marker_flags.is_code {
+                        if *stack_marker_flags.is_code {
+                            return Err(syn::Error::new(
+                                marker_flags.is_code.span,
+                                "Redundant code flag",
+                            ));
+                        } else if *stack_marker_flags.is_exec || *marker_flags.is_exec {
+                            return Err(syn::Error::new(
+                                marker_flags.is_code.span,
+                                "The exec stack cannot also be a code stack",
+                            ));
+                        } else {
+                            stack_marker_flags.is_code = marker_flags.is_code.clone();
+                        }
+                    }
                     if *marker_flags.ignore_doctests {
                         if !generate_builder {
                             return Err(syn::Error::new(
@@ -216,6 +238,25 @@ pub fn parse_fields(
                         }
                     }
 
+                    if marker_flags.size_fn.is_some() {
+                        if !derive_size_budget {
+                            return Err(syn::Error::new(
+                                marker_flags.size_fn.span,
+                                "Unknown flag size_fn. Maybe you meant to enable the size_budget \
+                                 feature of the push_state macro?",
+                            ));
+                        }
+
+                        if stack_marker_flags.size_fn.is_some() {
+                            return Err(syn::Error::new(
+                                marker_flags.size_fn.span,
+                                "Size function already set explicitly",
+                            ));
+                        } else {
+                            stack_marker_flags.size_fn = marker_flags.size_fn
+                        }
+                    }
+
                     if marker_flags.sample_values.is_some() {
                         if !generate_builder {
                             return Err(syn::Error::new(
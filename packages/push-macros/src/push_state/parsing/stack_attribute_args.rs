@@ -15,14 +15,18 @@ pub struct StackMarkerFlags {
     pub builder_name: SpannedValue<Option<Ident>>,
     pub instruction_name: SpannedValue<Option<Path>>,
     pub is_exec: SpannedValue<bool>,
+    pub is_code: SpannedValue<bool>,
     pub sample_values: SpannedValue<Option<Punctuated<Expr, Token![,]>>>,
+    pub size_fn: SpannedValue<Option<Path>>,
     pub ignore_doctests: SpannedValue<bool>,
 }
 
 syn::custom_keyword!(exec);
+syn::custom_keyword!(code);
 syn::custom_keyword!(builder_name);
 syn::custom_keyword!(instruction_name);
 syn::custom_keyword!(sample_values);
+syn::custom_keyword!(size_fn);
 syn::custom_keyword!(ignore_doctests);
 
 /// Any option passed to a field inside a struct to be used inside the macro,
@@ -30,6 +34,10 @@ syn::custom_keyword!(ignore_doctests);
 pub enum StackFieldOption {
     /// `exec` option, this determines which stack is the exec stack
     Exec(exec),
+    /// `code` option, this marks a stack that holds the same type of values
+    /// as the exec stack (like Push's code stack), so `HasStack` isn't
+    /// derived for it
+    Code(code),
     /// `ignore_doctests` option, this forces the doctests outputed for
     /// this stack to be annotated with `ignore`.
     IgnoreDoctests(ignore_doctests),
@@ -43,6 +51,10 @@ pub enum StackFieldOption {
     /// this can be used to change the instruction that is used
     /// to set input values, for example in the `with_int_input` method
     InstructionName(instruction_name, Token![=], Path),
+    /// `size_fn = some::path` option,
+    /// this sets the function that measures the values on this stack for
+    /// the state's size budget, instead of counting each value as one item
+    SizeFn(size_fn, Token![=], Path),
     /// `sample_values = comma, seperated, values` option, those are sample
     /// values used within doctests for example
     SampleValues(
@@ -57,6 +69,7 @@ impl ToTokens for StackFieldOption {
     fn to_tokens(&self, tokens: &mut TokenStream) {
         match self {
             Self::Exec(v) => v.to_tokens(tokens),
+            Self::Code(v) => v.to_tokens(tokens),
             Self::IgnoreDoctests(v) => v.to_tokens(tokens),
             Self::BuilderName(v, w, x) => {
                 v.to_tokens(tokens);
@@ -68,6 +81,11 @@ impl ToTokens for StackFieldOption {
                 w.to_tokens(tokens);
                 x.to_tokens(tokens);
             }
+            Self::SizeFn(v, w, x) => {
+                v.to_tokens(tokens);
+                w.to_tokens(tokens);
+                x.to_tokens(tokens);
+            }
             Self::SampleValues(v, w, b, y) => {
                 v.to_tokens(tokens);
                 w.to_tokens(tokens);
@@ -82,12 +100,16 @@ impl Parse for StackFieldOption {
     fn parse(input: ParseStream) -> syn::Result<Self> {
         Ok(if input.peek(exec) {
             StackFieldOption::Exec(input.parse()?)
+        } else if input.peek(code) {
+            StackFieldOption::Code(input.parse()?)
         } else if input.peek(ignore_doctests) {
             StackFieldOption::IgnoreDoctests(input.parse()?)
         } else if input.peek(builder_name) {
             StackFieldOption::BuilderName(input.parse()?, input.parse()?, input.parse()?)
         } else if input.peek(instruction_name) {
             StackFieldOption::InstructionName(input.parse()?, input.parse()?, input.parse()?)
+        } else if input.peek(size_fn) {
+            StackFieldOption::SizeFn(input.parse()?, input.parse()?, input.parse()?)
         } else if input.peek(sample_values) {
             let kw = input.parse()?;
             let eq_sign = input.parse()?;
@@ -109,9 +131,11 @@ impl Parse for StackMarkerFlags {
         let parsed_flags_list = Punctuated::<StackFieldOption, Token![,]>::parse_terminated(input)?;
 
         let mut exec_flag_set = false;
+        let mut code_flag_set = false;
         let mut ignore_doctests_flag_set = false;
         let mut builder_name_flag_set = false;
         let mut instruction_name_flag_set = false;
+        let mut size_fn_flag_set = false;
         let mut sample_values_flag_set = false;
 
         let mut current_flags = Self::default();
@@ -128,6 +152,16 @@ impl Parse for StackMarkerFlags {
                         span: flag_span,
                     };
                 }
+                StackFieldOption::Code(_) if code_flag_set => {
+                    return Err(syn::Error::new_spanned(flag, "Flag already set."));
+                }
+                StackFieldOption::Code(_) => {
+                    code_flag_set = true;
+                    current_flags.is_code = SpannedValue {
+                        value: true,
+                        span: flag_span,
+                    };
+                }
                 StackFieldOption::IgnoreDoctests(_) if ignore_doctests_flag_set => {
                     return Err(syn::Error::new_spanned(flag, "Flag already set."));
                 }
@@ -158,6 +192,16 @@ impl Parse for StackMarkerFlags {
                         span: flag_span,
                     };
                 }
+                StackFieldOption::SizeFn(_, _, _) if size_fn_flag_set => {
+                    return Err(syn::Error::new_spanned(flag, "Property already set."));
+                }
+                StackFieldOption::SizeFn(_, _, v) => {
+                    size_fn_flag_set = true;
+                    current_flags.size_fn = SpannedValue {
+                        value: Some(v),
+                        span: flag_span,
+                    };
+                }
                 StackFieldOption::SampleValues(_, _, _, _) if sample_values_flag_set => {
                     return Err(syn::Error::new_spanned(flag, "Property already set."));
                 }
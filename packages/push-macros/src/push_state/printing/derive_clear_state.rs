@@ -0,0 +1,29 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::Ident;
+
+use crate::push_state::parsing::{ExecStackInput, InputInstructionsInput, StacksInput};
+
+pub fn derive_clear_state(
+    struct_ident: &Ident,
+    stacks: &StacksInput,
+    exec_stack: &ExecStackInput,
+    input_instructions: &InputInstructionsInput,
+) -> TokenStream {
+    let stack_idents = stacks
+        .keys()
+        .chain(exec_stack.iter().map(|(ident, _, _)| ident));
+    let clear_input_instructions = input_instructions
+        .as_ref()
+        .map(|ident| quote! { self.#ident.clear(); });
+
+    quote! {
+        #[automatically_derived]
+        impl ::push::push_vm::ClearState for #struct_ident {
+            fn clear(&mut self) {
+                #(self.#stack_idents.clear();)*
+                #clear_input_instructions
+            }
+        }
+    }
+}
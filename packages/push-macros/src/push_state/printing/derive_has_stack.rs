@@ -1,6 +1,6 @@
 use proc_macro2::TokenStream;
 use quote::quote;
-use syn::Ident;
+use syn::{GenericArgument, Ident, PathArguments, Type, TypePath};
 
 use crate::push_state::parsing::{ExecStackInput, StacksInput};
 
@@ -14,8 +14,11 @@ pub fn derive_has_stack(
     // uncomment this
 
     // let mut stacks_to_derive_for = stacks
+    // Code stacks hold the same type of values as the exec stack, so they
+    // can't have their own `HasStack` implementation.
     let mut stacks_to_derive_for = stacks
         .iter()
+        .filter(|(_, (flags, _))| !*flags.is_code)
         .map(|(ident, (_, ty))| (ident, ty))
         .collect::<Vec<_>>();
     if let Some((ident, _, ty)) = &exec_stack {
@@ -25,25 +28,18 @@ pub fn derive_has_stack(
     stacks_to_derive_for
         .into_iter()
         .map(|(ident, ty)| {
+            let value_ty = stack_value_type(ty);
             quote! {
                 #[automatically_derived]
-                impl
-                    ::push::push_vm::stack::HasStack<
-                        <#ty as ::push::push_vm::stack::StackType>::Type
-                    >
-                for
-                    #struct_ident
-                {
+                impl ::push::push_vm::stack::HasStack<#value_ty> for #struct_ident {
                     fn stack<
-                        U: ::push::push_vm::stack::TypeEq<
-                            This = <#ty as ::push::push_vm::stack::StackType>::Type>
+                        U: ::push::push_vm::stack::TypeEq<This = #value_ty>
                     >(&self) -> &#ty {
                         &self.#ident
                     }
 
                     fn stack_mut<
-                        U: ::push::push_vm::stack::TypeEq<
-                            This = <#ty as ::push::push_vm::stack::StackType>::Type>
+                        U: ::push::push_vm::stack::TypeEq<This = #value_ty>
                     >(&mut self) -> &mut #ty {
                         &mut self.#ident
                     }
@@ -52,3 +48,24 @@ pub fn derive_has_stack(
         })
         .collect::<proc_macro2::TokenStream>()
 }
+
+/// The type of the values on a stack of type `ty`. When `ty` is written as
+/// `Stack<T>` this is just `T`, as the compiler can't always tell that
+/// implementations for `<Stack<T> as StackType>::Type` with different `T`s
+/// don't overlap when the state is defined outside of the `push` crate.
+fn stack_value_type(ty: &Type) -> TokenStream {
+    if let Type::Path(TypePath { qself: None, path }) = ty {
+        if let Some(segment) = path.segments.last() {
+            if segment.ident == "Stack" {
+                if let PathArguments::AngleBracketed(arguments) = &segment.arguments {
+                    if let [GenericArgument::Type(value_ty)] =
+                        arguments.args.iter().collect::<Vec<_>>()[..]
+                    {
+                        return quote! { #value_ty };
+                    }
+                }
+            }
+        }
+    }
+    quote! { <#ty as ::push::push_vm::stack::StackType>::Type }
+}
@@ -0,0 +1,80 @@
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::Ident;
+
+use crate::push_state::parsing::{stack_attribute_args::StackMarkerFlags, ExecStackInput, StacksInput};
+
+pub fn derive_size_budget(
+    macro_span: Span,
+    struct_ident: &Ident,
+    field_idents: &[Ident],
+    stacks: &StacksInput,
+    exec_stack: &ExecStackInput,
+) -> syn::Result<TokenStream> {
+    let Some((exec_stack_ident, exec_stack_flags, _)) = exec_stack else {
+        return Err(syn::Error::new(
+            macro_span,
+            "Need to declare exactly one exec stack using #[stack(exec)] to use the size budget \
+             feature",
+        ));
+    };
+
+    let set_stack_budgets = stacks
+        .iter()
+        .map(|(ident, (flags, _))| (ident, flags))
+        .chain(std::iter::once((exec_stack_ident, exec_stack_flags)))
+        .map(|(ident, StackMarkerFlags { size_fn, .. })| {
+            let set_size_fn = size_fn
+                .as_ref()
+                .map(|size_fn| quote! { self.#ident.set_size_fn(#size_fn); });
+            quote! {
+                #set_size_fn
+                self.#ident.set_size_budget(::core::clone::Clone::clone(&size_budget));
+            }
+        });
+
+    Ok(quote! {
+        #[automatically_derived]
+        impl ::push::push_vm::SizeBudgetState for #struct_ident {
+            fn size_budget(
+                &self,
+            ) -> ::core::option::Option<
+                &::std::sync::Arc<::push::push_vm::size_budget::SizeBudget>,
+            > {
+                self.#exec_stack_ident.size_budget()
+            }
+
+            fn set_size_budget(
+                &mut self,
+                size_budget: ::core::option::Option<
+                    ::std::sync::Arc<::push::push_vm::size_budget::SizeBudget>,
+                >,
+            ) {
+                #(#set_stack_budgets)*
+            }
+        }
+
+        // A clone gets its own size budget with the same limit, rather than
+        // sharing (and being charged to) the original's budget, so that
+        // running one of them can't make the other overflow.
+        #[automatically_derived]
+        impl ::core::clone::Clone for #struct_ident {
+            fn clone(&self) -> Self {
+                let mut clone = Self {
+                    #(#field_idents: ::core::clone::Clone::clone(&self.#field_idents),)*
+                };
+                if let ::core::option::Option::Some(size_budget) =
+                    ::push::push_vm::SizeBudgetState::size_budget(self)
+                {
+                    ::push::push_vm::SizeBudgetState::set_size_budget(
+                        &mut clone,
+                        ::core::option::Option::Some(::std::sync::Arc::new(
+                            ::push::push_vm::size_budget::SizeBudget::new(size_budget.limit()),
+                        )),
+                    );
+                }
+                clone
+            }
+        }
+    })
+}
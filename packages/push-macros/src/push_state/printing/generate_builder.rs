@@ -111,7 +111,9 @@ pub fn generate_builder(
     let (impl_generics, type_generics, where_clause) = struct_generics.split_for_impl();
 
     let with_inputs_impl = input_instructions.map(|input_instructions_field| {
-        let with_inputs = stacks.iter().map(
+        // There are no input instructions for code stacks, whose values are
+        // programs.
+        let with_inputs = stacks.iter().filter(|(_, (flags, _))| !*flags.is_code).map(
             |(
                 field,
                 (
@@ -138,23 +140,18 @@ pub fn generate_builder(
 
                 quote! {
                     /// Adds a input instruction to the current current state's set
-                    /// of instructions. The name for the input must have been included
-                    /// in the `Inputs` provided when the `Builder` was initially constructed.
-                    /// Here you provide the name and the boolean value for that
+                    /// of instructions. Here you provide the name and the value for that
                     /// input variable. That will create a new `PushInstruction::push_[type]()`
                     /// instruction that will push the specified value onto the stack
-                    /// when performed.
-                    ///
-                    /// # Panics
-                    /// This panics if the `input_name` provided isn't included in the set of
-                    /// names in the `Inputs` object used in the construction of the `Builder`.
+                    /// when performed. The input variable gets the next free input slot,
+                    /// unless it was already bound, in which case it keeps its slot.
                     #[must_use]
                     pub fn #fn_ident(
                             mut self,
                             input_name: &str,
                             input_value: <#ty as ::push::push_vm::stack::StackType>::Type
                     ) -> Self {
-                        self.partial_state.#input_instructions_field.insert(
+                        self.partial_state.#input_instructions_field.bind(
                             ::push::instruction::variable_name::VariableName::from(input_name),
                             #instruction_path(input_value),
                         );
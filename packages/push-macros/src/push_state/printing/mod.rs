@@ -1,2 +1,4 @@
+pub mod derive_clear_state;
 pub mod derive_has_stack;
+pub mod derive_size_budget;
 pub mod generate_builder;
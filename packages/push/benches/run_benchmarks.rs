@@ -0,0 +1,41 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use push::push_vm::{program::PushProgram, push_state::PushState, text, State};
+
+#[allow(clippy::unwrap_used)]
+fn run_push_program(program: &[PushProgram]) {
+    let state = PushState::builder()
+        .with_max_stack_size(1_000)
+        .with_program(program.iter().cloned())
+        .unwrap()
+        .with_int_input("x", 5)
+        .build();
+    black_box(state.run_to_completion().ok());
+}
+
+#[allow(clippy::unwrap_used)]
+fn benchmark_push_recoverable_errors(c: &mut Criterion) {
+    // Every one of these instructions underflows, so this is dominated by
+    // the cost of recoverable errors.
+    let program =
+        text::parse(&"Int-Add Bool-And Float-Multiply Int-Subtract Bool-Or ".repeat(200)).unwrap();
+    c.bench_function("Run a Push program of recoverable errors", move |b| {
+        b.iter(|| run_push_program(&program));
+    });
+}
+
+#[allow(clippy::unwrap_used)]
+fn benchmark_push_mixed(c: &mut Criterion) {
+    let program =
+        text::parse(&"x 3 Int-Add x Int-Multiply Int-IsEven Bool-Not Float-Add ".repeat(125))
+            .unwrap();
+    c.bench_function("Run a Push program of mixed instructions", move |b| {
+        b.iter(|| run_push_program(&program));
+    });
+}
+
+criterion_group!(
+    push_benches,
+    benchmark_push_recoverable_errors,
+    benchmark_push_mixed
+);
+criterion_main!(push_benches);
@@ -5,9 +5,10 @@ use std::ops::Not;
 use anyhow::{ensure, Result};
 use clap::Parser;
 use ec_core::{
+    evaluation::generalisation::GeneralisationReport,
     generation::Generation,
     generator::{collection::ConvertToCollectionGenerator, Generator},
-    individual::{ec::WithScorer, scorer::FnScorer},
+    individual::ec::WithScorer,
     operator::{
         genome_extractor::GenomeExtractor,
         genome_scorer::GenomeScorer,
@@ -15,15 +16,16 @@ use ec_core::{
         selector::{best::Best, lexicase::Lexicase, Select, Selector},
         Composable,
     },
-    test_results::{self, TestResults},
 };
 use ec_linear::mutator::umad::Umad;
 use ordered_float::OrderedFloat;
 use push::{
-    evaluation::cases::{Case, Cases},
-    genome::plushy::{GeneGenerator, Plushy},
+    evaluation::{
+        cases::Cases,
+        push_scorer::{top, Penalties, PushScorer},
+    },
+    genome::plushy::GeneGenerator,
     instruction::{variable_name::VariableName, FloatInstruction},
-    push_vm::{program::PushProgram, push_state::PushState, HasStack, State},
     vec_into,
 };
 use rand::thread_rng;
@@ -49,52 +51,9 @@ fn target_fn(input: Of64) -> Of64 {
     sub_expr * sub_expr * sub_expr + 1.0
 }
 
-fn build_push_state(
-    program: impl DoubleEndedIterator<Item = PushProgram> + ExactSizeIterator,
-    input: Of64,
-) -> PushState {
-    #[allow(clippy::unwrap_used)]
-    PushState::builder()
-        .with_max_stack_size(1000)
-        .with_program(program)
-        // This will return an error if the program is longer than the allowed
-        // max stack size.
-        // We arguably should check that and return an error here.
-        .unwrap()
-        .with_float_input("x", input)
-        .build()
-}
-
-fn score_program(
-    program: impl DoubleEndedIterator<Item = PushProgram> + ExactSizeIterator,
-    Case { input, output }: Case<Of64>,
-) -> Of64 {
-    let state = build_push_state(program, input);
-    #[allow(clippy::option_if_let_else)]
-    match state.run_to_completion() {
-        Ok(final_state) => OrderedFloat(
-            final_state
-                .stack::<Of64>()
-                .top()
-                .map_or(PENALTY_VALUE, |answer| (answer - output).abs()),
-        ),
-        Err(_) => {
-            // Do some logging, perhaps?
-            OrderedFloat(PENALTY_VALUE)
-        }
-    }
-}
-
-fn score_genome(
-    genome: &Plushy,
-    training_cases: &Cases<Of64>,
-) -> TestResults<test_results::Error<Of64>> {
-    let program = Vec::<PushProgram>::from(genome.clone());
-    let errors: TestResults<test_results::Error<Of64>> = training_cases
-        .iter()
-        .map(|&case| score_program(program.iter().cloned(), case))
-        .collect();
-    errors
+#[allow(clippy::trivially_copy_pass_by_ref)]
+fn abs_error(actual: &Of64, expected: &Of64) -> Of64 {
+    OrderedFloat((actual.0 - expected.0).abs())
 }
 
 fn main() -> Result<()> {
@@ -108,7 +67,7 @@ fn main() -> Result<()> {
     // I have to multiply that by 8 to get integer values, so:
     // -33, -31, -29, ..., 31, 33.
     let testing_inputs = (-33..=33).step_by(2).map(|n| Of64::from(n) / 8.0);
-    let _testing_cases = Cases::from_inputs(testing_inputs, |&i| target_fn(i));
+    let testing_cases = Cases::from_inputs(testing_inputs, |&i| target_fn(i));
 
     /*
      * The `scorer` will need to take an evolved program (sequence of
@@ -117,13 +76,26 @@ fn main() -> Result<()> {
      * i.e., the absolute difference between the returned value and the
      * expected value.
      */
-    let scorer = FnScorer(
-        |genome: &Plushy| -> TestResults<test_results::Error<Of64>> {
-            score_genome(genome, &training_cases)
-        },
-    );
-
-    let selector = Lexicase::new(training_cases.len());
+    let scorer = PushScorer::new(
+        training_cases,
+        &["x"],
+        top::<Of64>,
+        abs_error,
+        Penalties::uniform(OrderedFloat(PENALTY_VALUE)),
+    )?;
+
+    // The testing cases are never used for selection, so how the best
+    // individual does on them shows how well it generalises.
+    let testing_scorer = PushScorer::new(
+        testing_cases,
+        &["x"],
+        top::<Of64>,
+        abs_error,
+        Penalties::uniform(OrderedFloat(PENALTY_VALUE)),
+    )?;
+    let mut generalisation_report = GeneralisationReport::new(testing_scorer);
+
+    let selector = Lexicase::new(scorer.cases().len());
 
     let mut rng = thread_rng();
 
@@ -142,7 +114,7 @@ fn main() -> Result<()> {
 
     let population = gene_generator
         .to_collection_generator(args.max_initial_instructions)
-        .with_scorer(scorer)
+        .with_scorer(&scorer)
         .into_collection_generator(args.population_size)
         .generate(&mut rng)?;
 
@@ -156,7 +128,7 @@ fn main() -> Result<()> {
     let make_new_individual = Select::new(selector)
         .then(GenomeExtractor)
         .then(Mutate::new(umad))
-        .wrap::<GenomeScorer<_, _>>(scorer);
+        .wrap::<GenomeScorer<_, _>>(&scorer);
 
     let mut generation = Generation::new(make_new_individual, population);
 
@@ -173,6 +145,8 @@ fn main() -> Result<()> {
         // TODO: Change 2 to be the smallest number of digits needed for
         // args.num_generations-1.
         println!("Generation {generation_number:2} best is {best:#?}");
+        let generalisation = generalisation_report.record(generation_number, best);
+        println!("{generalisation} (gap {})", generalisation.gap());
 
         if best.test_results.total_result.error == OrderedFloat(0.0) {
             println!("SUCCESS");
@@ -180,5 +154,9 @@ fn main() -> Result<()> {
         }
     }
 
+    if let Some(best_testing) = generalisation_report.best_testing() {
+        println!("Best generalisation: {best_testing}");
+    }
+
     Ok(())
 }
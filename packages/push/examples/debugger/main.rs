@@ -0,0 +1,165 @@
+use std::{
+    io::{stdin, BufRead},
+    path::PathBuf,
+};
+
+use anyhow::{bail, Context, Result};
+use clap::Parser;
+use push::{
+    instruction::{variable_name::VariableName, PushInstruction},
+    push_vm::{
+        program::PushProgram,
+        push_state::{ProgramState, PushState},
+        text,
+        trace::{Debugger, Status},
+        HasStack,
+    },
+};
+
+/*
+ * A small interactive debugger for Push programs, which replays a program
+ * from its text form (see `push::push_vm::text`), e.g.,
+ *
+ *   cargo run --example debugger -- --input x=5 'x 3 Int-Add x Int-Multiply'
+ *
+ * At the prompt, `s` (or `step`) performs one instruction, `c` (or
+ * `continue`) runs to the next breakpoint (or the step limit), `b <instruction>` (or `break`)
+ * toggles a breakpoint, `p` (or `print`) prints the whole state, and `q`
+ * (or `quit`) stops.
+ */
+
+/// Step through a Push program
+#[derive(Parser, Debug)]
+#[clap(author, version, about, long_about = None)]
+struct Args {
+    /// The program, in the text form, e.g., 'x 3 Int-Add'
+    program: String,
+
+    /// Bind an input variable to a boolean, integer, or float value, e.g.,
+    /// `x=5`
+    #[clap(short, long, value_parser = parse_input)]
+    input: Vec<(VariableName, PushInstruction)>,
+
+    /// Stop before every occurrence of this instruction, e.g., `Int-Add`
+    #[clap(short, long = "break")]
+    breakpoint: Vec<String>,
+
+    /// Write the trace to this file as JSON Lines when the debugger stops
+    #[clap(short, long)]
+    trace: Option<PathBuf>,
+
+    /// Maximum stack size
+    #[clap(short, long, value_parser, default_value_t = 1_000)]
+    max_stack_size: usize,
+
+    /// Stop the program after this many steps
+    #[clap(short, long, value_parser, default_value_t = 1_000)]
+    step_limit: usize,
+}
+
+/// Parse an input binding, where the value has to be a literal so that an
+/// input can't be bound to itself (or another input).
+fn parse_input(binding: &str) -> Result<(VariableName, PushInstruction)> {
+    let Some((name, value)) = binding.split_once('=') else {
+        bail!("Inputs should look like `x=5`, but got {binding:?}");
+    };
+    let Some(value) = text::parse_literal(value) else {
+        bail!(
+            "The value of input {name} should be a boolean, integer, or float, but got {value:?}"
+        );
+    };
+    Ok((VariableName::from(name), value))
+}
+
+fn print_status(debugger: &Debugger, status: Status) {
+    if let Some(step) = debugger.trace().steps().last() {
+        println!(
+            "{:>4}: {:<20} int {:?}, float {:?}, bool {:?}",
+            step.step, step.instruction, step.stacks.int, step.stacks.float, step.stacks.bool
+        );
+        if let Some(error) = &step.recovered_error {
+            println!("      recovered from: {error}");
+        }
+        if let Some(error) = &step.fatal_error {
+            println!("      fatal error: {error}");
+        }
+    }
+    match status {
+        Status::Paused | Status::Breakpoint => {
+            if let Ok(next) = debugger.state().stack::<PushProgram>().next_program() {
+                let marker = if status == Status::Breakpoint {
+                    " (breakpoint)"
+                } else {
+                    ""
+                };
+                println!("next: {next}{marker}");
+            }
+        }
+        Status::Finished => println!("The program has finished"),
+        Status::Failed => println!("The program stopped with a fatal error"),
+        Status::StepLimit => println!(
+            "The program reached the step limit of {}",
+            debugger.step_limit()
+        ),
+    }
+}
+
+fn main() -> Result<()> {
+    let args = Args::parse();
+
+    let program = text::parse(&args.program)?;
+    let mut state = PushState::builder()
+        .with_max_stack_size(args.max_stack_size)
+        .with_program(program)?
+        .build();
+    for (name, value) in args.input {
+        state.bind_input(name, value);
+    }
+
+    let mut debugger = Debugger::new(state).with_step_limit(args.step_limit);
+    for breakpoint in &args.breakpoint {
+        debugger.add_breakpoint(text::parse_instruction(breakpoint)?);
+    }
+    print_status(&debugger, debugger.status());
+
+    for line in stdin().lock().lines() {
+        let line = line.context("Failed to read a command")?;
+        let mut words = line.split_whitespace();
+        let status = match (words.next(), words.next()) {
+            (Some("s" | "step"), None) => debugger.step(),
+            (Some("c" | "continue"), None) => debugger.resume(),
+            (Some("b" | "break"), Some(instruction)) => {
+                let instruction = match text::parse_instruction(instruction) {
+                    Ok(instruction) => instruction,
+                    Err(error) => {
+                        println!("{error}");
+                        continue;
+                    }
+                };
+                if debugger.breakpoints().contains(&instruction) {
+                    debugger.remove_breakpoint(&instruction);
+                    println!("Removed the breakpoint on {instruction}");
+                } else {
+                    println!("Added a breakpoint on {instruction}");
+                    debugger.add_breakpoint(instruction);
+                }
+                continue;
+            }
+            (Some("p" | "print"), None) => {
+                println!("{:?}", debugger.state());
+                continue;
+            }
+            (Some("q" | "quit"), None) => break,
+            _ => {
+                println!("Commands: s(tep), c(ontinue), b(reak) <instruction>, p(rint), q(uit)");
+                continue;
+            }
+        };
+        print_status(&debugger, status);
+    }
+
+    if let Some(path) = args.trace {
+        debugger.trace().write_json_lines_file(path)?;
+    }
+    Ok(())
+}
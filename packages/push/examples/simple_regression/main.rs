@@ -7,10 +7,8 @@ use clap::Parser;
 use ec_core::{
     generation::Generation,
     generator::{collection::ConvertToCollectionGenerator, Generator},
-    individual::{
-        ec::{EcIndividual, WithScorer},
-        scorer::FnScorer,
-    },
+    evaluation::cases::WithTarget,
+    individual::ec::{EcIndividual, WithScorer},
     operator::{
         genome_extractor::GenomeExtractor,
         genome_scorer::GenomeScorer,
@@ -26,9 +24,9 @@ use ec_core::{
 use ec_linear::mutator::umad::Umad;
 use ordered_float::OrderedFloat;
 use push::{
+    evaluation::push_scorer::{top, Penalties, PushScorer},
     genome::plushy::{GeneGenerator, Plushy},
     instruction::{variable_name::VariableName, FloatInstruction},
-    push_vm::{program::PushProgram, push_state::PushState, HasStack, State},
     vec_into,
 };
 use rand::thread_rng;
@@ -53,9 +51,11 @@ fn main() -> Result<()> {
     let args = Args::parse();
 
     // Inputs from -4 (inclusive) to 4 (exclusive) in increments of 0.25.
-    let training_inputs = (-4 * 4..4 * 4)
-        .map(|n| OrderedFloat(f64::from(n) / 4.0))
-        .collect::<Vec<_>>();
+    let training_inputs = (-4 * 4..4 * 4).map(|n| OrderedFloat(f64::from(n) / 4.0));
+    // The target polynomial is x^3 - 2x^2 - x
+    let training_cases = training_inputs.with_target(|&input| {
+        input * input * input - OrderedFloat::<f64>::from(2f64) * input * input - input
+    });
 
     /*
      * The `scorer` will need to take an evolved program (sequence of
@@ -63,44 +63,16 @@ fn main() -> Result<()> {
      * (exclusive) in increments of 0.25, collecting together the errors,
      * i.e., the absolute difference between the returned value and the
      * expected value.
-     *
-     * The target polynomial is x^3 - 2x^2 - x
      */
-    let scorer = FnScorer(
-        |genome: &Plushy| -> TestResults<test_results::Error<OrderedFloat<f64>>> {
-            let program = Vec::<PushProgram>::from(genome.clone());
-            let errors: TestResults<test_results::Error<OrderedFloat<f64>>> = training_inputs
-                .iter()
-                .map(|&input| {
-                    #[allow(clippy::unwrap_used)]
-                    let state = PushState::builder()
-                        .with_max_stack_size(1000)
-                        .with_program(program.clone())
-                        // This will return an error if the program is longer than the allowed
-                        // max stack size.
-                        // We arguably should check that and return an error here.
-                        .unwrap()
-                        .with_float_input("x", input)
-                        .build();
-                    let expected = input * input * input
-                        - OrderedFloat::<f64>::from(2f64) * input * input
-                        - input;
-                    #[allow(clippy::option_if_let_else)]
-                    match state.run_to_completion() {
-                        Ok(final_state) => final_state
-                            .stack::<OrderedFloat<f64>>()
-                            .top()
-                            .map_or(penalty_value, |answer| (answer - expected).abs().into()),
-                        Err(_) => {
-                            // Do some logging, perhaps?
-                            penalty_value
-                        }
-                    }
-                })
-                .collect();
-            errors
+    let scorer = PushScorer::new(
+        training_cases,
+        &["x"],
+        top::<OrderedFloat<f64>>,
+        |actual: &OrderedFloat<f64>, expected: &OrderedFloat<f64>| {
+            OrderedFloat((actual.0 - expected.0).abs())
         },
-    );
+        Penalties::uniform(penalty_value),
+    )?;
 
     let num_test_cases = 10;
     let lexicase = Lexicase::new(num_test_cases);
@@ -124,7 +96,7 @@ fn main() -> Result<()> {
 
     let population = gene_generator
         .to_collection_generator(args.max_initial_instructions)
-        .with_scorer(scorer)
+        .with_scorer(&scorer)
         .into_collection_generator(args.population_size)
         .generate(&mut rng)?;
 
@@ -138,7 +110,7 @@ fn main() -> Result<()> {
     let make_new_individual = Select::new(selector)
         .then(GenomeExtractor)
         .then(Mutate::new(umad))
-        .wrap::<GenomeScorer<_, _>>(scorer);
+        .wrap::<GenomeScorer<_, _>>(&scorer);
 
     let mut generation = Generation::new(make_new_individual, population);
 
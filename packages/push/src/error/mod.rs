@@ -1,32 +1,38 @@
-use self::{
-    into_state::IntoState,
-    stateful::{FatalError, RecoverableError, StatefulError},
-    try_recover::TryRecover,
-};
+use self::try_recover::TryRecover;
 
 pub mod into_state;
+pub mod policy;
 pub mod stateful;
 pub mod try_recover;
 
-#[derive(Debug)]
-pub enum Error<S, E> {
-    Recoverable(RecoverableError<S, E>),
-    Fatal(FatalError<S, E>),
+/// An error from performing an instruction. Instructions work on a mutable
+/// reference to the state, so these don't carry the state around with them.
+///
+/// Instructions must be "transactional": if an instruction returns a
+/// `Recoverable` error then it must have left the state unchanged, so that
+/// the program can just carry on with the next instruction. After a `Fatal`
+/// error the state may be left part way through the instruction, but the
+/// program stops anyway.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error<E> {
+    Recoverable(E),
+    Fatal(E),
 }
 
-pub type InstructionResult<S, E> = core::result::Result<S, Error<S, E>>;
+pub type InstructionResult<E> = core::result::Result<(), Error<E>>;
 
 /// Maps a (presumably error) type into an `InstructionResult`.
-/// This is used to convert `InstructionResult<S, E1>`
-/// into `InstructionResult<S, E2>`, i.e. do `map_err()` on
+///
+/// This is used to convert `InstructionResult<E1>`
+/// into `InstructionResult<E2>`, i.e. do `map_err()` on
 /// the inner error types of an `InstructionResult`, preserving
-/// the other fields in `Error`.
-pub trait MapInstructionError<S, E> {
+/// whether the error is recoverable.
+pub trait MapInstructionError<T, E> {
     ///
     /// # Errors
     ///
-    /// This always returns an error type.
-    fn map_err_into(self) -> InstructionResult<S, E>;
+    /// This returns an error if `self` was an error.
+    fn map_err_into(self) -> Result<T, Error<E>>;
 }
 
 // MizardX@Twitch's initial suggestion here had `E2` as a generic on the
@@ -34,22 +40,22 @@ pub trait MapInstructionError<S, E> {
 // some additional flexibility, although it wasn't clear that we would use it.
 // The current approach (suggested by esitsu@Twitch) simplified the
 // `MapInstructionError` trait in a nice way, so I went with that.
-impl<S, E1, E2> MapInstructionError<S, E2> for InstructionResult<S, E1>
+impl<T, E1, E2> MapInstructionError<T, E2> for Result<T, Error<E1>>
 where
     E1: Into<E2>,
 {
-    fn map_err_into(self) -> InstructionResult<S, E2> {
+    fn map_err_into(self) -> Result<T, Error<E2>> {
         self.map_err(|e| e.map_inner_err(Into::into))
     }
 }
 
-impl<S, E> Error<S, E> {
-    pub fn fatal(state: S, error: impl Into<E>) -> Self {
-        Self::Fatal(FatalError::new(state, error.into()))
+impl<E> Error<E> {
+    pub fn fatal(error: impl Into<E>) -> Self {
+        Self::Fatal(error.into())
     }
 
-    pub fn recoverable(state: S, error: impl Into<E>) -> Self {
-        Self::Recoverable(RecoverableError::new(state, error.into()))
+    pub fn recoverable(error: impl Into<E>) -> Self {
+        Self::Recoverable(error.into())
     }
 
     pub const fn is_recoverable(&self) -> bool {
@@ -60,51 +66,36 @@ impl<S, E> Error<S, E> {
         matches!(self, Self::Fatal(_))
     }
 
-    pub const fn state(&self) -> &S {
+    pub const fn error(&self) -> &E {
         match self {
-            Self::Recoverable(StatefulError { state, .. })
-            | Self::Fatal(StatefulError { state, .. }) => state,
+            Self::Recoverable(error) | Self::Fatal(error) => error,
         }
     }
 
-    pub const fn error(&self) -> &E {
+    pub fn into_inner(self) -> E {
         match self {
-            Self::Recoverable(StatefulError { error, .. })
-            | Self::Fatal(StatefulError { error, .. }) => error,
+            Self::Recoverable(error) | Self::Fatal(error) => error,
         }
     }
 
-    pub fn map_inner_err<F, E1>(self, f: F) -> Error<S, E1>
+    pub fn map_inner_err<F, E1>(self, f: F) -> Error<E1>
     where
         F: FnOnce(E) -> E1,
     {
         match self {
-            Self::Recoverable(RecoverableError { state, error, .. }) => {
-                Error::Recoverable(RecoverableError::new_boxed(state, f(error)))
-            }
-            Self::Fatal(FatalError { state, error, .. }) => {
-                Error::Fatal(FatalError::new_boxed(state, f(error)))
-            }
+            Self::Recoverable(error) => Error::Recoverable(f(error)),
+            Self::Fatal(error) => Error::Fatal(f(error)),
         }
     }
 }
 
-impl<S, E> IntoState<S> for Error<S, E> {
-    fn into_state(self) -> S {
+impl<E> TryRecover<()> for InstructionResult<E> {
+    type Error = E;
+
+    fn try_recover(self) -> Result<(), E> {
         match self {
-            Self::Recoverable(StatefulError { state, .. })
-            | Self::Fatal(StatefulError { state, .. }) => *state,
+            Ok(()) | Err(Error::Recoverable(_)) => Ok(()),
+            Err(Error::Fatal(error)) => Err(error),
         }
     }
 }
-
-impl<S, E> TryRecover<S> for Result<S, Error<S, E>> {
-    type Error = FatalError<S, E>;
-
-    fn try_recover(self) -> Result<S, FatalError<S, E>> {
-        self.or_else(|err| match err {
-            Error::Recoverable(s) => Ok(s.into_state()),
-            Error::Fatal(error) => Err(error),
-        })
-    }
-}
@@ -0,0 +1,170 @@
+use strum::{EnumCount, IntoEnumIterator};
+
+use super::Error;
+use crate::instruction::instruction_error::{ErrorKind, PushInstructionError};
+
+/// What to do when performing an instruction fails.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorAction {
+    /// Stop the program with the error.
+    Abort,
+    /// Carry on with the next instruction.
+    Ignore,
+    /// Carry on with the next instruction, adding the error to the state's
+    /// [`ErrorCounts`].
+    Count,
+}
+
+/// How a state handles the errors from the instructions it performs.
+///
+/// By default recoverable errors are counted and fatal errors abort the
+/// program, which is how instructions were designed to be used. The action
+/// for a particular [`ErrorKind`] can be overridden, e.g., to make programs
+/// that rely on stack underflows fail, or to carry on after stack overflows.
+/// Instructions are only guaranteed to leave the state unchanged when they
+/// return recoverable errors, so carrying on after a fatal error may leave
+/// an instruction part way done.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ErrorPolicy {
+    recoverable: ErrorAction,
+    fatal: ErrorAction,
+    overrides: Vec<(ErrorKind, ErrorAction)>,
+}
+
+impl Default for ErrorPolicy {
+    fn default() -> Self {
+        Self {
+            recoverable: ErrorAction::Count,
+            fatal: ErrorAction::Abort,
+            overrides: Vec::new(),
+        }
+    }
+}
+
+impl ErrorPolicy {
+    /// Use `action` for recoverable errors that don't have an override.
+    #[must_use]
+    pub const fn with_recoverable(mut self, action: ErrorAction) -> Self {
+        self.recoverable = action;
+        self
+    }
+
+    /// Use `action` for fatal errors that don't have an override.
+    #[must_use]
+    pub const fn with_fatal(mut self, action: ErrorAction) -> Self {
+        self.fatal = action;
+        self
+    }
+
+    /// Use `action` for all errors of the given `kind`, whether they're
+    /// recoverable or fatal.
+    #[must_use]
+    pub fn with_action(mut self, kind: ErrorKind, action: ErrorAction) -> Self {
+        self.overrides.retain(|(k, _)| *k != kind);
+        self.overrides.push((kind, action));
+        self
+    }
+
+    /// The action to take for `error`.
+    #[must_use]
+    pub fn action(&self, error: &Error<PushInstructionError>) -> ErrorAction {
+        let kind = error.error().kind();
+        self.overrides.iter().find(|(k, _)| *k == kind).map_or_else(
+            || {
+                if error.is_recoverable() {
+                    self.recoverable
+                } else {
+                    self.fatal
+                }
+            },
+            |(_, action)| *action,
+        )
+    }
+}
+
+/// The number of errors of each kind that were counted while running a
+/// program.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ErrorCounts {
+    counts: [usize; ErrorKind::COUNT],
+}
+
+impl ErrorCounts {
+    pub const fn record(&mut self, kind: ErrorKind) {
+        self.counts[kind.index()] += 1;
+    }
+
+    #[must_use]
+    pub const fn get(&self, kind: ErrorKind) -> usize {
+        self.counts[kind.index()]
+    }
+
+    /// The total number of errors of all kinds.
+    #[must_use]
+    pub fn total(&self) -> usize {
+        self.counts.iter().sum()
+    }
+
+    /// The kinds of errors that were counted at least once, with their
+    /// counts.
+    pub fn iter(&self) -> impl Iterator<Item = (ErrorKind, usize)> + '_ {
+        ErrorKind::iter()
+            .map(|kind| (kind, self.get(kind)))
+            .filter(|&(_, count)| count > 0)
+    }
+
+    pub const fn clear(&mut self) {
+        self.counts = [0; ErrorKind::COUNT];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::push_vm::stack::StackError;
+
+    #[test]
+    fn kinds_are_indexed_in_order() {
+        assert!(
+            ErrorKind::iter()
+                .enumerate()
+                .all(|(index, kind)| kind.index() == index)
+        );
+        assert_eq!(ErrorKind::iter().count(), ErrorKind::COUNT);
+    }
+
+    #[test]
+    fn overrides_take_precedence() {
+        let underflow = Error::recoverable(StackError::Underflow {
+            num_requested: 1,
+            num_present: 0,
+        });
+        let overflow = Error::fatal(StackError::Overflow { stack_type: "bool" });
+        let policy = ErrorPolicy::default();
+        assert_eq!(policy.action(&underflow), ErrorAction::Count);
+        assert_eq!(policy.action(&overflow), ErrorAction::Abort);
+
+        let policy = policy
+            .with_recoverable(ErrorAction::Ignore)
+            .with_action(ErrorKind::StackUnderflow, ErrorAction::Abort)
+            .with_action(ErrorKind::StackOverflow, ErrorAction::Count);
+        assert_eq!(policy.action(&underflow), ErrorAction::Abort);
+        assert_eq!(policy.action(&overflow), ErrorAction::Count);
+    }
+
+    #[test]
+    fn counts() {
+        let mut counts = ErrorCounts::default();
+        counts.record(ErrorKind::IntOverflow);
+        counts.record(ErrorKind::StackUnderflow);
+        counts.record(ErrorKind::IntOverflow);
+        assert_eq!(counts.get(ErrorKind::IntOverflow), 2);
+        assert_eq!(counts.total(), 3);
+        assert_eq!(
+            counts.iter().collect::<Vec<_>>(),
+            [(ErrorKind::StackUnderflow, 1), (ErrorKind::IntOverflow, 2)]
+        );
+        counts.clear();
+        assert_eq!(counts.total(), 0);
+    }
+}
@@ -29,7 +29,9 @@ pub struct StatefulError<S, E, Severity: ErrorSeverity> {
     // the big piece (the state in our case), and doing that brought the size down to
     // 40 bytes. Since `Error`s are only constructed through `::fatal()` or `::recoverable()`,
     // we'd nicely encapsulated this and only had to make changes in those two places to
-    // get things working.
+    // get things working. Instructions now work on `&mut S` and return plain
+    // `Error<E>`s, so these are only built once per run, e.g., by
+    // `run_to_completion()` when it hits a fatal error.
     pub(super) state: Box<S>,
     pub(super) error: E,
     _p: PhantomData<Severity>,
@@ -47,6 +49,14 @@ impl<S, E, Severity: ErrorSeverity> StatefulError<S, E, Severity> {
             _p: PhantomData,
         }
     }
+
+    pub const fn state(&self) -> &S {
+        &self.state
+    }
+
+    pub const fn error(&self) -> &E {
+        &self.error
+    }
 }
 
 impl<S, E, Severity: ErrorSeverity> IntoState<S> for StatefulError<S, E, Severity> {
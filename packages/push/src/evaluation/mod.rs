@@ -1 +1,5 @@
-pub mod cases;
+pub mod push_scorer;
+
+// `Cases` aren't specific to Push, so they live in `ec-core`; they're
+// re-exported here so existing code can keep using `push::evaluation::cases`.
+pub use ec_core::evaluation::cases;
@@ -0,0 +1,596 @@
+//! A [`Scorer`] for [`Plushy`] genomes that runs the program on each of a
+//! set of [`Cases`] and collects the errors.
+
+use std::sync::Mutex;
+
+use anyhow::{ensure, Result};
+use ec_core::{
+    evaluation::cases::{Case, Cases},
+    individual::scorer::Scorer,
+    test_results::{Error, TestResults},
+};
+use ordered_float::OrderedFloat;
+
+use crate::{
+    error::policy::ErrorPolicy,
+    genome::plushy::Plushy,
+    instruction::{
+        instruction_error::PushInstructionError, variable_name::VariableName, IntArithmetic,
+        PushInstruction,
+    },
+    push_vm::{
+        big_int_push_state::BigIntPushState,
+        bytecode::Bytecode,
+        program::PushProgram,
+        push_state::{ProgramState, PushState},
+        ClearState, HasStack, SizeBudgetState,
+    },
+};
+
+/// Values that can be bound to a program's input variables, where each
+/// value is bound to one variable.
+pub trait Inputs {
+    /// The instructions that push each of the values onto their stacks, in
+    /// the same order as the input variables.
+    fn input_instructions(&self) -> Vec<PushInstruction>;
+}
+
+impl Inputs for i64 {
+    fn input_instructions(&self) -> Vec<PushInstruction> {
+        vec![PushInstruction::push_int(*self)]
+    }
+}
+
+impl Inputs for OrderedFloat<f64> {
+    fn input_instructions(&self) -> Vec<PushInstruction> {
+        vec![PushInstruction::push_float(*self)]
+    }
+}
+
+impl Inputs for bool {
+    fn input_instructions(&self) -> Vec<PushInstruction> {
+        vec![PushInstruction::push_bool(*self)]
+    }
+}
+
+macro_rules! tuple_inputs {
+    ($($t:ident $index:tt),*) => {
+        impl<$($t: Inputs),*> Inputs for ($($t,)*) {
+            fn input_instructions(&self) -> Vec<PushInstruction> {
+                let mut instructions = Vec::new();
+                $(instructions.extend(self.$index.input_instructions());)*
+                instructions
+            }
+        }
+    };
+}
+
+tuple_inputs!(A 0, B 1);
+tuple_inputs!(A 0, B 1, C 2);
+tuple_inputs!(A 0, B 1, C 2, D 3);
+
+/// The states that a [`PushScorer`] can run programs on.
+pub trait ScoringState: ProgramState + ClearState + SizeBudgetState {
+    /// An empty state where each stack holds at most `max_stack_size`
+    /// values, which handles errors with `error_policy` and integer
+    /// overflow with `int_arithmetic`.
+    fn for_scoring(
+        max_stack_size: usize,
+        error_policy: ErrorPolicy,
+        int_arithmetic: IntArithmetic,
+    ) -> Self;
+}
+
+impl ScoringState for PushState {
+    fn for_scoring(
+        max_stack_size: usize,
+        error_policy: ErrorPolicy,
+        int_arithmetic: IntArithmetic,
+    ) -> Self {
+        Self::builder()
+            .with_max_stack_size(max_stack_size)
+            .with_no_program()
+            .build()
+            .with_error_policy(error_policy)
+            .with_int_arithmetic(int_arithmetic)
+    }
+}
+
+impl ScoringState for BigIntPushState {
+    fn for_scoring(
+        max_stack_size: usize,
+        error_policy: ErrorPolicy,
+        int_arithmetic: IntArithmetic,
+    ) -> Self {
+        Self::new(max_stack_size)
+            .with_error_policy(error_policy)
+            .with_int_arithmetic(int_arithmetic)
+    }
+}
+
+/// Read the output from the top of the stack of `T`s, for use as the
+/// `read_output` function of a [`PushScorer`] on [`PushState`]s.
+///
+/// For other states, use a closure that takes the state, e.g.,
+/// `|state: &BigIntPushState| state.stack::<BigInt>().top().ok().cloned()`.
+#[must_use]
+pub fn top<T>(state: &PushState) -> Option<T>
+where
+    PushState: HasStack<T>,
+    T: Clone,
+{
+    state.stack::<T>().top().ok().cloned()
+}
+
+/// The errors to use for a case when we can't get an output from the
+/// program to compare to the expected output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Penalties<E> {
+    /// There was no output at the end of the program, e.g., because the
+    /// stack that the output is read from was empty.
+    pub missing_output: E,
+    /// The program was stopped by a fatal error, e.g., a stack overflow,
+    /// or was too long to fit on the exec stack.
+    pub fatal_error: E,
+    /// The program was stopped because it hit the step limit. If this is
+    /// `None` we read the output from the state when it was stopped, as
+    /// `PushGP` does.
+    pub step_limit: Option<E>,
+}
+
+impl<E: Clone> Penalties<E> {
+    /// The same penalty for a missing output or a fatal error, with the
+    /// output read as usual when the program hits the step limit.
+    pub fn uniform(penalty: E) -> Self {
+        Self {
+            missing_output: penalty.clone(),
+            fatal_error: penalty,
+            step_limit: None,
+        }
+    }
+}
+
+/// Scores a [`Plushy`] by running its program once for each case, with the
+/// case's input bound to the input variables, and comparing the output to
+/// the case's expected output with an error function.
+///
+/// # Examples
+///
+/// ```
+/// # use ec_core::{evaluation::cases::WithTarget, individual::scorer::Scorer};
+/// # use push::{
+/// #     evaluation::push_scorer::{top, Penalties, PushScorer},
+/// #     genome::plushy::{Plushy, PushGene},
+/// #     instruction::{variable_name::VariableName, IntInstruction},
+/// # };
+/// let cases = (0..5).with_target(|x| 2 * x);
+/// let scorer = PushScorer::new(
+///     cases,
+///     &["x"],
+///     top::<i64>,
+///     |actual: &i64, expected: &i64| (actual - expected).abs(),
+///     Penalties::uniform(1_000),
+/// )?;
+///
+/// let doubles = Plushy::new([
+///     PushGene::from(VariableName::from("x")),
+///     PushGene::from(VariableName::from("x")),
+///     PushGene::from(IntInstruction::Add),
+/// ]);
+/// assert_eq!(scorer.score(&doubles).total_result.error, 0);
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+///
+/// The programs are run on [`PushState`]s, unless `read_output` takes
+/// another [`ScoringState`], e.g., a [`BigIntPushState`] for problems with
+/// large integers.
+///
+/// Each program is run on a state that is cleared between cases rather than
+/// rebuilt. The scorer keeps a pool of these states, so that they're reused
+/// across calls to [`Scorer::score`], with one state for each thread that is
+/// scoring at the same time.
+pub struct PushScorer<Input, Output, R, F, E, S = PushState> {
+    cases: Cases<Input, Output>,
+    input_names: Vec<VariableName>,
+    read_output: R,
+    error_fn: F,
+    penalties: Penalties<E>,
+    max_stack_size: usize,
+    step_limit: usize,
+    error_policy: ErrorPolicy,
+    int_arithmetic: IntArithmetic,
+    size_budget: Option<usize>,
+    states: Mutex<Vec<S>>,
+}
+
+impl<Input, Output, R, F, E, S> PushScorer<Input, Output, R, F, E, S>
+where
+    Input: Inputs,
+{
+    pub const DEFAULT_MAX_STACK_SIZE: usize = 1_000;
+    pub const DEFAULT_STEP_LIMIT: usize = 1_000;
+
+    /// Make a scorer for `cases`, where the `input_names` are the names of
+    /// the input variables that the values in each case's input are bound
+    /// to, `read_output` gets the program's output from its final state,
+    /// and `error_fn` compares the actual and expected outputs.
+    ///
+    /// # Errors
+    ///
+    /// This fails if the number of values in any of the inputs isn't the
+    /// same as the number of input names.
+    pub fn new(
+        cases: Cases<Input, Output>,
+        input_names: &[&str],
+        read_output: R,
+        error_fn: F,
+        penalties: Penalties<E>,
+    ) -> Result<Self>
+    where
+        R: Fn(&S) -> Option<Output>,
+    {
+        for case in &cases {
+            let num_values = case.input.input_instructions().len();
+            ensure!(
+                num_values == input_names.len(),
+                "There are {} input names ({input_names:?}), but an input has {num_values} values",
+                input_names.len(),
+            );
+        }
+        Ok(Self {
+            cases,
+            input_names: input_names
+                .iter()
+                .map(|&name| VariableName::from(name))
+                .collect(),
+            read_output,
+            error_fn,
+            penalties,
+            max_stack_size: Self::DEFAULT_MAX_STACK_SIZE,
+            step_limit: Self::DEFAULT_STEP_LIMIT,
+            error_policy: ErrorPolicy::default(),
+            int_arithmetic: IntArithmetic::default(),
+            size_budget: None,
+            states: Mutex::default(),
+        })
+    }
+
+    #[must_use]
+    pub fn with_max_stack_size(mut self, max_stack_size: usize) -> Self {
+        self.max_stack_size = max_stack_size;
+        self.states = Mutex::default();
+        self
+    }
+
+    #[must_use]
+    pub const fn with_step_limit(mut self, step_limit: usize) -> Self {
+        self.step_limit = step_limit;
+        self
+    }
+
+    /// Run the programs with `error_policy`, e.g., so that programs that
+    /// rely on errors get the fatal error penalty.
+    #[must_use]
+    pub fn with_error_policy(mut self, error_policy: ErrorPolicy) -> Self {
+        self.error_policy = error_policy;
+        self.states = Mutex::default();
+        self
+    }
+
+    /// Run the programs with `int_arithmetic` to handle integer overflow,
+    /// instead of the default [`IntArithmetic::Checked`].
+    #[must_use]
+    pub fn with_int_arithmetic(mut self, int_arithmetic: IntArithmetic) -> Self {
+        self.int_arithmetic = int_arithmetic;
+        self.states = Mutex::default();
+        self
+    }
+
+    /// Limit the total size of the values on all the stacks to `limit`
+    /// while running the programs; see [`SizeBudgetState::with_size_budget`].
+    #[must_use]
+    pub fn with_size_budget(mut self, limit: usize) -> Self {
+        self.size_budget = Some(limit);
+        self.states = Mutex::default();
+        self
+    }
+}
+
+impl<Input, Output, R, F, E, S> PushScorer<Input, Output, R, F, E, S> {
+    pub const fn cases(&self) -> &Cases<Input, Output> {
+        &self.cases
+    }
+}
+
+impl<Input, Output, R, F, E, S> PushScorer<Input, Output, R, F, E, S>
+where
+    Input: Inputs,
+    R: Fn(&S) -> Option<Output>,
+    F: Fn(&Output, &Output) -> E,
+    E: Clone,
+    S: ScoringState,
+{
+    /// A state from the pool, or a new one if the pool is empty.
+    fn take_state(&self) -> S {
+        self.states
+            .lock()
+            .ok()
+            .and_then(|mut states| states.pop())
+            .unwrap_or_else(|| {
+                let state = S::for_scoring(
+                    self.max_stack_size,
+                    self.error_policy.clone(),
+                    self.int_arithmetic,
+                );
+                match self.size_budget {
+                    Some(limit) => state.with_size_budget(limit),
+                    None => state,
+                }
+            })
+    }
+
+    /// Put `state` back in the pool so that it can be reused.
+    fn return_state(&self, state: S) {
+        if let Ok(mut states) = self.states.lock() {
+            states.push(state);
+        }
+    }
+
+    fn output_error(&self, state: &S, expected: &Output) -> E {
+        (self.read_output)(state).map_or_else(
+            || self.penalties.missing_output.clone(),
+            |actual| (self.error_fn)(&actual, expected),
+        )
+    }
+
+    /// Clear `state` and bind the input variables to the values in `case`.
+    /// The variables are always bound in the same order, so each one is
+    /// in the same slot for every case.
+    fn bind_inputs(&self, state: &mut S, case: &Case<Input, Output>) {
+        state.clear();
+        for (name, instruction) in self.input_names.iter().zip(case.input.input_instructions()) {
+            state.bind_input(name.clone(), instruction);
+        }
+    }
+
+    /// The error for `case`, where `state` has already had its inputs bound
+    /// and `program` is `None` if the program couldn't be compiled, e.g.,
+    /// because it uses an undefined input variable.
+    fn case_error(
+        &self,
+        program: Option<&Bytecode>,
+        state: &mut S,
+        case: &Case<Input, Output>,
+    ) -> E {
+        let Some(program) = program else {
+            return self.penalties.fatal_error.clone();
+        };
+        match (
+            program.run_in_place(state, self.step_limit),
+            &self.penalties.step_limit,
+        ) {
+            (Ok(()), _) | (Err(PushInstructionError::StepLimitExceeded { .. }), None) => {
+                self.output_error(state, &case.output)
+            }
+            (Err(PushInstructionError::StepLimitExceeded { .. }), Some(penalty)) => penalty.clone(),
+            (Err(_), _) => self.penalties.fatal_error.clone(),
+        }
+    }
+}
+
+impl<Input, Output, R, F, E, S> Scorer<Plushy> for PushScorer<Input, Output, R, F, E, S>
+where
+    Input: Inputs,
+    R: Fn(&S) -> Option<Output>,
+    F: Fn(&Output, &Output) -> E,
+    E: Clone,
+    S: ScoringState,
+    TestResults<Error<E>>: FromIterator<E>,
+{
+    type Score = TestResults<Error<E>>;
+
+    fn score(&self, genome: &Plushy) -> Self::Score {
+        let program = Vec::<PushProgram>::from(genome);
+        // Run the program on the same state for every case, rather than
+        // building a new state each time. Since the inputs are in the same
+        // slots for every case, we only need to compile the program (against
+        // the state for the first case) once.
+        let mut state = self.take_state();
+        let mut bytecode = None;
+        let results = self
+            .cases
+            .iter()
+            .map(|case| {
+                self.bind_inputs(&mut state, case);
+                let bytecode =
+                    bytecode.get_or_insert_with(|| Bytecode::compile_for(&program, &state).ok());
+                self.case_error(bytecode.as_ref(), &mut state, case)
+            })
+            .collect();
+        self.return_state(state);
+        results
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use ec_core::evaluation::cases::WithTarget;
+    use num_bigint::BigInt;
+
+    use super::*;
+    use crate::{
+        error::policy::ErrorAction,
+        genome::plushy::PushGene,
+        instruction::instruction_error::ErrorKind,
+        instruction::{FloatInstruction, IntInstruction},
+        list_into::vec_into,
+    };
+
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    fn abs_error(actual: &i64, expected: &i64) -> i64 {
+        (actual - expected).abs()
+    }
+
+    fn penalties() -> Penalties<i64> {
+        Penalties {
+            missing_output: 100,
+            fatal_error: 200,
+            step_limit: Some(300),
+        }
+    }
+
+    fn errors(
+        scorer: &impl Scorer<Plushy, Score = TestResults<Error<i64>>>,
+        genes: Vec<PushGene>,
+    ) -> Vec<i64> {
+        scorer
+            .score(&Plushy::new(genes))
+            .results
+            .into_iter()
+            .map(|result| result.error)
+            .collect()
+    }
+
+    #[test]
+    fn binds_tuple_inputs() {
+        let cases = [(1, 2), (5, 3)].with_target(|&(x, y)| x - y);
+        let scorer =
+            PushScorer::new(cases, &["x", "y"], top::<i64>, abs_error, penalties()).unwrap();
+        // `Subtract` subtracts the second value from the top value.
+        let genes = vec_into![
+            VariableName::from("y"),
+            VariableName::from("x"),
+            IntInstruction::Subtract,
+        ];
+        assert_eq!(errors(&scorer, genes), [0, 0]);
+        // Only pushing `y` is off by `x - 2y`.
+        assert_eq!(errors(&scorer, vec_into![VariableName::from("y")]), [3, 1]);
+    }
+
+    #[test]
+    fn penalties_for_missing_outputs_and_limits() {
+        let cases = [1].with_target(|&x| x);
+        let scorer = PushScorer::new(cases, &["x"], top::<i64>, abs_error, penalties())
+            .unwrap()
+            .with_max_stack_size(4)
+            .with_step_limit(3);
+
+        let float_only = vec_into![FloatInstruction::Push(OrderedFloat(1.0))];
+        assert_eq!(errors(&scorer, float_only), [100]);
+        let too_long = vec_into![
+            IntInstruction::Push(1),
+            IntInstruction::Push(2),
+            IntInstruction::Push(3),
+            IntInstruction::Push(4),
+            IntInstruction::Push(5),
+        ];
+        assert_eq!(errors(&scorer, too_long), [200]);
+        let undefined_input = vec_into![VariableName::from("y")];
+        assert_eq!(errors(&scorer, undefined_input), [200]);
+        let too_many_steps = vec_into![
+            VariableName::from("x"),
+            VariableName::from("x"),
+            VariableName::from("x"),
+            IntInstruction::Add,
+        ];
+        assert_eq!(errors(&scorer, too_many_steps.clone()), [300]);
+
+        // Without a step limit penalty the output is read from the state
+        // when the program was stopped.
+        let scorer = PushScorer::new(
+            [1].with_target(|&x| x),
+            &["x"],
+            top::<i64>,
+            abs_error,
+            Penalties::uniform(1_000),
+        )
+        .unwrap()
+        .with_step_limit(3);
+        assert_eq!(errors(&scorer, too_many_steps), [0]);
+    }
+
+    #[test]
+    fn error_policy() {
+        let cases = [1].with_target(|&x| x);
+        let scorer = PushScorer::new(cases, &["x"], top::<i64>, abs_error, penalties()).unwrap();
+        // `Add` underflows, which is normally just counted.
+        let underflows = vec_into![VariableName::from("x"), IntInstruction::Add];
+        assert_eq!(errors(&scorer, underflows.clone()), [0]);
+
+        let scorer = scorer.with_error_policy(
+            ErrorPolicy::default().with_action(ErrorKind::StackUnderflow, ErrorAction::Abort),
+        );
+        assert_eq!(errors(&scorer, underflows), [200]);
+    }
+
+    #[test]
+    fn size_budget() {
+        let cases = [1].with_target(|&x| x);
+        let scorer = PushScorer::new(cases, &["x"], top::<i64>, abs_error, penalties())
+            .unwrap()
+            .with_size_budget(3);
+        let pushes = |n| (0..n).map(|i| IntInstruction::Push(i).into()).collect();
+        assert_eq!(errors(&scorer, pushes(3)), [1]);
+        // Four pushes don't fit on the exec stack within the budget.
+        assert_eq!(errors(&scorer, pushes(4)), [200]);
+    }
+
+    #[test]
+    fn int_arithmetic() {
+        let x = 1 << 62;
+        let cases = [x].with_target(|_| i64::MAX);
+        let scorer = PushScorer::new(cases, &["x"], top::<i64>, abs_error, penalties()).unwrap();
+        let doubles = vec_into![
+            VariableName::from("x"),
+            VariableName::from("x"),
+            IntInstruction::Add,
+        ];
+        // The overflow is counted and leaves `x` on top.
+        assert_eq!(errors(&scorer, doubles.clone()), [i64::MAX - x]);
+
+        let scorer = scorer.with_int_arithmetic(IntArithmetic::Saturating);
+        assert_eq!(errors(&scorer, doubles), [0]);
+    }
+
+    #[test]
+    fn big_ints() {
+        let cases = [3, 1 << 20].with_target(|&x| BigInt::from(x).pow(4));
+        let scorer = PushScorer::new(
+            cases,
+            &["x"],
+            |state: &BigIntPushState| state.stack::<BigInt>().top().ok().cloned(),
+            |actual: &BigInt, expected: &BigInt| i64::from(actual != expected),
+            penalties(),
+        )
+        .unwrap();
+        let fourth_power = vec_into![
+            VariableName::from("x"),
+            IntInstruction::Square,
+            IntInstruction::Square,
+        ];
+        assert_eq!(errors(&scorer, fourth_power), [0, 0]);
+        assert_eq!(errors(&scorer, vec_into![]), [100, 100]);
+    }
+
+    #[test]
+    fn reuses_states() {
+        let cases = [1, 2].with_target(|&x| x);
+        let scorer = PushScorer::new(cases, &["x"], top::<i64>, abs_error, penalties()).unwrap();
+        assert_eq!(errors(&scorer, vec_into![VariableName::from("x")]), [0, 0]);
+        // The state left over from the last program must not leak into the
+        // next one.
+        let leftover = vec_into![IntInstruction::Push(5), IntInstruction::Push(5)];
+        assert_eq!(errors(&scorer, leftover), [4, 3]);
+        assert_eq!(errors(&scorer, vec_into![]), [100, 100]);
+        assert_eq!(scorer.states.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn mismatched_input_names() {
+        let cases = [(1, 2)].with_target(|&(x, y)| x + y);
+        assert!(
+            PushScorer::new(cases, &["x"], top::<i64>, abs_error, Penalties::uniform(1)).is_err()
+        );
+    }
+}
@@ -81,6 +81,10 @@ impl Plushy {
         }
     }
 
+    pub fn iter(&self) -> std::slice::Iter<'_, PushGene> {
+        self.genes.iter()
+    }
+
     #[must_use]
     pub fn get_genes(&self) -> Vec<PushGene> {
         self.genes.clone()
@@ -92,6 +96,8 @@ impl Genome for Plushy {
 }
 
 impl Linear for Plushy {
+    type GeneMut<'a> = &'a mut PushGene;
+
     fn size(&self) -> usize {
         self.genes.len()
     }
@@ -122,6 +128,16 @@ impl IntoIterator for Plushy {
     }
 }
 
+impl<'a> IntoIterator for &'a Plushy {
+    type Item = &'a PushGene;
+
+    type IntoIter = std::slice::Iter<'a, PushGene>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
 impl FromIterator<PushGene> for Plushy {
     fn from_iter<T: IntoIterator<Item = PushGene>>(iterable: T) -> Self {
         Self {
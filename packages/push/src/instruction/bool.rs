@@ -2,13 +2,13 @@ use std::ops::Not;
 
 use strum_macros::EnumIter;
 
-use super::{Instruction, PushInstruction, PushInstructionError};
+use super::{Instruction, IntState, PushInstruction, PushInstructionError, PushInteger};
 use crate::{
     error::{InstructionResult, MapInstructionError},
     push_vm::stack::{HasStack, StackPush},
 };
 
-#[derive(Debug, strum_macros::Display, Clone, PartialEq, Eq, EnumIter)]
+#[derive(Debug, strum_macros::Display, Copy, Clone, PartialEq, Eq, EnumIter)]
 #[non_exhaustive]
 pub enum BoolInstruction {
     Push(bool),
@@ -24,9 +24,10 @@ pub enum BoolInstruction {
     // BoolFromFloat,
 }
 
-impl<S> Instruction<S> for BoolInstruction
+impl<S, I> Instruction<S> for BoolInstruction
 where
-    S: Clone + HasStack<bool> + HasStack<i64>,
+    S: IntState<Int = I> + HasStack<I>,
+    I: PushInteger,
 {
     type Error = PushInstructionError;
 
@@ -79,10 +80,10 @@ where
     //   - Hold operations in some kind of queue and apply the at the end when we
     //     know they'll all work
 
-    fn perform(&self, mut state: S) -> InstructionResult<S, Self::Error> {
+    fn perform(&self, state: &mut S) -> InstructionResult<Self::Error> {
         let bool_stack = state.stack_mut::<bool>();
         match self {
-            Self::Push(b) => state.with_push(*b).map_err_into(),
+            Self::Push(b) => state.try_push(*b).map_err_into(),
             Self::Not => bool_stack.pop().map(Not::not).with_stack_push(state),
             Self::And => bool_stack
                 .pop2()
@@ -101,11 +102,11 @@ where
                 .map(|(x, y)| !x || y)
                 .with_stack_push(state),
             Self::FromInt => {
-                let mut state = state.not_full::<bool>().map_err_into()?;
+                state.not_full::<bool>().map_err_into()?;
                 state
-                    .stack_mut::<i64>()
+                    .stack_mut::<I>()
                     .pop()
-                    .map(|i| i != 0)
+                    .map(|i| !i.is_zero())
                     .with_stack_push(state)
             }
         }
@@ -137,7 +138,7 @@ mod property_tests {
         #[test]
         fn ops_do_not_crash(instr in proptest::sample::select(all_instructions()),
                 x in any::<bool>(), y in any::<bool>(), i in any::<i64>()) {
-            let state = PushState::builder()
+            let mut state = PushState::builder()
                 .with_max_stack_size(1000)
                 .with_no_program()
                 .with_bool_values([x, y])
@@ -145,33 +146,33 @@ mod property_tests {
                 .with_int_values([i])
                 .unwrap()
                 .build();
-            instr.perform(state).unwrap();
+            instr.perform(&mut state).unwrap();
         }
 
         #[test]
         fn and_is_correct(x in any::<bool>(), y in any::<bool>()) {
-            let state = PushState::builder()
+            let mut state = PushState::builder()
                 .with_max_stack_size(1000)
                 .with_no_program()
                 .with_bool_values([x, y])
                 .unwrap()
                 .build();
-            let result_state = BoolInstruction::And.perform(state).unwrap();
-            prop_assert_eq!(result_state.bool.size(), 1);
-            prop_assert_eq!(*result_state.bool.top().unwrap(), x && y);
+            BoolInstruction::And.perform(&mut state).unwrap();
+            prop_assert_eq!(state.bool.size(), 1);
+            prop_assert_eq!(*state.bool.top().unwrap(), x && y);
         }
 
         #[test]
         fn implies_is_correct(x in any::<bool>(), y in any::<bool>()) {
-            let state = PushState::builder()
+            let mut state = PushState::builder()
                 .with_max_stack_size(1000)
                 .with_no_program()
                 .with_bool_values([x, y])
                 .unwrap()
                 .build();
-            let result_state = BoolInstruction::Implies.perform(state).unwrap();
-            prop_assert_eq!(result_state.bool.size(), 1);
-            prop_assert_eq!(*result_state.bool.top().unwrap(), !x || y);
+            BoolInstruction::Implies.perform(&mut state).unwrap();
+            prop_assert_eq!(state.bool.size(), 1);
+            prop_assert_eq!(*state.bool.top().unwrap(), !x || y);
         }
     }
 }
@@ -0,0 +1,311 @@
+use std::sync::Arc;
+
+use strum_macros::EnumIter;
+
+use super::{
+    Instruction, IntArithmetic, IntState, NumOpens, PushInstruction, PushInstructionError,
+    PushInteger,
+};
+use crate::{
+    error::{Error, InstructionResult, MapInstructionError},
+    push_vm::{
+        program::PushProgram,
+        stack::{HasStack, Stack, StackError},
+    },
+};
+
+/// The most points (instructions and blocks) that the code instructions
+/// will build into a single program, so that programs that build code
+/// can't make it grow exponentially.
+pub const MAX_CODE_POINTS: usize = 1 << 12;
+
+/// Instructions that treat code as data, using the code stack.
+///
+/// These treat a program on the code stack as a list: a block is the list
+/// of the programs in it, and an instruction is a list of just that
+/// instruction (see [`PushProgram::contents`]).
+#[derive(Debug, strum_macros::Display, Copy, Clone, PartialEq, Eq, EnumIter)]
+#[non_exhaustive]
+pub enum CodeInstruction {
+    /// Move the next program on the exec stack onto the code stack, rather
+    /// than performing it.
+    Quote,
+    Pop,
+    /// Concatenate the top two programs, the top one first.
+    Append,
+    /// The first program in the top program, or an empty block if it's
+    /// empty.
+    Car,
+    /// The top program without its first program.
+    Cdr,
+    /// Add the second program to the front of the top program.
+    Cons,
+    /// Perform the top program, and then pop it.
+    Do,
+    /// Pop the top program and the top integer, and perform the program
+    /// that many times (or not at all if the integer isn't positive).
+    ///
+    /// Unlike Push's `CODE.DO*TIMES`, this doesn't push the index of each
+    /// iteration onto the integer stack, so the program is performed the
+    /// same way every time.
+    Repeat,
+    /// Pop the top boolean and the top two programs, and perform the second
+    /// program if the boolean is true, and the top one if it's false.
+    If,
+    /// The number of programs in the top program.
+    Length,
+    /// Whether the second program is one of the programs in the top
+    /// program.
+    Member,
+    /// The program in the top program whose position is the absolute value
+    /// of the top integer, wrapping around at the end, or an empty block if
+    /// it's empty.
+    Nth,
+    /// Replace every occurrence of the second program in the top program
+    /// with the third program.
+    Subst,
+}
+
+impl From<CodeInstruction> for PushInstruction {
+    fn from(instr: CodeInstruction) -> Self {
+        Self::Code(instr)
+    }
+}
+
+impl NumOpens for CodeInstruction {
+    fn num_opens(&self) -> usize {
+        match self {
+            Self::Quote => 1,
+            _ => 0,
+        }
+    }
+}
+
+#[derive(thiserror::Error, Debug, Clone, Eq, PartialEq)]
+pub enum CodeInstructionError {
+    #[error("Instruction {op} would build a program with more than {MAX_CODE_POINTS} points")]
+    TooLarge { op: CodeInstruction },
+}
+
+/// A state with a code stack that [`CodeInstruction`]s can be performed on.
+///
+/// The code stack holds the same type of values as the exec stack, which is
+/// the state's `HasStack<PushProgram>` stack, so it's reached through this
+/// trait instead. The code instructions also use the state's integers (see
+/// [`IntState`]) and booleans.
+pub trait CodeState: HasStack<PushProgram> + HasStack<bool> {
+    fn code(&self) -> &Stack<PushProgram>;
+    fn code_mut(&mut self) -> &mut Stack<PushProgram>;
+}
+
+impl CodeInstruction {
+    /// Check that `program` isn't bigger than [`MAX_CODE_POINTS`].
+    fn check_points(self, program: &PushProgram) -> InstructionResult<PushInstructionError> {
+        match program.points_within(MAX_CODE_POINTS) {
+            Some(_) => Ok(()),
+            None => Err(Error::recoverable(CodeInstructionError::TooLarge {
+                op: self,
+            })),
+        }
+    }
+}
+
+/// Replace the top `num_to_replace` programs on the code stack with
+/// `program`.
+///
+/// A program built from the ones it replaces can be larger than them, e.g.,
+/// `Code-Subst` can put copies of a program in many places, so this checks
+/// the code stack's size budget before removing them (see
+/// [`Stack::replace`]). If `program` doesn't fit, the operands are left on
+/// the stack.
+fn replace_code<S: CodeState>(
+    num_to_replace: usize,
+    program: PushProgram,
+    state: &mut S,
+) -> InstructionResult<PushInstructionError> {
+    state
+        .code_mut()
+        .replace(num_to_replace, program)
+        .map_err(Error::fatal)
+}
+
+/// Push `programs` onto the exec stack, in order, so that the last of them
+/// is performed first, checking first that there's room for all of them.
+fn push_exec<S, I>(programs: I, state: &mut S) -> InstructionResult<PushInstructionError>
+where
+    S: CodeState,
+    I: IntoIterator<Item = PushProgram>,
+    I::IntoIter: ExactSizeIterator,
+{
+    let programs = programs.into_iter();
+    let exec = state.stack_mut::<PushProgram>();
+    if exec.size() + programs.len() > exec.max_stack_size() {
+        return Err(Error::fatal(StackError::Overflow {
+            stack_type: std::any::type_name::<PushProgram>(),
+        }));
+    }
+    for program in programs {
+        exec.push(program).map_err(Error::fatal)?;
+    }
+    Ok(())
+}
+
+/// `n` as a count, where negative numbers count as zero and numbers that
+/// are too large for a `usize` count as `usize::MAX`.
+fn count<I: PushInteger>(n: &I) -> usize {
+    match n.to_i64() {
+        Some(n) => usize::try_from(n).unwrap_or(0),
+        None if *n > I::from_i64(0) => usize::MAX,
+        None => 0,
+    }
+}
+
+/// Check that there's room on the `T` stack for a result.
+fn check_not_full<S: HasStack<T>, T>(state: &S) -> InstructionResult<PushInstructionError> {
+    state.not_full::<T>().map_err_into()
+}
+
+impl<S, I> Instruction<S> for CodeInstruction
+where
+    S: CodeState + IntState<Int = I> + HasStack<I>,
+    I: PushInteger,
+{
+    type Error = PushInstructionError;
+
+    #[allow(clippy::too_many_lines)]
+    fn perform(&self, state: &mut S) -> InstructionResult<Self::Error> {
+        match self {
+            Self::Quote => {
+                let program = state
+                    .stack::<PushProgram>()
+                    .next_program()
+                    .map_err(Error::recoverable)?;
+                self.check_points(program)?;
+                if state.code().is_full() {
+                    return Err(Error::fatal(StackError::Overflow { stack_type: "code" }));
+                }
+                let program = state
+                    .stack_mut::<PushProgram>()
+                    .pop_program()
+                    .map_err(Error::fatal)?
+                    .without_cursor();
+                state.code_mut().push(program).map_err(Error::fatal)
+            }
+            Self::Pop => state
+                .code_mut()
+                .pop()
+                .map(|_| ())
+                .map_err(Error::recoverable),
+            Self::Append | Self::Cons => {
+                let (x, y) = state.code().top2().map_err(Error::recoverable)?;
+                let program = match self {
+                    Self::Append => {
+                        PushProgram::block(x.contents().iter().chain(y.contents()).cloned())
+                    }
+                    Self::Cons => {
+                        PushProgram::block(std::iter::once(y).chain(x.contents()).cloned())
+                    }
+                    _ => unreachable!("We failed to handle a code-building instruction: {self:?}"),
+                };
+                self.check_points(&program)?;
+                replace_code(2, program, state)
+            }
+            Self::Car | Self::Cdr => {
+                let x = state.code().top().map_err(Error::recoverable)?;
+                let contents = x.contents();
+                let program = match self {
+                    Self::Car => contents
+                        .first()
+                        .cloned()
+                        .unwrap_or_else(|| PushProgram::block([])),
+                    Self::Cdr => PushProgram::block(contents.iter().skip(1).cloned()),
+                    _ => unreachable!("We failed to handle a code list instruction: {self:?}"),
+                };
+                replace_code(1, program, state)
+            }
+            Self::Do => {
+                let program = state.code().top().map_err(Error::recoverable)?.clone();
+                push_exec([Self::Pop.into(), program], state)
+            }
+            Self::Repeat => {
+                let program = state.code().top().map_err(Error::recoverable)?;
+                let times = count(state.stack::<I>().top().map_err(Error::recoverable)?);
+                // The block would have `times` programs, so we don't build
+                // one that's too large, even if the exec stack has room for
+                // it.
+                if times > MAX_CODE_POINTS {
+                    return Err(Error::recoverable(CodeInstructionError::TooLarge {
+                        op: *self,
+                    }));
+                }
+                // Performing the block would push all its programs onto the
+                // exec stack, and pushing the block charges its points to the
+                // exec stack's size budget, so this fails the same way that
+                // would, but without building the block.
+                let block_size = times.saturating_mul(program.size()).saturating_add(1);
+                let exec = state.stack::<PushProgram>();
+                if exec.size() + times > exec.max_stack_size()
+                    || exec
+                        .size_budget()
+                        .is_some_and(|budget| times > 0 && !budget.has_room_for(block_size))
+                {
+                    return Err(Error::fatal(StackError::Overflow {
+                        stack_type: std::any::type_name::<PushProgram>(),
+                    }));
+                }
+                let block = (times > 0)
+                    .then(|| PushProgram::Block(Arc::from(vec![program.clone(); times])));
+                state.code_mut().discard(1).map_err(Error::fatal)?;
+                state.stack_mut::<I>().discard(1).map_err(Error::fatal)?;
+                push_exec(block, state)
+            }
+            Self::If => {
+                let (x, y) = state.code().top2().map_err(Error::recoverable)?;
+                let condition = *state.stack::<bool>().top().map_err(Error::recoverable)?;
+                let program = if condition { y.clone() } else { x.clone() };
+                state.code_mut().discard(2).map_err(Error::fatal)?;
+                state.stack_mut::<bool>().discard(1).map_err(Error::fatal)?;
+                push_exec([program], state)
+            }
+            Self::Length => {
+                let x = state.code().top().map_err(Error::recoverable)?;
+                let length = I::from_i64(i64::try_from(x.contents().len()).unwrap_or(i64::MAX));
+                check_not_full::<_, I>(state)?;
+                state.code_mut().discard(1).map_err(Error::fatal)?;
+                state.try_push(length).map_err_into()
+            }
+            Self::Member => {
+                let (x, y) = state.code().top2().map_err(Error::recoverable)?;
+                let is_member = x.contents().contains(y);
+                check_not_full::<_, bool>(state)?;
+                state.code_mut().discard(2).map_err(Error::fatal)?;
+                state.try_push(is_member).map_err_into()
+            }
+            Self::Nth => {
+                let x = state.code().top().map_err(Error::recoverable)?;
+                let n = state.stack::<I>().top().map_err(Error::recoverable)?;
+                let contents = x.contents();
+                // The remainder has the sign of `n`, so its absolute value
+                // is the position.
+                let program = i64::try_from(contents.len())
+                    .ok()
+                    .filter(|&len| len > 0)
+                    .and_then(|len| n.rem(&I::from_i64(len), IntArithmetic::Wrapping))
+                    .and_then(|index| index.to_i64())
+                    .and_then(|index| usize::try_from(index.unsigned_abs()).ok())
+                    .and_then(|index| contents.get(index))
+                    .cloned()
+                    .unwrap_or_else(|| PushProgram::block([]));
+                state.stack_mut::<I>().discard(1).map_err(Error::fatal)?;
+                replace_code(1, program, state)
+            }
+            Self::Subst => {
+                let (x, y, z) = state.code().top3().map_err(Error::recoverable)?;
+                let program = x.substitute(y, z, MAX_CODE_POINTS).ok_or_else(|| {
+                    Error::recoverable(CodeInstructionError::TooLarge { op: *self })
+                })?;
+                replace_code(3, program, state)
+            }
+        }
+    }
+}
@@ -1,3 +1,5 @@
+use strum_macros::EnumIter;
+
 use super::NumOpens;
 use crate::instruction::PushInstruction;
 
@@ -13,7 +15,7 @@ use crate::instruction::PushInstruction;
  * followed by another copy of exec_while.
  */
 
-#[derive(Debug, Clone, Eq, PartialEq)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, EnumIter)]
 pub enum ExecInstruction {
     Dup,
     IfElse,
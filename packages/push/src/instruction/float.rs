@@ -35,13 +35,13 @@ impl From<FloatInstruction> for PushInstruction {
 
 impl<S> Instruction<S> for FloatInstruction
 where
-    S: Clone + HasStack<OrderedFloat<f64>> + HasStack<bool>,
+    S: HasStack<OrderedFloat<f64>> + HasStack<bool>,
 {
     type Error = PushInstructionError;
 
-    fn perform(&self, mut state: S) -> InstructionResult<S, Self::Error> {
+    fn perform(&self, state: &mut S) -> InstructionResult<Self::Error> {
         match self {
-            Self::Push(f) => state.with_push(*f).map_err_into(),
+            Self::Push(f) => state.try_push(*f).map_err_into(),
 
             // All these instructions pop at least one value from the float stack, so we're
             // guaranteed that there will be space for the result. So we don't have to check that
@@ -71,12 +71,9 @@ where
 
             Self::Dup => {
                 if state.stack::<OrderedFloat<f64>>().is_full() {
-                    return Err(Error::fatal(
-                        state,
-                        StackError::Overflow {
-                            stack_type: "float",
-                        },
-                    ));
+                    return Err(Error::fatal(StackError::Overflow {
+                        stack_type: "float",
+                    }));
                 }
                 let float_stack: &mut Stack<OrderedFloat<f64>> =
                     state.stack_mut::<OrderedFloat<f64>>();
@@ -92,11 +89,11 @@ where
 
 impl FloatInstruction {
     fn binary_arithmetic<S>(
-        mut state: S,
+        state: &mut S,
         op: impl FnOnce(OrderedFloat<f64>, OrderedFloat<f64>) -> OrderedFloat<f64>,
-    ) -> Result<S, Error<S, PushInstructionError>>
+    ) -> InstructionResult<PushInstructionError>
     where
-        S: Clone + HasStack<OrderedFloat<f64>>,
+        S: HasStack<OrderedFloat<f64>>,
     {
         let float_stack = state.stack_mut::<OrderedFloat<f64>>();
         float_stack
@@ -107,17 +104,14 @@ impl FloatInstruction {
     }
 
     fn binary_predicate<S>(
-        mut state: S,
+        state: &mut S,
         op: impl FnOnce(&OrderedFloat<f64>, &OrderedFloat<f64>) -> bool,
-    ) -> Result<S, Error<S, PushInstructionError>>
+    ) -> InstructionResult<PushInstructionError>
     where
-        S: Clone + HasStack<OrderedFloat<f64>> + HasStack<bool>,
+        S: HasStack<OrderedFloat<f64>> + HasStack<bool>,
     {
         if state.stack::<bool>().is_full() {
-            return Err(Error::fatal(
-                state,
-                StackError::Overflow { stack_type: "bool" },
-            ));
+            return Err(Error::fatal(StackError::Overflow { stack_type: "bool" }));
         }
         let float_stack: &mut Stack<OrderedFloat<f64>> = state.stack_mut::<OrderedFloat<f64>>();
         float_stack
@@ -125,6 +119,6 @@ impl FloatInstruction {
             .map_err(PushInstructionError::from)
             .map(|(x, y)| op(x, y))
             .with_stack_push(state)
-            .with_stack_discard::<OrderedFloat<f64>>(1)
+            .with_stack_discard::<OrderedFloat<f64>>(1, state)
     }
 }
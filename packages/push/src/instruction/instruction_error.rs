@@ -1,15 +1,67 @@
-use super::IntInstructionError;
+use super::{variable_name::VariableName, CodeInstructionError, IntInstructionError};
 use crate::push_vm::stack::StackError;
 
 /// An error that can occur when performing a `PushInstruction`.
-#[derive(thiserror::Error, Debug, Eq, PartialEq)]
+#[derive(thiserror::Error, Debug, Clone, Eq, PartialEq)]
 pub enum PushInstructionError {
     /// Stack errors can be things like stack over- or underflows.
     #[error(transparent)]
     StackError(#[from] StackError),
     #[error("Exceeded the maximum step limit {step_limit}")]
     StepLimitExceeded { step_limit: usize },
+    /// The program used an input variable that wasn't bound to a value.
+    #[error("The input variable {name} isn't bound to a value")]
+    UndefinedInput { name: VariableName },
+    /// A compiled program used an input slot that the state doesn't have.
+    #[error("There is no input slot {slot}")]
+    UndefinedInputSlot { slot: usize },
     /// Int errors can be things like integer overflows.
     #[error(transparent)]
     Int(#[from] IntInstructionError),
+    /// Code errors are when code instructions would build programs that
+    /// are too large.
+    #[error(transparent)]
+    Code(#[from] CodeInstructionError),
+}
+
+/// The kinds of [`PushInstructionError`], without their details, e.g., for
+/// counting how often each kind of error happens. The number of kinds is
+/// [`EnumCount::COUNT`](strum::EnumCount::COUNT).
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, strum_macros::EnumIter, strum_macros::EnumCount,
+)]
+pub enum ErrorKind {
+    StackUnderflow,
+    StackOverflow,
+    StepLimitExceeded,
+    UndefinedInput,
+    UndefinedInputSlot,
+    IntOverflow,
+    CodeTooLarge,
+}
+
+impl ErrorKind {
+    /// The position of this kind in [`ErrorKind::iter()`](strum::IntoEnumIterator::iter).
+    #[must_use]
+    // The variants have no fields or explicit discriminants, so this is
+    // lossless and matches their order.
+    #[allow(clippy::as_conversions)]
+    pub const fn index(self) -> usize {
+        self as usize
+    }
+}
+
+impl PushInstructionError {
+    #[must_use]
+    pub const fn kind(&self) -> ErrorKind {
+        match self {
+            Self::StackError(StackError::Underflow { .. }) => ErrorKind::StackUnderflow,
+            Self::StackError(StackError::Overflow { .. }) => ErrorKind::StackOverflow,
+            Self::StepLimitExceeded { .. } => ErrorKind::StepLimitExceeded,
+            Self::UndefinedInput { .. } => ErrorKind::UndefinedInput,
+            Self::UndefinedInputSlot { .. } => ErrorKind::UndefinedInputSlot,
+            Self::Int(IntInstructionError::Overflow { .. }) => ErrorKind::IntOverflow,
+            Self::Code(CodeInstructionError::TooLarge { .. }) => ErrorKind::CodeTooLarge,
+        }
+    }
 }
@@ -1,11 +1,13 @@
-use std::ops::Neg;
-
+use ordered_float::OrderedFloat;
 use strum_macros::EnumIter;
 
-use super::{Instruction, PushInstruction, PushInstructionError};
+use super::{
+    integer::{IntArithmetic, PushInteger},
+    Instruction, PushInstruction, PushInstructionError,
+};
 use crate::{
     error::{Error, InstructionResult, MapInstructionError},
-    push_vm::stack::{HasStack, Stack, StackDiscard, StackError, StackPush},
+    push_vm::stack::{HasStack, StackDiscard, StackError, StackPush},
 };
 
 #[derive(Debug, strum_macros::Display, Copy, Clone, PartialEq, Eq, EnumIter)]
@@ -26,10 +28,10 @@ pub enum IntInstruction {
     Mod,
     Power,
     Square,
-    // We can't easily convert from i64 to f64, etc.,
-    // so we might need to do the log(n) integer
-    // implementation of sqrt.
-    // Sqrt,
+    /// The integer square root of the absolute value.
+    Sqrt,
+    /// -1, 0, or 1 depending on the sign.
+    Sign,
     IsEven,
     IsOdd,
     Equal,
@@ -39,7 +41,20 @@ pub enum IntInstruction {
     GreaterThan,
     GreaterThanEqual,
 
+    BitAnd,
+    BitOr,
+    BitXor,
+    BitNot,
+    /// Shift the top value left by the second value, or right if the
+    /// second value is negative.
+    ShiftLeft,
+    /// Shift the top value right by the second value, or left if the
+    /// second value is negative.
+    ShiftRight,
+
     FromBoolean,
+    /// Convert the top float to an integer, rounding towards zero.
+    FromFloat,
 }
 
 impl From<IntInstruction> for PushInstruction {
@@ -48,7 +63,7 @@ impl From<IntInstruction> for PushInstruction {
     }
 }
 
-#[derive(thiserror::Error, Debug, Eq, PartialEq)]
+#[derive(thiserror::Error, Debug, Clone, Eq, PartialEq)]
 pub enum IntInstructionError {
     #[error("Integer arithmetic overflow for instruction {op}")]
     Overflow {
@@ -60,156 +75,123 @@ pub enum IntInstructionError {
     },
 }
 
-impl<S> Instruction<S> for IntInstruction
+/// A state that [`IntInstruction`]s can be performed on.
+///
+/// Its integer stack
+/// holds values of type [`IntState::Int`], so a state with a `Stack<BigInt>`
+/// gets arbitrary-precision integers, and it chooses how overflow is
+/// handled.
+pub trait IntState: HasStack<bool> + HasStack<OrderedFloat<f64>> {
+    type Int: PushInteger;
+
+    fn int_arithmetic(&self) -> IntArithmetic;
+}
+
+impl IntInstruction {
+    /// What to do when this instruction overflowed with the given
+    /// arithmetic mode.
+    fn overflowed(self, arithmetic: IntArithmetic) -> InstructionResult<PushInstructionError> {
+        if arithmetic == IntArithmetic::NoopOnOverflow {
+            Ok(())
+        } else {
+            Err(Error::recoverable(IntInstructionError::Overflow {
+                op: self,
+            }))
+        }
+    }
+
+    /// Replace the top `num_to_replace` integers with `result`, where `None`
+    /// means that the operation overflowed.
+    fn replace_ints<S, I>(
+        self,
+        result: Result<Option<I>, StackError>,
+        num_to_replace: usize,
+        state: &mut S,
+    ) -> InstructionResult<PushInstructionError>
+    where
+        S: IntState + HasStack<I>,
+    {
+        match result {
+            Ok(Some(value)) => state.try_replace(num_to_replace, value).map_err_into(),
+            Ok(None) => self.overflowed(state.int_arithmetic()),
+            Err(error) => Err(Error::recoverable(error)),
+        }
+    }
+}
+
+impl<S, I> Instruction<S> for IntInstruction
 where
-    S: Clone + HasStack<i64> + HasStack<bool>,
+    S: IntState<Int = I> + HasStack<I>,
+    I: PushInteger,
 {
     type Error = PushInstructionError;
 
-    #[allow(clippy::too_many_lines, clippy::cognitive_complexity)]
-    #[allow(unreachable_code, clippy::let_unit_value)] // Remove this
-    fn perform(&self, mut state: S) -> InstructionResult<S, Self::Error> {
+    #[allow(clippy::too_many_lines)]
+    fn perform(&self, state: &mut S) -> InstructionResult<Self::Error> {
+        let arithmetic = state.int_arithmetic();
         match self {
-            Self::Push(_)
-            | Self::Negate
+            Self::Push(i) => state.try_push(I::from_i64(*i)).map_err_into(),
+
+            // All these instructions pop at least one value from the integer stack, so
+            // we're guaranteed that there will be space for the result.
+            // So we don't have to check that
+            // any stacks are full before we start.
+            Self::Negate
             | Self::Abs
             | Self::Inc
             | Self::Dec
             | Self::Square
-            | Self::Add
+            | Self::Sqrt
+            | Self::Sign
+            | Self::BitNot => {
+                let result = state.stack::<I>().top().map(|x| match self {
+                    Self::Negate => x.neg(arithmetic),
+                    Self::Abs => x.abs(arithmetic),
+                    Self::Inc => x.add(&I::from_i64(1), arithmetic),
+                    Self::Dec => x.sub(&I::from_i64(1), arithmetic),
+                    Self::Square => x.mul(x, arithmetic),
+                    Self::Sqrt => Some(x.sqrt()),
+                    Self::Sign => Some(x.sign()),
+                    Self::BitNot => Some(x.bit_not()),
+                    _ => unreachable!("We failed to handle a unary Int instruction: {self:?}"),
+                });
+                self.replace_ints(result, 1, state)
+            }
+
+            Self::Add
             | Self::Subtract
             | Self::Multiply
             | Self::ProtectedDivide
             | Self::Mod
             | Self::Power
             | Self::Min
-            | Self::Max => {
-                // All these instructions pop at least one value from the integer stack, so
-                // we're guaranteed that there will be space for the result.
-                // So we don't have to check that
-                // any stacks are full before we start.
-                let int_stack = state.stack_mut::<i64>();
-                match self {
-                    Self::Push(i) => state.with_push(*i).map_err_into(),
-                    Self::Negate => int_stack.top().map(Neg::neg).with_stack_replace(1, state),
-                    Self::Abs => int_stack
-                        .top()
-                        .copied()
-                        .map(i64::abs)
-                        .with_stack_replace(1, state),
-
-                    // This works, but is going to be nasty after we repeat a lot. There should
-                    // perhaps be another trait method somewhere that eliminates a lot of this
-                    // boilerplate.
-                    Self::Inc => int_stack
-                        .top()
-                        .map_err(PushInstructionError::from)
-                        .map(|&i| i.checked_add(1))
-                        .and_then(|v| {
-                            v.ok_or(IntInstructionError::Overflow { op: *self })
-                                .map_err(Into::into)
-                        })
-                        .with_stack_replace(1, state),
-
-                    Self::Dec => int_stack
-                        .top()
-                        .map_err(PushInstructionError::from)
-                        .map(|&i| i.checked_sub(1))
-                        .and_then(|v| {
-                            v.ok_or(IntInstructionError::Overflow { op: *self })
-                                .map_err(Into::into)
-                        })
-                        .with_stack_replace(1, state),
-
-                    Self::Square => int_stack
-                        .top()
-                        .map_err(PushInstructionError::from)
-                        .map(|&x| x.checked_mul(x))
-                        .and_then(|v| {
-                            v.ok_or(IntInstructionError::Overflow { op: *self })
-                                .map_err(Into::into)
-                        })
-                        .with_stack_replace(1, state),
-
-                    Self::Add => int_stack
-                        .top2()
-                        .map_err(PushInstructionError::from)
-                        .map(|(&x, &y)| x.checked_add(y))
-                        .and_then(|v| {
-                            v.ok_or(IntInstructionError::Overflow { op: *self })
-                                .map_err(Into::into)
-                        })
-                        .with_stack_replace(2, state),
-
-                    Self::Subtract => int_stack
-                        .top2()
-                        .map_err(PushInstructionError::from)
-                        .map(|(&x, &y)| x.checked_sub(y))
-                        .and_then(|v| {
-                            v.ok_or(IntInstructionError::Overflow { op: *self })
-                                .map_err(Into::into)
-                        })
-                        .with_stack_replace(2, state),
-
-                    Self::Multiply => int_stack
-                        .top2()
-                        .map_err(PushInstructionError::from)
-                        .map(|(x, y)| (*x).checked_mul(*y))
-                        .and_then(|v| {
-                            v.ok_or(IntInstructionError::Overflow { op: *self })
-                                .map_err(Into::into)
-                        })
-                        .with_stack_replace(2, state),
-
-                    Self::ProtectedDivide => int_stack
-                        .top2()
-                        .map_err(PushInstructionError::from)
-                        .map(|(&x, &y)| if y == 0 { Some(1) } else { x.checked_div(y) })
-                        .and_then(|v| {
-                            v.ok_or(IntInstructionError::Overflow { op: *self })
-                                .map_err(Into::into)
-                        })
-                        .with_stack_replace(2, state),
-
-                    Self::Mod => int_stack
-                        .top2()
-                        .map_err(PushInstructionError::from)
-                        .map(|(&x, &y)| if y == 0 { Some(0) } else { x.checked_rem(y) })
-                        .and_then(|v| {
-                            v.ok_or(IntInstructionError::Overflow { op: *self })
-                                .map_err(Into::into)
-                        })
-                        .with_stack_replace(2, state),
-
-                    Self::Power => int_stack
-                        .top2()
-                        .map_err(PushInstructionError::from)
-                        .map(|(&x, &y)| {
-                            let x: i32 = x.try_into().ok()?;
-                            let y: u32 = y.try_into().ok()?;
-                            x.checked_pow(y).map(i64::from)
-                        })
-                        .and_then(|v| {
-                            v.ok_or(IntInstructionError::Overflow { op: *self })
-                                .map_err(Into::into)
-                        })
-                        .with_stack_replace(2, state),
-
-                    Self::Min => int_stack
-                        .top2()
-                        .map_err(PushInstructionError::from)
-                        .map(|(&x, &y)| x.min(y))
-                        .with_stack_replace(2, state),
-
-                    Self::Max => int_stack
-                        .top2()
-                        .map_err(PushInstructionError::from)
-                        .map(|(&x, &y)| x.max(y))
-                        .with_stack_replace(2, state),
+            | Self::Max
+            | Self::BitAnd
+            | Self::BitOr
+            | Self::BitXor
+            | Self::ShiftLeft
+            | Self::ShiftRight => {
+                let result = state.stack::<I>().top2().map(|(x, y)| match self {
+                    Self::Add => x.add(y, arithmetic),
+                    Self::Subtract => x.sub(y, arithmetic),
+                    Self::Multiply => x.mul(y, arithmetic),
+                    Self::ProtectedDivide if y.is_zero() => Some(I::from_i64(1)),
+                    Self::ProtectedDivide => x.div(y, arithmetic),
+                    Self::Mod if y.is_zero() => Some(I::from_i64(0)),
+                    Self::Mod => x.rem(y, arithmetic),
+                    Self::Power => x.pow(y, arithmetic),
+                    Self::Min => Some(x.min(y).clone()),
+                    Self::Max => Some(x.max(y).clone()),
+                    Self::BitAnd => Some(x.bit_and(y)),
+                    Self::BitOr => Some(x.bit_or(y)),
+                    Self::BitXor => Some(x.bit_xor(y)),
+                    Self::ShiftLeft => x.shift_left(y, arithmetic),
+                    Self::ShiftRight => x.shift_right(y, arithmetic),
                     _ => {
                         unreachable!("We failed to handle an arithmetic Int instruction: {self:?}")
                     }
-                }
+                });
+                self.replace_ints(result, 2, state)
             }
             Self::IsEven
             | Self::IsOdd
@@ -224,70 +206,67 @@ where
                 // the instruction, we need to check for the case that the boolean stack is
                 // already full, and return an `Overflow` error if it is.
                 if state.stack::<bool>().is_full() {
-                    return Err(Error::fatal(
-                        state,
-                        StackError::Overflow { stack_type: "bool" },
-                    ));
+                    return Err(Error::fatal(StackError::Overflow { stack_type: "bool" }));
                 }
-                let int_stack: &mut Stack<i64> = state.stack_mut::<i64>();
+                let int_stack = state.stack::<I>();
                 match self {
                     // TODO: Write a test for IsEven that makes sure
                     // all the stack manipulation is correct.
                     Self::IsEven => int_stack
                         .top()
                         .map_err(PushInstructionError::from)
-                        .map(|&x| x % 2 == 0)
+                        .map(PushInteger::is_even)
                         .with_stack_push(state)
-                        .with_stack_discard::<i64>(1),
+                        .with_stack_discard::<I>(1, state),
 
                     Self::IsOdd => int_stack
                         .top()
                         .map_err(PushInstructionError::from)
-                        .map(|&x| x % 2 == 1)
+                        .map(|x| !x.is_even())
                         .with_stack_push(state)
-                        .with_stack_discard::<i64>(1),
+                        .with_stack_discard::<I>(1, state),
 
                     Self::Equal => int_stack
                         .top2()
                         .map_err(PushInstructionError::from)
-                        .map(|(&x, &y)| x == y)
+                        .map(|(x, y)| x == y)
                         .with_stack_push(state)
-                        .with_stack_discard::<i64>(1),
+                        .with_stack_discard::<I>(1, state),
 
                     Self::NotEqual => int_stack
                         .top2()
                         .map_err(PushInstructionError::from)
-                        .map(|(&x, &y)| x != y)
+                        .map(|(x, y)| x != y)
                         .with_stack_push(state)
-                        .with_stack_discard::<i64>(1),
+                        .with_stack_discard::<I>(1, state),
 
                     Self::LessThan => int_stack
                         .top2()
                         .map_err(PushInstructionError::from)
-                        .map(|(&x, &y)| x < y)
+                        .map(|(x, y)| x < y)
                         .with_stack_push(state)
-                        .with_stack_discard::<i64>(1),
+                        .with_stack_discard::<I>(1, state),
 
                     Self::LessThanEqual => int_stack
                         .top2()
                         .map_err(PushInstructionError::from)
-                        .map(|(&x, &y)| x <= y)
+                        .map(|(x, y)| x <= y)
                         .with_stack_push(state)
-                        .with_stack_discard::<i64>(1),
+                        .with_stack_discard::<I>(1, state),
 
                     Self::GreaterThan => int_stack
                         .top2()
                         .map_err(PushInstructionError::from)
-                        .map(|(&x, &y)| x > y)
+                        .map(|(x, y)| x > y)
                         .with_stack_push(state)
-                        .with_stack_discard::<i64>(1),
+                        .with_stack_discard::<I>(1, state),
 
                     Self::GreaterThanEqual => int_stack
                         .top2()
                         .map_err(PushInstructionError::from)
-                        .map(|(&x, &y)| x >= y)
+                        .map(|(x, y)| x >= y)
                         .with_stack_push(state)
-                        .with_stack_discard::<i64>(1),
+                        .with_stack_discard::<I>(1, state),
                     _ => unreachable!(
                         "We failed to implement a boolean-valued operation on integers: {self:?}"
                     ),
@@ -298,10 +277,22 @@ where
                 bool_stack
                     .top()
                     .map_err(PushInstructionError::from)
-                    .map(|&b| i64::from(b))
+                    .map(|&b| I::from_i64(i64::from(b)))
                     .with_stack_push(state)
-                    .with_stack_discard::<bool>(1)
+                    .with_stack_discard::<bool>(1, state)
             }
+            Self::FromFloat => match state
+                .stack::<OrderedFloat<f64>>()
+                .top()
+                .map(|x| I::from_f64(x.into_inner(), arithmetic))
+            {
+                Ok(Some(value)) => state
+                    .try_push(value)
+                    .map_err_into()
+                    .with_stack_discard::<OrderedFloat<f64>>(1, state),
+                Ok(None) => self.overflowed(arithmetic),
+                Err(error) => Err(Error::recoverable(error)),
+            },
         }
     }
 }
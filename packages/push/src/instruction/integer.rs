@@ -0,0 +1,512 @@
+use num_bigint::BigInt;
+use num_traits::{FromPrimitive, Signed, ToPrimitive, Zero};
+
+/// How [`IntInstruction`](super::IntInstruction)s handle results that don't
+/// fit in the integer type.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum IntArithmetic {
+    /// Fail with a recoverable
+    /// [`IntInstructionError::Overflow`](super::IntInstructionError::Overflow)
+    /// error, leaving the stacks unchanged.
+    #[default]
+    Checked,
+    /// Wrap around at the boundary of the integer type.
+    Wrapping,
+    /// Clamp the result to the smallest or largest value of the integer
+    /// type.
+    Saturating,
+    /// Leave the stacks unchanged without reporting an error.
+    NoopOnOverflow,
+}
+
+impl IntArithmetic {
+    /// Combine the `checked` result of an operation with its wrapping and
+    /// saturating versions, which are only computed if `checked` overflowed.
+    fn apply<T>(
+        self,
+        checked: Option<T>,
+        wrapping: impl FnOnce() -> T,
+        saturating: impl FnOnce() -> T,
+    ) -> Option<T> {
+        checked.or_else(|| match self {
+            Self::Wrapping => Some(wrapping()),
+            Self::Saturating => Some(saturating()),
+            Self::Checked | Self::NoopOnOverflow => None,
+        })
+    }
+}
+
+/// The integer types that [`IntInstruction`](super::IntInstruction)s can
+/// work on.
+///
+/// The operations that can overflow return `None` when they do, after
+/// taking the [`IntArithmetic`] mode into account. They also return `None`
+/// when there's no sensible result (e.g., a negative exponent), even when
+/// wrapping or saturating.
+pub trait PushInteger: Clone + Ord {
+    fn from_i64(i: i64) -> Self;
+
+    /// Convert `x` to an integer, rounding towards zero. `NaN` and values
+    /// out of range overflow; the wrapping mode saturates for these since
+    /// wrapping doesn't make much sense for floats.
+    fn from_f64(x: f64, arithmetic: IntArithmetic) -> Option<Self>;
+
+    /// `self` as an `i64`, or `None` if it's out of range.
+    fn to_i64(&self) -> Option<i64>;
+
+    fn is_zero(&self) -> bool;
+    fn is_even(&self) -> bool;
+
+    /// -1, 0, or 1 depending on the sign of `self`.
+    #[must_use]
+    fn sign(&self) -> Self;
+
+    fn add(&self, other: &Self, arithmetic: IntArithmetic) -> Option<Self>;
+    fn sub(&self, other: &Self, arithmetic: IntArithmetic) -> Option<Self>;
+    fn mul(&self, other: &Self, arithmetic: IntArithmetic) -> Option<Self>;
+    fn neg(&self, arithmetic: IntArithmetic) -> Option<Self>;
+    fn abs(&self, arithmetic: IntArithmetic) -> Option<Self>;
+
+    /// `self / other` rounding towards zero, where `other` is not zero.
+    fn div(&self, other: &Self, arithmetic: IntArithmetic) -> Option<Self>;
+
+    /// The remainder of `self / other`, where `other` is not zero.
+    fn rem(&self, other: &Self, arithmetic: IntArithmetic) -> Option<Self>;
+
+    fn pow(&self, exponent: &Self, arithmetic: IntArithmetic) -> Option<Self>;
+
+    /// The integer square root of the absolute value of `self`.
+    #[must_use]
+    fn sqrt(&self) -> Self;
+
+    #[must_use]
+    fn bit_and(&self, other: &Self) -> Self;
+    #[must_use]
+    fn bit_or(&self, other: &Self) -> Self;
+    #[must_use]
+    fn bit_xor(&self, other: &Self) -> Self;
+    #[must_use]
+    fn bit_not(&self) -> Self;
+
+    /// Shift `self` left by `amount` bits, or right if `amount` is negative.
+    /// This overflows if any bits are lost off the left.
+    fn shift_left(&self, amount: &Self, arithmetic: IntArithmetic) -> Option<Self>;
+
+    /// Shift `self` right by `amount` bits, rounding down, or left if
+    /// `amount` is negative.
+    fn shift_right(&self, amount: &Self, arithmetic: IntArithmetic) -> Option<Self>;
+}
+
+fn i64_shift_left(x: i64, amount: u64, arithmetic: IntArithmetic) -> Option<i64> {
+    let small_amount = u32::try_from(amount).ok().filter(|&n| n < i64::BITS);
+    let checked = if x == 0 {
+        Some(0)
+    } else {
+        small_amount
+            .map(|n| x << n)
+            .filter(|&shifted| small_amount.is_some_and(|n| shifted >> n == x))
+    };
+    arithmetic.apply(
+        checked,
+        || small_amount.map_or(0, |n| x << n),
+        || if x < 0 { i64::MIN } else { i64::MAX },
+    )
+}
+
+fn i64_shift_right(x: i64, amount: u64) -> i64 {
+    u32::try_from(amount)
+        .ok()
+        .filter(|&n| n < i64::BITS)
+        .map_or(x >> (i64::BITS - 1), |n| x >> n)
+}
+
+impl PushInteger for i64 {
+    fn from_i64(i: i64) -> Self {
+        i
+    }
+
+    fn to_i64(&self) -> Option<i64> {
+        Some(*self)
+    }
+
+    fn from_f64(x: f64, arithmetic: IntArithmetic) -> Option<Self> {
+        let saturating = || {
+            if x.is_nan() {
+                0
+            } else if x < 0.0 {
+                Self::MIN
+            } else {
+                Self::MAX
+            }
+        };
+        arithmetic.apply(x.to_i64(), saturating, saturating)
+    }
+
+    fn is_zero(&self) -> bool {
+        *self == 0
+    }
+
+    fn is_even(&self) -> bool {
+        self % 2 == 0
+    }
+
+    fn sign(&self) -> Self {
+        self.signum()
+    }
+
+    fn add(&self, other: &Self, arithmetic: IntArithmetic) -> Option<Self> {
+        arithmetic.apply(
+            self.checked_add(*other),
+            || self.wrapping_add(*other),
+            || self.saturating_add(*other),
+        )
+    }
+
+    fn sub(&self, other: &Self, arithmetic: IntArithmetic) -> Option<Self> {
+        arithmetic.apply(
+            self.checked_sub(*other),
+            || self.wrapping_sub(*other),
+            || self.saturating_sub(*other),
+        )
+    }
+
+    fn mul(&self, other: &Self, arithmetic: IntArithmetic) -> Option<Self> {
+        arithmetic.apply(
+            self.checked_mul(*other),
+            || self.wrapping_mul(*other),
+            || self.saturating_mul(*other),
+        )
+    }
+
+    fn neg(&self, arithmetic: IntArithmetic) -> Option<Self> {
+        arithmetic.apply(
+            self.checked_neg(),
+            || self.wrapping_neg(),
+            || self.saturating_neg(),
+        )
+    }
+
+    fn abs(&self, arithmetic: IntArithmetic) -> Option<Self> {
+        arithmetic.apply(
+            self.checked_abs(),
+            || self.wrapping_abs(),
+            || self.saturating_abs(),
+        )
+    }
+
+    fn div(&self, other: &Self, arithmetic: IntArithmetic) -> Option<Self> {
+        arithmetic.apply(
+            self.checked_div(*other),
+            || self.wrapping_div(*other),
+            || self.saturating_div(*other),
+        )
+    }
+
+    fn rem(&self, other: &Self, arithmetic: IntArithmetic) -> Option<Self> {
+        // The only overflow is `MIN % -1`, which is mathematically 0.
+        arithmetic.apply(
+            self.checked_rem(*other),
+            || self.wrapping_rem(*other),
+            || self.wrapping_rem(*other),
+        )
+    }
+
+    fn pow(&self, exponent: &Self, arithmetic: IntArithmetic) -> Option<Self> {
+        if *exponent < 0 {
+            return None;
+        }
+        // Exponents this big overflow unless `self` is -1, 0, or 1, so we
+        // only need to keep the parity of the exponent.
+        let exponent =
+            u32::try_from(*exponent).unwrap_or_else(|_| u32::MAX - u32::from(exponent % 2 == 0));
+        arithmetic.apply(
+            self.checked_pow(exponent),
+            || self.wrapping_pow(exponent),
+            || self.saturating_pow(exponent),
+        )
+    }
+
+    fn sqrt(&self) -> Self {
+        // The square root of any `u64` fits in an `i64`.
+        Self::try_from(self.unsigned_abs().isqrt()).unwrap_or(Self::MAX)
+    }
+
+    fn bit_and(&self, other: &Self) -> Self {
+        self & other
+    }
+
+    fn bit_or(&self, other: &Self) -> Self {
+        self | other
+    }
+
+    fn bit_xor(&self, other: &Self) -> Self {
+        self ^ other
+    }
+
+    fn bit_not(&self) -> Self {
+        !self
+    }
+
+    fn shift_left(&self, amount: &Self, arithmetic: IntArithmetic) -> Option<Self> {
+        if *amount < 0 {
+            Some(i64_shift_right(*self, amount.unsigned_abs()))
+        } else {
+            i64_shift_left(*self, amount.unsigned_abs(), arithmetic)
+        }
+    }
+
+    fn shift_right(&self, amount: &Self, arithmetic: IntArithmetic) -> Option<Self> {
+        if *amount < 0 {
+            i64_shift_left(*self, amount.unsigned_abs(), arithmetic)
+        } else {
+            Some(i64_shift_right(*self, amount.unsigned_abs()))
+        }
+    }
+}
+
+/// The largest number of bits a [`BigInt`] on an integer stack can have.
+///
+/// Without a limit, a few repeated squarings would exhaust memory, so
+/// larger results overflow. Wrapping and saturating don't make sense for
+/// arbitrary-precision integers, so with a [`BigInt`] stack those modes
+/// behave like [`IntArithmetic::Checked`].
+pub const MAX_BIG_INT_BITS: u64 = 1 << 16;
+
+fn fits(x: BigInt) -> Option<BigInt> {
+    (x.bits() <= MAX_BIG_INT_BITS).then_some(x)
+}
+
+fn big_int_shift_left(x: &BigInt, amount: &BigInt) -> Option<BigInt> {
+    if Zero::is_zero(x) {
+        return Some(BigInt::zero());
+    }
+    amount
+        .to_u64()
+        .filter(|&n| n <= MAX_BIG_INT_BITS)
+        .and_then(|n| usize::try_from(n).ok())
+        .and_then(|n| fits(x << n))
+}
+
+fn big_int_shift_right(x: &BigInt, amount: &BigInt) -> BigInt {
+    // Shifting right rounds down, so once every bit is gone we're left
+    // with 0 or -1.
+    match amount.to_usize().filter(|&n| n.to_u64() < Some(x.bits())) {
+        Some(n) => x >> n,
+        None if x.is_negative() => BigInt::from(-1),
+        None => BigInt::zero(),
+    }
+}
+
+impl PushInteger for BigInt {
+    fn from_i64(i: i64) -> Self {
+        Self::from(i)
+    }
+
+    fn to_i64(&self) -> Option<i64> {
+        <Self as ToPrimitive>::to_i64(self)
+    }
+
+    fn from_f64(x: f64, _: IntArithmetic) -> Option<Self> {
+        <Self as FromPrimitive>::from_f64(x.trunc()).and_then(fits)
+    }
+
+    fn is_zero(&self) -> bool {
+        Zero::is_zero(self)
+    }
+
+    fn is_even(&self) -> bool {
+        !self.bit(0)
+    }
+
+    fn sign(&self) -> Self {
+        self.signum()
+    }
+
+    fn add(&self, other: &Self, _: IntArithmetic) -> Option<Self> {
+        fits(self + other)
+    }
+
+    fn sub(&self, other: &Self, _: IntArithmetic) -> Option<Self> {
+        fits(self - other)
+    }
+
+    fn mul(&self, other: &Self, _: IntArithmetic) -> Option<Self> {
+        fits(self * other)
+    }
+
+    fn neg(&self, _: IntArithmetic) -> Option<Self> {
+        Some(-self)
+    }
+
+    fn abs(&self, _: IntArithmetic) -> Option<Self> {
+        Some(Signed::abs(self))
+    }
+
+    fn div(&self, other: &Self, _: IntArithmetic) -> Option<Self> {
+        Some(self / other)
+    }
+
+    fn rem(&self, other: &Self, _: IntArithmetic) -> Option<Self> {
+        Some(self % other)
+    }
+
+    fn pow(&self, exponent: &Self, _: IntArithmetic) -> Option<Self> {
+        if exponent.is_negative() {
+            return None;
+        }
+        if self.magnitude() <= &1u8.into() {
+            let result = if Zero::is_zero(exponent) {
+                Self::from(1)
+            } else if PushInteger::is_even(exponent) {
+                Signed::abs(self)
+            } else {
+                self.clone()
+            };
+            return Some(result);
+        }
+        // The result has at least `(bits - 1) * exponent + 1` bits, so
+        // check that before doing any work.
+        let exponent = exponent
+            .to_u32()
+            .filter(|&e| (self.bits() - 1).saturating_mul(u64::from(e)) < MAX_BIG_INT_BITS)?;
+        fits(Self::pow(self, exponent))
+    }
+
+    fn sqrt(&self) -> Self {
+        Signed::abs(self).sqrt()
+    }
+
+    fn bit_and(&self, other: &Self) -> Self {
+        self & other
+    }
+
+    fn bit_or(&self, other: &Self) -> Self {
+        self | other
+    }
+
+    fn bit_xor(&self, other: &Self) -> Self {
+        self ^ other
+    }
+
+    fn bit_not(&self) -> Self {
+        !self
+    }
+
+    fn shift_left(&self, amount: &Self, _: IntArithmetic) -> Option<Self> {
+        if amount.is_negative() {
+            Some(big_int_shift_right(self, &-amount))
+        } else {
+            big_int_shift_left(self, amount)
+        }
+    }
+
+    fn shift_right(&self, amount: &Self, _: IntArithmetic) -> Option<Self> {
+        if amount.is_negative() {
+            big_int_shift_left(self, &-amount)
+        } else {
+            Some(big_int_shift_right(self, amount))
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn i64_modes() {
+        let max = i64::MAX;
+        assert_eq!(max.add(&1, IntArithmetic::Checked), None);
+        assert_eq!(max.add(&1, IntArithmetic::NoopOnOverflow), None);
+        assert_eq!(max.add(&1, IntArithmetic::Wrapping), Some(i64::MIN));
+        assert_eq!(max.add(&1, IntArithmetic::Saturating), Some(max));
+        assert_eq!(i64::MIN.neg(IntArithmetic::Saturating), Some(max));
+        assert_eq!(
+            PushInteger::pow(&3_i64, &50, IntArithmetic::Saturating),
+            Some(max)
+        );
+        assert_eq!(
+            PushInteger::pow(&-3_i64, &(1 << 40), IntArithmetic::Saturating),
+            Some(max)
+        );
+        assert_eq!(
+            PushInteger::pow(&-1_i64, &((1 << 40) + 1), IntArithmetic::Checked),
+            Some(-1)
+        );
+        assert_eq!(PushInteger::pow(&2_i64, &-1, IntArithmetic::Wrapping), None);
+    }
+
+    #[test]
+    fn i64_shifts_and_conversions() {
+        assert_eq!(3_i64.shift_left(&2, IntArithmetic::Checked), Some(12));
+        assert_eq!(3_i64.shift_left(&62, IntArithmetic::Checked), None);
+        assert_eq!(
+            (-1_i64).shift_left(&63, IntArithmetic::Checked),
+            Some(i64::MIN)
+        );
+        assert_eq!(1_i64.shift_left(&64, IntArithmetic::Wrapping), Some(0));
+        assert_eq!(
+            (-3_i64).shift_left(&70, IntArithmetic::Saturating),
+            Some(i64::MIN)
+        );
+        assert_eq!(12_i64.shift_left(&-2, IntArithmetic::Checked), Some(3));
+        assert_eq!((-7_i64).shift_right(&1, IntArithmetic::Checked), Some(-4));
+        assert_eq!((-7_i64).shift_right(&100, IntArithmetic::Checked), Some(-1));
+        assert_eq!(i64::MIN.sqrt(), 3_037_000_499);
+        assert_eq!((-17_i64).sqrt(), 4);
+        assert_eq!(
+            <i64 as PushInteger>::from_f64(-2.7, IntArithmetic::Checked),
+            Some(-2)
+        );
+        assert_eq!(
+            <i64 as PushInteger>::from_f64(f64::NAN, IntArithmetic::Checked),
+            None
+        );
+        assert_eq!(
+            <i64 as PushInteger>::from_f64(1e30, IntArithmetic::Wrapping),
+            Some(i64::MAX)
+        );
+    }
+
+    #[test]
+    fn big_ints_dont_overflow_i64() {
+        let max = BigInt::from(i64::MAX);
+        let one = BigInt::from(1);
+        assert_eq!(
+            max.add(&one, IntArithmetic::Checked),
+            Some(BigInt::from(i64::MAX) + 1)
+        );
+        let big = PushInteger::pow(&BigInt::from(3), &BigInt::from(100), IntArithmetic::Checked);
+        assert_eq!(big, Some(num_traits::Pow::pow(BigInt::from(3), 100u32)));
+        assert_eq!(
+            one.shift_left(&BigInt::from(100), IntArithmetic::Checked),
+            Some(BigInt::from(1) << 100usize)
+        );
+        assert_eq!(
+            BigInt::from(-5).shift_right(&BigInt::from(1000), IntArithmetic::Checked),
+            Some(BigInt::from(-1))
+        );
+        assert_eq!(
+            PushInteger::pow(
+                &BigInt::from(-1),
+                &(BigInt::from(1) << 80usize),
+                IntArithmetic::Checked
+            ),
+            Some(one)
+        );
+    }
+
+    #[test]
+    fn big_ints_have_a_size_limit() {
+        let two = BigInt::from(2);
+        let exponent = BigInt::from(MAX_BIG_INT_BITS);
+        assert_eq!(
+            PushInteger::pow(&two, &exponent, IntArithmetic::Saturating),
+            None
+        );
+        assert_eq!(two.shift_left(&exponent, IntArithmetic::Wrapping), None);
+        let almost = PushInteger::pow(&two, &(exponent - 2), IntArithmetic::Checked).unwrap();
+        assert_eq!(almost.mul(&almost, IntArithmetic::Checked), None);
+    }
+}
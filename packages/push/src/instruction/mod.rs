@@ -2,18 +2,22 @@ use ordered_float::OrderedFloat;
 
 pub use self::{
     bool::BoolInstruction,
+    code::{CodeInstruction, CodeInstructionError, CodeState, MAX_CODE_POINTS},
     exec::ExecInstruction,
     float::FloatInstruction,
-    int::{IntInstruction, IntInstructionError},
+    int::{IntInstruction, IntInstructionError, IntState},
+    integer::{IntArithmetic, PushInteger, MAX_BIG_INT_BITS},
 };
 use self::{instruction_error::PushInstructionError, variable_name::VariableName};
-use crate::{error::InstructionResult, push_vm::push_state::PushState};
+use crate::{error::InstructionResult, push_vm::push_state::ProgramState};
 
 mod bool;
+mod code;
 mod exec;
 mod float;
 pub mod instruction_error;
 mod int;
+mod integer;
 pub mod variable_name;
 
 /*
@@ -39,18 +43,21 @@ pub mod variable_name;
 pub trait Instruction<S> {
     type Error;
 
+    /// Perform the instruction, updating `state` in place.
+    ///
     /// # Errors
     ///
     /// This returns an error if the instruction being performed
     /// returns some kind of error. This could include things like
     /// stack over- or underflows, or numeric errors like integer overflow.
-    fn perform(&self, state: S) -> InstructionResult<S, Self::Error>;
+    /// If the error is recoverable then `state` must be unchanged.
+    fn perform(&self, state: &mut S) -> InstructionResult<Self::Error>;
 }
 
 impl<S, E> Instruction<S> for Box<dyn Instruction<S, Error = E>> {
     type Error = E;
 
-    fn perform(&self, state: S) -> InstructionResult<S, E> {
+    fn perform(&self, state: &mut S) -> InstructionResult<E> {
         self.as_ref().perform(state)
     }
 }
@@ -63,6 +70,7 @@ pub enum PushInstruction {
     BoolInstruction(BoolInstruction),
     IntInstruction(IntInstruction),
     FloatInstruction(FloatInstruction),
+    Code(CodeInstruction),
 }
 
 impl PushInstruction {
@@ -82,20 +90,17 @@ impl PushInstruction {
     }
 }
 
-impl Instruction<PushState> for PushInstruction {
+impl<S: ProgramState> Instruction<S> for PushInstruction {
     type Error = PushInstructionError;
 
-    fn perform(&self, state: PushState) -> InstructionResult<PushState, Self::Error> {
+    fn perform(&self, state: &mut S) -> InstructionResult<Self::Error> {
         match self {
-            Self::InputVar(var_name) => {
-                // TODO: Should `push_input` return the new state?
-                // Or add a `with_input` that returns the new state and keep `push_input`?
-                state.with_input(var_name)
-            }
+            Self::InputVar(var_name) => state.push_input(var_name),
             Self::Exec(_) => todo!(),
             Self::BoolInstruction(i) => i.perform(state),
             Self::IntInstruction(i) => i.perform(state),
             Self::FloatInstruction(i) => i.perform(state),
+            Self::Code(i) => i.perform(state),
         }
     }
 }
@@ -110,6 +115,7 @@ impl NumOpens for PushInstruction {
     fn num_opens(&self) -> usize {
         match self {
             Self::Exec(i) => i.num_opens(),
+            Self::Code(i) => i.num_opens(),
             _ => 0,
         }
     }
@@ -123,6 +129,7 @@ impl std::fmt::Debug for PushInstruction {
             Self::BoolInstruction(instruction) => write!(f, "Bool-{instruction}"),
             Self::IntInstruction(instruction) => write!(f, "Int-{instruction:?}"),
             Self::FloatInstruction(instruction) => write!(f, "Float-{instruction:?}"),
+            Self::Code(instruction) => write!(f, "Code-{instruction:?}"),
         }
     }
 }
@@ -0,0 +1,175 @@
+use num_bigint::BigInt;
+use ordered_float::OrderedFloat;
+
+use super::{
+    input_slots::InputSlots,
+    program::PushProgram,
+    push_state::ProgramState,
+    stack::{Stack, StackError},
+    State,
+};
+use crate::{
+    error::{
+        policy::{ErrorCounts, ErrorPolicy},
+        stateful::FatalError,
+    },
+    instruction::{
+        instruction_error::PushInstructionError, CodeState, IntArithmetic, IntState,
+        PushInstruction,
+    },
+};
+
+/// A state like [`PushState`](super::push_state::PushState), but whose
+/// integers are arbitrary-precision [`BigInt`]s.
+///
+/// Its integers can have up to
+/// [`MAX_BIG_INT_BITS`](crate::instruction::MAX_BIG_INT_BITS) bits, so that
+/// programs for problems with large integers aren't stopped by overflow.
+///
+/// The input variables are still bound to [`PushInstruction`]s, so integer
+/// inputs are `i64`s.
+#[derive(Default, Debug)]
+#[push_macros::push_state(clear_state, size_budget)]
+pub struct BigIntPushState {
+    #[stack(exec, size_fn = PushProgram::size)]
+    pub(crate) exec: Stack<PushProgram>,
+    #[stack(code, size_fn = PushProgram::size)]
+    pub(crate) code: Stack<PushProgram>,
+    #[stack]
+    pub(crate) int: Stack<BigInt>,
+    #[stack]
+    pub(crate) float: Stack<OrderedFloat<f64>>,
+    #[stack]
+    pub(crate) bool: Stack<bool>,
+    #[input_instructions]
+    input_instructions: InputSlots<PushInstruction>,
+    int_arithmetic: IntArithmetic,
+    error_policy: ErrorPolicy,
+    error_counts: ErrorCounts,
+}
+
+impl BigIntPushState {
+    /// An empty state where each stack holds at most `max_stack_size`
+    /// values.
+    #[must_use]
+    pub fn new(max_stack_size: usize) -> Self {
+        let mut state = Self::default();
+        state.exec.set_max_stack_size(max_stack_size);
+        state.code.set_max_stack_size(max_stack_size);
+        state.int.set_max_stack_size(max_stack_size);
+        state.float.set_max_stack_size(max_stack_size);
+        state.bool.set_max_stack_size(max_stack_size);
+        state
+    }
+
+    /// Push `program` onto the exec stack, so that its first program is
+    /// performed first.
+    ///
+    /// # Errors
+    ///
+    /// This fails if the exec stack doesn't have room for `program`.
+    pub fn with_program(mut self, program: Vec<PushProgram>) -> Result<Self, StackError> {
+        self.exec.try_extend(program)?;
+        Ok(self)
+    }
+
+    /// Use `int_arithmetic` to handle integers that don't fit in
+    /// [`MAX_BIG_INT_BITS`](crate::instruction::MAX_BIG_INT_BITS) bits,
+    /// instead of the default [`IntArithmetic::Checked`].
+    #[must_use]
+    pub const fn with_int_arithmetic(mut self, int_arithmetic: IntArithmetic) -> Self {
+        self.int_arithmetic = int_arithmetic;
+        self
+    }
+
+    /// Handle the errors from performing instructions with `error_policy`,
+    /// instead of the default [`ErrorPolicy`].
+    #[must_use]
+    pub fn with_error_policy(mut self, error_policy: ErrorPolicy) -> Self {
+        self.error_policy = error_policy;
+        self
+    }
+}
+
+impl IntState for BigIntPushState {
+    type Int = BigInt;
+
+    fn int_arithmetic(&self) -> IntArithmetic {
+        self.int_arithmetic
+    }
+}
+
+impl CodeState for BigIntPushState {
+    fn code(&self) -> &Stack<PushProgram> {
+        &self.code
+    }
+
+    fn code_mut(&mut self) -> &mut Stack<PushProgram> {
+        &mut self.code
+    }
+}
+
+impl ProgramState for BigIntPushState {
+    fn input_instructions(&self) -> &InputSlots<PushInstruction> {
+        &self.input_instructions
+    }
+
+    fn input_instructions_mut(&mut self) -> &mut InputSlots<PushInstruction> {
+        &mut self.input_instructions
+    }
+
+    fn error_policy(&self) -> &ErrorPolicy {
+        &self.error_policy
+    }
+
+    fn error_counts(&self) -> &ErrorCounts {
+        &self.error_counts
+    }
+
+    fn error_counts_mut(&mut self) -> &mut ErrorCounts {
+        &mut self.error_counts
+    }
+}
+
+impl State for BigIntPushState {
+    type Instruction = PushProgram;
+
+    // Use `run_with_step_limit` for programs that might not terminate.
+    fn run_to_completion(self) -> Result<Self, FatalError<Self, PushInstructionError>> {
+        self.run_with_step_limit(usize::MAX)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::{
+        instruction::variable_name::VariableName,
+        push_vm::{bytecode::Bytecode, text},
+    };
+
+    #[test]
+    fn runs_programs_with_big_ints() {
+        // Multiplying 2^40 by itself overflows an `i64`, and `Code-Repeat`
+        // takes its count from the big integers.
+        let program = text::parse("x x Int-Multiply 2 Code-Quote (Int-Inc) Code-Repeat").unwrap();
+        let state = || {
+            let mut state = BigIntPushState::new(100);
+            state.bind_input(VariableName::from("x"), PushInstruction::push_int(1 << 40));
+            state
+        };
+        let expected = vec![(BigInt::from(1) << 80) + 2];
+
+        let interpreted = state()
+            .with_program(program.clone())
+            .unwrap()
+            .run_to_completion()
+            .unwrap();
+        assert_eq!(interpreted.int, expected);
+        assert_eq!(interpreted.error_counts().total(), 0);
+
+        let compiled = Bytecode::compile(&program).run(state()).unwrap();
+        assert_eq!(compiled.int, expected);
+    }
+}
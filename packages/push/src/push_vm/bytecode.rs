@@ -0,0 +1,532 @@
+//! A compiled form of Push programs for running the same program many
+//! times, e.g., once for every test case when scoring a genome.
+//!
+//! [`Bytecode`] flattens a program into a single array of small, `Copy`
+//! opcodes, with integer and float constants and input variable names in
+//! constant pools, and a block table giving the opcodes of each block.
+//! Running the bytecode doesn't clone any programs or use the exec stack;
+//! instead it keeps a stack of frames (a block and a position in it).
+//!
+//! Running a [`Bytecode`] gives exactly the same result as running the
+//! program it was compiled from with
+//! [`State::run_to_completion`](super::State::run_to_completion) or
+//! [`ProgramState::run_with_step_limit`], including which instructions fail
+//! and when the exec stack overflows, since a frame stands for a cursor on
+//! the exec stack (and is charged to any [`SizeBudget`] like one). The one
+//! difference is that the exec stack is left empty in the state in a
+//! [`FatalError`] from the bytecode.
+//!
+//! Programs that use [`CodeInstruction`]s, which move code between the exec
+//! and code stacks, need a real exec stack, so their bytecode just runs the
+//! original program with the interpreter.
+
+use std::ops::Range;
+
+use ordered_float::OrderedFloat;
+
+use super::{
+    program::PushProgram, push_state::ProgramState, size_budget::SizeBudget, stack::StackError,
+};
+use crate::{
+    error::{stateful::FatalError, Error, InstructionResult},
+    genome::plushy::Plushy,
+    instruction::{
+        instruction_error::PushInstructionError, variable_name::VariableName, BoolInstruction,
+        CodeInstruction, ExecInstruction, FloatInstruction, Instruction, IntInstruction,
+        PushInstruction,
+    },
+};
+
+/// A single bytecode instruction. The `usize`s are indices into the
+/// constant pools or the block table of the [`Bytecode`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    PushInt(usize),
+    PushFloat(usize),
+    PushBool(bool),
+    /// An input variable, by its index in the pool of names.
+    Input(usize),
+    /// An input variable, by its slot in the state's inputs.
+    InputSlot(usize),
+    Int(IntInstruction),
+    Float(FloatInstruction),
+    Bool(BoolInstruction),
+    Exec(ExecInstruction),
+    Code(CodeInstruction),
+    Block(usize),
+}
+
+/// A position in one of the blocks of a [`Bytecode`]; this plays the part
+/// of a [`PushProgram::Cursor`] on the exec stack.
+#[derive(Debug, Clone, Copy)]
+struct Frame {
+    block: usize,
+    position: usize,
+}
+
+/// A Push program compiled to bytecode. See the [module
+/// documentation](self) for the details.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Bytecode {
+    ops: Vec<Op>,
+    /// The range of `ops` for each block, where block 0 is the top level
+    /// of the program.
+    blocks: Vec<Range<usize>>,
+    /// The number of points in the programs in each block, which is what a
+    /// cursor over the whole block is charged to a [`SizeBudget`].
+    points: Vec<usize>,
+    ints: Vec<i64>,
+    floats: Vec<OrderedFloat<f64>>,
+    inputs: Vec<VariableName>,
+    /// The program, if it uses code instructions and so has to be run with
+    /// the interpreter.
+    interpreted: Option<Vec<PushProgram>>,
+}
+
+impl From<&Plushy> for Bytecode {
+    fn from(plushy: &Plushy) -> Self {
+        Self::compile(&Vec::<PushProgram>::from(plushy))
+    }
+}
+
+impl Bytecode {
+    /// Compile `program`.
+    #[must_use]
+    pub fn compile(program: &[PushProgram]) -> Self {
+        let mut bytecode = Self::default();
+        // The blocks are compiled in the order they are found, so that the
+        // opcodes of each block are contiguous.
+        let mut blocks = vec![program];
+        let mut next = 0;
+        while let Some(&block) = blocks.get(next) {
+            let start = bytecode.ops.len();
+            for program in block {
+                let op = match program {
+                    PushProgram::Instruction(instruction) => {
+                        bytecode.compile_instruction(instruction)
+                    }
+                    PushProgram::Block(contents) => {
+                        blocks.push(contents);
+                        Op::Block(blocks.len() - 1)
+                    }
+                    PushProgram::Cursor {
+                        block, position, ..
+                    } => {
+                        blocks.push(block.get(*position..).unwrap_or_default());
+                        Op::Block(blocks.len() - 1)
+                    }
+                };
+                bytecode.ops.push(op);
+            }
+            bytecode.blocks.push(start..bytecode.ops.len());
+            next += 1;
+        }
+        // Nested blocks come after the blocks they're in, so counting from
+        // the end counts each block's points before they're needed.
+        bytecode.points = vec![0; bytecode.blocks.len()];
+        for block in (0..bytecode.blocks.len()).rev() {
+            bytecode.points[block] = bytecode.ops[bytecode.blocks[block].clone()]
+                .iter()
+                .map(|&op| bytecode.op_size(op))
+                .sum();
+        }
+        if bytecode.ops.iter().any(|op| matches!(op, Op::Code(_))) {
+            bytecode.interpreted = Some(program.to_vec());
+        }
+        bytecode
+    }
+
+    /// Compile `program` to run on states whose input variables are in the
+    /// same slots as in `state`, so that each use of an input variable
+    /// refers straight to its slot instead of looking it up by name.
+    ///
+    /// # Errors
+    ///
+    /// This fails with [`PushInstructionError::UndefinedInput`] if `program`
+    /// uses an input variable that isn't bound in `state`.
+    pub fn compile_for(
+        program: &[PushProgram],
+        state: &impl ProgramState,
+    ) -> Result<Self, PushInstructionError> {
+        let mut bytecode = Self::compile(program);
+        let slots = bytecode
+            .inputs
+            .iter()
+            .map(|name| {
+                state
+                    .input_slot(name)
+                    .ok_or_else(|| PushInstructionError::UndefinedInput { name: name.clone() })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        for op in &mut bytecode.ops {
+            if let Op::Input(index) = *op {
+                *op = Op::InputSlot(slots[index]);
+            }
+        }
+        Ok(bytecode)
+    }
+
+    fn compile_instruction(&mut self, instruction: &PushInstruction) -> Op {
+        match instruction {
+            PushInstruction::InputVar(name) => {
+                let index = self
+                    .inputs
+                    .iter()
+                    .position(|n| n == name)
+                    .unwrap_or_else(|| {
+                        self.inputs.push(name.clone());
+                        self.inputs.len() - 1
+                    });
+                Op::Input(index)
+            }
+            PushInstruction::Exec(instruction) => Op::Exec(*instruction),
+            PushInstruction::BoolInstruction(BoolInstruction::Push(b)) => Op::PushBool(*b),
+            PushInstruction::BoolInstruction(instruction) => Op::Bool(*instruction),
+            PushInstruction::IntInstruction(IntInstruction::Push(i)) => {
+                self.ints.push(*i);
+                Op::PushInt(self.ints.len() - 1)
+            }
+            PushInstruction::IntInstruction(instruction) => Op::Int(*instruction),
+            PushInstruction::FloatInstruction(FloatInstruction::Push(x)) => {
+                self.floats.push(*x);
+                Op::PushFloat(self.floats.len() - 1)
+            }
+            PushInstruction::FloatInstruction(instruction) => Op::Float(*instruction),
+            PushInstruction::Code(instruction) => Op::Code(*instruction),
+        }
+    }
+
+    /// The number of places the code still to be performed would take up
+    /// on the exec stack: one for each remaining top-level program, and one
+    /// for each block that's being performed.
+    fn exec_size(&self, frames: &[Frame]) -> usize {
+        frames.first().map_or(0, |top_level| {
+            self.blocks[top_level.block].len() - top_level.position + frames.len() - 1
+        })
+    }
+
+    /// The size of the code still to be performed for the exec stack's
+    /// [`SizeBudget`]. This is what the interpreter charges for the
+    /// programs and cursors on the exec stack: the points in the remaining
+    /// top-level programs, and in what's left of each block that's being
+    /// performed.
+    fn budget_size(&self, frames: &[Frame]) -> usize {
+        frames
+            .iter()
+            .map(|frame| {
+                let block = &self.blocks[frame.block];
+                self.ops[block.start + frame.position..block.end]
+                    .iter()
+                    .map(|&op| self.op_size(op))
+                    .sum::<usize>()
+            })
+            .sum()
+    }
+
+    /// The size of an opcode, like [`PushProgram::size`].
+    fn op_size(&self, op: Op) -> usize {
+        match op {
+            Op::Block(block) => 1 + self.points[block],
+            _ => 1,
+        }
+    }
+
+    /// The next opcode to perform, removing any frames that this finishes
+    /// (apart from the top level).
+    fn fetch(&self, frames: &mut Vec<Frame>) -> Option<Op> {
+        let frame = frames.last_mut()?;
+        let block = &self.blocks[frame.block];
+        let op = self
+            .ops
+            .get(block.start + frame.position..block.end)?
+            .first()?;
+        frame.position += 1;
+        if frame.position == block.len() && frames.len() > 1 {
+            frames.pop();
+        }
+        Some(*op)
+    }
+
+    fn perform<S: ProgramState>(
+        &self,
+        op: Op,
+        state: &mut S,
+        frames: &mut Vec<Frame>,
+    ) -> InstructionResult<PushInstructionError> {
+        match op {
+            Op::PushInt(index) => IntInstruction::Push(self.ints[index]).perform(state),
+            Op::PushFloat(index) => FloatInstruction::Push(self.floats[index]).perform(state),
+            Op::PushBool(b) => BoolInstruction::Push(b).perform(state),
+            Op::Input(index) => state.push_input(&self.inputs[index]),
+            Op::InputSlot(slot) => state.push_input_slot(slot),
+            Op::Int(instruction) => instruction.perform(state),
+            Op::Float(instruction) => instruction.perform(state),
+            Op::Bool(instruction) => instruction.perform(state),
+            Op::Exec(instruction) => PushInstruction::Exec(instruction).perform(state),
+            Op::Code(instruction) => instruction.perform(state),
+            Op::Block(block) => {
+                let block_size = self.blocks[block].len();
+                if block_size == 0 {
+                    return Ok(());
+                }
+                // This is the same check as for pushing a cursor onto the
+                // exec stack.
+                let overflow = StackError::Overflow {
+                    stack_type: std::any::type_name::<PushProgram>(),
+                };
+                let exec = state.stack::<PushProgram>();
+                if self.exec_size(frames) + block_size > exec.max_stack_size() {
+                    return Err(Error::fatal(overflow));
+                }
+                if let Some(budget) = exec.size_budget() {
+                    if !budget.try_charge(self.points[block]) {
+                        return Err(Error::fatal(overflow));
+                    }
+                }
+                frames.push(Frame { block, position: 0 });
+                Ok(())
+            }
+        }
+    }
+
+    /// Run the program on `state` like [`Self::run_with_step_limit`], but
+    /// updating `state` in place, e.g., so that the same state can be
+    /// [cleared](super::ClearState) and reused for the next input. The exec
+    /// stack of `state` should be empty, as anything on it is ignored.
+    ///
+    /// # Errors
+    ///
+    /// This fails if any of the performed instructions fails with an error
+    /// that the state's [`ErrorPolicy`](crate::error::policy::ErrorPolicy)
+    /// aborts on, or if the step limit is exceeded. `state` is then left as it
+    /// was when the program was stopped.
+    pub fn run_in_place<S: ProgramState>(
+        &self,
+        state: &mut S,
+        step_limit: usize,
+    ) -> Result<(), PushInstructionError> {
+        let exec = state.stack_mut::<PushProgram>();
+        if let Some(program) = &self.interpreted {
+            exec.clear();
+            exec.try_extend(program.iter().cloned())?;
+            return state.run_in_place(step_limit);
+        }
+        let mut frames = vec![Frame {
+            block: 0,
+            position: 0,
+        }];
+        let overflow = StackError::Overflow {
+            stack_type: std::any::type_name::<PushProgram>(),
+        };
+        // Loading the program onto the exec stack would overflow it.
+        if self.exec_size(&frames) > exec.max_stack_size() {
+            return Err(overflow.into());
+        }
+        // The code in the frames is charged to the exec stack's budget as
+        // it would be if it were on the exec stack, and refunded at the end.
+        let budget = exec.size_budget().cloned();
+        if let Some(budget) = &budget {
+            if !budget.try_charge(self.budget_size(&frames)) {
+                return Err(overflow.into());
+            }
+        }
+        state.error_counts_mut().clear();
+        let result = self.run_frames(state, &mut frames, step_limit, budget.as_deref());
+        if let Some(budget) = &budget {
+            budget.refund(self.budget_size(&frames));
+        }
+        result
+    }
+
+    fn run_frames<S: ProgramState>(
+        &self,
+        state: &mut S,
+        frames: &mut Vec<Frame>,
+        step_limit: usize,
+        budget: Option<&SizeBudget>,
+    ) -> Result<(), PushInstructionError> {
+        for _ in 0..step_limit {
+            let Some(op) = self.fetch(frames) else {
+                return Ok(());
+            };
+            if let Some(budget) = budget {
+                budget.refund(self.op_size(op));
+            }
+            let result = self.perform(op, state, frames);
+            state.handle_error(result)?;
+        }
+        if self.exec_size(frames) == 0 {
+            Ok(())
+        } else {
+            Err(PushInstructionError::StepLimitExceeded { step_limit })
+        }
+    }
+
+    /// Run the program on `state` like
+    /// [`State::run_to_completion`](super::State::run_to_completion). The
+    /// exec stack of `state` should be empty, as anything on it is ignored.
+    ///
+    /// # Errors
+    ///
+    /// This fails if any of the performed instructions fails with an error
+    /// that the state's [`ErrorPolicy`](crate::error::policy::ErrorPolicy)
+    /// aborts on.
+    pub fn run<S: ProgramState>(&self, state: S) -> Result<S, FatalError<S, PushInstructionError>> {
+        self.run_with_step_limit(state, usize::MAX)
+    }
+
+    /// Run the program on `state` like [`ProgramState::run_with_step_limit`].
+    /// The exec stack of `state` should be empty, as anything on it is
+    /// ignored.
+    ///
+    /// # Errors
+    ///
+    /// This fails if any of the performed instructions fails with an error
+    /// that the state's [`ErrorPolicy`](crate::error::policy::ErrorPolicy)
+    /// aborts on, or if the step limit is exceeded.
+    pub fn run_with_step_limit<S: ProgramState>(
+        &self,
+        mut state: S,
+        step_limit: usize,
+    ) -> Result<S, FatalError<S, PushInstructionError>> {
+        match self.run_in_place(&mut state, step_limit) {
+            Ok(()) => Ok(state),
+            Err(error) => Err(FatalError::new(state, error)),
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::push_vm::{push_state::PushState, text, HasStack, SizeBudgetState};
+
+    fn state(max_stack_size: usize, size_budget: Option<usize>) -> PushState {
+        let mut state = PushState::builder()
+            .with_max_stack_size(max_stack_size)
+            .with_no_program()
+            .build();
+        if let Some(limit) = size_budget {
+            state = state.with_size_budget(limit);
+        }
+        state.bind_input(VariableName::from("x"), PushInstruction::push_int(5));
+        state
+    }
+
+    /// Run `program` with both interpreters and check that they agree.
+    fn check_same(
+        program: &str,
+        max_stack_size: usize,
+        size_budget: Option<usize>,
+        step_limit: usize,
+    ) {
+        let program = text::parse(program).unwrap();
+        let mut interpreted = state(max_stack_size, size_budget);
+        let loaded = interpreted
+            .stack_mut::<PushProgram>()
+            .try_extend(program.iter().cloned());
+        let interpreted = match loaded {
+            Ok(()) => interpreted.run_with_step_limit(step_limit),
+            Err(error) => Err(FatalError::new(interpreted, error.into())),
+        };
+        let compiled = Bytecode::compile(&program)
+            .run_with_step_limit(state(max_stack_size, size_budget), step_limit);
+        match (interpreted, compiled) {
+            (Ok(interpreted), Ok(compiled)) => {
+                assert_eq!(interpreted.int, compiled.int);
+                assert_eq!(interpreted.float, compiled.float);
+                assert_eq!(interpreted.bool, compiled.bool);
+            }
+            (Err(interpreted), Err(compiled)) => {
+                assert_eq!(interpreted.error(), compiled.error());
+                assert_eq!(interpreted.state().int, compiled.state().int);
+            }
+            (interpreted, compiled) => {
+                panic!("The interpreters disagree: {interpreted:?} and {compiled:?}")
+            }
+        }
+    }
+
+    const PROGRAMS: [&str; 5] = [
+        "",
+        "x 3 Int-Add x Int-Multiply",
+        "1 (2 (3 4) () 5) (Int-Add) Int-Add 2.5 Float-Dup Float-Multiply true Bool-Not",
+        "1 0 Int-ProtectedDivide Int-Add Bool-And x Int-IsEven",
+        "((((1))) 2) (((3)))",
+    ];
+
+    #[test]
+    fn agrees_with_the_interpreter() {
+        for program in PROGRAMS {
+            for max_stack_size in 0..8 {
+                for step_limit in 0..12 {
+                    check_same(program, max_stack_size, None, step_limit);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn agrees_with_the_interpreter_with_a_size_budget() {
+        for program in PROGRAMS {
+            for limit in 0..20 {
+                for step_limit in 0..12 {
+                    check_same(program, 8, Some(limit), step_limit);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn refunds_the_size_budget() {
+        let program = text::parse("1 (2 (3 4)) 5").unwrap();
+        let state = state(10, Some(6));
+        let budget = Arc::clone(state.exec.size_budget().unwrap());
+        let error = Bytecode::compile(&program)
+            .run_with_step_limit(state, 3)
+            .unwrap_err();
+        // Only the ints are left.
+        assert_eq!(budget.used(), error.state().int.size());
+    }
+
+    #[test]
+    fn resolves_input_slots() {
+        let program = text::parse("y x (y)").unwrap();
+        let mut state = state(10, None);
+        state.bind_input(VariableName::from("y"), PushInstruction::push_bool(true));
+        let bytecode = Bytecode::compile_for(&program, &state).unwrap();
+        assert_eq!(
+            bytecode.ops,
+            vec![
+                Op::InputSlot(1),
+                Op::InputSlot(0),
+                Op::Block(1),
+                Op::InputSlot(1)
+            ]
+        );
+        let state = bytecode.run(state).unwrap();
+        assert_eq!(&state.int, &vec![5]);
+        assert_eq!(&state.bool, &vec![true, true]);
+
+        assert_eq!(
+            Bytecode::compile_for(&program, &self::state(10, None)).unwrap_err(),
+            PushInstructionError::UndefinedInput {
+                name: VariableName::from("y")
+            }
+        );
+    }
+
+    #[test]
+    fn shares_constants() {
+        let program = text::parse("x (x 2) x 2.5").unwrap();
+        let bytecode = Bytecode::compile(&program);
+        assert_eq!(bytecode.blocks, vec![0..4, 4..6]);
+        assert_eq!(bytecode.points, vec![6, 2]);
+        assert_eq!(bytecode.inputs, vec![VariableName::from("x")]);
+        assert_eq!(bytecode.ints, vec![2]);
+        assert_eq!(bytecode.floats, vec![OrderedFloat(2.5)]);
+    }
+}
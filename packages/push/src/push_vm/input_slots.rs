@@ -0,0 +1,93 @@
+use crate::instruction::{variable_name::VariableName, PushInstruction};
+
+/// The instructions bound to a state's input variables.
+///
+/// Each input variable has a slot, which is its index in the order the
+/// variables were first bound. Programs can be compiled against a state
+/// (see [`Bytecode::compile_for`](super::bytecode::Bytecode::compile_for))
+/// so that each use of an input variable refers straight to its slot
+/// instead of looking the variable up by name every time it's performed.
+///
+/// The instructions can be of any type, so a state with, e.g., string or
+/// vector stacks can use an instruction type that pushes those values.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InputSlots<I = PushInstruction> {
+    slots: Vec<(VariableName, I)>,
+}
+
+// Implemented by hand so that we don't need `I: Default`.
+impl<I> Default for InputSlots<I> {
+    fn default() -> Self {
+        Self { slots: Vec::new() }
+    }
+}
+
+impl<I> InputSlots<I> {
+    /// Bind `name` to `instruction`, returning the slot for `name`. If
+    /// `name` is already bound, this replaces its instruction and keeps its
+    /// slot.
+    pub fn bind(&mut self, name: VariableName, instruction: I) -> usize {
+        if let Some(slot) = self.slot(&name) {
+            self.slots[slot].1 = instruction;
+            slot
+        } else {
+            self.slots.push((name, instruction));
+            self.slots.len() - 1
+        }
+    }
+
+    /// The slot for `name`, or `None` if `name` isn't bound.
+    #[must_use]
+    pub fn slot(&self, name: &VariableName) -> Option<usize> {
+        self.slots.iter().position(|(n, _)| n == name)
+    }
+
+    /// The instruction in `slot`.
+    #[must_use]
+    pub fn get(&self, slot: usize) -> Option<&I> {
+        self.slots.get(slot).map(|(_, instruction)| instruction)
+    }
+
+    /// The instruction bound to `name`.
+    #[must_use]
+    pub fn get_by_name(&self, name: &VariableName) -> Option<&I> {
+        self.slot(name).and_then(|slot| self.get(slot))
+    }
+
+    /// The names of the input variables, in slot order.
+    pub fn names(&self) -> impl Iterator<Item = &VariableName> {
+        self.slots.iter().map(|(name, _)| name)
+    }
+
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.slots.len()
+    }
+
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.slots.is_empty()
+    }
+
+    /// Remove all the bindings, keeping the capacity.
+    pub fn clear(&mut self) {
+        self.slots.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rebinding_keeps_the_slot() {
+        let mut inputs = InputSlots::default();
+        assert_eq!(inputs.bind(VariableName::from("x"), 1), 0);
+        assert_eq!(inputs.bind(VariableName::from("y"), 2), 1);
+        assert_eq!(inputs.bind(VariableName::from("x"), 3), 0);
+        assert_eq!(inputs.get(0), Some(&3));
+        assert_eq!(inputs.get_by_name(&VariableName::from("y")), Some(&2));
+        assert_eq!(inputs.slot(&VariableName::from("z")), None);
+        assert_eq!(inputs.len(), 2);
+    }
+}
@@ -1,11 +1,20 @@
+use std::sync::Arc;
+
+use self::size_budget::SizeBudget;
 use crate::{
     error::{stateful::FatalError, InstructionResult},
     instruction::Instruction,
 };
 
+pub mod big_int_push_state;
+pub mod bytecode;
+pub mod input_slots;
 pub mod program;
 pub mod push_state;
+pub mod size_budget;
 pub mod stack;
+pub mod text;
+pub mod trace;
 
 pub use self::stack::HasStack;
 
@@ -17,9 +26,9 @@ pub trait State: Sized {
     ///
     /// Fails if the instruction being performed fails.
     fn perform(
-        self,
+        &mut self,
         instruction: &Self::Instruction,
-    ) -> InstructionResult<Self, <Self::Instruction as Instruction<Self>>::Error> {
+    ) -> InstructionResult<<Self::Instruction as Instruction<Self>>::Error> {
         instruction.perform(self)
     }
 
@@ -31,6 +40,38 @@ pub trait State: Sized {
     ) -> Result<Self, FatalError<Self, <Self::Instruction as Instruction<Self>>::Error>>;
 }
 
+/// States that can be cleared in place, so that one state can be used to run
+/// a program on many inputs without reallocating its stacks. This is
+/// implemented by the `push_state` macro.
+pub trait ClearState {
+    /// Empty all the stacks (including the exec stack) and remove all the
+    /// input bindings, keeping the stacks' capacities and maximum sizes.
+    fn clear(&mut self);
+}
+
+/// States whose stacks can share a [`SizeBudget`].
+///
+/// The budget limits the total size of the values on all of the stacks.
+/// This is implemented by the `push_state` macro, along with a `Clone` that
+/// gives the clone its own budget.
+pub trait SizeBudgetState: Sized {
+    /// The budget shared by the stacks, if there is one.
+    fn size_budget(&self) -> Option<&Arc<SizeBudget>>;
+
+    /// Share `size_budget` between all the stacks, instead of any previous
+    /// budget.
+    fn set_size_budget(&mut self, size_budget: Option<Arc<SizeBudget>>);
+
+    /// Limit the total size of the values on all the stacks to `limit`.
+    /// Pushes that would exceed the limit fail with a fatal stack overflow
+    /// error. This is on top of the maximum size of each stack.
+    #[must_use]
+    fn with_size_budget(mut self, limit: usize) -> Self {
+        self.set_size_budget(Some(Arc::new(SizeBudget::new(limit))));
+        self
+    }
+}
+
 /*
  * exec: 5 8 9 int_plus 6 int_is_even bool_or
  * int: <empty>
@@ -1,4 +1,10 @@
-use super::{push_state::PushState, stack::StackError, HasStack};
+use std::sync::Arc;
+
+use super::{
+    push_state::ProgramState,
+    stack::{Stack, StackError},
+    HasStack,
+};
 use crate::{
     error::{Error, InstructionResult},
     genome::plushy::{Plushy, PushGene},
@@ -7,10 +13,30 @@ use crate::{
     },
 };
 
+/// A Push program, which can be cloned cheaply.
+///
+/// Blocks are immutable and shared, so cloning a program
+/// (e.g., to run it on every test case, or when a loop performs the same
+/// block over and over) never copies the code inside its blocks.
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub enum PushProgram {
     Instruction(PushInstruction),
-    Block(Vec<PushProgram>),
+    Block(Arc<[Self]>),
+    /// The programs in `block` from `position` on, i.e., what's left of a
+    /// block that's being performed. Performing a block pushes one of these
+    /// onto the exec stack instead of copying the block's contents there,
+    /// and [`Stack::pop_program`] takes the programs out of it one at a time.
+    /// These only appear on the exec stack, never in programs built from
+    /// genomes or text, and are turned back into blocks (see
+    /// [`PushProgram::without_cursor`]) when they leave it. `points` is the
+    /// number of points in those programs, so that [`PushProgram::size`]
+    /// doesn't have to count them every time the cursor moves on.
+    #[doc(hidden)]
+    Cursor {
+        block: Arc<[Self]>,
+        position: usize,
+        points: usize,
+    },
 }
 
 impl From<Plushy> for Vec<PushProgram> {
@@ -22,6 +48,16 @@ impl From<Plushy> for Vec<PushProgram> {
     }
 }
 
+// This avoids cloning the whole genome when we only need the program.
+impl From<&Plushy> for Vec<PushProgram> {
+    fn from(plushy: &Plushy) -> Self {
+        let mut genes = plushy.into_iter().cloned();
+        let mut program = Self::new();
+        PushProgram::parse_from_plushy(true, &mut genes, &mut program);
+        program
+    }
+}
+
 impl<T> From<T> for PushProgram
 where
     T: Into<PushInstruction>,
@@ -53,45 +89,213 @@ impl PushProgram {
                     for _ in 0..num_opens {
                         let mut block = Vec::new();
                         Self::parse_from_plushy(false, genes, &mut block);
-                        program.push(Self::Block(block));
+                        program.push(Self::block(block));
                     }
                 }
             }
         }
     }
-}
 
-// This is for "performing" an instruction that is in
-// fact a block of instructions. To perform this instruction
-// we need to push all the instructions in the block onto
-// the stack in the correct order, i.e., the first instruction
-// in the block should be the top instruction on the exec
-// stack after all the pushing is done.
-impl<S, I> Instruction<S> for Vec<I>
-where
-    S: HasStack<I>,
-    I: Instruction<S> + Clone,
-    I::Error: From<StackError>,
-{
-    type Error = I::Error;
+    /// A block containing `programs`.
+    pub fn block(programs: impl IntoIterator<Item = Self>) -> Self {
+        Self::Block(programs.into_iter().collect())
+    }
+
+    /// This program, but with a cursor turned into a block of the programs
+    /// it has left, so that cursors don't escape the exec stack (e.g., when
+    /// `Code-Quote` moves a program to the code stack).
+    #[must_use]
+    pub fn without_cursor(self) -> Self {
+        match self {
+            Self::Cursor {
+                block, position, ..
+            } => Self::block(block.get(position..).unwrap_or_default().iter().cloned()),
+            program => program,
+        }
+    }
+
+    /// The size of this program for a [`SizeBudget`](super::size_budget::SizeBudget),
+    /// which is its number of points (see [`Self::points_within`]), or the
+    /// number of points in the programs that a cursor has left.
+    #[must_use]
+    pub fn size(&self) -> usize {
+        match self {
+            Self::Instruction(_) => 1,
+            Self::Block(block) => 1 + Self::total_size(block),
+            Self::Cursor { points, .. } => *points,
+        }
+    }
+
+    fn total_size(programs: &[Self]) -> usize {
+        programs.iter().map(Self::size).sum()
+    }
+
+    /// This program as a list, as the code instructions see it: the
+    /// programs in a block (or what's left of one in a cursor), or just the
+    /// program itself if it's an instruction.
+    #[must_use]
+    pub fn contents(&self) -> &[Self] {
+        match self {
+            Self::Instruction(_) => std::slice::from_ref(self),
+            Self::Block(block) => block,
+            Self::Cursor {
+                block, position, ..
+            } => block.get(*position..).unwrap_or_default(),
+        }
+    }
+
+    /// The number of points in this program, i.e., the number of
+    /// instructions and blocks in it, counting the program itself, or
+    /// `None` if that's more than `limit`. This stops counting once it
+    /// passes `limit`, so it's cheap even for programs that share lots of
+    /// code.
+    #[must_use]
+    pub fn points_within(&self, limit: usize) -> Option<usize> {
+        let mut remaining = limit;
+        self.count_points(&mut remaining)?;
+        Some(limit - remaining)
+    }
+
+    fn count_points(&self, remaining: &mut usize) -> Option<()> {
+        *remaining = remaining.checked_sub(1)?;
+        if let Self::Block(_) | Self::Cursor { .. } = self {
+            for program in self.contents() {
+                program.count_points(remaining)?;
+            }
+        }
+        Some(())
+    }
 
-    fn perform(&self, mut state: S) -> InstructionResult<S, Self::Error> {
-        // If the size of the block + the size of the exec stack exceed the max stack
-        // size then we generate a fatal error.
-        if let Err(err) = state.stack_mut::<I>().try_extend(self.iter().cloned()) {
-            return Err(Error::fatal(state, err));
+    /// This program with every occurrence of `old` replaced by `new`, or
+    /// `None` if the result would have more than `limit` points.
+    #[must_use]
+    pub fn substitute(&self, old: &Self, new: &Self, limit: usize) -> Option<Self> {
+        let new_points = new.points_within(limit)?;
+        let mut remaining = limit;
+        self.substitute_within(old, new, new_points, &mut remaining)
+    }
+
+    fn substitute_within(
+        &self,
+        old: &Self,
+        new: &Self,
+        new_points: usize,
+        remaining: &mut usize,
+    ) -> Option<Self> {
+        if self == old {
+            *remaining = remaining.checked_sub(new_points)?;
+            return Some(new.clone());
+        }
+        *remaining = remaining.checked_sub(1)?;
+        match self {
+            Self::Instruction(_) => Some(self.clone()),
+            Self::Block(_) | Self::Cursor { .. } => Some(Self::Block(
+                self.contents()
+                    .iter()
+                    .map(|program| program.substitute_within(old, new, new_points, remaining))
+                    .collect::<Option<_>>()?,
+            )),
         }
-        Ok(state)
+    }
+
+    /// Push a cursor over the programs in `block` from `position` on onto
+    /// the exec stack, so that they are the next programs to be performed,
+    /// with the first of them on top. `points` is the number of points in
+    /// those programs.
+    fn push_cursor<S: HasStack<Self>>(
+        state: &mut S,
+        block: &Arc<[Self]>,
+        position: usize,
+        points: usize,
+    ) -> InstructionResult<PushInstructionError> {
+        let remaining = block.len().saturating_sub(position);
+        if remaining == 0 {
+            return Ok(());
+        }
+        let exec = state.stack_mut::<Self>();
+        // The cursor only takes up one place on the exec stack, but we still
+        // generate a fatal error if the programs it stands for wouldn't all
+        // fit there, so that (as in Push) the size of the exec stack bounds
+        // the amount of code waiting to be performed.
+        if exec.size() + remaining > exec.max_stack_size() {
+            return Err(Error::fatal(StackError::Overflow {
+                stack_type: std::any::type_name::<Self>(),
+            }));
+        }
+        exec.push(Self::Cursor {
+            block: Arc::clone(block),
+            position,
+            points,
+        })
+        .map_err(Error::fatal)
     }
 }
 
-impl Instruction<PushState> for PushProgram {
+impl<S: ProgramState> Instruction<S> for PushProgram {
     type Error = PushInstructionError;
 
-    fn perform(&self, state: PushState) -> InstructionResult<PushState, Self::Error> {
+    fn perform(&self, state: &mut S) -> InstructionResult<Self::Error> {
         match self {
             Self::Instruction(i) => i.perform(state),
-            Self::Block(block) => block.perform(state),
+            Self::Block(block) => Self::push_cursor(state, block, 0, Self::total_size(block)),
+            Self::Cursor {
+                block,
+                position,
+                points,
+            } => Self::push_cursor(state, block, *position, *points),
+        }
+    }
+}
+
+impl Stack<PushProgram> {
+    /// The program that [`pop_program`](Self::pop_program) would return,
+    /// looking inside the cursor on top of the stack if there is one.
+    ///
+    /// # Errors
+    ///
+    /// Returns `StackError::Underflow` if the stack is empty.
+    pub fn next_program(&self) -> Result<&PushProgram, StackError> {
+        match self.top()? {
+            PushProgram::Cursor {
+                block, position, ..
+            } => block.get(*position).ok_or(StackError::Underflow {
+                num_requested: 1,
+                num_present: 0,
+            }),
+            program => Ok(program),
+        }
+    }
+
+    /// Removes the next program to perform from the stack. If the top of the
+    /// stack is a cursor this takes the first program from the cursor,
+    /// leaving the rest (if any) on the stack.
+    ///
+    /// # Errors
+    ///
+    /// Returns `StackError::Underflow` if the stack is empty.
+    pub fn pop_program(&mut self) -> Result<PushProgram, StackError> {
+        match self.pop()? {
+            PushProgram::Cursor {
+                block,
+                position,
+                points,
+            } => {
+                let program = block.get(position).cloned().ok_or(StackError::Underflow {
+                    num_requested: 1,
+                    num_present: 0,
+                })?;
+                if position + 1 < block.len() {
+                    // This can't overflow (or exceed the size budget) since
+                    // we just popped the cursor, which was bigger.
+                    self.push(PushProgram::Cursor {
+                        block,
+                        position: position + 1,
+                        points: points.saturating_sub(program.size()),
+                    })?;
+                }
+                Ok(program)
+            }
+            program => Ok(program),
         }
     }
 }
@@ -107,7 +311,7 @@ mod test {
             BoolInstruction, ExecInstruction, FloatInstruction, Instruction, IntInstruction,
         },
         list_into::{arr_into, vec_into},
-        push_vm::{push_state::PushState, HasStack},
+        push_vm::{push_state::PushState, HasStack, State},
     };
 
     #[test]
@@ -130,10 +334,10 @@ mod test {
             vec_into![
                 IntInstruction::Add,
                 ExecInstruction::IfElse,
-                PushProgram::Block(vec_into![IntInstruction::Multiply]),
-                PushProgram::Block(vec_into![
+                PushProgram::block(vec_into![IntInstruction::Multiply]),
+                PushProgram::block(vec_into![
                     ExecInstruction::Dup,
-                    PushProgram::Block(vec_into![IntInstruction::Subtract])
+                    PushProgram::block(vec_into![IntInstruction::Subtract])
                 ])
             ]
         );
@@ -146,20 +350,57 @@ mod test {
             FloatInstruction::Multiply,
             BoolInstruction::And,
         ];
-        let block = dbg!(PushProgram::Block(instructions));
-        let state = PushState::builder()
+        let block = dbg!(PushProgram::block(instructions));
+        let mut state = PushState::builder()
             .with_max_stack_size(100)
             .with_no_program()
             .build();
-        let mut result = block.perform(state).unwrap();
-        let exec_stack = result.stack_mut::<PushProgram>();
-        assert_eq!(exec_stack.size(), 3);
-        assert_eq!(exec_stack.pop().unwrap(), IntInstruction::Add.into());
-        assert_eq!(exec_stack.pop().unwrap(), FloatInstruction::Multiply.into());
-        assert_eq!(exec_stack.pop().unwrap(), BoolInstruction::And.into());
+        block.perform(&mut state).unwrap();
+        let exec_stack = state.stack_mut::<PushProgram>();
+        // The block's contents are shared with a single cursor on the stack.
+        assert_eq!(exec_stack.size(), 1);
+        assert_eq!(
+            exec_stack.next_program().unwrap(),
+            &IntInstruction::Add.into()
+        );
+        assert_eq!(
+            exec_stack.pop_program().unwrap(),
+            IntInstruction::Add.into()
+        );
+        assert_eq!(
+            exec_stack.pop_program().unwrap(),
+            FloatInstruction::Multiply.into()
+        );
+        assert_eq!(exec_stack.size(), 1);
+        assert_eq!(
+            exec_stack.pop_program().unwrap(),
+            BoolInstruction::And.into()
+        );
         assert_eq!(exec_stack.size(), 0);
     }
 
+    #[test]
+    fn cursors_become_blocks() {
+        let block = PushProgram::block(vec_into![
+            IntInstruction::Add,
+            FloatInstruction::Multiply,
+            BoolInstruction::And,
+        ]);
+        let PushProgram::Block(contents) = &block else {
+            unreachable!("We just made a block");
+        };
+        let cursor = PushProgram::Cursor {
+            block: contents.clone(),
+            position: 1,
+            points: 2,
+        };
+        assert_eq!(
+            cursor.without_cursor(),
+            PushProgram::block(vec_into![FloatInstruction::Multiply, BoolInstruction::And])
+        );
+        assert_eq!(block.clone().without_cursor(), block);
+    }
+
     #[test]
     fn block_overflows() {
         let instructions = vec_into![
@@ -167,14 +408,68 @@ mod test {
             FloatInstruction::Multiply,
             BoolInstruction::And,
         ];
-        let block = PushProgram::Block(instructions);
-        let state = PushState::builder()
+        let block = PushProgram::block(instructions);
+        let mut state = PushState::builder()
             // Set the max stack size to 2, so when we execute the block it overflows
             .with_max_stack_size(2)
             .with_no_program()
             .build();
-        let Error::Fatal(_) = block.perform(state).unwrap_err() else {
+        let Error::Fatal(_) = block.perform(&mut state).unwrap_err() else {
             panic!("Performing the block didn't generate an overflow error");
         };
     }
+
+    #[test]
+    fn nested_blocks_share_code() {
+        let inner = PushProgram::block(vec_into![IntInstruction::Inc, IntInstruction::Inc]);
+        let program: Vec<PushProgram> = vec_into![
+            IntInstruction::Push(1),
+            inner.clone(),
+            PushProgram::block([inner.clone(), inner]),
+        ];
+        let state = PushState::builder()
+            .with_max_stack_size(100)
+            .with_program(program)
+            .unwrap()
+            .build()
+            .run_to_completion()
+            .unwrap();
+        assert_eq!(&state.int, &vec![7]);
+        assert!(state.exec.is_empty());
+    }
+
+    #[test]
+    fn size_counts_nested_points() {
+        let inner = PushProgram::block(vec_into![IntInstruction::Inc, IntInstruction::Dec]);
+        let program = PushProgram::block([inner.clone(), IntInstruction::Inc.into(), inner]);
+        assert_eq!(program.size(), 8);
+        assert_eq!(PushProgram::block([]).size(), 1);
+
+        let mut state = PushState::builder()
+            .with_max_stack_size(100)
+            .with_no_program()
+            .build();
+        program.perform(&mut state).unwrap();
+        // The cursor stands for the programs in the block, without the block.
+        let exec = state.stack_mut::<PushProgram>();
+        assert_eq!(exec.top().unwrap().size(), 7);
+        assert_eq!(exec.pop_program().unwrap().size(), 3);
+        assert_eq!(exec.top().unwrap().size(), 4);
+        assert_eq!(exec.pop_program().unwrap().size(), 1);
+        assert_eq!(exec.top().unwrap().size(), 3);
+    }
+
+    #[test]
+    fn points_and_substitute() {
+        let inner = PushProgram::block(vec_into![IntInstruction::Inc, IntInstruction::Dec]);
+        let program = PushProgram::block([inner.clone(), IntInstruction::Inc.into(), inner]);
+        assert_eq!(program.points_within(100), Some(8));
+        assert_eq!(program.points_within(7), None);
+
+        let inc = IntInstruction::Inc.into();
+        let pair = PushProgram::block(vec_into![IntInstruction::Add, IntInstruction::Add]);
+        let substituted = program.substitute(&inc, &pair, 100).unwrap();
+        assert_eq!(substituted.points_within(100), Some(14));
+        assert_eq!(program.substitute(&inc, &pair, 13), None);
+    }
 }
@@ -1,14 +1,16 @@
-use std::collections::HashMap;
-
 pub use ordered_float::OrderedFloat;
 
 use crate::{
-    error::{stateful::FatalError, try_recover::TryRecover, InstructionResult},
+    error::{
+        policy::{ErrorAction, ErrorCounts, ErrorPolicy},
+        stateful::FatalError,
+        Error, InstructionResult,
+    },
     instruction::{
-        instruction_error::PushInstructionError, variable_name::VariableName, Instruction,
-        PushInstruction,
+        instruction_error::PushInstructionError, variable_name::VariableName, CodeState,
+        Instruction, IntArithmetic, IntState, PushInstruction,
     },
-    push_vm::{program::PushProgram, stack::Stack, State},
+    push_vm::{input_slots::InputSlots, program::PushProgram, stack::Stack, HasStack, State},
 };
 
 // TODO: It might make sense to separate out the specification of
@@ -19,12 +21,15 @@ use crate::{
 // or Python implementation for comparison/testing purposes.
 
 // Because `f64` doesn't impl `Eq`, having a float stack means
-// that `PushState` also can't impl `Eq`.
-#[derive(Default, Debug, Clone)]
-#[push_macros::push_state(builder)]
+// that `PushState` also can't impl `Eq`. `Clone` comes from the
+// `size_budget` feature of the macro, so that a clone gets its own budget.
+#[derive(Default, Debug)]
+#[push_macros::push_state(builder, clear_state, size_budget)]
 pub struct PushState {
-    #[stack(exec)]
+    #[stack(exec, size_fn = PushProgram::size)]
     pub(crate) exec: Stack<PushProgram>,
+    #[stack(code, size_fn = PushProgram::size)]
+    pub(crate) code: Stack<PushProgram>,
     #[stack(sample_values = [4, 5, 7])]
     pub(crate) int: Stack<i64>,
     #[stack(sample_values = [OrderedFloat(4.3), OrderedFloat(5.1), OrderedFloat(2.1)])]
@@ -38,90 +43,236 @@ pub struct PushState {
     // `Vec`. When I substantially increased the size of the programs,
     // however, the difference pretty much disappeared, presumably
     // because the execution of long programs swamps the cost of
-    // initialization of `PushState`.
+    // initialization of `PushState`. These are now a `Vec` of slots, and
+    // compiled programs (see `Bytecode::compile_for()`) refer to inputs by
+    // slot, so we only search by name when compiling.
     #[input_instructions]
-    pub(super) input_instructions: HashMap<VariableName, PushInstruction>,
+    pub(super) input_instructions: InputSlots<PushInstruction>,
+    int_arithmetic: IntArithmetic,
+    error_policy: ErrorPolicy,
+    pub(super) error_counts: ErrorCounts,
 }
 
-impl PushState {
-    // /// # Panics
-    // ///
-    // /// This panics if we try to access a variable whose `var_index` isn't in the
-    // /// variable map.
-    // pub fn push_input(&mut self, var_name: &VariableName) {
-    //     let instruction = self
-    //         .input_instructions
-    //         .iter()
-    //         .find_map(|(n, v)| if n == var_name { Some(v) } else { None })
-    //         .unwrap_or_else(|| panic!(
-    //              "Failed to get an instruction for the input \
-    //               variable '{var_name}' that hadn't been defined"
-    //         ))
-    //         .clone();
-    //     instruction.perform(self);
-    // }
+impl IntState for PushState {
+    type Int = i64;
+
+    fn int_arithmetic(&self) -> IntArithmetic {
+        self.int_arithmetic
+    }
+}
+
+impl CodeState for PushState {
+    fn code(&self) -> &Stack<PushProgram> {
+        &self.code
+    }
+
+    fn code_mut(&mut self) -> &mut Stack<PushProgram> {
+        &mut self.code
+    }
+}
+
+/// A state that Push programs can be run on, with an exec stack of
+/// [`PushProgram`]s, input variables bound to [`PushInstruction`]s, and an
+/// [`ErrorPolicy`] for the errors from performing instructions.
+///
+/// This is implemented by [`PushState`] and by
+/// [`BigIntPushState`](super::big_int_push_state::BigIntPushState), whose
+/// integers are arbitrary-precision, and provides the methods that run
+/// programs on them.
+pub trait ProgramState: CodeState + IntState + HasStack<<Self as IntState>::Int> + Sized {
+    fn input_instructions(&self) -> &InputSlots<PushInstruction>;
+    fn input_instructions_mut(&mut self) -> &mut InputSlots<PushInstruction>;
+    fn error_policy(&self) -> &ErrorPolicy;
 
+    /// The errors counted by the error policy during the last run. These
+    /// are reset at the start of each run.
+    fn error_counts(&self) -> &ErrorCounts;
+    fn error_counts_mut(&mut self) -> &mut ErrorCounts;
+
+    /// Handle `result`, from performing an instruction, according to the
+    /// error policy.
+    ///
     /// # Errors
     ///
-    /// This returns an error if the `PushInstruction` returns an error,
-    /// which really shouldn't happen.
+    /// This returns the error in `result` if the policy says that the
+    /// program should stop.
+    fn handle_error(
+        &mut self,
+        result: InstructionResult<PushInstructionError>,
+    ) -> Result<(), PushInstructionError> {
+        let Err(error) = result else {
+            return Ok(());
+        };
+        match self.error_policy().action(&error) {
+            ErrorAction::Abort => Err(error.into_inner()),
+            ErrorAction::Ignore => Ok(()),
+            ErrorAction::Count => {
+                self.error_counts_mut().record(error.error().kind());
+                Ok(())
+            }
+        }
+    }
+
+    /// Perform the instruction bound to the input variable `var_name`.
     ///
-    /// # Panics
+    /// # Errors
     ///
-    /// This panics if there is no instruction associated with `var_name`, i.e.,
-    /// we have not yet added that variable name to the map of names to
-    /// instructions.
-    pub fn with_input(
-        self,
-        var_name: &VariableName,
-    ) -> InstructionResult<Self, <PushInstruction as Instruction<Self>>::Error> {
-        // TODO: This `panic` here is icky, and we really should deal with it better.
-        // I wonder if the fact that this index might not be there should be telling
-        // us something...
-        let instruction = self
-            .input_instructions
-            .iter()
-            .find_map(|(n, v)| if n == var_name { Some(v) } else { None })
-            .unwrap_or_else(|| {
-                panic!(
-                    "Failed to get an instruction for the input variable '{var_name}' that hadn't \
-                     been defined"
-                )
+    /// This returns a fatal [`PushInstructionError::UndefinedInput`] error if
+    /// `var_name` isn't bound, and otherwise any error from performing the
+    /// bound instruction.
+    fn push_input(&mut self, var_name: &VariableName) -> InstructionResult<PushInstructionError> {
+        let slot = self.input_slot(var_name).ok_or_else(|| {
+            Error::fatal(PushInstructionError::UndefinedInput {
+                name: var_name.clone(),
             })
+        })?;
+        self.push_input_slot(slot)
+    }
+
+    /// Perform the instruction in input slot `slot`.
+    ///
+    /// # Errors
+    ///
+    /// This returns a fatal [`PushInstructionError::UndefinedInputSlot`] error
+    /// if there's no such slot, and otherwise any error from performing the
+    /// instruction in the slot.
+    fn push_input_slot(&mut self, slot: usize) -> InstructionResult<PushInstructionError> {
+        let instruction = self
+            .input_instructions()
+            .get(slot)
+            .ok_or_else(|| Error::fatal(PushInstructionError::UndefinedInputSlot { slot }))?
             .clone();
         instruction.perform(self)
     }
+
+    /// Bind the input variable `var_name` to `instruction`, which is
+    /// performed whenever the program uses that variable, and return its
+    /// slot. This replaces any existing binding for `var_name`, keeping its
+    /// slot.
+    fn bind_input(&mut self, var_name: VariableName, instruction: PushInstruction) -> usize {
+        self.input_instructions_mut().bind(var_name, instruction)
+    }
+
+    /// The input slot for `var_name`, or `None` if it isn't bound.
+    fn input_slot(&self, var_name: &VariableName) -> Option<usize> {
+        self.input_instructions().slot(var_name)
+    }
+
+    /// Run the program like [`State::run_to_completion`], but stop with a
+    /// [`PushInstructionError::StepLimitExceeded`] error if the program
+    /// hasn't finished after performing `step_limit` instructions (where
+    /// pushing the contents of a block counts as a step). The state in
+    /// that error is the state when the program was stopped.
+    ///
+    /// # Errors
+    ///
+    /// This fails if any of the performed instructions fails with an error
+    /// that the state's [`ErrorPolicy`] aborts on, or if the step limit is
+    /// exceeded.
+    fn run_with_step_limit(
+        mut self,
+        step_limit: usize,
+    ) -> Result<Self, FatalError<Self, PushInstructionError>> {
+        match self.run_in_place(step_limit) {
+            Ok(()) => Ok(self),
+            Err(error) => Err(FatalError::new(self, error)),
+        }
+    }
+
+    /// Run the program like [`Self::run_with_step_limit`], but updating the
+    /// state in place.
+    ///
+    /// # Errors
+    ///
+    /// This fails like [`Self::run_with_step_limit`].
+    fn run_in_place(&mut self, step_limit: usize) -> Result<(), PushInstructionError> {
+        self.error_counts_mut().clear();
+        for _ in 0..step_limit {
+            let Ok(program) = self.stack_mut::<PushProgram>().pop_program() else {
+                return Ok(());
+            };
+            let result = program.perform(self);
+            self.handle_error(result)?;
+        }
+        if self.stack::<PushProgram>().is_empty() {
+            Ok(())
+        } else {
+            Err(PushInstructionError::StepLimitExceeded { step_limit })
+        }
+    }
+}
+
+impl ProgramState for PushState {
+    fn input_instructions(&self) -> &InputSlots<PushInstruction> {
+        &self.input_instructions
+    }
+
+    fn input_instructions_mut(&mut self) -> &mut InputSlots<PushInstruction> {
+        &mut self.input_instructions
+    }
+
+    fn error_policy(&self) -> &ErrorPolicy {
+        &self.error_policy
+    }
+
+    fn error_counts(&self) -> &ErrorCounts {
+        &self.error_counts
+    }
+
+    fn error_counts_mut(&mut self) -> &mut ErrorCounts {
+        &mut self.error_counts
+    }
+}
+
+impl PushState {
+    /// Use `int_arithmetic` to handle integer overflow, instead of the
+    /// default [`IntArithmetic::Checked`]. This isn't changed by
+    /// [`ClearState::clear`](super::ClearState::clear).
+    #[must_use]
+    pub const fn with_int_arithmetic(mut self, int_arithmetic: IntArithmetic) -> Self {
+        self.int_arithmetic = int_arithmetic;
+        self
+    }
+
+    /// Handle the errors from performing instructions with `error_policy`,
+    /// instead of the default [`ErrorPolicy`].
+    #[must_use]
+    pub fn with_error_policy(mut self, error_policy: ErrorPolicy) -> Self {
+        self.error_policy = error_policy;
+        self
+    }
 }
 
 impl State for PushState {
     type Instruction = PushProgram;
 
-    // TODO: Need to have some kind of execution limit to prevent infinite loops.
-    fn run_to_completion(mut self) -> Result<Self, FatalError<Self, PushInstructionError>> {
-        // The `pop()` call can only return a `StackError`, which is either underflow or
-        // overflow, with the latter not possible when just popping. So I'm not going to
-        // bother capturing the error here.
-        while let Ok(program) = self.exec.pop() {
-            self = self.perform(&program).try_recover()?;
-        }
-        Ok(self)
+    // Use `run_with_step_limit` for programs that might not terminate.
+    fn run_to_completion(self) -> Result<Self, FatalError<Self, PushInstructionError>> {
+        self.run_with_step_limit(usize::MAX)
     }
 }
 
 #[cfg(test)]
 #[allow(clippy::unwrap_used)]
 mod simple_check {
+    use std::sync::Arc;
+
     use ordered_float::OrderedFloat;
 
-    use super::State;
+    use super::{ProgramState, State};
     use crate::{
+        error::{
+            into_state::IntoState,
+            policy::{ErrorAction, ErrorPolicy},
+        },
         genome::plushy::{Plushy, PushGene},
         instruction::{
-            variable_name::VariableName, BoolInstruction, FloatInstruction, IntInstruction,
-            PushInstruction,
+            instruction_error::{ErrorKind, PushInstructionError},
+            variable_name::VariableName,
+            BoolInstruction, FloatInstruction, IntInstruction, PushInstruction,
         },
         list_into::vec_into,
-        push_vm::{program::PushProgram, push_state::PushState},
+        push_vm::{program::PushProgram, push_state::PushState, ClearState, SizeBudgetState},
     };
 
     #[test]
@@ -179,4 +330,150 @@ mod simple_check {
         assert_eq!(&state.bool, &vec![true, false]);
         assert_eq!(&state.float, &vec![OrderedFloat(13.0)]);
     }
+
+    #[test]
+    fn step_limit() {
+        let program: Vec<PushProgram> = vec_into![
+            IntInstruction::Push(1),
+            IntInstruction::Push(2),
+            IntInstruction::Add,
+        ];
+        let state = || {
+            PushState::builder()
+                .with_max_stack_size(10)
+                .with_program(program.clone())
+                .unwrap()
+                .build()
+        };
+        let finished = state().run_with_step_limit(3).unwrap();
+        assert_eq!(&finished.int, &vec![3]);
+
+        let error = state().run_with_step_limit(2).unwrap_err();
+        assert_eq!(
+            error.error(),
+            &PushInstructionError::StepLimitExceeded { step_limit: 2 }
+        );
+        let stopped = error.into_state();
+        assert_eq!(&stopped.int, &vec![1, 2]);
+        assert_eq!(stopped.exec.size(), 1);
+    }
+
+    #[test]
+    fn clear() {
+        let mut state = PushState::builder()
+            .with_max_stack_size(10)
+            .with_program([PushProgram::from(IntInstruction::Push(1))])
+            .unwrap()
+            .with_int_values([3, 4])
+            .unwrap()
+            .with_bool_input("b", true)
+            .build();
+        state.clear();
+        assert!(state.exec.is_empty());
+        assert!(state.int.is_empty());
+        assert!(state.input_instructions.is_empty());
+        assert_eq!(state.int.max_stack_size(), 10);
+    }
+
+    #[test]
+    fn error_policy() {
+        let program: Vec<PushProgram> = vec_into![
+            BoolInstruction::And,
+            IntInstruction::Add,
+            IntInstruction::Push(2),
+            IntInstruction::Push(3),
+        ];
+        let state = |policy: ErrorPolicy| {
+            PushState::builder()
+                .with_max_stack_size(4)
+                .with_program(program.clone())
+                .unwrap()
+                .with_int_values([1, i64::MAX, 3])
+                .unwrap()
+                .build()
+                .with_error_policy(policy)
+        };
+
+        // The `Push(3)` overflows the int stack, which is fatal by default.
+        let error = state(ErrorPolicy::default())
+            .run_to_completion()
+            .unwrap_err();
+        assert_eq!(error.error().kind(), ErrorKind::StackOverflow);
+        let counts = error.state().error_counts();
+        assert_eq!(counts.get(ErrorKind::StackUnderflow), 1);
+        assert_eq!(counts.get(ErrorKind::IntOverflow), 1);
+
+        let finished = state(ErrorPolicy::default().with_fatal(ErrorAction::Count))
+            .run_to_completion()
+            .unwrap();
+        assert_eq!(&finished.int, &vec![3, i64::MAX, 1, 2]);
+        assert_eq!(finished.error_counts().total(), 3);
+
+        let ignored = state(
+            ErrorPolicy::default()
+                .with_recoverable(ErrorAction::Ignore)
+                .with_fatal(ErrorAction::Ignore),
+        )
+        .run_to_completion()
+        .unwrap();
+        assert_eq!(ignored.error_counts().total(), 0);
+
+        let error =
+            state(ErrorPolicy::default().with_action(ErrorKind::IntOverflow, ErrorAction::Abort))
+                .run_to_completion()
+                .unwrap_err();
+        assert_eq!(error.error().kind(), ErrorKind::IntOverflow);
+    }
+
+    #[test]
+    fn size_budget() {
+        // The exec stack starts with one block, and performing it pushes a
+        // cursor over its one program. That's an inner block with 3 points,
+        // which doesn't fit in the budget alongside the ints.
+        let program = vec![PushProgram::block([PushProgram::block(vec_into![
+            IntInstruction::Push(1),
+            IntInstruction::Push(2)
+        ])])];
+        let state = PushState::builder()
+            .with_max_stack_size(10)
+            .with_program(program)
+            .unwrap()
+            .with_int_values([7, 8])
+            .unwrap()
+            .build()
+            .with_size_budget(3);
+        let error = state.run_to_completion().unwrap_err();
+        assert_eq!(error.error().kind(), ErrorKind::StackOverflow);
+        let state = error.state();
+        assert_eq!(&state.int, &vec![8, 7]);
+        assert!(state.exec.is_empty());
+        assert_eq!(state.exec.size_budget().unwrap().used(), 2);
+    }
+
+    #[test]
+    fn clones_have_their_own_size_budget() {
+        let state = PushState::builder()
+            .with_max_stack_size(10)
+            .with_no_program()
+            .with_int_values([1, 2])
+            .unwrap()
+            .build()
+            .with_size_budget(3);
+        let mut clone = state.clone();
+        let budget = state.int.size_budget().unwrap();
+        let clone_budget = clone.int.size_budget().unwrap();
+        assert!(!Arc::ptr_eq(budget, clone_budget));
+        assert_eq!(clone_budget.limit(), 3);
+        assert_eq!(budget.used(), 2);
+        assert_eq!(clone_budget.used(), 2);
+
+        // Filling the clone's budget leaves room in the original's.
+        clone.bool.push(true).unwrap();
+        assert!(clone.int.push(3).is_err());
+        assert_eq!(state.int.size_budget().unwrap().used(), 2);
+        let mut state = state;
+        state.int.push(3).unwrap();
+        drop(clone);
+        assert_eq!(state.int.size_budget().unwrap().used(), 3);
+    }
 }
@@ -0,0 +1,107 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// A limit on the total size of the values on a group of stacks, e.g., all
+/// the stacks of a state, so that a program can't use up memory by filling
+/// every stack to its maximum size.
+///
+/// Each stack measures its values with its size function (see
+/// [`Stack::set_size_fn`](super::stack::Stack::set_size_fn)), which counts
+/// each value as one item by default. A stack of strings or vectors could
+/// count approximate bytes instead, e.g., with [`String::len`] or
+/// [`approximate_bytes`].
+///
+/// The budget is shared by the stacks through an `Arc`, and pushes that
+/// would exceed it fail with [`StackError::Overflow`](super::stack::StackError::Overflow),
+/// leaving the stack unchanged. Cloning a stack shares its budget, so the
+/// clone's values count against the budget too (until it's dropped), but
+/// cloning a state (see [`SizeBudgetState`](super::SizeBudgetState)) gives
+/// the clone a budget of its own.
+#[derive(Debug)]
+pub struct SizeBudget {
+    limit: usize,
+    used: AtomicUsize,
+}
+
+impl SizeBudget {
+    #[must_use]
+    pub const fn new(limit: usize) -> Self {
+        Self {
+            limit,
+            used: AtomicUsize::new(0),
+        }
+    }
+
+    #[must_use]
+    pub const fn limit(&self) -> usize {
+        self.limit
+    }
+
+    /// The total size of the values on all the stacks using this budget.
+    #[must_use]
+    pub fn used(&self) -> usize {
+        self.used.load(Ordering::Relaxed)
+    }
+
+    /// Whether `size` more would fit within the limit, e.g., to check before
+    /// building a large value rather than after.
+    #[must_use]
+    pub fn has_room_for(&self, size: usize) -> bool {
+        self.used()
+            .checked_add(size)
+            .is_some_and(|total| total <= self.limit)
+    }
+
+    /// Add `size` to the amount used, returning `false` (and leaving the
+    /// budget unchanged) if that would exceed the limit.
+    #[must_use]
+    pub fn try_charge(&self, size: usize) -> bool {
+        self.used
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |used| {
+                used.checked_add(size).filter(|&total| total <= self.limit)
+            })
+            .is_ok()
+    }
+
+    /// Add `size` to the amount used, even if that exceeds the limit. This
+    /// is for values that are already on a stack, e.g., when a budget is
+    /// attached to a stack that isn't empty.
+    pub fn charge(&self, size: usize) {
+        self.used.fetch_add(size, Ordering::Relaxed);
+    }
+
+    /// Give back `size` that was previously charged.
+    pub fn refund(&self, size: usize) {
+        self.used.fetch_sub(size, Ordering::Relaxed);
+    }
+}
+
+/// The approximate number of bytes used by `values`, for use as the size
+/// function of a stack of vectors.
+// This takes a `&Vec<T>` rather than a slice so that it can be used as a
+// `fn(&Vec<T>) -> usize`.
+#[allow(clippy::ptr_arg)]
+#[must_use]
+pub const fn approximate_bytes<T>(values: &Vec<T>) -> usize {
+    std::mem::size_of_val(values.as_slice())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn charges_up_to_the_limit() {
+        let budget = SizeBudget::new(5);
+        assert!(budget.try_charge(3));
+        assert!(budget.has_room_for(2));
+        assert!(!budget.has_room_for(3));
+        assert!(!budget.try_charge(3));
+        assert_eq!(budget.used(), 3);
+        assert!(budget.try_charge(2));
+        budget.refund(4);
+        assert_eq!(budget.used(), 1);
+        budget.charge(10);
+        assert_eq!(budget.used(), 11);
+        assert!(!budget.try_charge(0));
+    }
+}
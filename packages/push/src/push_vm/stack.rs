@@ -1,5 +1,8 @@
+use std::sync::Arc;
+
 use collectable::TryExtend;
 
+use super::size_budget::SizeBudget;
 use crate::error::{Error, InstructionResult, MapInstructionError};
 
 pub trait TypeEq {
@@ -17,34 +20,22 @@ pub trait HasStack<T> {
     /// # Errors
     ///
     /// Returns a fatal error if the stack is in fact full.
-    fn not_full<U: TypeEq<This = T>>(self) -> InstructionResult<Self, StackError>
-    where
-        Self: Sized,
-    {
+    fn not_full<U: TypeEq<This = T>>(&self) -> InstructionResult<StackError> {
         if self.stack::<U>().is_full() {
-            Err(Error::fatal(
-                self,
-                StackError::Overflow {
-                    // TODO: Should make sure to overflow a stack so we know what this looks like.
-                    stack_type: std::any::type_name::<T>(),
-                },
-            ))
+            Err(Error::fatal(StackError::Overflow {
+                // TODO: Should make sure to overflow a stack so we know what this looks like.
+                stack_type: std::any::type_name::<T>(),
+            }))
         } else {
-            Ok(self)
+            Ok(())
         }
     }
 
     /// # Errors
     ///
     /// Returns a fatal error if pushing onto the specified stack overflows.
-    fn with_push(mut self, value: T) -> InstructionResult<Self, StackError>
-    where
-        Self: Sized,
-    {
-        match self.stack_mut::<T>().push(value) {
-            Ok(()) => Ok(self),
-            Err(error) => Err(Error::fatal(self, error)),
-        }
+    fn try_push(&mut self, value: T) -> InstructionResult<StackError> {
+        self.stack_mut::<T>().push(value).map_err(Error::fatal)
     }
 
     /// This removes `num_to_replace` items from the `<T>` stack,
@@ -69,24 +60,17 @@ pub trait HasStack<T> {
     ///
     /// This also returns a fatal error if pushing onto the specified stack
     /// overflows, which should really never happen assuming we pop at least
-    /// one value off the stack.
-    fn with_replace(
-        mut self,
-        num_to_replace: usize,
-        value: T,
-    ) -> InstructionResult<Self, StackError>
-    where
-        Self: Sized,
-    {
-        let stack = self.stack_mut::<T>();
-        match stack.discard(num_to_replace) {
-            Ok(()) => self.with_push(value),
-            Err(error) => Err(Error::fatal(self, error)),
-        }
+    /// one value off the stack, unless the stack has a [`SizeBudget`] and
+    /// `value` is bigger than the values that were removed.
+    fn try_replace(&mut self, num_to_replace: usize, value: T) -> InstructionResult<StackError> {
+        self.stack_mut::<T>()
+            .discard(num_to_replace)
+            .map_err(Error::fatal)?;
+        self.try_push(value)
     }
 }
 
-#[derive(thiserror::Error, Debug, Eq, PartialEq)]
+#[derive(thiserror::Error, Debug, Clone, Eq, PartialEq)]
 pub enum StackError {
     #[error("Requested {num_requested} elements from stack with {num_present} elements.")]
     Underflow {
@@ -97,12 +81,57 @@ pub enum StackError {
     Overflow { stack_type: &'static str },
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug)]
 pub struct Stack<T> {
     max_stack_size: usize,
     values: Vec<T>,
+    /// The budget shared with other stacks, if any, along with the function
+    /// that measures the values for it and the total size of the values
+    /// that are on this stack.
+    size_budget: Option<Arc<SizeBudget>>,
+    size_fn: fn(&T) -> usize,
+    budget_used: usize,
+}
+
+// The size of every value is one item unless a stack says otherwise.
+const fn one_item<T>(_: &T) -> usize {
+    1
+}
+
+// These are implemented by hand so that a clone's values are charged to the
+// budget it shares with the original stack, and so that comparisons ignore
+// the budget.
+impl<T: Clone> Clone for Stack<T> {
+    fn clone(&self) -> Self {
+        if let Some(budget) = &self.size_budget {
+            budget.charge(self.budget_used);
+        }
+        Self {
+            max_stack_size: self.max_stack_size,
+            values: self.values.clone(),
+            size_budget: self.size_budget.clone(),
+            size_fn: self.size_fn,
+            budget_used: self.budget_used,
+        }
+    }
 }
 
+impl<T> Drop for Stack<T> {
+    fn drop(&mut self) {
+        if let Some(budget) = &self.size_budget {
+            budget.refund(self.budget_used);
+        }
+    }
+}
+
+impl<T: PartialEq> PartialEq for Stack<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.max_stack_size == other.max_stack_size && self.values == other.values
+    }
+}
+
+impl<T: Eq> Eq for Stack<T> {}
+
 pub trait StackType {
     type Type;
 }
@@ -120,6 +149,9 @@ impl<T> Default for Stack<T> {
         Self {
             max_stack_size: usize::MAX,
             values: Vec::default(),
+            size_budget: None,
+            size_fn: one_item,
+            budget_used: 0,
         }
     }
 }
@@ -172,6 +204,56 @@ impl<T> Stack<T> {
         self.max_stack_size
     }
 
+    /// Charges the values on this stack, and any that are pushed onto it,
+    /// to `size_budget` (instead of any previous budget). If the values
+    /// already on the stack exceed the budget nothing more can be pushed
+    /// onto any of the stacks using it until some are removed.
+    pub fn set_size_budget(&mut self, size_budget: Option<Arc<SizeBudget>>) {
+        self.release_budget();
+        self.size_budget = size_budget;
+        self.charge_budget();
+    }
+
+    #[must_use]
+    pub const fn size_budget(&self) -> Option<&Arc<SizeBudget>> {
+        self.size_budget.as_ref()
+    }
+
+    /// Sets the function that measures the size of each value for the
+    /// stack's [`SizeBudget`]. By default each value counts as one item.
+    pub fn set_size_fn(&mut self, size_fn: fn(&T) -> usize) {
+        self.release_budget();
+        self.size_fn = size_fn;
+        self.charge_budget();
+    }
+
+    /// Refunds everything on this stack to the budget.
+    fn release_budget(&mut self) {
+        if let Some(budget) = &self.size_budget {
+            budget.refund(self.budget_used);
+        }
+        self.budget_used = 0;
+    }
+
+    /// Charges everything on this stack to the budget, even if that exceeds
+    /// the budget's limit.
+    fn charge_budget(&mut self) {
+        if let Some(budget) = &self.size_budget {
+            self.budget_used = self.values.iter().map(self.size_fn).sum();
+            budget.charge(self.budget_used);
+        }
+    }
+
+    /// Refunds `value`, which has just been removed from the stack, to the
+    /// budget.
+    fn refund(&mut self, value: &T) {
+        if let Some(budget) = &self.size_budget {
+            let size = (self.size_fn)(value);
+            budget.refund(size);
+            self.budget_used -= size;
+        }
+    }
+
     /// Returns the size of this stack.
     #[must_use]
     pub fn size(&self) -> usize {
@@ -184,6 +266,13 @@ impl<T> Stack<T> {
         self.values.is_empty()
     }
 
+    /// Removes all the elements from the stack, keeping its capacity (and
+    /// its maximum size) so that it can be reused without reallocating.
+    pub fn clear(&mut self) {
+        self.release_budget();
+        self.values.clear();
+    }
+
     /// Returns `true` if the stack has `max_stack_size()` elements.
     #[must_use]
     pub fn is_full(&self) -> bool {
@@ -230,6 +319,23 @@ impl<T> Stack<T> {
         }
     }
 
+    /// Returns references to the top three elements of the stack, or an
+    /// error if the stack has less than three elements.
+    ///
+    /// # Errors
+    ///
+    /// Returns `StackError::Underflow` error if the stack has less than
+    /// three elements.
+    pub fn top3(&self) -> Result<(&T, &T, &T), StackError> {
+        match self.values.as_slice() {
+            [.., z, y, x] => Ok((x, y, z)),
+            _ => Err(StackError::Underflow {
+                num_requested: 3,
+                num_present: self.size(),
+            }),
+        }
+    }
+
     /// Removes the top element from a stack and returns it, or
     /// `StackError::Underflow` if it is empty.
     ///
@@ -237,10 +343,12 @@ impl<T> Stack<T> {
     ///
     /// Returns `StackError::Underflow` if the stack is empty.
     pub fn pop(&mut self) -> Result<T, StackError> {
-        self.values.pop().ok_or(StackError::Underflow {
+        let value = self.values.pop().ok_or(StackError::Underflow {
             num_requested: 1,
             num_present: 0,
-        })
+        })?;
+        self.refund(&value);
+        Ok(value)
     }
 
     /// Removes the top two elements from a stack and returns them in a pair.
@@ -293,22 +401,69 @@ impl<T> Stack<T> {
 
     /// Pushes `value` onto the top of the stack, returning
     /// `StackError::StackOverflow` if doing so would exceed the
-    /// `max_stack_size()` for this stack.
+    /// `max_stack_size()` for this stack, or the stack's [`SizeBudget`].
     ///
     /// # Errors
     ///
     /// Returns `StackError::Overflow` if the stack was already full, i.e.,
     /// pushing on `value` would cause the stack size to exceed
-    /// `max_stack_size()`.
+    /// `max_stack_size()`, or if there isn't room for `value` in the
+    /// budget.
     pub fn push(&mut self, value: T) -> Result<(), StackError> {
+        let overflow = StackError::Overflow {
+            stack_type: std::any::type_name::<T>(),
+        };
         if self.size() == self.max_stack_size {
-            Err(StackError::Overflow {
-                stack_type: std::any::type_name::<T>(),
-            })
-        } else {
-            self.values.push(value);
-            Ok(())
+            return Err(overflow);
+        }
+        if let Some(budget) = &self.size_budget {
+            let size = (self.size_fn)(&value);
+            if !budget.try_charge(size) {
+                return Err(overflow);
+            }
+            self.budget_used += size;
+        }
+        self.values.push(value);
+        Ok(())
+    }
+
+    /// Replaces the top `num_to_replace` elements with `value`, checking
+    /// first that there's room for `value` in the stack's [`SizeBudget`]
+    /// once they're removed, so that the stack is unchanged if there isn't.
+    ///
+    /// # Errors
+    ///
+    /// Returns `StackError::Underflow` if the stack has fewer than
+    /// `num_to_replace` elements on it, or `StackError::Overflow` if there
+    /// isn't room for `value` on the stack or in the budget.
+    pub fn replace(&mut self, num_to_replace: usize, value: T) -> Result<(), StackError> {
+        let stack_size = self.size();
+        if num_to_replace > stack_size {
+            return Err(StackError::Underflow {
+                num_requested: num_to_replace,
+                num_present: stack_size,
+            });
+        }
+        let overflow = StackError::Overflow {
+            stack_type: std::any::type_name::<T>(),
+        };
+        if stack_size - num_to_replace == self.max_stack_size {
+            return Err(overflow);
+        }
+        if let Some(budget) = &self.size_budget {
+            let replaced: usize = self.values[stack_size - num_to_replace..]
+                .iter()
+                .map(self.size_fn)
+                .sum();
+            let fits = (budget.used() - replaced)
+                .checked_add((self.size_fn)(&value))
+                .is_some_and(|total| total <= budget.limit());
+            if !fits {
+                return Err(overflow);
+            }
         }
+        self.discard(num_to_replace)?;
+        self.push(value)
     }
 
     /// Adds the given sequence of values to this stack.
@@ -331,6 +486,9 @@ impl<T> Stack<T> {
     /// elements would cause the stack size to exceed maximum stack size
     /// for this stack, as set with [`Stack::set_max_stack_size`].
     ///
+    /// It's also returned if there isn't room for the elements in the
+    /// stack's [`SizeBudget`].
+    ///
     /// # Examples
     ///
     /// ```
@@ -367,7 +525,18 @@ impl<T> Stack<T> {
                 stack_type: std::any::type_name::<T>(),
             });
         }
+        let old_size = self.size();
         self.values.extend(iter.rev());
+        if let Some(budget) = &self.size_budget {
+            let size = self.values[old_size..].iter().map(self.size_fn).sum();
+            if !budget.try_charge(size) {
+                self.values.truncate(old_size);
+                return Err(StackError::Overflow {
+                    stack_type: std::any::type_name::<T>(),
+                });
+            }
+            self.budget_used += size;
+        }
         Ok(())
     }
 }
@@ -378,9 +547,11 @@ pub trait StackPush<T, E> {
     ///
     /// # Errors
     ///
-    /// Returns an error of type `E` if pushing this value fails, e.g.,
-    /// if adding this element exceeded the maximum stack size.
-    fn with_stack_push<S>(self, state: S) -> InstructionResult<S, E>
+    /// Returns a recoverable error of type `E` if `self` is an error, in
+    /// which case the state is unchanged, or a fatal error if pushing this
+    /// value fails, e.g., if adding this element exceeded the maximum stack
+    /// size.
+    fn with_stack_push<S>(self, state: &mut S) -> InstructionResult<E>
     where
         S: HasStack<T>;
 
@@ -389,10 +560,12 @@ pub trait StackPush<T, E> {
     ///
     /// # Errors
     ///
-    /// Returns an error of type `E` if the replacement fails. This could
-    /// be, for example, because there aren't `num_to_replace` items on the
-    /// stack, or if adding the new element would exceed the maximum stack size.
-    fn with_stack_replace<S>(self, num_to_replace: usize, state: S) -> InstructionResult<S, E>
+    /// Returns a recoverable error of type `E` if `self` is an error, in
+    /// which case the state is unchanged, or a fatal error if the
+    /// replacement fails. This could be, for example, because there aren't
+    /// `num_to_replace` items on the stack, or if adding the new element
+    /// would exceed the maximum stack size.
+    fn with_stack_replace<S>(self, num_to_replace: usize, state: &mut S) -> InstructionResult<E>
     where
         S: HasStack<T>;
 }
@@ -401,60 +574,58 @@ impl<T, E1, E2> StackPush<T, E2> for Result<T, E1>
 where
     E2: From<E1> + From<StackError>,
 {
-    fn with_stack_push<S>(self, state: S) -> InstructionResult<S, E2>
+    fn with_stack_push<S>(self, state: &mut S) -> InstructionResult<E2>
     where
         S: HasStack<T>,
     {
         match self {
-            Ok(val) => state.with_push(val).map_err_into(),
-            Err(err) => Err(Error::recoverable(state, err)),
+            Ok(val) => state.try_push(val).map_err_into(),
+            Err(err) => Err(Error::recoverable(err)),
         }
     }
 
-    fn with_stack_replace<S>(self, num_to_replace: usize, state: S) -> InstructionResult<S, E2>
+    fn with_stack_replace<S>(self, num_to_replace: usize, state: &mut S) -> InstructionResult<E2>
     where
         S: HasStack<T>,
     {
         match self {
-            Ok(val) => state.with_replace(num_to_replace, val).map_err_into(),
-            Err(err) => Err(Error::recoverable(state, err)),
+            Ok(val) => state.try_replace(num_to_replace, val).map_err_into(),
+            Err(err) => Err(Error::recoverable(err)),
         }
     }
 }
 
-pub trait StackDiscard<S, E> {
-    /// Discards the top `num_to_discard` elements from `S`, returning an error
-    /// of type `E` if that fails.
+pub trait StackDiscard<E> {
+    /// Discards the top `num_to_discard` elements from the `T` stack in
+    /// `state` if `self` is `Ok`, returning an error of type `E` if that
+    /// fails.
     ///
     /// # Errors
     ///
-    /// Returns an error of type `E` if this fails, e.g., if there are not
+    /// Returns `self` if it was an error, or a fatal error if there are not
     /// `num_to_discard` elements in the stack.
-    fn with_stack_discard<T>(self, num_to_discard: usize) -> InstructionResult<S, E>
-    where
-        S: HasStack<T>;
+    fn with_stack_discard<T>(
+        self,
+        num_to_discard: usize,
+        state: &mut impl HasStack<T>,
+    ) -> InstructionResult<E>;
 }
 
-impl<S, E> StackDiscard<S, E> for InstructionResult<S, E>
+impl<E> StackDiscard<E> for InstructionResult<E>
 where
     E: From<StackError>,
 {
-    fn with_stack_discard<T>(self, num_to_discard: usize) -> Self
-    where
-        S: HasStack<T>,
-    {
-        match self {
-            Ok(mut state) => match state.stack_mut::<T>().discard(num_to_discard) {
-                Ok(()) => Ok(state),
-                // TODO: any::type_name::<T>() to get the type name – put this in Stack
-                // If this fails it's because we tried to pop too many things from the stack.
-                // We _should_ have previously checked that there were
-                // that many things (using `top()` for example),
-                // so really this should never happen.
-                Err(error) => Err(Error::fatal(state, error)),
-            },
-            Err(error) => Err(error),
-        }
+    fn with_stack_discard<T>(self, num_to_discard: usize, state: &mut impl HasStack<T>) -> Self {
+        self?;
+        // TODO: any::type_name::<T>() to get the type name – put this in Stack
+        // If this fails it's because we tried to pop too many things from the stack.
+        // We _should_ have previously checked that there were
+        // that many things (using `top()` for example),
+        // so really this should never happen.
+        state
+            .stack_mut::<T>()
+            .discard(num_to_discard)
+            .map_err(Error::fatal)
     }
 }
 
@@ -463,7 +634,9 @@ where
 
 #[cfg(test)]
 mod test {
-    use super::{Stack, StackError};
+    use std::sync::Arc;
+
+    use super::{SizeBudget, Stack, StackError};
 
     #[test]
     #[allow(clippy::unwrap_used)]
@@ -478,4 +651,58 @@ mod test {
             }
         );
     }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn size_budget_is_shared() {
+        let budget = Arc::new(SizeBudget::new(10));
+        let mut ints: Stack<i64> = Stack::default();
+        ints.set_size_budget(Some(Arc::clone(&budget)));
+        let mut strings: Stack<String> = Stack::default();
+        strings.set_size_fn(String::len);
+        strings.set_size_budget(Some(Arc::clone(&budget)));
+
+        strings.push("hello".to_string()).unwrap();
+        ints.try_extend([1, 2, 3]).unwrap();
+        assert_eq!(budget.used(), 8);
+        // Neither of these fit, and neither changes the stacks.
+        assert!(strings.push("abc".to_string()).is_err());
+        assert!(ints.try_extend([4, 5, 6]).is_err());
+        assert_eq!(ints.size(), 3);
+
+        strings.pop().unwrap();
+        strings.push("abc".to_string()).unwrap();
+        assert_eq!(budget.used(), 6);
+
+        let copy = ints.clone();
+        assert_eq!(budget.used(), 9);
+        drop(copy);
+        ints.clear();
+        assert_eq!(budget.used(), 3);
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn replace_checks_the_budget_first() {
+        let budget = Arc::new(SizeBudget::new(10));
+        let mut strings: Stack<String> = Stack::default();
+        strings.set_size_fn(String::len);
+        strings.set_size_budget(Some(Arc::clone(&budget)));
+        strings
+            .try_extend(["abc".to_string(), "defg".to_string()])
+            .unwrap();
+
+        // The replaced values make room for the new one.
+        strings.replace(1, "hijklm".to_string()).unwrap();
+        assert_eq!(budget.used(), 10);
+        // This doesn't fit even without them, so nothing is removed.
+        assert_eq!(
+            strings.replace(2, "x".repeat(11)),
+            Err(StackError::Overflow {
+                stack_type: std::any::type_name::<String>()
+            })
+        );
+        assert_eq!(strings.size(), 2);
+        assert_eq!(budget.used(), 10);
+    }
 }
@@ -0,0 +1,216 @@
+//! A text form for Push programs, so that programs can be printed, saved,
+//! and read back in, e.g., to replay an evolved program in the debugger.
+//!
+//! A program is a sequence of whitespace-separated tokens, where
+//!
+//! - `(` and `)` open and close a block,
+//! - `true`, `false`, integers (`-3`), and floats (`2.5`) push that value,
+//! - instruction names are the stack name followed by the instruction, as in
+//!   `Int-Add`, `Bool-Not`, or `Code-Quote`, and
+//! - anything else is an input variable.
+//!
+//! So `x 2 Int-Multiply ( 1.5 Float-Add ) 1.5 Float-Add` is a program that
+//! doubles the integer input `x` and then adds 1.5 to the top float twice.
+//! The exec instructions (e.g., `Exec-Dup`) can't be performed yet, so they
+//! aren't accepted.
+//! Floats are always printed with a decimal point (or an exponent) so that
+//! they can be read back in as floats.
+
+use std::{fmt::Display, mem};
+
+use anyhow::{bail, Result};
+use ordered_float::OrderedFloat;
+use strum::IntoEnumIterator;
+
+use super::program::PushProgram;
+use crate::instruction::{
+    variable_name::VariableName, BoolInstruction, CodeInstruction, ExecInstruction,
+    FloatInstruction, IntInstruction, PushInstruction,
+};
+
+/// All the instructions that are written as a name, i.e., everything except
+/// input variables and the instructions that push constants. This leaves
+/// out the exec instructions, which can't be performed yet.
+fn named_instructions() -> impl Iterator<Item = PushInstruction> {
+    IntInstruction::iter()
+        .filter(|instruction| !matches!(instruction, IntInstruction::Push(_)))
+        .map(PushInstruction::from)
+        .chain(
+            FloatInstruction::iter()
+                .filter(|instruction| !matches!(instruction, FloatInstruction::Push(_)))
+                .map(PushInstruction::from),
+        )
+        .chain(
+            BoolInstruction::iter()
+                .filter(|instruction| !matches!(instruction, BoolInstruction::Push(_)))
+                .map(PushInstruction::from),
+        )
+        .chain(CodeInstruction::iter().map(PushInstruction::from))
+}
+
+/// Parse a literal value (a boolean, integer, or float) into the
+/// instruction that pushes it, or return `None` if `token` isn't one.
+#[must_use]
+pub fn parse_literal(token: &str) -> Option<PushInstruction> {
+    token
+        .parse::<bool>()
+        .ok()
+        .map(PushInstruction::push_bool)
+        .or_else(|| token.parse::<i64>().ok().map(PushInstruction::push_int))
+        .or_else(|| {
+            token
+                .parse::<f64>()
+                .ok()
+                .map(|f| PushInstruction::push_float(OrderedFloat(f)))
+        })
+}
+
+/// Parse a single (non-parenthesis) token of the text form into an
+/// instruction.
+///
+/// # Errors
+///
+/// This fails if the token is empty or contains a parenthesis, or if it's
+/// the name of an exec instruction.
+pub fn parse_instruction(token: &str) -> Result<PushInstruction> {
+    if token.is_empty() || token.contains(['(', ')']) || token.contains(char::is_whitespace) {
+        bail!("Invalid instruction {token:?}");
+    }
+    if let Some(instruction) = parse_literal(token) {
+        return Ok(instruction);
+    }
+    if ExecInstruction::iter()
+        .any(|instruction| format!("{:?}", PushInstruction::from(instruction)) == token)
+    {
+        bail!("The exec instruction {token} can't be performed yet");
+    }
+    Ok(named_instructions()
+        .find(|instruction| format!("{instruction:?}") == token)
+        .unwrap_or_else(|| VariableName::from(token).into()))
+}
+
+/// Parse a program in the text form.
+///
+/// # Errors
+///
+/// This fails if the parentheses in `text` don't match.
+pub fn parse(text: &str) -> Result<Vec<PushProgram>> {
+    let spaced = text.replace('(', " ( ").replace(')', " ) ");
+    // The blocks that enclose the one we're currently parsing.
+    let mut enclosing = Vec::new();
+    let mut program = Vec::new();
+    for token in spaced.split_whitespace() {
+        match token {
+            "(" => enclosing.push(mem::take(&mut program)),
+            ")" => {
+                let Some(outer) = enclosing.pop() else {
+                    bail!("Unmatched `)` in program {text:?}");
+                };
+                let block = mem::replace(&mut program, outer);
+                program.push(PushProgram::block(block));
+            }
+            _ => program.push(PushProgram::Instruction(parse_instruction(token)?)),
+        }
+    }
+    if !enclosing.is_empty() {
+        bail!("{} unclosed `(` in program {text:?}", enclosing.len());
+    }
+    Ok(program)
+}
+
+/// Write `program` in the text form, which [`parse`] turns back into the
+/// same program.
+#[must_use]
+pub fn to_text(program: &[PushProgram]) -> String {
+    program
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+impl Display for PushInstruction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::IntInstruction(IntInstruction::Push(i)) => write!(f, "{i}"),
+            // `Debug` always includes a decimal point or an exponent for floats.
+            Self::FloatInstruction(FloatInstruction::Push(OrderedFloat(x))) => write!(f, "{x:?}"),
+            Self::BoolInstruction(BoolInstruction::Push(b)) => write!(f, "{b}"),
+            _ => write!(f, "{self:?}"),
+        }
+    }
+}
+
+impl Display for PushProgram {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Instruction(instruction) => write!(f, "{instruction}"),
+            Self::Block(block) => write!(f, "({})", to_text(block)),
+            // A cursor stands for its programs spliced into the exec stack,
+            // so they aren't in parentheses.
+            Self::Cursor {
+                block, position, ..
+            } => write!(f, "{}", to_text(block.get(*position..).unwrap_or_default())),
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::list_into::vec_into;
+
+    #[test]
+    fn parses_every_kind_of_token() {
+        let program = parse("x -3 2.5 true Int-Add Code-Quote (Bool-And) ( y Float-Dup )").unwrap();
+        assert_eq!(
+            program,
+            vec_into![
+                VariableName::from("x"),
+                IntInstruction::Push(-3),
+                FloatInstruction::Push(OrderedFloat(2.5)),
+                BoolInstruction::Push(true),
+                IntInstruction::Add,
+                CodeInstruction::Quote,
+                PushProgram::block(vec_into![BoolInstruction::And]),
+                PushProgram::block(vec_into![VariableName::from("y"), FloatInstruction::Dup]),
+            ]
+        );
+    }
+
+    #[test]
+    fn round_trips() {
+        let text = "x 1.0 Float-Add Code-Quote (Int-Inc (false)) 1e100";
+        let program = parse(text).unwrap();
+        assert_eq!(to_text(&program), text);
+        assert_eq!(parse(&to_text(&program)).unwrap(), program);
+    }
+
+    #[test]
+    fn exec_instructions_are_rejected() {
+        assert!(parse("x Exec-Dup (Int-Inc)").is_err());
+        assert!(parse_instruction("Exec-IfElse").is_err());
+    }
+
+    #[test]
+    fn literals() {
+        assert_eq!(parse_literal("-3"), Some(PushInstruction::push_int(-3)));
+        assert_eq!(
+            parse_literal("2.5"),
+            Some(PushInstruction::push_float(OrderedFloat(2.5)))
+        );
+        assert_eq!(
+            parse_literal("false"),
+            Some(PushInstruction::push_bool(false))
+        );
+        assert_eq!(parse_literal("x"), None);
+        assert_eq!(parse_literal("Int-Add"), None);
+    }
+
+    #[test]
+    fn mismatched_parentheses() {
+        assert!(parse("Int-Add )").is_err());
+        assert!(parse("( Int-Add ( x )").is_err());
+    }
+}
@@ -0,0 +1,400 @@
+//! Tracing the execution of a Push program, and stepping through it one
+//! instruction at a time.
+//!
+//! A [`Debugger`] performs the instructions on the exec stack one at a time,
+//! recording a [`TraceStep`] for each one, and can stop at breakpoints on
+//! particular instructions. [`PushState::run_with_trace`] runs a program the
+//! same way, up to a step limit, returning the whole [`Trace`], which can be
+//! written out as JSON Lines.
+
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+
+use super::{
+    program::PushProgram,
+    push_state::{ProgramState, PushState},
+    State,
+};
+use crate::{
+    error::stateful::FatalError,
+    instruction::{instruction_error::PushInstructionError, PushInstruction},
+};
+
+/// The top of each of the stacks, or `None` if that stack is empty.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StackTops {
+    /// The next program on the exec stack, in the text form from
+    /// [`text`](super::text).
+    pub exec: Option<String>,
+    pub int: Option<i64>,
+    pub float: Option<f64>,
+    pub bool: Option<bool>,
+    /// The top of the code stack, in the text form.
+    pub code: Option<String>,
+}
+
+impl From<&PushState> for StackTops {
+    fn from(state: &PushState) -> Self {
+        Self {
+            exec: state.exec.next_program().ok().map(ToString::to_string),
+            int: state.int.top().ok().copied(),
+            float: state.float.top().ok().map(|f| f.0),
+            bool: state.bool.top().ok().copied(),
+            code: state.code.top().ok().map(ToString::to_string),
+        }
+    }
+}
+
+/// What happened when one instruction (or block) from the exec stack was
+/// performed.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TraceStep {
+    /// The number of the step, counting from 0.
+    pub step: usize,
+    /// The instruction (or block) that was performed, in the text form.
+    pub instruction: String,
+    /// The top of each stack after the instruction was performed.
+    #[serde(flatten)]
+    pub stacks: StackTops,
+    /// The error that the instruction returned, if any, when the state's
+    /// error policy carried on after it. For recoverable errors the
+    /// instruction left the other stacks unchanged.
+    pub recovered_error: Option<String>,
+    /// The error that the instruction returned, if any, when the state's
+    /// error policy aborted on it, which stops the program.
+    pub fatal_error: Option<String>,
+}
+
+/// The steps that were performed while running a program.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Trace {
+    steps: Vec<TraceStep>,
+}
+
+impl Trace {
+    #[must_use]
+    pub fn steps(&self) -> &[TraceStep] {
+        &self.steps
+    }
+
+    /// Write the trace as JSON Lines, with one object per step.
+    ///
+    /// # Errors
+    ///
+    /// This fails if the data can't be written.
+    pub fn write_json_lines(&self, mut writer: impl Write) -> Result<()> {
+        for step in &self.steps {
+            serde_json::to_writer(&mut writer, step)?;
+            writeln!(writer)?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Write the trace to a JSON Lines file at `path`; see
+    /// [`Trace::write_json_lines`].
+    ///
+    /// # Errors
+    ///
+    /// This fails if the file can't be created or written.
+    pub fn write_json_lines_file(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let file =
+            File::create(path).with_context(|| format!("Failed to create {}", path.display()))?;
+        self.write_json_lines(BufWriter::new(file))
+            .with_context(|| format!("Failed to write trace to {}", path.display()))
+    }
+}
+
+/// Where a [`Debugger`] is in running its program.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    /// There are more instructions to perform.
+    Paused,
+    /// The next instruction on the exec stack has a breakpoint.
+    Breakpoint,
+    /// The exec stack is empty.
+    Finished,
+    /// An instruction returned a fatal error.
+    Failed,
+    /// The step limit was reached with instructions still to perform.
+    StepLimit,
+}
+
+/// Steps through a program, recording a [`Trace`].
+#[derive(Debug)]
+pub struct Debugger {
+    state: PushState,
+    trace: Trace,
+    breakpoints: Vec<PushInstruction>,
+    fatal_error: Option<PushInstructionError>,
+    step_limit: usize,
+}
+
+impl Debugger {
+    /// Debug the program on the exec stack of `state`, with no step limit.
+    #[must_use]
+    pub fn new(mut state: PushState) -> Self {
+        state.error_counts.clear();
+        Self {
+            state,
+            trace: Trace::default(),
+            breakpoints: Vec::new(),
+            fatal_error: None,
+            step_limit: usize::MAX,
+        }
+    }
+
+    /// Stop the program once `step_limit` instructions have been performed,
+    /// as [`PushState::run_with_step_limit`] does.
+    #[must_use]
+    pub const fn with_step_limit(mut self, step_limit: usize) -> Self {
+        self.step_limit = step_limit;
+        self
+    }
+
+    #[must_use]
+    pub const fn step_limit(&self) -> usize {
+        self.step_limit
+    }
+
+    /// Stop whenever `instruction` is the next instruction on the exec
+    /// stack.
+    pub fn add_breakpoint(&mut self, instruction: PushInstruction) {
+        if !self.breakpoints.contains(&instruction) {
+            self.breakpoints.push(instruction);
+        }
+    }
+
+    pub fn remove_breakpoint(&mut self, instruction: &PushInstruction) {
+        self.breakpoints
+            .retain(|breakpoint| breakpoint != instruction);
+    }
+
+    #[must_use]
+    pub fn breakpoints(&self) -> &[PushInstruction] {
+        &self.breakpoints
+    }
+
+    /// The current state, which after a fatal error is the state part way
+    /// through the instruction that failed.
+    #[must_use]
+    pub const fn state(&self) -> &PushState {
+        &self.state
+    }
+
+    #[must_use]
+    pub const fn trace(&self) -> &Trace {
+        &self.trace
+    }
+
+    #[must_use]
+    pub fn status(&self) -> Status {
+        if self.fatal_error.is_some() {
+            Status::Failed
+        } else {
+            match self.state.exec.next_program() {
+                Err(_) => Status::Finished,
+                Ok(_) if self.trace.steps.len() >= self.step_limit => Status::StepLimit,
+                Ok(PushProgram::Instruction(instruction))
+                    if self.breakpoints.contains(instruction) =>
+                {
+                    Status::Breakpoint
+                }
+                Ok(_) => Status::Paused,
+            }
+        }
+    }
+
+    /// Perform the next instruction on the exec stack, ignoring any
+    /// breakpoint on it. This does nothing if the program has finished or
+    /// failed, or has reached the step limit.
+    pub fn step(&mut self) -> Status {
+        let status = self.status();
+        if matches!(
+            status,
+            Status::Failed | Status::Finished | Status::StepLimit
+        ) {
+            return status;
+        }
+        let Ok(program) = self.state.exec.pop_program() else {
+            return Status::Finished;
+        };
+        let result = self.state.perform(&program);
+        let message = result.as_ref().err().map(|error| error.error().to_string());
+        // Errors that the state's error policy doesn't abort on are recovered
+        // from, even if they're fatal.
+        let (recovered_error, fatal_error) = match self.state.handle_error(result) {
+            Ok(()) => (message, None),
+            Err(error) => {
+                self.fatal_error = Some(error);
+                (None, message)
+            }
+        };
+        self.trace.steps.push(TraceStep {
+            step: self.trace.steps.len(),
+            instruction: program.to_string(),
+            stacks: StackTops::from(&self.state),
+            recovered_error,
+            fatal_error,
+        });
+        self.status()
+    }
+
+    /// Perform instructions until the program finishes, fails, or reaches the
+    /// step limit, or the next instruction has a breakpoint. This always
+    /// performs at least one instruction (if there's one to perform), so that
+    /// we can continue from a breakpoint.
+    pub fn resume(&mut self) -> Status {
+        let mut status = self.step();
+        while status == Status::Paused {
+            status = self.step();
+        }
+        status
+    }
+
+    /// The final state, or the fatal error that stopped the program, along
+    /// with the trace. Reaching the step limit is an error, as it is for
+    /// [`PushState::run_with_step_limit`].
+    pub fn finish(
+        self,
+    ) -> (
+        Result<PushState, FatalError<PushState, PushInstructionError>>,
+        Trace,
+    ) {
+        let step_limit = self.step_limit;
+        let error = match self.status() {
+            Status::StepLimit => Some(PushInstructionError::StepLimitExceeded { step_limit }),
+            _ => self.fatal_error,
+        };
+        let result = match error {
+            Some(error) => Err(FatalError::new(self.state, error)),
+            None => Ok(self.state),
+        };
+        (result, self.trace)
+    }
+}
+
+impl PushState {
+    /// Run the program like [`PushState::run_with_step_limit`], recording a
+    /// [`TraceStep`] for each instruction that is performed.
+    pub fn run_with_trace(
+        self,
+        step_limit: usize,
+    ) -> (Result<Self, FatalError<Self, PushInstructionError>>, Trace) {
+        // There are no breakpoints, so this runs until the program stops.
+        let mut debugger = Debugger::new(self).with_step_limit(step_limit);
+        debugger.resume();
+        debugger.finish()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::{
+        instruction::{variable_name::VariableName, IntInstruction},
+        push_vm::text,
+    };
+
+    fn state(program: &str, max_stack_size: usize) -> PushState {
+        PushState::builder()
+            .with_max_stack_size(max_stack_size)
+            .with_program(text::parse(program).unwrap())
+            .unwrap()
+            .with_int_input("x", 5)
+            .build()
+    }
+
+    #[test]
+    fn records_each_step() {
+        let (result, trace) = state("x 3 Int-Add Bool-And true", 10).run_with_trace(100);
+        assert_eq!(&result.unwrap().int, &vec![8]);
+        let steps = trace.steps();
+        assert_eq!(steps.len(), 5);
+        assert_eq!(steps[0].instruction, "x");
+        assert_eq!(steps[0].stacks.exec.as_deref(), Some("3"));
+        assert_eq!(steps[2].stacks.int, Some(8));
+        // `Bool-And` underflows the bool stack, which is recovered from.
+        assert!(steps[3].recovered_error.is_some());
+        assert_eq!(steps[4].stacks.bool, Some(true));
+        assert_eq!(steps[4].stacks.exec, None);
+
+        let mut json = Vec::new();
+        trace.write_json_lines(&mut json).unwrap();
+        let json = String::from_utf8(json).unwrap();
+        assert_eq!(json.lines().count(), 5);
+        assert!(json.starts_with(
+            r#"{"step":0,"instruction":"x","exec":"3","int":5,"float":null,"bool":null,"#
+        ));
+    }
+
+    #[test]
+    fn stops_at_the_step_limit() {
+        let (result, trace) = state("1 2 3", 10).run_with_trace(2);
+        let error = result.unwrap_err();
+        assert_eq!(
+            error.error(),
+            &PushInstructionError::StepLimitExceeded { step_limit: 2 }
+        );
+        assert_eq!(&error.state().int, &vec![1, 2]);
+        assert_eq!(trace.steps().len(), 2);
+
+        let (result, trace) = state("1 2 3", 10).run_with_trace(3);
+        assert_eq!(&result.unwrap().int, &vec![1, 2, 3]);
+        assert_eq!(trace.steps().len(), 3);
+    }
+
+    #[test]
+    fn stops_at_breakpoints() {
+        let mut debugger = Debugger::new(state("1 2 Int-Add 3 Int-Add", 10));
+        debugger.add_breakpoint(IntInstruction::Add.into());
+        assert_eq!(debugger.resume(), Status::Breakpoint);
+        assert_eq!(&debugger.state().int, &vec![1, 2]);
+        assert_eq!(debugger.resume(), Status::Breakpoint);
+        assert_eq!(&debugger.state().int, &vec![3, 3]);
+        assert_eq!(debugger.step(), Status::Finished);
+        assert_eq!(&debugger.state().int, &vec![6]);
+        assert_eq!(debugger.step(), Status::Finished);
+        assert_eq!(debugger.trace().steps().len(), 5);
+    }
+
+    #[test]
+    fn resume_stops_at_the_step_limit() {
+        // The quoted block performs itself again with `Code-Do`, so this
+        // keeps going until the exec stack overflows.
+        let looping = "Code-Quote ( Code-Do ) Code-Do";
+        let mut debugger = Debugger::new(state(looping, 1_000)).with_step_limit(10);
+        assert_eq!(debugger.resume(), Status::StepLimit);
+        assert_eq!(debugger.trace().steps().len(), 10);
+        assert_eq!(debugger.step(), Status::StepLimit);
+        assert_eq!(debugger.resume(), Status::StepLimit);
+        assert_eq!(debugger.trace().steps().len(), 10);
+        let (result, _) = debugger.finish();
+        assert_eq!(
+            result.unwrap_err().error(),
+            &PushInstructionError::StepLimitExceeded { step_limit: 10 }
+        );
+    }
+
+    #[test]
+    fn stops_at_fatal_errors() {
+        let mut debugger = Debugger::new(state("1 (x x x)", 2));
+        debugger.add_breakpoint(VariableName::from("x").into());
+        // Pushing the block's contents overflows the exec stack, so we never
+        // get to the breakpoint.
+        assert_eq!(debugger.resume(), Status::Failed);
+        assert!(debugger.trace().steps()[1].fatal_error.is_some());
+        assert_eq!(debugger.step(), Status::Failed);
+        let (result, trace) = debugger.finish();
+        assert!(result.is_err());
+        assert_eq!(trace.steps().len(), 2);
+    }
+}
@@ -0,0 +1,227 @@
+#![allow(clippy::unwrap_used)]
+
+use push::{
+    error::Error,
+    instruction::{
+        instruction_error::PushInstructionError, CodeInstruction, CodeInstructionError, CodeState,
+        IntInstruction, MAX_CODE_POINTS,
+    },
+    push_vm::{
+        bytecode::Bytecode, program::PushProgram, push_state::PushState, text, HasStack,
+        SizeBudgetState, State,
+    },
+};
+
+use crate::common::perform;
+
+mod common;
+
+/// Run `program` with the interpreter and as bytecode, check that they
+/// agree, and return the final state.
+fn run(program: &str) -> PushState {
+    let program = text::parse(program).unwrap();
+    let interpreted = PushState::builder()
+        .with_max_stack_size(100)
+        .with_program(program.clone())
+        .unwrap()
+        .build()
+        .run_to_completion()
+        .unwrap();
+    let compiled = Bytecode::compile(&program)
+        .run(
+            PushState::builder()
+                .with_max_stack_size(100)
+                .with_no_program()
+                .build(),
+        )
+        .unwrap();
+    assert_eq!(interpreted.stack::<i64>(), compiled.stack::<i64>());
+    assert_eq!(interpreted.stack::<bool>(), compiled.stack::<bool>());
+    assert_eq!(interpreted.code(), compiled.code());
+    interpreted
+}
+
+fn code(text: &str) -> Vec<PushProgram> {
+    text::parse(text).unwrap()
+}
+
+#[test]
+fn quote_and_do() {
+    let state = run("Code-Quote (1 Int-Inc) Code-Do");
+    assert_eq!(state.stack::<i64>(), &vec![2]);
+    assert!(state.code().is_empty());
+
+    let state = run("Code-Quote Int-Add 2");
+    assert_eq!(state.stack::<i64>(), &vec![2]);
+    assert_eq!(state.code(), &code("Int-Add"));
+}
+
+#[test]
+fn car_cdr_cons_and_append() {
+    assert_eq!(run("Code-Quote (1 2 3) Code-Car").code(), &code("1"));
+    assert_eq!(run("Code-Quote (1 2 3) Code-Cdr").code(), &code("(2 3)"));
+    assert_eq!(run("Code-Quote Int-Add Code-Car").code(), &code("Int-Add"));
+    assert_eq!(run("Code-Quote Int-Add Code-Cdr").code(), &code("()"));
+    assert_eq!(run("Code-Quote () Code-Car").code(), &code("()"));
+    assert_eq!(
+        run("Code-Quote 0 Code-Quote (1 2) Code-Cons").code(),
+        &code("(0 1 2)")
+    );
+    assert_eq!(
+        run("Code-Quote (3 4) Code-Quote (1 2) Code-Append").code(),
+        &code("(1 2 3 4)")
+    );
+    assert_eq!(
+        run("Code-Quote 1 Code-Quote 2 Code-Append").code(),
+        &code("(2 1)")
+    );
+}
+
+#[test]
+fn length_member_and_nth() {
+    let state = run("Code-Quote (1 (2 3) 4) Code-Length");
+    assert_eq!(state.stack::<i64>(), &vec![3]);
+    assert!(state.code().is_empty());
+
+    let state = run("Code-Quote (2 3) Code-Quote (1 (2 3)) Code-Member");
+    assert_eq!(state.stack::<bool>(), &vec![true]);
+    assert!(state.code().is_empty());
+    let state = run("Code-Quote 2 Code-Quote (1 (2 3)) Code-Member");
+    assert_eq!(state.stack::<bool>(), &vec![false]);
+
+    let state = run("-4 Code-Quote (1 2 3) Code-Nth");
+    assert_eq!(state.code(), &code("2"));
+    assert!(state.stack::<i64>().is_empty());
+    assert_eq!(run("7 Code-Quote () Code-Nth").code(), &code("()"));
+}
+
+#[test]
+fn if_and_repeat() {
+    let program = "Code-Quote (1) Code-Quote (2) Code-If";
+    assert_eq!(run(&format!("true {program}")).stack::<i64>(), &vec![1]);
+    assert_eq!(run(&format!("false {program}")).stack::<i64>(), &vec![2]);
+
+    let state = run("0 Code-Quote Int-Inc 3 Code-Repeat");
+    assert_eq!(state.stack::<i64>(), &vec![3]);
+    assert!(state.code().is_empty());
+    let state = run("Code-Quote Int-Inc -1 Code-Repeat");
+    assert!(state.stack::<i64>().is_empty());
+    assert!(state.code().is_empty());
+}
+
+#[test]
+fn repeat_overflows_the_exec_stack() {
+    let mut state = PushState::builder()
+        .with_max_stack_size(10)
+        .with_int_values([11])
+        .unwrap()
+        .with_code_values([IntInstruction::Inc.into()])
+        .unwrap()
+        .with_no_program()
+        .build();
+    let Error::Fatal(_) = perform(CodeInstruction::Repeat, &mut state).unwrap_err() else {
+        panic!("Doing the code 11 times didn't overflow the exec stack");
+    };
+}
+
+#[test]
+fn repeat_is_limited_with_an_unbounded_exec_stack() {
+    let state = |times| {
+        PushState::builder()
+            .with_max_stack_size(usize::MAX)
+            .with_int_values([times])
+            .unwrap()
+            .with_code_values([IntInstruction::Inc.into()])
+            .unwrap()
+            .with_no_program()
+            .build()
+    };
+    // This would try to build a block of 10^15 programs.
+    let mut huge = state(1_000_000_000_000_000);
+    let error = perform(CodeInstruction::Repeat, &mut huge).unwrap_err();
+    assert_eq!(
+        error,
+        Error::recoverable(PushInstructionError::from(CodeInstructionError::TooLarge {
+            op: CodeInstruction::Repeat
+        }))
+    );
+    assert_eq!(huge.code().size(), 1);
+    assert!(huge.stack::<PushProgram>().is_empty());
+
+    let times = i64::try_from(MAX_CODE_POINTS).unwrap();
+    let mut largest = state(times);
+    perform(CodeInstruction::Repeat, &mut largest).unwrap();
+    assert_eq!(
+        largest
+            .stack::<PushProgram>()
+            .top()
+            .unwrap()
+            .contents()
+            .len(),
+        MAX_CODE_POINTS
+    );
+
+    // The exec stack's size budget is checked before building the block.
+    let mut over_budget = state(times).with_size_budget(100);
+    let Error::Fatal(_) = perform(CodeInstruction::Repeat, &mut over_budget).unwrap_err() else {
+        panic!("Doing the code {times} times didn't exceed the size budget");
+    };
+}
+
+#[test]
+fn subst() {
+    assert_eq!(
+        run("Code-Quote 9 Code-Quote 1 Code-Quote (1 (2 1)) Code-Subst").code(),
+        &code("(9 (2 9))")
+    );
+}
+
+#[test]
+fn building_too_large_a_program_fails() {
+    let big = PushProgram::block(vec![IntInstruction::Inc.into(); MAX_CODE_POINTS - 1]);
+    let mut state = PushState::builder()
+        .with_max_stack_size(100)
+        .with_code_values([big, IntInstruction::Dec.into()])
+        .unwrap()
+        .with_no_program()
+        .build();
+    let error = perform(CodeInstruction::Cons, &mut state).unwrap_err();
+    assert_eq!(
+        error,
+        Error::recoverable(PushInstructionError::from(CodeInstructionError::TooLarge {
+            op: CodeInstruction::Cons
+        }))
+    );
+    assert_eq!(state.code().size(), 2);
+    // The block itself is small enough to take apart.
+    perform(CodeInstruction::Cdr, &mut state).unwrap();
+    assert_eq!(
+        state.code().top().unwrap().contents().len(),
+        MAX_CODE_POINTS - 2
+    );
+}
+
+#[test]
+fn subst_over_budget_keeps_its_operands() {
+    let new_state = || {
+        PushState::builder()
+            .with_max_stack_size(100)
+            .with_code_values(code(
+                "(1 1 1 1) 1 (Int-Inc Int-Inc Int-Inc Int-Inc Int-Inc)",
+            ))
+            .unwrap()
+            .with_no_program()
+            .build()
+    };
+    // The result has four copies of the third program, so it's larger
+    // than the three programs it replaces.
+    let mut state = new_state().with_size_budget(20);
+    let Error::Fatal(_) = perform(CodeInstruction::Subst, &mut state).unwrap_err() else {
+        panic!("The substituted program didn't exceed the size budget");
+    };
+    assert_eq!(state.code().size(), 3);
+
+    let mut state = new_state().with_size_budget(30);
+    perform(CodeInstruction::Subst, &mut state).unwrap();
+    assert_eq!(state.code().top().unwrap().size(), 25);
+}
@@ -0,0 +1,53 @@
+use push::{
+    error::{stateful::FatalError, InstructionResult},
+    instruction::{
+        instruction_error::PushInstructionError, CodeState, Instruction, PushInstruction,
+    },
+    push_vm::{
+        bytecode::Bytecode,
+        program::PushProgram,
+        push_state::{OrderedFloat, PushState},
+        HasStack, State,
+    },
+};
+
+/// Perform `instruction` on `state`, after checking that running it as a
+/// one-instruction program gives the same result with the [`PushProgram`]
+/// interpreter and with [`Bytecode`].
+pub fn perform(
+    instruction: impl Into<PushInstruction>,
+    state: &mut PushState,
+) -> InstructionResult<PushInstructionError> {
+    let instruction = instruction.into();
+    let program = [PushProgram::Instruction(instruction.clone())];
+
+    let mut interpreted = state.clone();
+    let interpreted = match interpreted
+        .stack_mut::<PushProgram>()
+        .try_extend(program.iter().cloned())
+    {
+        Ok(()) => interpreted.run_to_completion(),
+        Err(error) => Err(FatalError::new(interpreted, error.into())),
+    };
+    let compiled = Bytecode::compile(&program).run(state.clone());
+    match (&interpreted, &compiled) {
+        (Ok(interpreted), Ok(compiled)) => assert_same_stacks(interpreted, compiled),
+        (Err(interpreted), Err(compiled)) => {
+            assert_eq!(interpreted.error(), compiled.error());
+            assert_same_stacks(interpreted.state(), compiled.state());
+        }
+        _ => panic!("The interpreters disagree: {interpreted:?} and {compiled:?}"),
+    }
+
+    instruction.perform(state)
+}
+
+fn assert_same_stacks(interpreted: &PushState, compiled: &PushState) {
+    assert_eq!(interpreted.stack::<i64>(), compiled.stack::<i64>());
+    assert_eq!(
+        interpreted.stack::<OrderedFloat<f64>>(),
+        compiled.stack::<OrderedFloat<f64>>()
+    );
+    assert_eq!(interpreted.stack::<bool>(), compiled.stack::<bool>());
+    assert_eq!(interpreted.code(), compiled.code());
+}
@@ -0,0 +1,141 @@
+#![allow(clippy::unwrap_used)]
+
+use push::{
+    error::{Error, InstructionResult},
+    instruction::{
+        instruction_error::PushInstructionError, variable_name::VariableName, Instruction,
+    },
+    push_vm::{
+        input_slots::InputSlots, size_budget::approximate_bytes, stack::Stack, ClearState,
+        HasStack, SizeBudgetState,
+    },
+};
+
+/// Instructions for a state with string and vector stacks, whose inputs
+/// are strings and vectors.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum TextInstruction {
+    PushString(String),
+    PushVector(Vec<i64>),
+    Input(VariableName),
+}
+
+impl TextInstruction {
+    const fn push_string(s: String) -> Self {
+        Self::PushString(s)
+    }
+
+    const fn push_vector(v: Vec<i64>) -> Self {
+        Self::PushVector(v)
+    }
+}
+
+#[derive(Default, Debug)]
+#[push::push_state(builder, clear_state, size_budget)]
+struct TextState {
+    #[stack(exec)]
+    exec: Stack<TextInstruction>,
+    #[stack(instruction_name = TextInstruction::push_string, size_fn = String::len)]
+    text: Stack<String>,
+    #[stack(instruction_name = TextInstruction::push_vector, size_fn = approximate_bytes)]
+    vector: Stack<Vec<i64>>,
+    #[input_instructions]
+    input_instructions: InputSlots<TextInstruction>,
+}
+
+impl Instruction<TextState> for TextInstruction {
+    type Error = PushInstructionError;
+
+    fn perform(&self, state: &mut TextState) -> InstructionResult<Self::Error> {
+        match self {
+            Self::PushString(s) => state.text.push(s.clone()).map_err(Error::fatal),
+            Self::PushVector(v) => state.vector.push(v.clone()).map_err(Error::fatal),
+            Self::Input(name) => state
+                .input_instructions
+                .get_by_name(name)
+                .cloned()
+                .ok_or_else(|| {
+                    Error::fatal(PushInstructionError::UndefinedInput { name: name.clone() })
+                })?
+                .perform(state),
+        }
+    }
+}
+
+#[test]
+fn string_and_vector_inputs() {
+    let mut state = TextState::builder()
+        .with_max_stack_size(10)
+        .with_no_program()
+        .with_text_input("greeting", "hello".to_string())
+        .with_vector_input("xs", vec![1, 2, 3])
+        .build();
+    let greeting = VariableName::from("greeting");
+    let xs = VariableName::from("xs");
+    assert_eq!(state.input_instructions.slot(&greeting), Some(0));
+    assert_eq!(state.input_instructions.slot(&xs), Some(1));
+
+    TextInstruction::Input(xs).perform(&mut state).unwrap();
+    TextInstruction::Input(greeting.clone())
+        .perform(&mut state)
+        .unwrap();
+    assert_eq!(state.stack::<String>(), &vec!["hello".to_string()]);
+    assert_eq!(state.stack::<Vec<i64>>(), &vec![vec![1, 2, 3]]);
+
+    state.clear();
+    assert!(state.stack::<String>().is_empty());
+    let Err(Error::Fatal(error)) = TextInstruction::Input(greeting.clone()).perform(&mut state)
+    else {
+        panic!("An undefined input should be a fatal error");
+    };
+    assert_eq!(
+        error,
+        PushInstructionError::UndefinedInput { name: greeting }
+    );
+}
+
+#[test]
+fn clear_state_empties_a_custom_state() {
+    let mut state = TextState::builder()
+        .with_max_stack_size(10)
+        .with_program([TextInstruction::push_string("left over".to_string())])
+        .unwrap()
+        .with_text_input("greeting", "hello".to_string())
+        .build();
+    state.text.push("hi".to_string()).unwrap();
+    state.vector.push(vec![4]).unwrap();
+
+    state.clear();
+    assert!(state.exec.is_empty());
+    assert!(state.text.is_empty());
+    assert!(state.vector.is_empty());
+    assert_eq!(
+        state
+            .input_instructions
+            .slot(&VariableName::from("greeting")),
+        None
+    );
+    // Clearing keeps the maximum stack sizes.
+    assert_eq!(state.text.max_stack_size(), 10);
+    assert_eq!(state.exec.max_stack_size(), 10);
+}
+
+#[test]
+fn size_budget_measures_a_custom_state() {
+    let mut state = TextState::builder()
+        .with_max_stack_size(10)
+        .with_no_program()
+        .build()
+        .with_size_budget(20);
+    state.text.push("hello".to_string()).unwrap();
+    state.vector.push(vec![1]).unwrap();
+    assert_eq!(state.size_budget().unwrap().used(), 13);
+    assert!(state.text.push("a long string".to_string()).is_err());
+
+    // A clone gets its own budget, charged for the values it copied.
+    let mut clone = state.clone();
+    assert_eq!(clone.size_budget().unwrap().used(), 13);
+    clone.text.push("1234567".to_string()).unwrap();
+    assert_eq!(clone.size_budget().unwrap().used(), 20);
+    assert_eq!(state.size_budget().unwrap().used(), 13);
+}
@@ -4,10 +4,14 @@
 use ordered_float::OrderedFloat;
 use proptest::{arbitrary::any, prop_assert_eq, proptest};
 use push::{
-    instruction::{FloatInstruction, Instruction, PushInstruction},
+    instruction::{FloatInstruction, PushInstruction},
     push_vm::{push_state::PushState, stack::StackError, HasStack},
 };
 
+use crate::common::perform;
+
+mod common;
+
 #[test]
 fn to_push_instruction() {
     let float_instruction = FloatInstruction::Add;
@@ -18,34 +22,34 @@ fn to_push_instruction() {
 #[test]
 fn push_float() {
     let x = OrderedFloat(589.632);
-    let state = PushState::builder()
+    let mut state = PushState::builder()
         .with_max_stack_size(100)
         .with_no_program()
         .build();
-    let result = FloatInstruction::Push(x).perform(state).unwrap();
-    assert_eq!(result.stack::<OrderedFloat<f64>>().size(), 1);
-    assert_eq!(*result.stack::<OrderedFloat<f64>>().top().unwrap(), x);
+    perform(FloatInstruction::Push(x), &mut state).unwrap();
+    assert_eq!(state.stack::<OrderedFloat<f64>>().size(), 1);
+    assert_eq!(*state.stack::<OrderedFloat<f64>>().top().unwrap(), x);
 }
 
 #[test]
 fn add() {
     let x = OrderedFloat(409.37);
     let y = OrderedFloat(512.825);
-    let state = PushState::builder()
+    let mut state = PushState::builder()
         .with_max_stack_size(100)
         .with_float_values(vec![x, y])
         .unwrap()
         .with_no_program()
         .build();
-    let result = FloatInstruction::Add.perform(state).unwrap();
-    assert_eq!(result.stack::<OrderedFloat<f64>>().size(), 1);
-    assert_eq!(*result.stack::<OrderedFloat<f64>>().top().unwrap(), x + y);
+    perform(FloatInstruction::Add, &mut state).unwrap();
+    assert_eq!(state.stack::<OrderedFloat<f64>>().size(), 1);
+    assert_eq!(*state.stack::<OrderedFloat<f64>>().top().unwrap(), x + y);
 }
 
 #[test]
 fn overflow_bool_stack() {
     let x = OrderedFloat(409.37);
-    let state = PushState::builder()
+    let mut state = PushState::builder()
         // Set the max stack size to 2 so we can cause it to overflow.
         .with_max_stack_size(2)
         // Push two copies of x so we can call `FloatInstruction::Equal`
@@ -56,7 +60,7 @@ fn overflow_bool_stack() {
         .unwrap()
         .with_no_program()
         .build();
-    let result = FloatInstruction::Equal.perform(state).unwrap_err();
+    let result = perform(FloatInstruction::Equal, &mut state).unwrap_err();
     assert_eq!(
         result.error(),
         &StackError::Overflow { stack_type: "bool" }.into()
@@ -66,15 +70,15 @@ fn overflow_bool_stack() {
 #[test]
 fn dup() {
     let x = OrderedFloat(409.37);
-    let state = PushState::builder()
+    let mut state = PushState::builder()
         .with_max_stack_size(100)
         .with_float_values(std::iter::once(x))
         .unwrap()
         .with_no_program()
         .build();
-    let mut result = FloatInstruction::Dup.perform(state).unwrap();
-    assert_eq!(result.stack::<OrderedFloat<f64>>().size(), 2);
-    let float_stack = result.stack_mut::<OrderedFloat<f64>>();
+    perform(FloatInstruction::Dup, &mut state).unwrap();
+    assert_eq!(state.stack::<OrderedFloat<f64>>().size(), 2);
+    let float_stack = state.stack_mut::<OrderedFloat<f64>>();
     let (&a, &b) = float_stack.top2().unwrap();
     assert_eq!(a, x);
     assert_eq!(b, x);
@@ -84,112 +88,112 @@ proptest! {
     #[test]
     fn add_prop(x in any::<OrderedFloat<f64>>(), y in any::<OrderedFloat<f64>>()) {
         let expected_result = x + y;
-        let state = PushState::builder()
+        let mut state = PushState::builder()
             .with_max_stack_size(100)
             .with_float_values([x, y])
             .unwrap()
             .with_no_program()
             .build();
-        let result = FloatInstruction::Add.perform(state).unwrap();
-        let output = result.stack::<OrderedFloat<f64>>().top().unwrap();
+        perform(FloatInstruction::Add, &mut state).unwrap();
+        let output = state.stack::<OrderedFloat<f64>>().top().unwrap();
         prop_assert_eq!(*output, expected_result);
     }
 
     #[test]
     fn subtract_prop(x in any::<OrderedFloat<f64>>(), y in any::<OrderedFloat<f64>>()) {
         let expected_result = x - y;
-        let state = PushState::builder()
+        let mut state = PushState::builder()
             .with_max_stack_size(100)
             .with_float_values([x, y])
             .unwrap()
             .with_no_program()
             .build();
-        let result = FloatInstruction::Subtract.perform(state).unwrap();
-        let output = result.stack::<OrderedFloat<f64>>().top().unwrap();
+        perform(FloatInstruction::Subtract, &mut state).unwrap();
+        let output = state.stack::<OrderedFloat<f64>>().top().unwrap();
         prop_assert_eq!(*output, expected_result);
     }
 
     #[test]
     fn multiply_prop(x in any::<OrderedFloat<f64>>(), y in any::<OrderedFloat<f64>>()) {
         let expected_result = x * y;
-        let state = PushState::builder()
+        let mut state = PushState::builder()
             .with_max_stack_size(100)
             .with_float_values([x, y])
             .unwrap()
             .with_no_program()
             .build();
-        let result = FloatInstruction::Multiply.perform(state).unwrap();
-        let output = result.stack::<OrderedFloat<f64>>().top().unwrap();
+        perform(FloatInstruction::Multiply, &mut state).unwrap();
+        let output = state.stack::<OrderedFloat<f64>>().top().unwrap();
         prop_assert_eq!(*output, expected_result);
     }
 
     #[test]
     fn protected_divide_prop(x in any::<OrderedFloat<f64>>(), y in any::<OrderedFloat<f64>>()) {
         let expected_result = if y == 0.0 { OrderedFloat(1.0) } else { x / y };
-        let state = PushState::builder()
+        let mut state = PushState::builder()
             .with_max_stack_size(100)
             .with_float_values([x, y])
             .unwrap()
             .with_no_program()
             .build();
-        let result = FloatInstruction::ProtectedDivide.perform(state).unwrap();
-        let output = result.stack::<OrderedFloat<f64>>().top().unwrap();
+        perform(FloatInstruction::ProtectedDivide, &mut state).unwrap();
+        let output = state.stack::<OrderedFloat<f64>>().top().unwrap();
         prop_assert_eq!(*output, expected_result);
     }
 
     #[test]
     fn equal_prop(x in any::<OrderedFloat<f64>>(), y in any::<OrderedFloat<f64>>()) {
         let expected_result = x == y;
-        let state = PushState::builder()
+        let mut state = PushState::builder()
             .with_max_stack_size(100)
             .with_float_values([x, y])
             .unwrap()
             .with_no_program()
             .build();
-        let result = FloatInstruction::Equal.perform(state).unwrap();
-        let output = result.stack::<bool>().top().unwrap();
+        perform(FloatInstruction::Equal, &mut state).unwrap();
+        let output = state.stack::<bool>().top().unwrap();
         prop_assert_eq!(*output, expected_result);
     }
 
     #[test]
     fn not_equal_prop(x in any::<OrderedFloat<f64>>(), y in any::<OrderedFloat<f64>>()) {
         let expected_result = x != y;
-        let state = PushState::builder()
+        let mut state = PushState::builder()
             .with_max_stack_size(100)
             .with_float_values([x, y])
             .unwrap()
             .with_no_program()
             .build();
-        let result = FloatInstruction::NotEqual.perform(state).unwrap();
-        let output = result.stack::<bool>().top().unwrap();
+        perform(FloatInstruction::NotEqual, &mut state).unwrap();
+        let output = state.stack::<bool>().top().unwrap();
         prop_assert_eq!(*output, expected_result);
     }
 
     #[test]
     fn greater_than_prop(x in any::<OrderedFloat<f64>>(), y in any::<OrderedFloat<f64>>()) {
         let expected_result = x > y;
-        let state = PushState::builder()
+        let mut state = PushState::builder()
             .with_max_stack_size(100)
             .with_float_values([x, y])
             .unwrap()
             .with_no_program()
             .build();
-        let result = FloatInstruction::GreaterThan.perform(state).unwrap();
-        let output = result.stack::<bool>().top().unwrap();
+        perform(FloatInstruction::GreaterThan, &mut state).unwrap();
+        let output = state.stack::<bool>().top().unwrap();
         prop_assert_eq!(*output, expected_result);
     }
 
     #[test]
     fn less_than_prop(x in any::<OrderedFloat<f64>>(), y in any::<OrderedFloat<f64>>()) {
         let expected_result = x < y;
-        let state = PushState::builder()
+        let mut state = PushState::builder()
             .with_max_stack_size(100)
             .with_float_values([x, y])
             .unwrap()
             .with_no_program()
             .build();
-        let result = FloatInstruction::LessThan.perform(state).unwrap();
-        let output = result.stack::<bool>().top().unwrap();
+        perform(FloatInstruction::LessThan, &mut state).unwrap();
+        let output = state.stack::<bool>().top().unwrap();
         prop_assert_eq!(*output, expected_result);
     }
 
@@ -199,29 +203,28 @@ proptest! {
         y in any::<OrderedFloat<f64>>()
     ) {
         let expected_result = x >= y;
-        let state = PushState::builder()
+        let mut state = PushState::builder()
             .with_max_stack_size(100)
             .with_float_values([x, y])
             .unwrap()
             .with_no_program()
             .build();
-        let result =
-            FloatInstruction::GreaterThanOrEqual.perform(state).unwrap();
-        let output = result.stack::<bool>().top().unwrap();
+        perform(FloatInstruction::GreaterThanOrEqual, &mut state).unwrap();
+        let output = state.stack::<bool>().top().unwrap();
         prop_assert_eq!(*output, expected_result);
     }
 
     #[test]
     fn less_than_or_equal_prop(x in any::<OrderedFloat<f64>>(), y in any::<OrderedFloat<f64>>()) {
         let expected_result = x <= y;
-        let state = PushState::builder()
+        let mut state = PushState::builder()
             .with_max_stack_size(100)
             .with_float_values([x, y])
             .unwrap()
             .with_no_program()
             .build();
-        let result = FloatInstruction::LessThanOrEqual.perform(state).unwrap();
-        let output = result.stack::<bool>().top().unwrap();
+        perform(FloatInstruction::LessThanOrEqual, &mut state).unwrap();
+        let output = state.stack::<bool>().top().unwrap();
         prop_assert_eq!(*output, expected_result);
     }
 }
@@ -2,42 +2,52 @@
 #![allow(clippy::tuple_array_conversions)]
 
 use proptest::{arbitrary::any, prop_assert_eq, proptest};
+use num_bigint::BigInt;
 use push::{
     instruction::{
-        instruction_error::PushInstructionError, Instruction, IntInstruction, IntInstructionError,
+        instruction_error::PushInstructionError, Instruction, IntArithmetic, IntInstruction,
+        IntInstructionError, IntState, MAX_BIG_INT_BITS,
+    },
+    push_vm::{
+        push_state::{OrderedFloat, PushState},
+        stack::Stack,
+        HasStack,
     },
-    push_vm::{push_state::PushState, HasStack},
 };
 use strum::IntoEnumIterator;
 
+use crate::common::perform;
+
+mod common;
+
 #[test]
 fn add() {
     let x = 409;
     let y = 512;
-    let state = PushState::builder()
+    let mut state = PushState::builder()
         .with_max_stack_size(100)
         .with_int_values([x, y])
         .unwrap()
         .with_no_program()
         .build();
-    let result = IntInstruction::Add.perform(state).unwrap();
-    assert_eq!(result.stack::<i64>().size(), 1);
-    assert_eq!(*result.stack::<i64>().top().unwrap(), x + y);
+    perform(IntInstruction::Add, &mut state).unwrap();
+    assert_eq!(state.stack::<i64>().size(), 1);
+    assert_eq!(*state.stack::<i64>().top().unwrap(), x + y);
 }
 
 #[test]
 fn add_overflows() {
     let x = 4_098_586_571_925_584_936;
     let y = 5_124_785_464_929_190_872;
-    let state = PushState::builder()
+    let mut state = PushState::builder()
         .with_max_stack_size(100)
         .with_int_values([x, y])
         .unwrap()
         .with_no_program()
         .build();
 
-    let result = IntInstruction::Add.perform(state).unwrap_err();
-    assert_eq!(result.state().stack::<i64>().size(), 2);
+    let result = perform(IntInstruction::Add, &mut state).unwrap_err();
+    assert_eq!(state.stack::<i64>().size(), 2);
     assert_eq!(
         result.error(),
         &PushInstructionError::from(IntInstructionError::Overflow {
@@ -50,16 +60,16 @@ fn add_overflows() {
 #[test]
 fn inc_overflows() {
     let x = i64::MAX;
-    let state = PushState::builder()
+    let mut state = PushState::builder()
         .with_max_stack_size(100)
         .with_int_values(std::iter::once(x))
         .unwrap()
         .with_no_program()
         .build();
 
-    let result = IntInstruction::Inc.perform(state).unwrap_err();
-    assert_eq!(result.state().stack::<i64>().size(), 1);
-    assert_eq!(result.state().stack::<i64>().top().unwrap(), &i64::MAX);
+    let result = perform(IntInstruction::Inc, &mut state).unwrap_err();
+    assert_eq!(state.stack::<i64>().size(), 1);
+    assert_eq!(state.stack::<i64>().top().unwrap(), &i64::MAX);
     assert_eq!(
         result.error(),
         &IntInstructionError::Overflow {
@@ -73,15 +83,15 @@ fn inc_overflows() {
 #[test]
 fn dec_overflows() {
     let x = i64::MIN;
-    let state = PushState::builder()
+    let mut state = PushState::builder()
         .with_max_stack_size(100)
         .with_int_values(std::iter::once(x))
         .unwrap()
         .with_no_program()
         .build();
-    let result = IntInstruction::Dec.perform(state).unwrap_err();
-    assert_eq!(result.state().stack::<i64>().size(), 1);
-    assert_eq!(result.state().stack::<i64>().top().unwrap(), &i64::MIN);
+    let result = perform(IntInstruction::Dec, &mut state).unwrap_err();
+    assert_eq!(state.stack::<i64>().size(), 1);
+    assert_eq!(state.stack::<i64>().top().unwrap(), &i64::MIN);
     assert_eq!(
         result.error(),
         &IntInstructionError::Overflow {
@@ -92,6 +102,131 @@ fn dec_overflows() {
     assert!(result.is_recoverable());
 }
 
+fn state_with(int_arithmetic: IntArithmetic, values: [i64; 2]) -> PushState {
+    PushState::builder()
+        .with_max_stack_size(100)
+        .with_int_values(values)
+        .unwrap()
+        .with_no_program()
+        .build()
+        .with_int_arithmetic(int_arithmetic)
+}
+
+#[test]
+fn arithmetic_modes() {
+    let values = [5, i64::MAX];
+    let mut state = state_with(IntArithmetic::Wrapping, values);
+    perform(IntInstruction::Add, &mut state).unwrap();
+    assert_eq!(state.stack::<i64>(), &vec![i64::MIN + 4]);
+
+    let mut state = state_with(IntArithmetic::Saturating, values);
+    perform(IntInstruction::Add, &mut state).unwrap();
+    assert_eq!(state.stack::<i64>(), &vec![i64::MAX]);
+
+    let mut state = state_with(IntArithmetic::NoopOnOverflow, values);
+    perform(IntInstruction::Add, &mut state).unwrap();
+    assert_eq!(state.stack::<i64>(), &vec![i64::MAX, 5]);
+}
+
+#[test]
+fn bits_and_shifts() {
+    let mut state = state_with(IntArithmetic::Checked, [3, 12]);
+    perform(IntInstruction::BitXor, &mut state).unwrap();
+    assert_eq!(state.stack::<i64>(), &vec![15]);
+
+    // Shifts the top value by the second value.
+    let mut state = state_with(IntArithmetic::Checked, [-3, 4]);
+    perform(IntInstruction::ShiftLeft, &mut state).unwrap();
+    assert_eq!(state.stack::<i64>(), &vec![-48]);
+
+    let mut state = state_with(IntArithmetic::Checked, [3, 62]);
+    let result = perform(IntInstruction::ShiftLeft, &mut state).unwrap_err();
+    assert_eq!(
+        result.error(),
+        &IntInstructionError::Overflow {
+            op: IntInstruction::ShiftLeft
+        }
+        .into()
+    );
+    assert_eq!(state.stack::<i64>(), &vec![62, 3]);
+}
+
+#[test]
+fn sqrt_sign_and_from_float() {
+    let mut state = state_with(IntArithmetic::Checked, [-50, -5]);
+    perform(IntInstruction::Sqrt, &mut state).unwrap();
+    perform(IntInstruction::Sign, &mut state).unwrap();
+    assert_eq!(state.stack::<i64>(), &vec![-5, 1]);
+
+    let mut state = PushState::builder()
+        .with_max_stack_size(100)
+        .with_float_values([OrderedFloat(-7.9), OrderedFloat(1e100)])
+        .unwrap()
+        .with_no_program()
+        .build();
+    perform(IntInstruction::FromFloat, &mut state).unwrap();
+    assert_eq!(state.stack::<i64>(), &vec![-7]);
+    assert!(
+        perform(IntInstruction::FromFloat, &mut state)
+            .unwrap_err()
+            .is_recoverable()
+    );
+
+    let mut state = state.with_int_arithmetic(IntArithmetic::Saturating);
+    perform(IntInstruction::FromFloat, &mut state).unwrap();
+    assert_eq!(state.stack::<i64>(), &vec![-7, i64::MAX]);
+}
+
+/// A state whose integer stack holds arbitrary-precision integers.
+#[derive(Default, Debug, Clone)]
+#[push::push_state(builder)]
+struct BigIntState {
+    #[stack(exec)]
+    exec: Stack<IntInstruction>,
+    #[stack]
+    int: Stack<BigInt>,
+    #[stack]
+    float: Stack<OrderedFloat<f64>>,
+    #[stack]
+    bool: Stack<bool>,
+}
+
+impl IntState for BigIntState {
+    type Int = BigInt;
+
+    fn int_arithmetic(&self) -> IntArithmetic {
+        IntArithmetic::Checked
+    }
+}
+
+#[test]
+fn big_ints() {
+    let mut state = BigIntState::builder()
+        .with_max_stack_size(100)
+        .with_no_program()
+        .build();
+    for instruction in [
+        IntInstruction::Push(i64::MAX),
+        IntInstruction::Inc,
+        IntInstruction::Square,
+    ] {
+        instruction.perform(&mut state).unwrap();
+    }
+    let expected = (BigInt::from(i64::MAX) + 1) * (BigInt::from(i64::MAX) + 1);
+    assert_eq!(state.stack::<BigInt>(), &vec![expected]);
+    IntInstruction::IsOdd.perform(&mut state).unwrap();
+    assert_eq!(state.stack::<bool>(), &vec![false]);
+
+    IntInstruction::Push(3).perform(&mut state).unwrap();
+    // Squaring doesn't run away with all the memory.
+    let result = (0..20)
+        .map(|_| IntInstruction::Square.perform(&mut state))
+        .find(Result::is_err)
+        .unwrap();
+    assert!(result.unwrap_err().is_recoverable());
+    assert!(state.stack::<BigInt>().top().unwrap().bits() <= MAX_BIG_INT_BITS);
+}
+
 fn all_instructions() -> Vec<IntInstruction> {
     IntInstruction::iter().collect()
 }
@@ -101,43 +236,55 @@ proptest! {
 
     #[test]
     fn negate(x in any::<i64>()) {
-        let state = PushState::builder()
+        let mut state = PushState::builder()
             .with_max_stack_size(100)
             .with_int_values(std::iter::once(x))
             .unwrap()
             .with_no_program()
             .build();
-        let result = IntInstruction::Negate.perform(state).unwrap();
-        prop_assert_eq!(result.stack::<i64>().size(), 1);
-        prop_assert_eq!(*result.stack::<i64>().top().unwrap(), -x);
+        let result = perform(IntInstruction::Negate, &mut state);
+        if let Some(negated) = x.checked_neg() {
+            result.unwrap();
+            prop_assert_eq!(state.stack::<i64>().size(), 1);
+            prop_assert_eq!(*state.stack::<i64>().top().unwrap(), negated);
+        } else {
+            assert!(result.unwrap_err().is_recoverable());
+            prop_assert_eq!(*state.stack::<i64>().top().unwrap(), x);
+        }
     }
 
     #[test]
     fn abs(x in any::<i64>()) {
-        let state = PushState::builder()
+        let mut state = PushState::builder()
             .with_max_stack_size(100)
             .with_int_values(std::iter::once(x))
             .unwrap()
             .with_no_program()
             .build();
-        let result = IntInstruction::Abs.perform(state).unwrap();
-        prop_assert_eq!(result.stack::<i64>().size(), 1);
-        prop_assert_eq!(*result.stack::<i64>().top().unwrap(), x.abs());
+        let result = perform(IntInstruction::Abs, &mut state);
+        if let Some(abs) = x.checked_abs() {
+            result.unwrap();
+            prop_assert_eq!(state.stack::<i64>().size(), 1);
+            prop_assert_eq!(*state.stack::<i64>().top().unwrap(), abs);
+        } else {
+            assert!(result.unwrap_err().is_recoverable());
+            prop_assert_eq!(*state.stack::<i64>().top().unwrap(), x);
+        }
     }
 
     #[test]
     fn sqr(x in any::<i64>()) {
-        let state = PushState::builder()
+        let mut state = PushState::builder()
             .with_max_stack_size(100)
             .with_int_values(std::iter::once(x))
             .unwrap()
             .with_no_program()
             .build();
-        let result = IntInstruction::Square.perform(state);
+        let result = perform(IntInstruction::Square, &mut state);
         if let Some(x_squared) = x.checked_mul(x) {
-            let result = result.unwrap();
-            prop_assert_eq!(result.stack::<i64>().size(), 1);
-            let output = *result.stack::<i64>().top().unwrap();
+            result.unwrap();
+            prop_assert_eq!(state.stack::<i64>().size(), 1);
+            let output = *state.stack::<i64>().top().unwrap();
             prop_assert_eq!(output, x_squared);
         } else {
             let result = result.unwrap_err();
@@ -148,34 +295,35 @@ proptest! {
                 }.into()
             );
             assert!(result.is_recoverable());
-            let top_int = result.state().stack::<i64>().top().unwrap();
+            let top_int = state.stack::<i64>().top().unwrap();
             prop_assert_eq!(*top_int, x);
         }
     }
 
     #[test]
     fn add_does_not_crash(x in any::<i64>(), y in any::<i64>()) {
-        let state = PushState::builder()
+        let mut state = PushState::builder()
             .with_max_stack_size(100)
             .with_int_values([x,y])
             .unwrap()
             .with_no_program()
             .build();
-        let _ = IntInstruction::Add.perform(state);
+        let _ = perform(IntInstruction::Add, &mut state);
     }
 
     #[test]
     fn add_adds_or_does_nothing(x in any::<i64>(), y in any::<i64>()) {
-        let state = PushState::builder()
+        let mut state = PushState::builder()
             .with_max_stack_size(100)
             .with_int_values([x, y])
             .unwrap()
             .with_no_program()
             .build();
-        let result = IntInstruction::Add.perform(state);
+        let result = perform(IntInstruction::Add, &mut state);
         #[allow(clippy::unwrap_used)]
         if let Some(expected_result) = x.checked_add(y) {
-            let output = result.unwrap().stack_mut::<i64>().pop().unwrap();
+            result.unwrap();
+            let output = state.stack_mut::<i64>().pop().unwrap();
             prop_assert_eq!(output, expected_result);
         } else {
             // This only checks that `x` is still on the top of the stack.
@@ -191,23 +339,24 @@ proptest! {
                 .into()
             );
             assert!(result.is_recoverable());
-            let top_int = result.state().stack::<i64>().top().unwrap();
+            let top_int = state.stack::<i64>().top().unwrap();
             prop_assert_eq!(*top_int, x);
         }
     }
 
     #[test]
     fn subtract_subs_or_does_nothing(x in any::<i64>(), y in any::<i64>()) {
-        let state = PushState::builder()
+        let mut state = PushState::builder()
             .with_max_stack_size(100)
             .with_int_values([x, y])
             .unwrap()
             .with_no_program()
             .build();
-        let result = IntInstruction::Subtract.perform(state);
+        let result = perform(IntInstruction::Subtract, &mut state);
         #[allow(clippy::unwrap_used)]
         if let Some(expected_result) = x.checked_sub(y) {
-            let output = result.unwrap().stack_mut::<i64>().pop().unwrap();
+            result.unwrap();
+            let output = state.stack_mut::<i64>().pop().unwrap();
             prop_assert_eq!(output, expected_result);
         } else {
             // This only checks that `x` is still on the top of the stack.
@@ -223,23 +372,24 @@ proptest! {
                 .into()
             );
             assert!(result.is_recoverable());
-            let top_int = result.state().stack::<i64>().top().unwrap();
+            let top_int = state.stack::<i64>().top().unwrap();
             prop_assert_eq!(*top_int, x);
         }
     }
 
     #[test]
     fn multiply_works_or_does_nothing(x in any::<i64>(), y in any::<i64>()) {
-        let state = PushState::builder()
+        let mut state = PushState::builder()
             .with_max_stack_size(100)
             .with_int_values([x, y])
             .unwrap()
             .with_no_program()
             .build();
-        let result = IntInstruction::Multiply.perform(state);
+        let result = perform(IntInstruction::Multiply, &mut state);
         #[allow(clippy::unwrap_used)]
         if let Some(expected_result) = x.checked_mul(y) {
-            let output = result.unwrap().stack_mut::<i64>().pop().unwrap();
+            result.unwrap();
+            let output = state.stack_mut::<i64>().pop().unwrap();
             prop_assert_eq!(output, expected_result);
         } else {
             // This only checks that `x` is still on the top of the stack.
@@ -255,22 +405,23 @@ proptest! {
                 .into()
             );
             assert!(result.is_recoverable());
-            let top_int = result.state().stack::<i64>().top().unwrap();
+            let top_int = state.stack::<i64>().top().unwrap();
             prop_assert_eq!(*top_int, x);
         }
     }
 
     #[test]
     fn protected_divide_zero_denominator(x in any::<i64>()) {
-        let state = PushState::builder()
+        let mut state = PushState::builder()
             .with_max_stack_size(100)
             .with_int_values([x, 0])
             .unwrap()
             .with_no_program()
             .build();
-        let result = IntInstruction::ProtectedDivide.perform(state);
+        let result = perform(IntInstruction::ProtectedDivide, &mut state);
         #[allow(clippy::unwrap_used)]
-        let output = result.unwrap().stack_mut::<i64>().pop().unwrap();
+        result.unwrap();
+            let output = state.stack_mut::<i64>().pop().unwrap();
         // Dividing by zero should always return 1.
         prop_assert_eq!(output, 1);
     }
@@ -280,16 +431,17 @@ proptest! {
         x in any::<i64>(),
         y in any::<i64>()
     ) {
-        let state = PushState::builder()
+        let mut state = PushState::builder()
             .with_max_stack_size(100)
             .with_int_values([x, y])
             .unwrap()
             .with_no_program()
             .build();
-        let result = IntInstruction::ProtectedDivide.perform(state);
+        let result = perform(IntInstruction::ProtectedDivide, &mut state);
         #[allow(clippy::unwrap_used)]
         if let Some(expected_result) = x.checked_div(y) {
-            let output = result.unwrap().stack_mut::<i64>().pop().unwrap();
+            result.unwrap();
+            let output = state.stack_mut::<i64>().pop().unwrap();
             prop_assert_eq!(output, expected_result);
         } else {
             // This only checks that `x` is still on the top of the stack.
@@ -305,41 +457,44 @@ proptest! {
                 .into()
             );
             assert!(result.is_recoverable());
-            let top_int = result.state().stack::<i64>().top().unwrap();
+            let top_int = state.stack::<i64>().top().unwrap();
             prop_assert_eq!(*top_int, x);
         }
     }
 
     #[test]
     fn mod_zero_denominator(x in any::<i64>()) {
-        let state =PushState::builder()
+        let mut state = PushState::builder()
             .with_max_stack_size(100)
             .with_int_values([0,x])
             .unwrap()
             .with_no_program()
             .build();
-        let result = IntInstruction::Mod.perform(state);
+        let result = perform(IntInstruction::Mod, &mut state);
         #[allow(clippy::unwrap_used)]
-        let output = result.unwrap().stack_mut::<i64>().pop().unwrap();
+        result.unwrap();
+            let output = state.stack_mut::<i64>().pop().unwrap();
         // Modding by zero should always return 0 since x % x = 0 for all x != 0.
         prop_assert_eq!(output, 0);
     }
 
     #[test]
     fn mod_rems_or_does_nothing(x in any::<i64>(), y in any::<i64>()) {
-        let state =PushState::builder()
+        let mut state = PushState::builder()
             .with_max_stack_size(100)
             .with_int_values([x, y])
             .unwrap()
             .with_no_program()
             .build();
-        let result = IntInstruction::Mod.perform(state);
+        let result = perform(IntInstruction::Mod, &mut state);
         #[allow(clippy::unwrap_used)]
         if let Some(expected_result) = x.checked_rem(y) {
-            let output = result.unwrap().stack_mut::<i64>().pop().unwrap();
+            result.unwrap();
+            let output = state.stack_mut::<i64>().pop().unwrap();
             prop_assert_eq!(output, expected_result);
         } else if y == 0 {
-            let output: i64 = *result.unwrap().stack_mut::<i64>().top().unwrap();
+            result.unwrap();
+            let output: i64 = *state.stack_mut::<i64>().top().unwrap();
             // Modding by zero should always return 0 since x % x == 0 for all x != 0.
             prop_assert_eq!(output, 0);
         } else {
@@ -356,20 +511,20 @@ proptest! {
                 .into()
             );
             assert!(result.is_recoverable());
-            let top_int = result.state().stack::<i64>().top().unwrap();
+            let top_int = state.stack::<i64>().top().unwrap();
             prop_assert_eq!(*top_int, x);
         }
     }
 
     #[test]
     fn inc_does_not_crash(x in any::<i64>()) {
-        let state = PushState::builder()
+        let mut state = PushState::builder()
             .with_max_stack_size(100)
             .with_int_values(std::iter::once(x))
             .unwrap()
             .with_no_program()
             .build();
-        let _ = IntInstruction::Inc.perform(state);
+        let _ = perform(IntInstruction::Inc, &mut state);
     }
 
     #[test]
@@ -378,7 +533,7 @@ proptest! {
             x in any::<i64>(),
             y in any::<i64>(),
             b in proptest::bool::ANY) {
-        let state = PushState::builder()
+        let mut state = PushState::builder()
             .with_max_stack_size(100)
             .with_int_values([x, y])
             .unwrap()
@@ -386,6 +541,6 @@ proptest! {
             .unwrap()
             .with_no_program()
             .build();
-        let _ = instr.perform(state);
+        let _ = perform(instr, &mut state);
     }
 }